@@ -0,0 +1,73 @@
+//! compares block-at-a-time CBC decryption against the batched `par_decrypt_blocks` path
+//!
+//! run with `cargo bench --bench cbc_decrypt`
+
+use std::hint::black_box;
+use std::time::{Duration, Instant};
+
+use crypt::aes::{
+    aes::*,
+    cbc::{cbc_decrypt, cbc_encrypt, par_decrypt_blocks},
+    constant::NUM_ROUND_KEYS_128,
+};
+use crypt::block_cipher::BlockCipher;
+
+const ITERATIONS: u32 = 5;
+
+fn time<F: FnMut()>(mut f: F) -> Duration {
+    // warm up once so the first iteration doesn't pay for page faults
+    f();
+
+    let start = Instant::now();
+    for _ in 0..ITERATIONS {
+        f();
+    }
+
+    start.elapsed() / ITERATIONS
+}
+
+fn throughput(bytes: usize, elapsed: Duration) -> f64 {
+    bytes as f64 / (1024.0 * 1024.0) / elapsed.as_secs_f64()
+}
+
+fn main() {
+    let key: AesKey128 = [0x61; 16];
+    let mut key_schedule: [AesBlock; NUM_ROUND_KEYS_128] = Default::default();
+    key_schedule_128(&key, &mut key_schedule);
+    let cipher = Aes128::new(&key);
+
+    for mib in [1, 4, 16] {
+        let size = mib * 1024 * 1024;
+        let input: Vec<u8> = (0..size).map(|i| i as u8).collect();
+        let ciphertext = cbc_encrypt(&input, &cipher);
+
+        let serial = time(|| {
+            let mut output = ciphertext[16..].to_vec();
+            for block in output.chunks_exact_mut(16) {
+                let mut state = block_from_bytes((&*block).try_into().unwrap());
+                decrypt_block(&mut state, &key_schedule);
+                block.copy_from_slice(&bytes_from_block(&state));
+            }
+            black_box(output);
+        });
+
+        let batched = time(|| {
+            let mut output = ciphertext[16..].to_vec();
+            par_decrypt_blocks(&cipher, &mut output);
+            black_box(output);
+        });
+
+        let full = time(|| {
+            black_box(cbc_decrypt(&cipher, &ciphertext).unwrap());
+        });
+
+        println!(
+            "{:>3} MiB: block-at-a-time {:>8.2} MiB/s | par_decrypt_blocks {:>8.2} MiB/s ({:.2}x) | cbc_decrypt {:>8.2} MiB/s",
+            mib,
+            throughput(size, serial),
+            throughput(size, batched),
+            serial.as_secs_f64() / batched.as_secs_f64(),
+            throughput(size, full),
+        );
+    }
+}
@@ -0,0 +1,87 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AeadError {
+    /// the tag didn't match the ciphertext and associated data - nothing was decrypted
+    AuthenticationFailed,
+    /// the ciphertext is shorter than a tag
+    CiphertextTooShort,
+    /// the message is longer than a single nonce can encrypt
+    MessageTooLong,
+}
+
+impl fmt::Display for AeadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AeadError::AuthenticationFailed => write!(f, "authentication failed"),
+            AeadError::CiphertextTooShort => write!(f, "ciphertext is shorter than the tag"),
+            AeadError::MessageTooLong => write!(f, "message too long for a single nonce"),
+        }
+    }
+}
+
+impl std::error::Error for AeadError {}
+
+/// authenticated encryption with associated data, shared by every AEAD in the crate so that a
+/// file format can be written once and pick its cipher per file
+///
+/// a nonce must never be reused with the same key
+pub trait Aead: Sized {
+    const KEY_SIZE: usize;
+    const NONCE_SIZE: usize;
+    const TAG_SIZE: usize;
+
+    type Key;
+    type Nonce;
+    type Tag: AsRef<[u8]> + Copy;
+
+    fn new(key: &Self::Key) -> Self;
+
+    /// encrypt `buffer` in place, returning the tag over it and `aad`
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &Self::Nonce,
+        aad: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<Self::Tag, AeadError>;
+
+    /// check `tag` in constant time and only then decrypt `buffer` in place - on failure
+    /// `buffer` is left holding the ciphertext
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &Self::Nonce,
+        aad: &[u8],
+        buffer: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), AeadError>;
+
+    /// encrypt to ciphertext || tag
+    fn encrypt(
+        &self,
+        nonce: &Self::Nonce,
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Vec<u8>, AeadError> {
+        let mut out = plaintext.to_vec();
+        let tag = self.encrypt_in_place_detached(nonce, aad, &mut out)?;
+        out.extend_from_slice(tag.as_ref());
+        Ok(out)
+    }
+
+    /// decrypt ciphertext || tag as produced by `encrypt`
+    fn decrypt(
+        &self,
+        nonce: &Self::Nonce,
+        aad: &[u8],
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>, AeadError> {
+        if ciphertext.len() < Self::TAG_SIZE {
+            return Err(AeadError::CiphertextTooShort);
+        }
+
+        let (ciphertext, tag) = ciphertext.split_at(ciphertext.len() - Self::TAG_SIZE);
+        let mut out = ciphertext.to_vec();
+        self.decrypt_in_place_detached(nonce, aad, &mut out, tag)?;
+        Ok(out)
+    }
+}
@@ -1,4 +1,5 @@
 use super::constant::*;
+use crate::block_cipher::BlockCipher;
 
 pub type AesColumn = [u8; 4];
 pub type AesBlock = [AesColumn; 4];
@@ -34,7 +35,25 @@ pub fn gf_mult(a: u8, b: u8) -> u8 {
         b >>= 1;
     }
 
-    return result as u8;
+    result as u8
+}
+
+pub fn block_from_bytes(bytes: &[u8; 16]) -> AesBlock {
+    let mut block: AesBlock = Default::default();
+    for (col, chunk) in block.iter_mut().zip(bytes.chunks_exact(4)) {
+        col.copy_from_slice(chunk);
+    }
+
+    block
+}
+
+pub fn bytes_from_block(block: &AesBlock) -> [u8; 16] {
+    let mut bytes = [0u8; 16];
+    for (chunk, col) in bytes.chunks_exact_mut(4).zip(block.iter()) {
+        chunk.copy_from_slice(col);
+    }
+
+    bytes
 }
 
 pub fn gf_word_add(a: AesColumn, b: AesColumn, dest: &mut AesColumn) {
@@ -70,51 +89,41 @@ pub fn key_schedule_128(key: &AesKey128, keys_out: &mut [AesBlock; NUM_ROUND_KEY
 pub fn mix_columns(state: &mut AesBlock) {
     let mut tmp: AesColumn = [0, 0, 0, 0];
 
-    for i in 0..4 {
-        tmp[0] =
-            gf_mult(0x02, state[i][0]) ^ gf_mult(0x03, state[i][1]) ^ state[i][2] ^ state[i][3];
-        tmp[1] =
-            state[i][0] ^ gf_mult(0x02, state[i][1]) ^ gf_mult(0x03, state[i][2]) ^ state[i][3];
-        tmp[2] =
-            state[i][0] ^ state[i][1] ^ gf_mult(0x02, state[i][2]) ^ gf_mult(0x03, state[i][3]);
-        tmp[3] =
-            gf_mult(0x03, state[i][0]) ^ state[i][1] ^ state[i][2] ^ gf_mult(0x02, state[i][3]);
-
-        state[i][0] = tmp[0];
-        state[i][1] = tmp[1];
-        state[i][2] = tmp[2];
-        state[i][3] = tmp[3];
+    for col in state.iter_mut() {
+        tmp[0] = gf_mult(0x02, col[0]) ^ gf_mult(0x03, col[1]) ^ col[2] ^ col[3];
+        tmp[1] = col[0] ^ gf_mult(0x02, col[1]) ^ gf_mult(0x03, col[2]) ^ col[3];
+        tmp[2] = col[0] ^ col[1] ^ gf_mult(0x02, col[2]) ^ gf_mult(0x03, col[3]);
+        tmp[3] = gf_mult(0x03, col[0]) ^ col[1] ^ col[2] ^ gf_mult(0x02, col[3]);
+
+        *col = tmp;
     }
 }
 
 pub fn inv_mix_columns(state: &mut AesBlock) {
     let mut tmp: AesColumn = [0, 0, 0, 0];
 
-    for i in 0..4 {
-        tmp[0] = gf_mult(0x0e, state[i][0])
-            ^ gf_mult(0x0b, state[i][1])
-            ^ gf_mult(0x0d, state[i][2])
-            ^ gf_mult(0x09, state[i][3]);
-
-        tmp[1] = gf_mult(0x09, state[i][0])
-            ^ gf_mult(0x0e, state[i][1])
-            ^ gf_mult(0x0b, state[i][2])
-            ^ gf_mult(0x0d, state[i][3]);
-
-        tmp[2] = gf_mult(0x0d, state[i][0])
-            ^ gf_mult(0x09, state[i][1])
-            ^ gf_mult(0x0e, state[i][2])
-            ^ gf_mult(0x0b, state[i][3]);
-
-        tmp[3] = gf_mult(0x0b, state[i][0])
-            ^ gf_mult(0x0d, state[i][1])
-            ^ gf_mult(0x09, state[i][2])
-            ^ gf_mult(0x0e, state[i][3]);
-
-        state[i][0] = tmp[0];
-        state[i][1] = tmp[1];
-        state[i][2] = tmp[2];
-        state[i][3] = tmp[3];
+    for col in state.iter_mut() {
+        tmp[0] = gf_mult(0x0e, col[0])
+            ^ gf_mult(0x0b, col[1])
+            ^ gf_mult(0x0d, col[2])
+            ^ gf_mult(0x09, col[3]);
+
+        tmp[1] = gf_mult(0x09, col[0])
+            ^ gf_mult(0x0e, col[1])
+            ^ gf_mult(0x0b, col[2])
+            ^ gf_mult(0x0d, col[3]);
+
+        tmp[2] = gf_mult(0x0d, col[0])
+            ^ gf_mult(0x09, col[1])
+            ^ gf_mult(0x0e, col[2])
+            ^ gf_mult(0x0b, col[3]);
+
+        tmp[3] = gf_mult(0x0b, col[0])
+            ^ gf_mult(0x0d, col[1])
+            ^ gf_mult(0x09, col[2])
+            ^ gf_mult(0x0e, col[3]);
+
+        *col = tmp;
     }
 }
 
@@ -127,32 +136,21 @@ pub fn rot_word(word: &mut AesColumn) {
 }
 
 pub fn sub_bytes(state: &mut AesBlock, table: &[u8]) {
-    let mut index: usize;
-
-    for col in 0..4 {
-        for row in 0..4 {
-            index = state[col][row] as usize;
-            state[col][row] = table[index];
-        }
+    for col in state.iter_mut() {
+        sub_word(col, table);
     }
 }
 
 pub fn sub_word(word: &mut AesColumn, table: &[u8]) {
-    let mut index: usize;
-
-    for i in 0..4 {
-        index = word[i] as usize;
-        word[i] = table[index];
+    for byte in word.iter_mut() {
+        *byte = table[*byte as usize];
     }
 }
 
 pub fn shift_rows(state: &mut [[u8; 4]; 4]) {
-    let mut tmp_a: u8;
-    let tmp_b: u8;
-
     // shift row 1
     // [0] [1] [2] [3] --> [1] [2] [3] [0]
-    tmp_a = state[0][1];
+    let mut tmp_a = state[0][1];
     state[0][1] = state[1][1];
     state[1][1] = state[2][1];
     state[2][1] = state[3][1];
@@ -161,7 +159,7 @@ pub fn shift_rows(state: &mut [[u8; 4]; 4]) {
     // shift row 2
     // [0] [1] [2] [3] --> [2] [3] [0] [1]
     tmp_a = state[0][2];
-    tmp_b = state[1][2];
+    let tmp_b = state[1][2];
     state[0][2] = state[2][2];
     state[1][2] = state[3][2];
     state[2][2] = tmp_a;
@@ -177,12 +175,9 @@ pub fn shift_rows(state: &mut [[u8; 4]; 4]) {
 }
 
 pub fn inv_shift_rows(state: &mut [[u8; 4]; 4]) {
-    let mut tmp_a: u8;
-    let tmp_b: u8;
-
     // shift row 1
     // [0] [1] [2] [3] --> [3] [0] [1] [2]
-    tmp_a = state[3][1];
+    let mut tmp_a = state[3][1];
     state[3][1] = state[2][1];
     state[2][1] = state[1][1];
     state[1][1] = state[0][1];
@@ -191,7 +186,7 @@ pub fn inv_shift_rows(state: &mut [[u8; 4]; 4]) {
     // shift row 2
     // [0] [1] [2] [3] --> [2] [3] [0] [1]
     tmp_a = state[0][2];
-    tmp_b = state[1][2];
+    let tmp_b = state[1][2];
     state[0][2] = state[2][2];
     state[1][2] = state[3][2];
     state[2][2] = tmp_a;
@@ -218,7 +213,7 @@ pub fn encrypt_block(state: &mut AesBlock, key_schedule: &[AesBlock]) {
     assert_eq!(key_schedule.len(), NUM_ROUND_KEYS_128);
 
     add_round_key(state, &key_schedule[0]);
-    for i in 1..NUM_ROUND_KEYS_128 {
+    for (i, round_key) in key_schedule.iter().enumerate().skip(1) {
         sub_bytes(state, &SBOX_ENCRYPT);
         shift_rows(state);
 
@@ -228,7 +223,7 @@ pub fn encrypt_block(state: &mut AesBlock, key_schedule: &[AesBlock]) {
             mix_columns(state);
         }
 
-        add_round_key(state, &key_schedule[i]);
+        add_round_key(state, round_key);
     }
 }
 
@@ -251,6 +246,86 @@ pub fn decrypt_block(state: &mut AesBlock, key_schedule: &[AesBlock]) {
     add_round_key(state, &key_schedule[0])
 }
 
+/// decrypt a batch of independent blocks, running each round across the whole batch before
+/// moving on to the next so that the work for neighbouring blocks can overlap in the pipeline
+pub fn decrypt_blocks(states: &mut [AesBlock], key_schedule: &[AesBlock]) {
+    assert_eq!(key_schedule.len(), NUM_ROUND_KEYS_128);
+
+    for group in states.chunks_mut(PARALLEL_BLOCKS) {
+        let mut rnd = NUM_ROUND_KEYS_128 - 1;
+        for i in 1..NUM_ROUND_KEYS_128 {
+            for state in group.iter_mut() {
+                add_round_key(state, &key_schedule[rnd]);
+                if i != 1 {
+                    inv_mix_columns(state);
+                }
+
+                inv_shift_rows(state);
+                sub_bytes(state, &SBOX_DECRYPT);
+            }
+
+            rnd = rnd.wrapping_sub(1);
+        }
+
+        for state in group.iter_mut() {
+            add_round_key(state, &key_schedule[0]);
+        }
+    }
+}
+
+/// AES-128 with its key schedule expanded up front, for use with the generic block modes
+#[derive(Clone)]
+pub struct Aes128 {
+    key_schedule: [AesBlock; NUM_ROUND_KEYS_128],
+}
+
+impl Aes128 {
+    pub fn key_schedule(&self) -> &[AesBlock] {
+        &self.key_schedule
+    }
+}
+
+impl BlockCipher for Aes128 {
+    const BLOCK_SIZE: usize = 16;
+
+    type Key = AesKey128;
+
+    fn new(key: &AesKey128) -> Self {
+        let mut key_schedule: [AesBlock; NUM_ROUND_KEYS_128] = Default::default();
+        key_schedule_128(key, &mut key_schedule);
+        Aes128 { key_schedule }
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let mut state = block_from_bytes((&*block).try_into().unwrap());
+        encrypt_block(&mut state, &self.key_schedule);
+        block.copy_from_slice(&bytes_from_block(&state));
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let mut state = block_from_bytes((&*block).try_into().unwrap());
+        decrypt_block(&mut state, &self.key_schedule);
+        block.copy_from_slice(&bytes_from_block(&state));
+    }
+
+    fn decrypt_blocks(&self, blocks: &mut [u8]) {
+        let mut states: [AesBlock; PARALLEL_BLOCKS] = Default::default();
+
+        for group in blocks.chunks_mut(16 * PARALLEL_BLOCKS) {
+            let count = group.len() / 16;
+            for (state, block) in states.iter_mut().zip(group.chunks_exact(16)) {
+                *state = block_from_bytes(block.try_into().unwrap());
+            }
+
+            decrypt_blocks(&mut states[..count], &self.key_schedule);
+
+            for (state, block) in states.iter().zip(group.chunks_exact_mut(16)) {
+                block.copy_from_slice(&bytes_from_block(state));
+            }
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -491,4 +566,26 @@ mod tests {
 
         assert_eq!(input, expected);
     }
+
+    #[test]
+    fn test_decrypt_blocks_matches_decrypt_block() {
+        let key: AesKey128 = [
+            0x2b, 0x7e, 0x15, 0x16, 0x28, 0xae, 0xd2, 0xa6, 0xab, 0xf7, 0x15, 0x88, 0x09, 0xcf,
+            0x4f, 0x3c,
+        ];
+        let mut round_keys: [AesBlock; NUM_ROUND_KEYS_128] = Default::default();
+        key_schedule_128(&key, &mut round_keys);
+
+        // an uneven batch so the trailing partial group is exercised too
+        let mut batch: Vec<AesBlock> = (0..11u8)
+            .map(|i| block_from_bytes(&[i.wrapping_mul(0x1d); 16]))
+            .collect();
+        let mut expected = batch.clone();
+        for state in expected.iter_mut() {
+            decrypt_block(state, &round_keys);
+        }
+
+        decrypt_blocks(&mut batch, &round_keys);
+        assert_eq!(batch, expected);
+    }
 }
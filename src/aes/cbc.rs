@@ -1,166 +1,517 @@
-use super::{aes::*, constant::NUM_ROUND_KEYS_128};
+use super::constant::PARALLEL_BLOCKS;
+use crate::block_cipher::BlockCipher;
 use rand::{thread_rng, Rng};
+use std::fmt;
+
+// inputs smaller than this aren't worth the cost of spawning threads to decrypt
+const PARALLEL_THRESHOLD: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CbcError {
+    /// ciphertext is shorter than an iv and one block, or isn't a whole number of blocks
+    InvalidLength,
+    /// the PKCS#7 padding on the final block is malformed
+    InvalidPadding,
+    /// the caller-provided output buffer can't hold the result
+    OutputTooSmall,
+}
 
-fn transmute_and_encrypt(
-    state: &[u8; 16],
-    output: &mut Vec<u8>,
-    key_schedule: &[AesBlock; NUM_ROUND_KEYS_128],
-) -> [u8; 16] {
-    unsafe {
-        let mut block: AesBlock = std::mem::transmute(state.to_owned());
-        encrypt_block(&mut block, key_schedule);
-        let encrypted: [u8; 16] = std::mem::transmute(block);
-        output.extend_from_slice(&encrypted);
-
-        //prev_state = encrypted;
-        return encrypted;
+impl fmt::Display for CbcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CbcError::InvalidLength => write!(f, "invalid CBC ciphertext length"),
+            CbcError::InvalidPadding => write!(f, "invalid CBC padding"),
+            CbcError::OutputTooSmall => write!(f, "output buffer too small"),
+        }
     }
 }
 
-pub fn cbc_encrypt(input: &[u8], key: &AesKey128) -> Vec<u8> {
-    assert_eq!(key.len(), 16);
+impl std::error::Error for CbcError {}
 
+fn generate_iv(block_size: usize) -> Vec<u8> {
     // not a cryptographically secure IV generation implementation
-    let mut iv = [0u8; 16];
+    let mut iv = vec![0u8; block_size];
     thread_rng().fill(&mut iv[..]);
+    iv
+}
 
-    println!("\niv:");
-    for byte in iv {
-        print!("{:02x?}", byte);
+/// number of PKCS#7 padding bytes needed for an input of `len` bytes - always between 1 and the
+/// block size, so a full block of padding is added when the input is already block aligned
+pub fn padding_len<C: BlockCipher>(len: usize) -> usize {
+    C::BLOCK_SIZE - (len % C::BLOCK_SIZE)
+}
+
+/// size of the `iv || ciphertext` produced by `cbc_encrypt` for an input of `len` bytes
+pub fn cbc_encrypted_len<C: BlockCipher>(len: usize) -> usize {
+    C::BLOCK_SIZE + len + padding_len::<C>(len)
+}
+
+/// CBC-encrypt whole blocks in place, chaining from `iv`
+pub fn cbc_encrypt_blocks<C: BlockCipher>(cipher: &C, iv: &[u8], blocks: &mut [u8]) {
+    assert_eq!(iv.len(), C::BLOCK_SIZE);
+    assert!(blocks.len().is_multiple_of(C::BLOCK_SIZE));
+
+    let mut prev_state = iv.to_vec();
+    for block in blocks.chunks_exact_mut(C::BLOCK_SIZE) {
+        for (curr, prev) in block.iter_mut().zip(prev_state.iter()) {
+            *curr ^= prev;
+        }
+
+        cipher.encrypt_block(block);
+        prev_state.copy_from_slice(block);
     }
-    println!();
+}
 
-    let mut prev_state = iv;
+/// encrypt a buffer laid out as `iv || plaintext || padding` in place, leaving the iv untouched
+fn encrypt_padded<C: BlockCipher>(buffer: &mut [u8], cipher: &C) {
+    let (iv, blocks) = buffer.split_at_mut(C::BLOCK_SIZE);
+    cbc_encrypt_blocks(cipher, iv, blocks);
+}
 
-    let padding = 16 - (input.len() % 16);
-    let padding = if padding == 0 { 16 } else { padding };
+pub fn cbc_encrypt<C: BlockCipher>(input: &[u8], cipher: &C) -> Vec<u8> {
+    let iv = generate_iv(C::BLOCK_SIZE);
+    let padding = padding_len::<C>(input.len());
 
-    let output_size = input.len() + padding + 16;
-    let mut output = Vec::with_capacity(output_size);
+    let mut output = Vec::with_capacity(cbc_encrypted_len::<C>(input.len()));
     output.extend_from_slice(&iv);
+    output.extend_from_slice(input);
+    output.resize(output.capacity(), padding as u8);
+
+    encrypt_padded(&mut output, cipher);
+    output
+}
+
+/// encrypt the plaintext held in `buffer`, replacing it with `iv || ciphertext` - the existing
+/// allocation is reused if it has room for the iv and padding
+pub fn cbc_encrypt_in_place<C: BlockCipher>(buffer: &mut Vec<u8>, cipher: &C) {
+    let block_size = C::BLOCK_SIZE;
+    let input_len = buffer.len();
+    let padding = padding_len::<C>(input_len);
 
-    let mut key_schedule: [AesBlock; NUM_ROUND_KEYS_128] = Default::default();
-    key_schedule_128(key, &mut key_schedule);
+    buffer.resize(cbc_encrypted_len::<C>(input_len), padding as u8);
+    buffer.copy_within(..input_len, block_size);
+    buffer[..block_size].copy_from_slice(&generate_iv(block_size));
+    buffer[block_size + input_len..].fill(padding as u8);
 
-    let mut input_offset = 0;
+    encrypt_padded(buffer, cipher);
+}
+
+/// encrypt `input` into the start of `output`, which must be at least
+/// `cbc_encrypted_len(input.len())` bytes long - returns the number of bytes written
+pub fn cbc_encrypt_into<C: BlockCipher>(
+    input: &[u8],
+    cipher: &C,
+    output: &mut [u8],
+) -> Result<usize, CbcError> {
+    let block_size = C::BLOCK_SIZE;
+    let output_len = cbc_encrypted_len::<C>(input.len());
+    if output.len() < output_len {
+        return Err(CbcError::OutputTooSmall);
+    }
 
-    while input_offset < input.len() {
-        let mut state = [0u8; 16];
-        let bytes_remaining = input.len() - input_offset;
-        let block_size = bytes_remaining.min(16);
+    let output = &mut output[..output_len];
+    output[..block_size].copy_from_slice(&generate_iv(block_size));
+    output[block_size..block_size + input.len()].copy_from_slice(input);
+    output[block_size + input.len()..].fill(padding_len::<C>(input.len()) as u8);
+
+    encrypt_padded(output, cipher);
+    Ok(output_len)
+}
+
+fn thread_count(len: usize) -> usize {
+    if len < PARALLEL_THRESHOLD {
+        return 1;
+    }
+
+    std::thread::available_parallelism().map_or(1, |n| n.get())
+}
 
-        // fill block from input with indexation from the current block up to a maximum block size
-        // of 16 bytes
-        state[..block_size].copy_from_slice(&input[input_offset..input_offset + block_size]);
+/// decrypt every block of `data` in place, with no chaining between blocks - large inputs are
+/// split into contiguous runs that are decrypted on separate threads
+pub fn par_decrypt_blocks<C: BlockCipher>(cipher: &C, data: &mut [u8]) {
+    assert!(data.len().is_multiple_of(C::BLOCK_SIZE));
 
-        // if we are on the final block and there was not enough remaining input data, pad out the
-        // remaining bytes to reach the expected block size
-        if block_size < 16 {
-            for i in block_size..16 {
-                state[i] = padding as u8;
+    let threads = thread_count(data.len());
+    if threads < 2 {
+        cipher.decrypt_blocks(data);
+        return;
+    }
+
+    let per_thread = data.len().div_ceil(threads).next_multiple_of(C::BLOCK_SIZE);
+    std::thread::scope(|scope| {
+        for run in data.chunks_mut(per_thread) {
+            scope.spawn(move || cipher.decrypt_blocks(run));
+        }
+    });
+}
+
+/// CBC-decrypt a run of blocks in place, where `prev_state` is the ciphertext block that
+/// precedes the run
+fn cbc_decrypt_run<C: BlockCipher>(cipher: &C, prev_state: &[u8], blocks: &mut [u8]) {
+    let block_size = C::BLOCK_SIZE;
+    let mut prevs = vec![0u8; block_size * PARALLEL_BLOCKS];
+
+    // work backwards a group at a time, so that the ciphertext each group chains from is still
+    // intact when the group is decrypted
+    let mut end = blocks.len() / block_size;
+    while end > 0 {
+        let start = end.saturating_sub(PARALLEL_BLOCKS);
+        let group_len = (end - start) * block_size;
+
+        // the blocks a group chains from are the block before it and all but its last
+        match start {
+            0 => {
+                prevs[..block_size].copy_from_slice(prev_state);
+                prevs[block_size..group_len].copy_from_slice(&blocks[..group_len - block_size]);
             }
+            _ => prevs[..group_len]
+                .copy_from_slice(&blocks[(start - 1) * block_size..(end - 1) * block_size]),
         }
 
-        for (curr, prev) in state.iter_mut().zip(prev_state.iter()) {
+        let group = &mut blocks[start * block_size..end * block_size];
+        cipher.decrypt_blocks(group);
+        for (curr, prev) in group.iter_mut().zip(prevs.iter()) {
             *curr ^= prev;
         }
-        //for i in 0..16 {
-        //    state[i] ^= prev_state[i];
-        //}
 
-        prev_state = transmute_and_encrypt(&state, &mut output, &key_schedule);
-        input_offset += 16;
+        end = start;
     }
+}
 
-    if padding == 16 && input.len() % 16 == 0 {
-        let mut state = [padding as u8; 16];
-        for (curr, prev) in state.iter_mut().zip(prev_state.iter()) {
-            *curr ^= prev;
+/// CBC-decrypt whole blocks in place, chaining from `iv` - large inputs are split into runs
+/// that are decrypted on separate threads
+pub fn cbc_decrypt_blocks<C: BlockCipher>(cipher: &C, iv: &[u8], blocks: &mut [u8]) {
+    assert_eq!(iv.len(), C::BLOCK_SIZE);
+    assert!(blocks.len().is_multiple_of(C::BLOCK_SIZE));
+    cbc_decrypt_blocks_threaded(cipher, iv, blocks, thread_count(blocks.len()));
+}
+
+fn cbc_decrypt_blocks_threaded<C: BlockCipher>(
+    cipher: &C,
+    iv: &[u8],
+    blocks: &mut [u8],
+    threads: usize,
+) {
+    if threads < 2 {
+        cbc_decrypt_run(cipher, iv, blocks);
+        return;
+    }
+
+    let block_size = C::BLOCK_SIZE;
+    let per_thread = blocks.len().div_ceil(threads).next_multiple_of(block_size);
+
+    // each run chains from the last ciphertext block of the run before it, so those have to be
+    // copied out before any thread starts overwriting them
+    let mut prevs = iv.to_vec();
+    for run in blocks.chunks(per_thread) {
+        prevs.extend_from_slice(&run[run.len() - block_size..]);
+    }
+
+    std::thread::scope(|scope| {
+        for (run, prev_state) in blocks
+            .chunks_mut(per_thread)
+            .zip(prevs.chunks_exact(block_size))
+        {
+            scope.spawn(move || cbc_decrypt_run(cipher, prev_state, run));
         }
+    });
+}
+
+/// strip and verify PKCS#7 padding, returning the unpadded length
+fn unpad<C: BlockCipher>(decrypted: &[u8]) -> Result<usize, CbcError> {
+    let last_byte = decrypted[decrypted.len() - 1];
+    if last_byte == 0 || last_byte as usize > C::BLOCK_SIZE {
+        return Err(CbcError::InvalidPadding);
+    }
 
-        let _prev_state = transmute_and_encrypt(&state, &mut output, &key_schedule);
+    let unpadded_len = decrypted.len() - last_byte as usize;
+    if decrypted[unpadded_len..]
+        .iter()
+        .any(|&byte| byte != last_byte)
+    {
+        return Err(CbcError::InvalidPadding);
     }
 
-    return output;
+    Ok(unpadded_len)
 }
 
-pub fn cbc_decrypt(key: AesKey128, input: &[u8]) -> Option<Vec<u8>> {
-    // retrieve iv
-    let mut prev_state = &input[..16];
-    let mut key_schedule: [AesBlock; NUM_ROUND_KEYS_128] = Default::default();
-    key_schedule_128(&key, &mut key_schedule);
+fn check_ciphertext_len<C: BlockCipher>(len: usize) -> Result<(), CbcError> {
+    // need the iv plus at least one (padded) block
+    if len < 2 * C::BLOCK_SIZE || !len.is_multiple_of(C::BLOCK_SIZE) {
+        return Err(CbcError::InvalidLength);
+    }
+
+    Ok(())
+}
 
-    let mut output = Vec::with_capacity(input.len() - 16);
-    let mut last_byte = 0u8;
-    let mut input_offset = 16;
+pub fn cbc_decrypt<C: BlockCipher>(cipher: &C, input: &[u8]) -> Result<Vec<u8>, CbcError> {
+    check_ciphertext_len::<C>(input.len())?;
 
-    while input_offset < input.len() {
-        let curr_block = &input[input_offset..input_offset + 16];
+    let (iv, ciphertext) = input.split_at(C::BLOCK_SIZE);
+    let mut output = ciphertext.to_vec();
+    cbc_decrypt_blocks(cipher, iv, &mut output);
 
-        unsafe {
-            let mut state: AesBlock =
-                std::mem::transmute(*<&[u8; 16]>::try_from(curr_block).unwrap());
-            decrypt_block(&mut state, &key_schedule);
+    let unpadded_len = unpad::<C>(&output)?;
+    output.truncate(unpadded_len);
+    Ok(output)
+}
 
-            let mut decrypted: [u8; 16] = std::mem::transmute(state);
-            for (curr, prev) in decrypted.iter_mut().zip(prev_state.iter()) {
-                *curr ^= prev;
-            }
+/// decrypt `iv || ciphertext` held in `buffer` in place - on success, returns the plaintext,
+/// which is left in `buffer` directly after the iv
+pub fn cbc_decrypt_in_place<'a, C: BlockCipher>(
+    cipher: &C,
+    buffer: &'a mut [u8],
+) -> Result<&'a [u8], CbcError> {
+    check_ciphertext_len::<C>(buffer.len())?;
 
-            // verify padding if curr_block is the last block
-            if input_offset + 16 == input.len() {
-                last_byte = decrypted[15];
-                if last_byte == 0 || last_byte > 16 {
-                    return None;
-                }
-
-                for i in 16 - last_byte..15 {
-                    if decrypted[i as usize] != last_byte {
-                        return None;
-                    }
-                }
-            }
+    let (iv, blocks) = buffer.split_at_mut(C::BLOCK_SIZE);
+    cbc_decrypt_blocks(cipher, iv, blocks);
 
-            output.extend_from_slice(&decrypted);
-        }
+    let unpadded_len = unpad::<C>(blocks)?;
+    Ok(&blocks[..unpadded_len])
+}
 
-        input_offset += 16;
-        prev_state = curr_block;
+/// decrypt `iv || ciphertext` into the start of `output` - returns the plaintext length
+///
+/// `output` must have room for the padded plaintext (`input.len()` less one block), since the
+/// padding length isn't known until the last block is decrypted
+pub fn cbc_decrypt_into<C: BlockCipher>(
+    cipher: &C,
+    input: &[u8],
+    output: &mut [u8],
+) -> Result<usize, CbcError> {
+    check_ciphertext_len::<C>(input.len())?;
+
+    let (iv, ciphertext) = input.split_at(C::BLOCK_SIZE);
+    if output.len() < ciphertext.len() {
+        return Err(CbcError::OutputTooSmall);
     }
 
-    output.truncate(output.len() - last_byte as usize);
-    return Some(output);
+    let output = &mut output[..ciphertext.len()];
+    output.copy_from_slice(ciphertext);
+    cbc_decrypt_blocks(cipher, iv, output);
+
+    unpad::<C>(output)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::aes::aes::Aes128;
+    use crate::des::tdea::Tdea;
+    use crate::sm4::sm4::Sm4;
+    use crate::test_util::{for_each_128_bit_cipher, hex, CipherCheck};
 
     #[test]
     fn test_cbc_padding_needed() {
-        let key: AesKey128 = [0x61; 16];
+        let cipher = Aes128::new(&[0x61; 16]);
         let input: [u8; 9] = [0x41; 9];
 
-        let enc = cbc_encrypt(&input, &key);
+        let enc = cbc_encrypt(&input, &cipher);
         let enc_clone = enc.clone();
 
-        let dec = cbc_decrypt(key, &enc_clone);
+        let dec = cbc_decrypt(&cipher, &enc_clone);
 
-        assert!(dec.is_some());
         assert_eq!(dec.unwrap(), input);
     }
 
     #[test]
     fn test_cbc_full_block() {
-        let key: AesKey128 = [0x61; 16];
+        let cipher = Aes128::new(&[0x61; 16]);
         let input: [u8; 16] = [0x41; 16];
 
-        let enc = cbc_encrypt(&input, &key);
+        let enc = cbc_encrypt(&input, &cipher);
         let enc_clone = enc.clone();
 
-        let dec = cbc_decrypt(key, &enc_clone);
+        let dec = cbc_decrypt(&cipher, &enc_clone);
+
+        assert_eq!(dec.unwrap(), input);
+    }
+
+    #[test]
+    fn test_cbc_large_input() {
+        let cipher = Aes128::new(&[0x61; 16]);
+        let input: Vec<u8> = (0..PARALLEL_THRESHOLD * 2 + 7).map(|i| i as u8).collect();
+
+        let enc = cbc_encrypt(&input, &cipher);
+        let dec = cbc_decrypt(&cipher, &enc);
 
-        assert!(dec.is_some());
         assert_eq!(dec.unwrap(), input);
     }
+
+    #[test]
+    fn test_cbc_decrypt_rejects_malformed_input() {
+        let cipher = Aes128::new(&[0x61; 16]);
+        let mut enc = cbc_encrypt(&[0x41; 9], &cipher);
+
+        assert_eq!(
+            cbc_decrypt(&cipher, &enc[..16]),
+            Err(CbcError::InvalidLength)
+        );
+        assert_eq!(
+            cbc_decrypt(&cipher, &enc[..31]),
+            Err(CbcError::InvalidLength)
+        );
+
+        // flipping a bit in the iv flips the same bit of the padding byte in the only block
+        enc[15] ^= 0x10;
+        assert_eq!(cbc_decrypt(&cipher, &enc), Err(CbcError::InvalidPadding));
+    }
+
+    #[test]
+    fn test_cbc_in_place_round_trip() {
+        let cipher = Aes128::new(&[0x61; 16]);
+
+        for len in [0, 1, 15, 16, 17, 100] {
+            let input: Vec<u8> = (0..len as u8).collect();
+
+            let mut buffer = input.clone();
+            cbc_encrypt_in_place(&mut buffer, &cipher);
+            assert_eq!(buffer.len(), cbc_encrypted_len::<Aes128>(len));
+            assert_eq!(cbc_decrypt(&cipher, &buffer).unwrap(), input);
+
+            let dec = cbc_decrypt_in_place(&cipher, &mut buffer).unwrap();
+            assert_eq!(dec, input);
+        }
+    }
+
+    #[test]
+    fn test_cbc_into_round_trip() {
+        let cipher = Aes128::new(&[0x61; 16]);
+        let input = [0x41; 33];
+
+        let mut enc = [0u8; 64];
+        assert_eq!(
+            cbc_encrypt_into(&input, &cipher, &mut enc[..63]),
+            Err(CbcError::OutputTooSmall)
+        );
+        assert_eq!(cbc_encrypt_into(&input, &cipher, &mut enc), Ok(64));
+
+        let mut dec = [0u8; 48];
+        assert_eq!(
+            cbc_decrypt_into(&cipher, &enc, &mut dec[..47]),
+            Err(CbcError::OutputTooSmall)
+        );
+        assert_eq!(cbc_decrypt_into(&cipher, &enc, &mut dec), Ok(33));
+        assert_eq!(dec[..33], input);
+    }
+
+    #[test]
+    fn test_cbc_in_place_errors() {
+        let cipher = Aes128::new(&[0x61; 16]);
+        let mut buffer = vec![0x41; 9];
+        cbc_encrypt_in_place(&mut buffer, &cipher);
+
+        assert_eq!(
+            cbc_decrypt_in_place(&cipher, &mut buffer.clone()[..24]),
+            Err(CbcError::InvalidLength)
+        );
+
+        buffer[15] ^= 0x10;
+        assert_eq!(
+            cbc_decrypt_in_place(&cipher, &mut buffer),
+            Err(CbcError::InvalidPadding)
+        );
+    }
+
+    #[test]
+    fn test_cbc_decrypt_blocks_split_across_threads() {
+        let cipher = Aes128::new(&[0x61; 16]);
+
+        let iv = [0x13; 16];
+        let input: Vec<u8> = (0..16 * 37).map(|i| i as u8).collect();
+        let mut enc = input.clone();
+        cbc_encrypt_blocks(&cipher, &iv, &mut enc);
+
+        // force the threaded path regardless of input size or core count, with run boundaries
+        // that don't line up with the decryption groups
+        for threads in [1, 2, 3, 8] {
+            let mut dec = enc.clone();
+            cbc_decrypt_blocks_threaded(&cipher, &iv, &mut dec, threads);
+            assert_eq!(dec, input);
+        }
+    }
+
+    #[test]
+    fn test_padding_follows_block_size() {
+        assert_eq!(padding_len::<Aes128>(9), 7);
+        assert_eq!(padding_len::<Aes128>(16), 16);
+        assert_eq!(padding_len::<Tdea>(9), 7);
+        assert_eq!(padding_len::<Tdea>(16), 8);
+        assert_eq!(cbc_encrypted_len::<Tdea>(16), 32);
+    }
+
+    #[test]
+    fn test_cbc_eight_byte_blocks() {
+        let cipher = Tdea::new(&[0x61; 24]);
+
+        for len in [0, 1, 7, 8, 9, 100] {
+            let input: Vec<u8> = (0..len as u8).collect();
+
+            let enc = cbc_encrypt(&input, &cipher);
+            assert_eq!(enc.len(), cbc_encrypted_len::<Tdea>(len));
+            assert_eq!(cbc_decrypt(&cipher, &enc).unwrap(), input);
+
+            let mut buffer = input.clone();
+            cbc_encrypt_in_place(&mut buffer, &cipher);
+            assert_eq!(cbc_decrypt_in_place(&cipher, &mut buffer).unwrap(), input);
+        }
+
+        // a single iv and block is the shortest valid ciphertext
+        let enc = cbc_encrypt(&[], &cipher);
+        assert_eq!(
+            cbc_decrypt_in_place(&cipher, &mut enc.clone()[..8]),
+            Err(CbcError::InvalidLength)
+        );
+        assert_eq!(
+            cbc_decrypt_in_place(&cipher, &mut enc.clone()[..12]),
+            Err(CbcError::InvalidLength)
+        );
+
+        // a padding byte that would be valid for 16-byte blocks is rejected for 8-byte ones
+        let mut block = [0x41, 0x41, 0x41, 0x41, 0x41, 0x41, 0x41, 0x0c];
+        let iv = [0u8; 8];
+        cbc_encrypt_blocks(&cipher, &iv, &mut block);
+        let mut forged = iv.to_vec();
+        forged.extend_from_slice(&block);
+        assert_eq!(
+            cbc_decrypt_in_place(&cipher, &mut forged),
+            Err(CbcError::InvalidPadding)
+        );
+    }
+
+    struct RoundTrip;
+
+    impl CipherCheck for RoundTrip {
+        fn check<C: BlockCipher>(&self, cipher: &C) {
+            for len in [0, 1, 15, 16, 17, 100] {
+                let input: Vec<u8> = (0..len as u8).collect();
+
+                let enc = cbc_encrypt(&input, cipher);
+                assert_eq!(enc.len(), cbc_encrypted_len::<C>(len));
+                assert_eq!(cbc_decrypt(cipher, &enc).unwrap(), input);
+            }
+        }
+    }
+
+    #[test]
+    fn test_cbc_128_bit_ciphers() {
+        for_each_128_bit_cipher(RoundTrip);
+    }
+
+    #[test]
+    fn test_cbc_sm4_vector() {
+        // the CBC example from the SM4 Internet-Draft (draft-ribose-cfrg-sm4), also reproduced
+        // with OpenSSL
+        let cipher = Sm4::new(&hex("0123456789abcdeffedcba9876543210").try_into().unwrap());
+        let iv = hex("000102030405060708090a0b0c0d0e0f");
+        let plaintext = hex("aaaaaaaabbbbbbbbccccccccddddddddeeeeeeeeffffffffaaaaaaaabbbbbbbb");
+        let ciphertext = hex("78ebb11cc40b0a48312aaeb2040244cb4cb7016951909226979b0d15dc6a8f6d");
+
+        let mut blocks = plaintext.clone();
+        cbc_encrypt_blocks(&cipher, &iv, &mut blocks);
+        assert_eq!(blocks, ciphertext);
+        cbc_decrypt_blocks(&cipher, &iv, &mut blocks);
+        assert_eq!(blocks, plaintext);
+    }
 }
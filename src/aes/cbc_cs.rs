@@ -0,0 +1,402 @@
+use super::cbc::{cbc_decrypt_blocks, cbc_encrypt_blocks};
+use crate::block_cipher::BlockCipher;
+use std::fmt;
+
+/// ciphertext stealing variants from the SP 800-38A addendum - they only differ in how the last
+/// two ciphertext blocks are ordered
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsVariant {
+    /// the partial penultimate block is kept in place
+    Cs1,
+    /// the last two blocks are swapped only when the final plaintext block is partial
+    Cs2,
+    /// the last two blocks are always swapped (Kerberos, RFC 3962)
+    Cs3,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CsError {
+    /// ciphertext stealing needs at least one full block of input
+    InputTooShort,
+    /// the caller-provided output buffer can't hold the result
+    OutputTooSmall,
+}
+
+impl fmt::Display for CsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CsError::InputTooShort => write!(f, "input must be at least 16 bytes long"),
+            CsError::OutputTooSmall => write!(f, "output buffer too small"),
+        }
+    }
+}
+
+impl std::error::Error for CsError {}
+
+/// swap the last full block and the (possibly partial, `d`-byte) penultimate block of `data`
+/// between the CS1 ordering and the CS2/CS3 ordering - this is its own inverse given `d`
+fn swap_tail(data: &mut [u8], d: usize, to_cs1: bool) {
+    let tail_start = data.len() - 16 - d;
+    let tail = &mut data[tail_start..];
+
+    // CS1: C*_{n-1} (d bytes) || C_n (16 bytes)  <->  CS2/3: C_n (16 bytes) || C*_{n-1} (d bytes)
+    if to_cs1 {
+        tail.rotate_left(16);
+    } else {
+        tail.rotate_left(d);
+    }
+}
+
+fn needs_swap(variant: CsVariant, d: usize) -> bool {
+    match variant {
+        CsVariant::Cs1 => false,
+        CsVariant::Cs2 => d != 16,
+        CsVariant::Cs3 => true,
+    }
+}
+
+// the stealing arithmetic and the 16-byte iv assume 128-bit blocks
+fn check_block_size<C: BlockCipher>() {
+    assert_eq!(C::BLOCK_SIZE, 16, "CBC-CS needs a 128-bit block cipher");
+}
+
+fn xor_block(state: &mut [u8; 16], other: &[u8]) {
+    for (curr, prev) in state.iter_mut().zip(other.iter()) {
+        *curr ^= prev;
+    }
+}
+
+/// number of blocks `n` and the length `d` of the final (possibly partial) block
+fn block_counts(len: usize) -> (usize, usize) {
+    let n = len.div_ceil(16);
+    (n, len - 16 * (n - 1))
+}
+
+/// CBC encryption with ciphertext stealing, in place - the ciphertext is exactly as long as the
+/// plaintext, which must be at least one block long
+pub fn cbc_cs_encrypt_in_place<C: BlockCipher>(
+    variant: CsVariant,
+    cipher: &C,
+    iv: &[u8; 16],
+    buffer: &mut [u8],
+) -> Result<(), CsError> {
+    if buffer.len() < 16 {
+        return Err(CsError::InputTooShort);
+    }
+
+    check_block_size::<C>();
+    let (n, d) = block_counts(buffer.len());
+
+    // every block before the final partial block is plain CBC
+    let full_len = 16 * (n - 1) + if d == 16 { 16 } else { 0 };
+    cbc_encrypt_blocks(cipher, iv, &mut buffer[..full_len]);
+    if d == 16 {
+        if n > 1 && needs_swap(variant, d) {
+            swap_tail(buffer, d, false);
+        }
+        return Ok(());
+    }
+
+    // C_n = E(P*_n ^ C_{n-1}), with the final block zero padded
+    let cn_1_start = full_len - 16;
+    let mut state = [0u8; 16];
+    state[..d].copy_from_slice(&buffer[full_len..]);
+    xor_block(&mut state, &buffer[cn_1_start..full_len]);
+    cipher.encrypt_block(&mut state);
+
+    // drop the trailing bytes of C_{n-1}, which can be recomputed from C_n on decryption
+    buffer[cn_1_start + d..].copy_from_slice(&state);
+
+    if needs_swap(variant, d) {
+        swap_tail(buffer, d, false);
+    }
+
+    Ok(())
+}
+
+/// CBC decryption with ciphertext stealing, in place - the inverse of `cbc_cs_encrypt_in_place`
+/// for the same variant and IV
+pub fn cbc_cs_decrypt_in_place<C: BlockCipher>(
+    variant: CsVariant,
+    cipher: &C,
+    iv: &[u8; 16],
+    buffer: &mut [u8],
+) -> Result<(), CsError> {
+    if buffer.len() < 16 {
+        return Err(CsError::InputTooShort);
+    }
+
+    check_block_size::<C>();
+    let (n, d) = block_counts(buffer.len());
+
+    if n == 1 {
+        cbc_decrypt_blocks(cipher, iv, buffer);
+        return Ok(());
+    }
+
+    if needs_swap(variant, d) {
+        swap_tail(buffer, d, true);
+    }
+
+    // rebuild the full C_{n-1}: decrypting C_n yields P*_n ^ C_{n-1}, and since P*_n was zero
+    // padded its trailing 16 - d bytes are exactly the bytes of C_{n-1} that were stolen
+    let cn_1_start = 16 * (n - 2);
+    let cn_start = cn_1_start + d;
+
+    let mut z: [u8; 16] = buffer[cn_start..].try_into().unwrap();
+    cipher.decrypt_block(&mut z);
+
+    let mut cn_1 = [0u8; 16];
+    cn_1[..d].copy_from_slice(&buffer[cn_1_start..cn_start]);
+    cn_1[d..].copy_from_slice(&z[d..]);
+
+    let mut last_plain = z;
+    xor_block(&mut last_plain, &cn_1);
+
+    // P_{n-1} = D(C_{n-1}) ^ C_{n-2}, which has to be read before the leading blocks are
+    // decrypted in place over it
+    let cn_2 = match n {
+        2 => *iv,
+        _ => buffer[cn_1_start - 16..cn_1_start].try_into().unwrap(),
+    };
+    let mut second_last_plain = cn_1;
+    cipher.decrypt_block(&mut second_last_plain);
+    xor_block(&mut second_last_plain, &cn_2);
+
+    cbc_decrypt_blocks(cipher, iv, &mut buffer[..cn_1_start]);
+    buffer[cn_1_start..cn_1_start + 16].copy_from_slice(&second_last_plain);
+    buffer[cn_1_start + 16..].copy_from_slice(&last_plain[..d]);
+
+    Ok(())
+}
+
+/// CBC encryption with ciphertext stealing - the output is exactly as long as `input`, which
+/// must be at least one block long
+pub fn cbc_cs_encrypt<C: BlockCipher>(
+    variant: CsVariant,
+    cipher: &C,
+    iv: &[u8; 16],
+    input: &[u8],
+) -> Result<Vec<u8>, CsError> {
+    let mut output = input.to_vec();
+    cbc_cs_encrypt_in_place(variant, cipher, iv, &mut output)?;
+    Ok(output)
+}
+
+/// CBC decryption with ciphertext stealing - the inverse of `cbc_cs_encrypt` for the same
+/// variant and IV
+pub fn cbc_cs_decrypt<C: BlockCipher>(
+    variant: CsVariant,
+    cipher: &C,
+    iv: &[u8; 16],
+    input: &[u8],
+) -> Result<Vec<u8>, CsError> {
+    let mut output = input.to_vec();
+    cbc_cs_decrypt_in_place(variant, cipher, iv, &mut output)?;
+    Ok(output)
+}
+
+/// encrypt `input` into the start of `output`, which must be at least as long as `input` -
+/// returns the number of bytes written
+pub fn cbc_cs_encrypt_into<C: BlockCipher>(
+    variant: CsVariant,
+    cipher: &C,
+    iv: &[u8; 16],
+    input: &[u8],
+    output: &mut [u8],
+) -> Result<usize, CsError> {
+    if output.len() < input.len() {
+        return Err(CsError::OutputTooSmall);
+    }
+
+    let output = &mut output[..input.len()];
+    output.copy_from_slice(input);
+    cbc_cs_encrypt_in_place(variant, cipher, iv, output)?;
+    Ok(input.len())
+}
+
+/// decrypt `input` into the start of `output`, which must be at least as long as `input` -
+/// returns the number of bytes written
+pub fn cbc_cs_decrypt_into<C: BlockCipher>(
+    variant: CsVariant,
+    cipher: &C,
+    iv: &[u8; 16],
+    input: &[u8],
+    output: &mut [u8],
+) -> Result<usize, CsError> {
+    if output.len() < input.len() {
+        return Err(CsError::OutputTooSmall);
+    }
+
+    let output = &mut output[..input.len()];
+    output.copy_from_slice(input);
+    cbc_cs_decrypt_in_place(variant, cipher, iv, output)?;
+    Ok(input.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::aes::{Aes128, AesKey128};
+    use crate::camellia::camellia::Camellia;
+    use crate::test_util::{for_each_128_bit_cipher, hex, CipherCheck};
+
+    // RFC 3962 appendix B
+    const KEY: AesKey128 = *b"chicken teriyaki";
+    const INPUT: &[u8; 64] = b"I would like the General Gau's Chicken, please, and wonton soup.";
+
+    #[test]
+    fn test_cs3_rfc3962_vectors() {
+        let cases = [
+            (17, "c6353568f2bf8cb4d8a580362da7ff7f97"),
+            (
+                31,
+                "fc00783e0efdb2c1d445d4c8eff7ed22 97687268d6ecccc0c07b25e25ecfe5",
+            ),
+            (
+                32,
+                "39312523a78662d5be7fcbcc98ebf5a8 97687268d6ecccc0c07b25e25ecfe584",
+            ),
+            (
+                47,
+                "97687268d6ecccc0c07b25e25ecfe584 b3fffd940c16a18c1b5549d2f838029e
+                 39312523a78662d5be7fcbcc98ebf5",
+            ),
+            (
+                48,
+                "97687268d6ecccc0c07b25e25ecfe584 9dad8bbb96c4cdc03bc103e1a194bbd8
+                 39312523a78662d5be7fcbcc98ebf5a8",
+            ),
+            (
+                64,
+                "97687268d6ecccc0c07b25e25ecfe584 39312523a78662d5be7fcbcc98ebf5a8
+                 4807efe836ee89a526730dbc2f7bc840 9dad8bbb96c4cdc03bc103e1a194bbd8",
+            ),
+        ];
+
+        let cipher = Aes128::new(&KEY);
+        for (len, expected) in cases {
+            let expected = hex(expected);
+            let enc = cbc_cs_encrypt(CsVariant::Cs3, &cipher, &[0u8; 16], &INPUT[..len]).unwrap();
+            assert_eq!(enc, expected);
+
+            let dec = cbc_cs_decrypt(CsVariant::Cs3, &cipher, &[0u8; 16], &enc).unwrap();
+            assert_eq!(dec, &INPUT[..len]);
+        }
+    }
+
+    #[test]
+    fn test_cs1_cs2_ordering() {
+        let cipher = Aes128::new(&KEY);
+
+        // partial final block: CS2 matches CS3, CS1 keeps the truncated block first
+        let cs1 = cbc_cs_encrypt(CsVariant::Cs1, &cipher, &[0u8; 16], &INPUT[..31]).unwrap();
+        let cs2 = cbc_cs_encrypt(CsVariant::Cs2, &cipher, &[0u8; 16], &INPUT[..31]).unwrap();
+        assert_eq!(
+            cs1,
+            hex("97687268d6ecccc0c07b25e25ecfe5 fc00783e0efdb2c1d445d4c8eff7ed22")
+        );
+        assert_eq!(
+            cs2,
+            hex("fc00783e0efdb2c1d445d4c8eff7ed22 97687268d6ecccc0c07b25e25ecfe5")
+        );
+
+        // whole blocks: CS1 and CS2 are both plain unpadded CBC
+        let expected = hex(
+            "97687268d6ecccc0c07b25e25ecfe584 39312523a78662d5be7fcbcc98ebf5a8
+             9dad8bbb96c4cdc03bc103e1a194bbd8",
+        );
+        for variant in [CsVariant::Cs1, CsVariant::Cs2] {
+            let enc = cbc_cs_encrypt(variant, &cipher, &[0u8; 16], &INPUT[..48]).unwrap();
+            assert_eq!(enc, expected);
+        }
+    }
+
+    struct RoundTripAllLengths;
+
+    impl CipherCheck for RoundTripAllLengths {
+        fn check<C: BlockCipher>(&self, cipher: &C) {
+            let iv = [0x13; 16];
+            let input: Vec<u8> = (0..80).collect();
+
+            for variant in [CsVariant::Cs1, CsVariant::Cs2, CsVariant::Cs3] {
+                for len in 16..=input.len() {
+                    let enc = cbc_cs_encrypt(variant, cipher, &iv, &input[..len]).unwrap();
+                    assert_eq!(enc.len(), len);
+
+                    let dec = cbc_cs_decrypt(variant, cipher, &iv, &enc).unwrap();
+                    assert_eq!(dec, &input[..len]);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_cs_round_trip_all_lengths() {
+        for_each_128_bit_cipher(RoundTripAllLengths);
+    }
+
+    #[test]
+    fn test_cs3_camellia_vector() {
+        // CS3 under Camellia-128, checked against CBC over the zero-padded input from an
+        // independent implementation with the last two blocks swapped and truncated
+        let input: Vec<u8> = (0..31).collect();
+        let enc = cbc_cs_encrypt(
+            CsVariant::Cs3,
+            &Camellia::new(&[0x61; 16]),
+            &[0x13; 16],
+            &input,
+        )
+        .unwrap();
+        assert_eq!(
+            enc,
+            hex("232959ecd09bff78a3dae3213bfa8604 ba7c725013188f286d90c30130325e")
+        );
+    }
+
+    #[test]
+    fn test_cs_input_too_short() {
+        let cipher = Aes128::new(&[0x61; 16]);
+
+        assert_eq!(
+            cbc_cs_encrypt(CsVariant::Cs3, &cipher, &[0u8; 16], &[0x41; 15]),
+            Err(CsError::InputTooShort)
+        );
+        assert_eq!(
+            cbc_cs_decrypt(CsVariant::Cs1, &cipher, &[0u8; 16], &[]),
+            Err(CsError::InputTooShort)
+        );
+    }
+
+    #[test]
+    fn test_cs_in_place_and_into() {
+        let cipher = Aes128::new(&[0x61; 16]);
+        let iv = [0x13; 16];
+        let input: Vec<u8> = (0..45).collect();
+        let expected = cbc_cs_encrypt(CsVariant::Cs3, &cipher, &iv, &input).unwrap();
+
+        let mut buffer = input.clone();
+        cbc_cs_encrypt_in_place(CsVariant::Cs3, &cipher, &iv, &mut buffer).unwrap();
+        assert_eq!(buffer, expected);
+        cbc_cs_decrypt_in_place(CsVariant::Cs3, &cipher, &iv, &mut buffer).unwrap();
+        assert_eq!(buffer, input);
+
+        let mut output = [0u8; 64];
+        assert_eq!(
+            cbc_cs_encrypt_into(CsVariant::Cs3, &cipher, &iv, &input, &mut output[..44]),
+            Err(CsError::OutputTooSmall)
+        );
+        assert_eq!(
+            cbc_cs_encrypt_into(CsVariant::Cs3, &cipher, &iv, &input, &mut output),
+            Ok(45)
+        );
+        assert_eq!(output[..45], expected);
+
+        let mut decrypted = [0u8; 45];
+        assert_eq!(
+            cbc_cs_decrypt_into(CsVariant::Cs3, &cipher, &iv, &expected, &mut decrypted),
+            Ok(45)
+        );
+        assert_eq!(decrypted[..], input);
+    }
+}
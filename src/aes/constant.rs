@@ -1,4 +1,11 @@
 pub const NUM_ROUND_KEYS_128: usize = 11;
+pub const PARALLEL_BLOCKS: usize = 4;
+pub const GCM_NONCE_SIZE: usize = 12;
+pub const GCM_TAG_SIZE: usize = 16;
+// SP 800-38D section 5.2.1.1 - 2^39 - 256 bits of plaintext per nonce
+pub const GCM_MAX_MESSAGE_LEN: u64 = (1 << 36) - 32;
+// x^128 + x^7 + x^2 + x + 1 in GCM's bit-reflected order
+pub const GCM_R: u128 = 0xe1 << 120;
 pub const SBOX_ENCRYPT: [u8; 256] = [
     0x63, 0x7c, 0x77, 0x7b, 0xf2, 0x6b, 0x6f, 0xc5, 0x30, 0x01, 0x67, 0x2b, 0xfe, 0xd7, 0xab, 0x76,
     0xca, 0x82, 0xc9, 0x7d, 0xfa, 0x59, 0x47, 0xf0, 0xad, 0xd4, 0xa2, 0xaf, 0x9c, 0xa4, 0x72, 0xc0,
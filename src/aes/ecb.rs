@@ -0,0 +1,48 @@
+use crate::block_cipher::BlockCipher;
+
+/// ECB-encrypt whole blocks in place - every block is encrypted independently, so equal
+/// plaintext blocks give equal ciphertext blocks; only suitable for single-block payloads such
+/// as keys, or for building other modes
+pub fn ecb_encrypt_blocks<C: BlockCipher>(cipher: &C, blocks: &mut [u8]) {
+    assert!(blocks.len().is_multiple_of(C::BLOCK_SIZE));
+
+    for block in blocks.chunks_exact_mut(C::BLOCK_SIZE) {
+        cipher.encrypt_block(block);
+    }
+}
+
+/// ECB-decrypt whole blocks in place
+pub fn ecb_decrypt_blocks<C: BlockCipher>(cipher: &C, blocks: &mut [u8]) {
+    assert!(blocks.len().is_multiple_of(C::BLOCK_SIZE));
+    cipher.decrypt_blocks(blocks);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::aes::Aes128;
+    use crate::test_util::hex;
+
+    #[test]
+    fn test_ecb_aes128_sp800_38a() {
+        // SP 800-38A F.1.1 ECB-AES128.Encrypt
+        let aes = Aes128::new(&hex("2b7e151628aed2a6abf7158809cf4f3c").try_into().unwrap());
+        let plaintext = hex(
+            "6bc1bee22e409f96e93d7e117393172aae2d8a571e03ac9c9eb76fac45af8e51
+             30c81c46a35ce411e5fbc1191a0a52eff69f2445df4f9b17ad2b417be66c3710",
+        );
+
+        let mut buffer = plaintext.clone();
+        ecb_encrypt_blocks(&aes, &mut buffer);
+        assert_eq!(
+            buffer,
+            hex(
+                "3ad77bb40d7a3660a89ecaf32466ef97f5d3d58503b9699de785895a96fdbaaf
+                 43b1cd7f598ece23881b00e3ed0306887b0c785e27e8ad3f8223207104725dd4"
+            )
+        );
+
+        ecb_decrypt_blocks(&aes, &mut buffer);
+        assert_eq!(buffer, plaintext);
+    }
+}
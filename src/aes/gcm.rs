@@ -0,0 +1,274 @@
+use super::aes::{Aes128, AesKey128};
+use super::constant::*;
+use crate::aead::{Aead, AeadError};
+use crate::block_cipher::BlockCipher;
+use crate::ct::ct_eq;
+
+pub type GcmNonce = [u8; GCM_NONCE_SIZE];
+pub type GcmTag = [u8; GCM_TAG_SIZE];
+
+/// multiply in GF(2^128) with GCM's bit-reflected convention (SP 800-38D algorithm 1)
+pub fn gf128_mul(x: u128, y: u128) -> u128 {
+    let mut z = 0u128;
+    let mut v = y;
+    for i in 0..128 {
+        // masks rather than branches so the timing doesn't depend on the hash subkey
+        z ^= v & 0u128.wrapping_sub((x >> (127 - i)) & 1);
+        v = (v >> 1) ^ (GCM_R & 0u128.wrapping_sub(v & 1));
+    }
+    z
+}
+
+/// GHASH (SP 800-38D section 6.4) under the hash subkey `h`
+struct Ghash {
+    h: u128,
+    y: u128,
+}
+
+impl Ghash {
+    fn new(h: u128) -> Self {
+        Ghash { h, y: 0 }
+    }
+
+    // absorb `data` zero-padded to a whole number of blocks
+    fn update_padded(&mut self, data: &[u8]) {
+        for chunk in data.chunks(16) {
+            let mut block = [0u8; 16];
+            block[..chunk.len()].copy_from_slice(chunk);
+            self.y = gf128_mul(self.y ^ u128::from_be_bytes(block), self.h);
+        }
+    }
+
+    fn finalize(mut self, aad_len: usize, ciphertext_len: usize) -> u128 {
+        let lengths = ((aad_len as u128 * 8) << 64) | (ciphertext_len as u128 * 8);
+        self.y = gf128_mul(self.y ^ lengths, self.h);
+        self.y
+    }
+}
+
+// the 96-bit nonce fast path - J0 is nonce || 1 and the message starts at counter 2
+fn counter_block(nonce: &GcmNonce, counter: u32) -> [u8; 16] {
+    let mut block = [0u8; 16];
+    block[..GCM_NONCE_SIZE].copy_from_slice(nonce);
+    block[GCM_NONCE_SIZE..].copy_from_slice(&counter.to_be_bytes());
+    block
+}
+
+fn check_len(len: usize) -> Result<(), AeadError> {
+    if len as u64 > GCM_MAX_MESSAGE_LEN {
+        return Err(AeadError::MessageTooLong);
+    }
+    Ok(())
+}
+
+/// AES-128-GCM with a 96-bit nonce and a full 128-bit tag (SP 800-38D)
+#[derive(Clone)]
+pub struct Aes128Gcm {
+    cipher: Aes128,
+    h: u128,
+}
+
+impl Aes128Gcm {
+    // GCTR from counter 2 - check_len keeps the 32-bit counter from wrapping
+    fn apply_keystream(&self, nonce: &GcmNonce, buffer: &mut [u8]) {
+        for (i, chunk) in buffer.chunks_mut(16).enumerate() {
+            let mut keystream = counter_block(nonce, 2 + i as u32);
+            self.cipher.encrypt_block(&mut keystream);
+            for (b, k) in chunk.iter_mut().zip(keystream) {
+                *b ^= k;
+            }
+        }
+    }
+
+    fn compute_tag(&self, nonce: &GcmNonce, aad: &[u8], ciphertext: &[u8]) -> GcmTag {
+        let mut ghash = Ghash::new(self.h);
+        ghash.update_padded(aad);
+        ghash.update_padded(ciphertext);
+        let s = ghash.finalize(aad.len(), ciphertext.len());
+
+        let mut tag = counter_block(nonce, 1);
+        self.cipher.encrypt_block(&mut tag);
+        (u128::from_be_bytes(tag) ^ s).to_be_bytes()
+    }
+}
+
+impl Aead for Aes128Gcm {
+    const KEY_SIZE: usize = 16;
+    const NONCE_SIZE: usize = GCM_NONCE_SIZE;
+    const TAG_SIZE: usize = GCM_TAG_SIZE;
+
+    type Key = AesKey128;
+    type Nonce = GcmNonce;
+    type Tag = GcmTag;
+
+    fn new(key: &AesKey128) -> Self {
+        let cipher = Aes128::new(key);
+        let mut h = [0u8; 16];
+        cipher.encrypt_block(&mut h);
+        Aes128Gcm {
+            cipher,
+            h: u128::from_be_bytes(h),
+        }
+    }
+
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &GcmNonce,
+        aad: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<GcmTag, AeadError> {
+        check_len(buffer.len())?;
+
+        self.apply_keystream(nonce, buffer);
+        Ok(self.compute_tag(nonce, aad, buffer))
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &GcmNonce,
+        aad: &[u8],
+        buffer: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), AeadError> {
+        check_len(buffer.len())?;
+
+        let expected = self.compute_tag(nonce, aad, buffer);
+        if tag.len() != GCM_TAG_SIZE || !ct_eq(&expected, tag) {
+            return Err(AeadError::AuthenticationFailed);
+        }
+
+        self.apply_keystream(nonce, buffer);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chacha20::chacha20poly1305::ChaCha20Poly1305;
+    use crate::test_util::{hex, wycheproof_aead};
+
+    fn check(key: &str, nonce: &str, aad: &str, plaintext: &str, ciphertext: &str, tag: &str) {
+        let aead = Aes128Gcm::new(&hex(key).try_into().unwrap());
+        let nonce: GcmNonce = hex(nonce).try_into().unwrap();
+        let (aad, plaintext) = (hex(aad), hex(plaintext));
+
+        let mut buffer = plaintext.clone();
+        let computed = aead
+            .encrypt_in_place_detached(&nonce, &aad, &mut buffer)
+            .unwrap();
+        assert_eq!(buffer, hex(ciphertext));
+        assert_eq!(computed.to_vec(), hex(tag));
+
+        aead.decrypt_in_place_detached(&nonce, &aad, &mut buffer, &computed)
+            .unwrap();
+        assert_eq!(buffer, plaintext);
+    }
+
+    #[test]
+    fn test_gcm_spec_vectors() {
+        // test cases 1 to 4 from McGrew and Viega's GCM specification
+        let zero_key = "00000000000000000000000000000000";
+        let zero_nonce = "000000000000000000000000";
+        check(
+            zero_key,
+            zero_nonce,
+            "",
+            "",
+            "",
+            "58e2fccefa7e3061367f1d57a4e7455a",
+        );
+        check(
+            zero_key,
+            zero_nonce,
+            "",
+            "00000000000000000000000000000000",
+            "0388dace60b6a392f328c2b971b2fe78",
+            "ab6e47d42cec13bdf53a67b21257bddf",
+        );
+
+        let key = "feffe9928665731c6d6a8f9467308308";
+        let nonce = "cafebabefacedbaddecaf888";
+        let plaintext = "d9313225f88406e5a55909c5aff5269a86a7a9531534f7da2e4c303d8a318a72
+                         1c3c0c95956809532fcf0e2449a6b525b16aedf5aa0de657ba637b391aafd255";
+        let ciphertext = "42831ec2217774244b7221b784d0d49ce3aa212f2c02a4e035c17e2329aca12e
+                          21d514b25466931c7d8f6a5aac84aa051ba30b396a0aac973d58e091473f5985";
+        check(
+            key,
+            nonce,
+            "",
+            plaintext,
+            ciphertext,
+            "4d5c2af327cd64a62cf35abd2ba6fab4",
+        );
+        // the same message cut to 60 bytes, with associated data
+        check(
+            key,
+            nonce,
+            "feedfacedeadbeeffeedfacedeadbeefabaddad2",
+            &plaintext.replace(char::is_whitespace, "")[..120],
+            &ciphertext.replace(char::is_whitespace, "")[..120],
+            "5bc94fbc3221a5db94fae95ae7121a47",
+        );
+    }
+
+    #[test]
+    fn test_wycheproof() {
+        assert_eq!(
+            wycheproof_aead::<Aes128Gcm>(include_str!("wycheproof/aes_gcm_test.txt")),
+            49
+        );
+    }
+
+    #[test]
+    fn test_tampering_is_rejected() {
+        let aead = Aes128Gcm::new(&[0x61; 16]);
+        let nonce = [0x62; GCM_NONCE_SIZE];
+        let sealed = aead.encrypt(&nonce, b"header", b"attack at dawn").unwrap();
+
+        for i in 0..sealed.len() {
+            let mut tampered = sealed.clone();
+            tampered[i] ^= 0x01;
+            assert_eq!(
+                aead.decrypt(&nonce, b"header", &tampered),
+                Err(AeadError::AuthenticationFailed)
+            );
+        }
+        assert_eq!(
+            aead.decrypt(&nonce, b"headers", &sealed),
+            Err(AeadError::AuthenticationFailed)
+        );
+
+        // a failed open leaves the ciphertext in place
+        let mut buffer = sealed[..sealed.len() - GCM_TAG_SIZE].to_vec();
+        let tag = &sealed[sealed.len() - GCM_TAG_SIZE..];
+        assert!(aead
+            .decrypt_in_place_detached(&nonce, b"", &mut buffer, tag)
+            .is_err());
+        assert_eq!(buffer, &sealed[..sealed.len() - GCM_TAG_SIZE]);
+
+        // only the full tag is accepted
+        for len in [0, 1, 8, 15] {
+            assert_eq!(
+                aead.decrypt_in_place_detached(&nonce, b"header", &mut buffer, &tag[..len]),
+                Err(AeadError::AuthenticationFailed)
+            );
+        }
+    }
+
+    // a format written once against the trait, with the cipher picked by the caller
+    fn seal_record<A: Aead>(aead: &A, nonce: &A::Nonce, record: &[u8]) -> Vec<u8> {
+        let sealed = aead.encrypt(nonce, b"record v1", record).unwrap();
+        assert_eq!(sealed.len(), record.len() + A::TAG_SIZE);
+        assert_eq!(aead.decrypt(nonce, b"record v1", &sealed).unwrap(), record);
+        sealed
+    }
+
+    #[test]
+    fn test_shares_the_aead_trait() {
+        let record = b"the same record under either cipher";
+        let gcm = seal_record(&Aes128Gcm::new(&[0x01; 16]), &[0x02; 12], record);
+        let chacha = seal_record(&ChaCha20Poly1305::new(&[0x01; 32]), &[0x02; 12], record);
+        assert_ne!(gcm, chacha);
+    }
+}
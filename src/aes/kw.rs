@@ -0,0 +1,669 @@
+use crate::block_cipher::BlockCipher;
+use crate::ct::ct_eq;
+use std::fmt;
+
+// RFC 3394 section 2.2.3.1
+pub const KW_DEFAULT_IV: [u8; 8] = [0xa6; 8];
+// RFC 5649 section 3 - the low 32 bits of the alternative IV hold the message length
+pub const KWP_AIV_PREFIX: [u8; 4] = [0xa6, 0x59, 0x59, 0xa6];
+
+const SEMIBLOCK: usize = 8;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyWrapError {
+    /// input is not a valid length for the wrap/unwrap operation
+    InvalidLength,
+    /// the recovered integrity check value did not match the expected value
+    IntegrityCheckFailed,
+    /// the caller-provided output buffer can't hold the result
+    OutputTooSmall,
+}
+
+impl fmt::Display for KeyWrapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeyWrapError::InvalidLength => write!(f, "invalid key wrap input length"),
+            KeyWrapError::IntegrityCheckFailed => write!(f, "key wrap integrity check failed"),
+            KeyWrapError::OutputTooSmall => write!(f, "output buffer too small"),
+        }
+    }
+}
+
+impl std::error::Error for KeyWrapError {}
+
+// a block is two semiblocks, so KW and KWP are only defined for 128-bit block ciphers
+fn semiblocks<C: BlockCipher>(a: &[u8; 8], r: &[u8]) -> [u8; 16] {
+    assert_eq!(C::BLOCK_SIZE, 16, "KW and KWP need a 128-bit block cipher");
+
+    let mut block = [0u8; 16];
+    block[..8].copy_from_slice(a);
+    block[8..].copy_from_slice(r);
+    block
+}
+
+fn encrypt_semiblocks<C: BlockCipher>(a: &[u8; 8], r: &[u8], cipher: &C) -> [u8; 16] {
+    let mut block = semiblocks::<C>(a, r);
+    cipher.encrypt_block(&mut block);
+    block
+}
+
+fn decrypt_semiblocks<C: BlockCipher>(a: &[u8; 8], r: &[u8], cipher: &C) -> [u8; 16] {
+    let mut block = semiblocks::<C>(a, r);
+    cipher.decrypt_block(&mut block);
+    block
+}
+
+fn xor_counter(a: &mut [u8; 8], t: u64) {
+    for (byte, t_byte) in a.iter_mut().zip(t.to_be_bytes()) {
+        *byte ^= t_byte;
+    }
+}
+
+/// wrapping function `W` (SP 800-38F algorithm 1) - `r` holds the n >= 2 semiblocks to be
+/// wrapped and is overwritten with the output semiblocks
+fn wrap_core<C: BlockCipher>(iv: [u8; 8], r: &mut [u8], cipher: &C) -> [u8; 8] {
+    let n = r.len() / SEMIBLOCK;
+    let mut a = iv;
+
+    for j in 0..6 {
+        for (i, r_i) in r.chunks_exact_mut(SEMIBLOCK).enumerate() {
+            let b = encrypt_semiblocks(&a, r_i, cipher);
+            a.copy_from_slice(&b[..8]);
+            xor_counter(&mut a, (n * j + i + 1) as u64);
+            r_i.copy_from_slice(&b[8..]);
+        }
+    }
+
+    a
+}
+
+/// unwrapping function `W^-1` (SP 800-38F algorithm 2) - returns the recovered integrity check
+/// value and overwrites `r` with the unwrapped semiblocks
+fn unwrap_core<C: BlockCipher>(a_in: [u8; 8], r: &mut [u8], cipher: &C) -> [u8; 8] {
+    let n = r.len() / SEMIBLOCK;
+    let mut a = a_in;
+
+    for j in (0..6).rev() {
+        for (i, r_i) in r.chunks_exact_mut(SEMIBLOCK).enumerate().rev() {
+            xor_counter(&mut a, (n * j + i + 1) as u64);
+            let b = decrypt_semiblocks(&a, r_i, cipher);
+            a.copy_from_slice(&b[..8]);
+            r_i.copy_from_slice(&b[8..]);
+        }
+    }
+
+    a
+}
+
+/// size of the output of `kw_wrap` for `len` bytes of key data
+pub fn kw_wrapped_len(len: usize) -> usize {
+    len + SEMIBLOCK
+}
+
+/// size of the output of `kwp_wrap` for `len` bytes of key data, which is zero padded out to a
+/// whole number of semiblocks
+pub fn kwp_wrapped_len(len: usize) -> usize {
+    len.div_ceil(SEMIBLOCK) * SEMIBLOCK + SEMIBLOCK
+}
+
+fn check_kw_key_data_len(len: usize) -> Result<(), KeyWrapError> {
+    if len < 2 * SEMIBLOCK || !len.is_multiple_of(SEMIBLOCK) {
+        return Err(KeyWrapError::InvalidLength);
+    }
+
+    Ok(())
+}
+
+fn check_kw_wrapped_len(len: usize) -> Result<(), KeyWrapError> {
+    if len < 3 * SEMIBLOCK || !len.is_multiple_of(SEMIBLOCK) {
+        return Err(KeyWrapError::InvalidLength);
+    }
+
+    Ok(())
+}
+
+fn check_kwp_key_data_len(len: usize) -> Result<(), KeyWrapError> {
+    if len == 0 || len as u64 > u32::MAX as u64 {
+        return Err(KeyWrapError::InvalidLength);
+    }
+
+    Ok(())
+}
+
+fn check_kwp_wrapped_len(len: usize) -> Result<(), KeyWrapError> {
+    if len < 2 * SEMIBLOCK || !len.is_multiple_of(SEMIBLOCK) {
+        return Err(KeyWrapError::InvalidLength);
+    }
+
+    Ok(())
+}
+
+/// KW-unwrap the semiblocks `r` under the first semiblock `a` - on failure `r` is zeroed, so
+/// that unverified key material isn't handed back in the caller's buffer
+fn kw_unwrap_semiblocks<C: BlockCipher>(
+    a: [u8; 8],
+    r: &mut [u8],
+    cipher: &C,
+) -> Result<(), KeyWrapError> {
+    let a = unwrap_core(a, r, cipher);
+    if !ct_eq(&a, &KW_DEFAULT_IV) {
+        r.fill(0);
+        return Err(KeyWrapError::IntegrityCheckFailed);
+    }
+
+    Ok(())
+}
+
+/// KWP-wrap the zero-padded semiblocks `r`, which hold `len` bytes of key data - returns the
+/// first output semiblock
+fn kwp_wrap_semiblocks<C: BlockCipher>(len: usize, r: &mut [u8], cipher: &C) -> [u8; 8] {
+    let mut aiv = [0u8; 8];
+    aiv[..4].copy_from_slice(&KWP_AIV_PREFIX);
+    aiv[4..].copy_from_slice(&(len as u32).to_be_bytes());
+
+    // a single padded semiblock is encrypted directly as one block with the AIV
+    if r.len() == SEMIBLOCK {
+        let c = encrypt_semiblocks(&aiv, r, cipher);
+        r.copy_from_slice(&c[8..]);
+        c[..8].try_into().unwrap()
+    } else {
+        wrap_core(aiv, r, cipher)
+    }
+}
+
+/// KWP-unwrap the semiblocks `r` under the first semiblock `a`, checking the AIV prefix, the
+/// encoded message length and that all padding bytes are zero - returns the unpadded length,
+/// and zeroes `r` on failure
+fn kwp_unwrap_semiblocks<C: BlockCipher>(
+    a: [u8; 8],
+    r: &mut [u8],
+    cipher: &C,
+) -> Result<usize, KeyWrapError> {
+    let a = if r.len() == SEMIBLOCK {
+        let p = decrypt_semiblocks(&a, r, cipher);
+        r.copy_from_slice(&p[8..]);
+        p[..8].try_into().unwrap()
+    } else {
+        unwrap_core(a, r, cipher)
+    };
+
+    let padded_len = r.len();
+    let mli = u32::from_be_bytes(a[4..].try_into().unwrap()) as usize;
+
+    // all checks are folded together so that a failure doesn't reveal which one tripped
+    let mut valid = ct_eq(&a[..4], &KWP_AIV_PREFIX);
+    valid &= mli > padded_len - SEMIBLOCK && mli <= padded_len;
+
+    let mli = mli.min(padded_len);
+    valid &= r[mli..].iter().fold(0u8, |acc, b| acc | b) == 0;
+
+    if !valid {
+        r.fill(0);
+        return Err(KeyWrapError::IntegrityCheckFailed);
+    }
+
+    Ok(mli)
+}
+
+/// Key Wrap (RFC 3394) - `key_data` must be at least two 64-bit semiblocks long and a
+/// multiple of 8 bytes
+pub fn kw_wrap<C: BlockCipher>(cipher: &C, key_data: &[u8]) -> Result<Vec<u8>, KeyWrapError> {
+    let mut output = key_data.to_vec();
+    kw_wrap_in_place(cipher, &mut output)?;
+    Ok(output)
+}
+
+/// Key Wrap of the key data held in `buffer`, which is replaced with the wrapped key - the
+/// existing allocation is reused if it has room for the extra semiblock
+pub fn kw_wrap_in_place<C: BlockCipher>(
+    cipher: &C,
+    buffer: &mut Vec<u8>,
+) -> Result<(), KeyWrapError> {
+    let len = buffer.len();
+    check_kw_key_data_len(len)?;
+
+    buffer.resize(kw_wrapped_len(len), 0);
+    buffer.copy_within(..len, SEMIBLOCK);
+
+    let a = wrap_core(KW_DEFAULT_IV, &mut buffer[SEMIBLOCK..], cipher);
+    buffer[..SEMIBLOCK].copy_from_slice(&a);
+    Ok(())
+}
+
+/// Key Wrap of `key_data` into the start of `output`, which must be at least
+/// `kw_wrapped_len(key_data.len())` bytes long - returns the number of bytes written
+pub fn kw_wrap_into<C: BlockCipher>(
+    cipher: &C,
+    key_data: &[u8],
+    output: &mut [u8],
+) -> Result<usize, KeyWrapError> {
+    check_kw_key_data_len(key_data.len())?;
+
+    let output_len = kw_wrapped_len(key_data.len());
+    if output.len() < output_len {
+        return Err(KeyWrapError::OutputTooSmall);
+    }
+
+    let (a, r) = output[..output_len].split_at_mut(SEMIBLOCK);
+    r.copy_from_slice(key_data);
+    a.copy_from_slice(&wrap_core(KW_DEFAULT_IV, r, cipher));
+    Ok(output_len)
+}
+
+/// Key Unwrap (RFC 3394) - fails with `IntegrityCheckFailed` if the wrapped data was
+/// tampered with or wrapped under a different key
+pub fn kw_unwrap<C: BlockCipher>(cipher: &C, wrapped: &[u8]) -> Result<Vec<u8>, KeyWrapError> {
+    let mut output = wrapped.to_vec();
+    let len = kw_unwrap_in_place(cipher, &mut output)?.len();
+
+    output.copy_within(SEMIBLOCK.., 0);
+    output.truncate(len);
+    Ok(output)
+}
+
+/// Key Unwrap of the wrapped key held in `buffer`, in place - on success, returns the key
+/// data, which is left in `buffer` directly after the first semiblock
+pub fn kw_unwrap_in_place<'a, C: BlockCipher>(
+    cipher: &C,
+    buffer: &'a mut [u8],
+) -> Result<&'a [u8], KeyWrapError> {
+    check_kw_wrapped_len(buffer.len())?;
+
+    let (a, r) = buffer.split_at_mut(SEMIBLOCK);
+    kw_unwrap_semiblocks(a.try_into().unwrap(), r, cipher)?;
+    Ok(r)
+}
+
+/// Key Unwrap of `wrapped` into the start of `output`, which must have room for the key
+/// data (`wrapped.len()` less one semiblock) - returns the number of bytes written
+pub fn kw_unwrap_into<C: BlockCipher>(
+    cipher: &C,
+    wrapped: &[u8],
+    output: &mut [u8],
+) -> Result<usize, KeyWrapError> {
+    check_kw_wrapped_len(wrapped.len())?;
+
+    let (a, r) = wrapped.split_at(SEMIBLOCK);
+    if output.len() < r.len() {
+        return Err(KeyWrapError::OutputTooSmall);
+    }
+
+    let output = &mut output[..r.len()];
+    output.copy_from_slice(r);
+    kw_unwrap_semiblocks(a.try_into().unwrap(), output, cipher)?;
+    Ok(r.len())
+}
+
+/// Key Wrap with Padding (RFC 5649) - accepts any key data between 1 and 2^32 bytes long
+pub fn kwp_wrap<C: BlockCipher>(cipher: &C, key_data: &[u8]) -> Result<Vec<u8>, KeyWrapError> {
+    let mut output = key_data.to_vec();
+    kwp_wrap_in_place(cipher, &mut output)?;
+    Ok(output)
+}
+
+/// Key Wrap with Padding of the key data held in `buffer`, which is replaced with the
+/// wrapped key - the existing allocation is reused if it has room for the padding and the
+/// extra semiblock
+pub fn kwp_wrap_in_place<C: BlockCipher>(
+    cipher: &C,
+    buffer: &mut Vec<u8>,
+) -> Result<(), KeyWrapError> {
+    let len = buffer.len();
+    check_kwp_key_data_len(len)?;
+
+    buffer.resize(kwp_wrapped_len(len), 0);
+    buffer.copy_within(..len, SEMIBLOCK);
+    buffer[SEMIBLOCK + len..].fill(0);
+
+    let a = kwp_wrap_semiblocks(len, &mut buffer[SEMIBLOCK..], cipher);
+    buffer[..SEMIBLOCK].copy_from_slice(&a);
+    Ok(())
+}
+
+/// Key Wrap with Padding of `key_data` into the start of `output`, which must be at least
+/// `kwp_wrapped_len(key_data.len())` bytes long - returns the number of bytes written
+pub fn kwp_wrap_into<C: BlockCipher>(
+    cipher: &C,
+    key_data: &[u8],
+    output: &mut [u8],
+) -> Result<usize, KeyWrapError> {
+    check_kwp_key_data_len(key_data.len())?;
+
+    let output_len = kwp_wrapped_len(key_data.len());
+    if output.len() < output_len {
+        return Err(KeyWrapError::OutputTooSmall);
+    }
+
+    let (a, r) = output[..output_len].split_at_mut(SEMIBLOCK);
+    r[..key_data.len()].copy_from_slice(key_data);
+    r[key_data.len()..].fill(0);
+    a.copy_from_slice(&kwp_wrap_semiblocks(key_data.len(), r, cipher));
+    Ok(output_len)
+}
+
+/// Key Unwrap with Padding (RFC 5649) - verifies the AIV prefix, the encoded message length
+/// and that all padding bytes are zero before returning the unpadded key data
+pub fn kwp_unwrap<C: BlockCipher>(cipher: &C, wrapped: &[u8]) -> Result<Vec<u8>, KeyWrapError> {
+    let mut output = wrapped.to_vec();
+    let len = kwp_unwrap_in_place(cipher, &mut output)?.len();
+
+    output.copy_within(SEMIBLOCK.., 0);
+    output.truncate(len);
+    Ok(output)
+}
+
+/// Key Unwrap with Padding of the wrapped key held in `buffer`, in place - on success,
+/// returns the unpadded key data, which is left in `buffer` directly after the first semiblock
+pub fn kwp_unwrap_in_place<'a, C: BlockCipher>(
+    cipher: &C,
+    buffer: &'a mut [u8],
+) -> Result<&'a [u8], KeyWrapError> {
+    check_kwp_wrapped_len(buffer.len())?;
+
+    let (a, r) = buffer.split_at_mut(SEMIBLOCK);
+    let len = kwp_unwrap_semiblocks(a.try_into().unwrap(), r, cipher)?;
+    Ok(&r[..len])
+}
+
+/// Key Unwrap with Padding of `wrapped` into the start of `output` - returns the length of
+/// the unpadded key data
+///
+/// `output` must have room for the padded key data (`wrapped.len()` less one semiblock), since
+/// the padding length isn't known until the data is unwrapped
+pub fn kwp_unwrap_into<C: BlockCipher>(
+    cipher: &C,
+    wrapped: &[u8],
+    output: &mut [u8],
+) -> Result<usize, KeyWrapError> {
+    check_kwp_wrapped_len(wrapped.len())?;
+
+    let (a, r) = wrapped.split_at(SEMIBLOCK);
+    if output.len() < r.len() {
+        return Err(KeyWrapError::OutputTooSmall);
+    }
+
+    let output = &mut output[..r.len()];
+    output.copy_from_slice(r);
+    kwp_unwrap_semiblocks(a.try_into().unwrap(), output, cipher)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::aes::{Aes128, AesKey128};
+    use crate::camellia::camellia::Camellia;
+    use crate::test_util::{for_each_128_bit_cipher, hex, CipherCheck};
+
+    #[test]
+    fn test_kw_rfc3394_128_bit_kek() {
+        // RFC 3394 section 4.1
+        let kek: AesKey128 = [
+            0x00, 0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d,
+            0x0e, 0x0f,
+        ];
+        let key_data: [u8; 16] = [
+            0x00, 0x11, 0x22, 0x33, 0x44, 0x55, 0x66, 0x77, 0x88, 0x99, 0xaa, 0xbb, 0xcc, 0xdd,
+            0xee, 0xff,
+        ];
+        let expected: [u8; 24] = [
+            0x1f, 0xa6, 0x8b, 0x0a, 0x81, 0x12, 0xb4, 0x47, 0xae, 0xf3, 0x4b, 0xd8, 0xfb, 0x5a,
+            0x7b, 0x82, 0x9d, 0x3e, 0x86, 0x23, 0x71, 0xd2, 0xcf, 0xe5,
+        ];
+
+        let cipher = Aes128::new(&kek);
+        let wrapped = kw_wrap(&cipher, &key_data).unwrap();
+        assert_eq!(wrapped, expected);
+
+        let unwrapped = kw_unwrap(&cipher, &wrapped).unwrap();
+        assert_eq!(unwrapped, key_data);
+    }
+
+    #[test]
+    fn test_kw_unwrap_integrity_failure() {
+        let cipher = Aes128::new(&[0x61; 16]);
+        let mut wrapped = kw_wrap(&cipher, &[0x41; 32]).unwrap();
+        wrapped[12] ^= 0x01;
+
+        assert_eq!(
+            kw_unwrap(&cipher, &wrapped),
+            Err(KeyWrapError::IntegrityCheckFailed)
+        );
+
+        let wrong_key = Aes128::new(&[0x62; 16]);
+        wrapped[12] ^= 0x01;
+        assert_eq!(
+            kw_unwrap(&wrong_key, &wrapped),
+            Err(KeyWrapError::IntegrityCheckFailed)
+        );
+    }
+
+    #[test]
+    fn test_kw_invalid_lengths() {
+        let cipher = Aes128::new(&[0x61; 16]);
+
+        assert_eq!(
+            kw_wrap(&cipher, &[0x41; 8]),
+            Err(KeyWrapError::InvalidLength)
+        );
+        assert_eq!(
+            kw_wrap(&cipher, &[0x41; 20]),
+            Err(KeyWrapError::InvalidLength)
+        );
+        assert_eq!(
+            kw_unwrap(&cipher, &[0x41; 16]),
+            Err(KeyWrapError::InvalidLength)
+        );
+    }
+
+    // the RFC 5649 section 6 inputs wrapped under the first 128 bits of its 192-bit KEK
+    #[test]
+    fn test_kwp_multi_block() {
+        let kek: AesKey128 = [
+            0x58, 0x40, 0xdf, 0x6e, 0x29, 0xb0, 0x2a, 0xf1, 0xab, 0x49, 0x3b, 0x70, 0x5b, 0xf1,
+            0x6e, 0xa1,
+        ];
+        let key_data: [u8; 20] = [
+            0xc3, 0x7b, 0x7e, 0x64, 0x92, 0x58, 0x43, 0x40, 0xbe, 0xd1, 0x22, 0x07, 0x80, 0x89,
+            0x41, 0x15, 0x50, 0x68, 0xf7, 0x38,
+        ];
+        let expected: [u8; 32] = [
+            0xed, 0x9f, 0x0e, 0xcf, 0xbb, 0x76, 0x1b, 0x73, 0x65, 0x83, 0x87, 0x33, 0xe3, 0xf4,
+            0x2f, 0x81, 0xa0, 0x49, 0xf0, 0x77, 0xe9, 0x01, 0xf6, 0x3b, 0xfe, 0x05, 0x19, 0xe8,
+            0xa1, 0x2e, 0x9b, 0xcf,
+        ];
+
+        let cipher = Aes128::new(&kek);
+        let wrapped = kwp_wrap(&cipher, &key_data).unwrap();
+        assert_eq!(wrapped, expected);
+
+        let unwrapped = kwp_unwrap(&cipher, &wrapped).unwrap();
+        assert_eq!(unwrapped, key_data);
+    }
+
+    #[test]
+    fn test_kwp_single_block() {
+        let kek: AesKey128 = [
+            0x58, 0x40, 0xdf, 0x6e, 0x29, 0xb0, 0x2a, 0xf1, 0xab, 0x49, 0x3b, 0x70, 0x5b, 0xf1,
+            0x6e, 0xa1,
+        ];
+        let key_data: [u8; 7] = [0x46, 0x6f, 0x72, 0x50, 0x61, 0x73, 0x69];
+        let expected: [u8; 16] = [
+            0x21, 0xf7, 0x57, 0x1c, 0x65, 0x31, 0xcc, 0x23, 0x8b, 0xab, 0xa6, 0x6b, 0xe3, 0xf0,
+            0x66, 0x2f,
+        ];
+
+        let cipher = Aes128::new(&kek);
+        let wrapped = kwp_wrap(&cipher, &key_data).unwrap();
+        assert_eq!(wrapped, expected);
+
+        let unwrapped = kwp_unwrap(&cipher, &wrapped).unwrap();
+        assert_eq!(unwrapped, key_data);
+    }
+
+    #[test]
+    fn test_kwp_unwrap_integrity_failure() {
+        let cipher = Aes128::new(&[0x61; 16]);
+
+        for len in [1, 7, 8, 9, 20, 33] {
+            let key_data = vec![0x41; len];
+            let mut wrapped = kwp_wrap(&cipher, &key_data).unwrap();
+            assert_eq!(kwp_unwrap(&cipher, &wrapped).unwrap(), key_data);
+
+            wrapped[0] ^= 0x80;
+            assert_eq!(
+                kwp_unwrap(&cipher, &wrapped),
+                Err(KeyWrapError::IntegrityCheckFailed)
+            );
+        }
+
+        assert_eq!(kwp_wrap(&cipher, &[]), Err(KeyWrapError::InvalidLength));
+    }
+
+    #[test]
+    fn test_kw_in_place_and_into() {
+        let cipher = Aes128::new(&[0x61; 16]);
+        let key_data: Vec<u8> = (0..32).collect();
+        let expected = kw_wrap(&cipher, &key_data).unwrap();
+
+        let mut buffer = key_data.clone();
+        kw_wrap_in_place(&cipher, &mut buffer).unwrap();
+        assert_eq!(buffer, expected);
+        assert_eq!(kw_unwrap_in_place(&cipher, &mut buffer).unwrap(), key_data);
+
+        let mut output = [0u8; 48];
+        assert_eq!(
+            kw_wrap_into(&cipher, &key_data, &mut output[..39]),
+            Err(KeyWrapError::OutputTooSmall)
+        );
+        assert_eq!(kw_wrap_into(&cipher, &key_data, &mut output), Ok(40));
+        assert_eq!(output[..40], expected);
+
+        let mut unwrapped = [0u8; 32];
+        assert_eq!(
+            kw_unwrap_into(&cipher, &expected, &mut unwrapped[..31]),
+            Err(KeyWrapError::OutputTooSmall)
+        );
+        assert_eq!(kw_unwrap_into(&cipher, &expected, &mut unwrapped), Ok(32));
+        assert_eq!(unwrapped[..], key_data);
+
+        assert_eq!(
+            kw_wrap_in_place(&cipher, &mut vec![0x41; 12]),
+            Err(KeyWrapError::InvalidLength)
+        );
+        assert_eq!(
+            kw_unwrap_into(&cipher, &expected[..36], &mut unwrapped),
+            Err(KeyWrapError::InvalidLength)
+        );
+    }
+
+    #[test]
+    fn test_kw_failed_unwrap_clears_output() {
+        let cipher = Aes128::new(&[0x61; 16]);
+        let mut wrapped = kw_wrap(&cipher, &[0x41; 16]).unwrap();
+        wrapped[12] ^= 0x01;
+
+        let mut buffer = wrapped.clone();
+        assert_eq!(
+            kw_unwrap_in_place(&cipher, &mut buffer),
+            Err(KeyWrapError::IntegrityCheckFailed)
+        );
+        assert!(buffer[SEMIBLOCK..].iter().all(|&b| b == 0));
+
+        let mut output = [0xff; 16];
+        assert_eq!(
+            kw_unwrap_into(&cipher, &wrapped, &mut output),
+            Err(KeyWrapError::IntegrityCheckFailed)
+        );
+        assert_eq!(output, [0u8; 16]);
+    }
+
+    #[test]
+    fn test_kwp_in_place_and_into() {
+        let cipher = Aes128::new(&[0x61; 16]);
+
+        for len in [1, 7, 8, 9, 20, 33] {
+            let key_data: Vec<u8> = (0..len as u8).collect();
+            let expected = kwp_wrap(&cipher, &key_data).unwrap();
+            assert_eq!(expected.len(), kwp_wrapped_len(len));
+
+            let mut buffer = key_data.clone();
+            kwp_wrap_in_place(&cipher, &mut buffer).unwrap();
+            assert_eq!(buffer, expected);
+            assert_eq!(kwp_unwrap_in_place(&cipher, &mut buffer).unwrap(), key_data);
+
+            let mut output = vec![0xff; expected.len()];
+            assert_eq!(
+                kwp_wrap_into(&cipher, &key_data, &mut output[..expected.len() - 1]),
+                Err(KeyWrapError::OutputTooSmall)
+            );
+            assert_eq!(
+                kwp_wrap_into(&cipher, &key_data, &mut output),
+                Ok(expected.len())
+            );
+            assert_eq!(output, expected);
+
+            // the output has to have room for the padding, even though it's stripped
+            let mut unwrapped = vec![0u8; expected.len() - SEMIBLOCK];
+            if len % SEMIBLOCK != 0 {
+                assert_eq!(
+                    kwp_unwrap_into(&cipher, &expected, &mut unwrapped[..len]),
+                    Err(KeyWrapError::OutputTooSmall)
+                );
+            }
+            assert_eq!(kwp_unwrap_into(&cipher, &expected, &mut unwrapped), Ok(len));
+            assert_eq!(unwrapped[..len], key_data);
+        }
+
+        assert_eq!(
+            kwp_wrap_in_place(&cipher, &mut Vec::new()),
+            Err(KeyWrapError::InvalidLength)
+        );
+        assert_eq!(
+            kwp_unwrap_in_place(&cipher, &mut [0u8; 12]),
+            Err(KeyWrapError::InvalidLength)
+        );
+    }
+
+    struct RoundTrip;
+
+    impl CipherCheck for RoundTrip {
+        fn check<C: BlockCipher>(&self, cipher: &C) {
+            for len in [16, 24, 40] {
+                let key_data: Vec<u8> = (0..len as u8).collect();
+                let wrapped = kw_wrap(cipher, &key_data).unwrap();
+                assert_eq!(wrapped.len(), kw_wrapped_len(len));
+                assert_eq!(kw_unwrap(cipher, &wrapped).unwrap(), key_data);
+            }
+
+            for len in [1, 7, 8, 9, 20, 33] {
+                let key_data: Vec<u8> = (0..len as u8).collect();
+                let mut wrapped = kwp_wrap(cipher, &key_data).unwrap();
+                assert_eq!(wrapped.len(), kwp_wrapped_len(len));
+                assert_eq!(kwp_unwrap(cipher, &wrapped).unwrap(), key_data);
+
+                wrapped[0] ^= 0x80;
+                assert_eq!(
+                    kwp_unwrap(cipher, &wrapped),
+                    Err(KeyWrapError::IntegrityCheckFailed)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_128_bit_ciphers() {
+        for_each_128_bit_cipher(RoundTrip);
+    }
+
+    #[test]
+    fn test_kw_camellia_vector() {
+        // the RFC 3394 section 4.1 inputs under Camellia-128 (the RFC 3657 construction),
+        // checked against an independent implementation
+        let cipher = Camellia::new(&hex("000102030405060708090a0b0c0d0e0f"));
+        let key_data = hex("00112233445566778899aabbccddeeff");
+        assert_eq!(
+            kw_wrap(&cipher, &key_data).unwrap(),
+            hex("635d6ac46eedebd3a7f4a06421a4cbd1746b24795ba2f708")
+        );
+    }
+}
@@ -1,3 +1,9 @@
+#[allow(clippy::module_inception)]
 pub mod aes;
 pub mod cbc;
+pub mod cbc_cs;
+pub mod cmac;
 pub mod constant;
+pub mod ecb;
+pub mod gcm;
+pub mod kw;
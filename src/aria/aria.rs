@@ -0,0 +1,200 @@
+use super::constant::*;
+use crate::aes::constant::{SBOX_DECRYPT, SBOX_ENCRYPT};
+use crate::block_cipher::{BlockCipher, KeySizeError};
+
+const SB1: &[u8; 256] = &SBOX_ENCRYPT;
+const SB3: &[u8; 256] = &SBOX_DECRYPT;
+
+// substitution layer type 1, used in odd rounds
+fn sl1(x: u128) -> u128 {
+    let mut bytes = x.to_be_bytes();
+    for (i, b) in bytes.iter_mut().enumerate() {
+        *b = [SB1, &SB2, SB3, &SB4][i % 4][*b as usize];
+    }
+    u128::from_be_bytes(bytes)
+}
+
+// substitution layer type 2, used in even rounds - the inverse of type 1
+fn sl2(x: u128) -> u128 {
+    let mut bytes = x.to_be_bytes();
+    for (i, b) in bytes.iter_mut().enumerate() {
+        *b = [SB3, &SB4, SB1, &SB2][i % 4][*b as usize];
+    }
+    u128::from_be_bytes(bytes)
+}
+
+// the diffusion layer - an involutory 16x16 binary matrix, given here as the input bytes xored
+// into each output byte
+fn a(x: u128) -> u128 {
+    const ROWS: [[usize; 7]; 16] = [
+        [3, 4, 6, 8, 9, 13, 14],
+        [2, 5, 7, 8, 9, 12, 15],
+        [1, 4, 6, 10, 11, 12, 15],
+        [0, 5, 7, 10, 11, 13, 14],
+        [0, 2, 5, 8, 11, 14, 15],
+        [1, 3, 4, 9, 10, 14, 15],
+        [0, 2, 7, 9, 10, 12, 13],
+        [1, 3, 6, 8, 11, 12, 13],
+        [0, 1, 4, 7, 10, 13, 15],
+        [0, 1, 5, 6, 11, 12, 14],
+        [2, 3, 5, 6, 8, 13, 15],
+        [2, 3, 4, 7, 9, 12, 14],
+        [1, 2, 6, 7, 9, 11, 12],
+        [0, 3, 6, 7, 8, 10, 13],
+        [0, 3, 4, 5, 9, 11, 14],
+        [1, 2, 4, 5, 8, 10, 15],
+    ];
+
+    let x = x.to_be_bytes();
+    let mut y = [0u8; 16];
+    for (y, row) in y.iter_mut().zip(ROWS.iter()) {
+        *y = row.iter().fold(0, |acc, &i| acc ^ x[i]);
+    }
+    u128::from_be_bytes(y)
+}
+
+// the odd and even round functions
+fn fo(d: u128, rk: u128) -> u128 {
+    a(sl1(d ^ rk))
+}
+
+fn fe(d: u128, rk: u128) -> u128 {
+    a(sl2(d ^ rk))
+}
+
+/// the ARIA block cipher (RFC 5794, KS X 1213) - an SPN of 12, 14 or 16 rounds for 128, 192 and
+/// 256-bit keys
+#[derive(Clone)]
+pub struct Aria {
+    // one more round key than rounds - only the first `rounds + 1` are used
+    encrypt_keys: [u128; 17],
+    decrypt_keys: [u128; 17],
+    rounds: usize,
+}
+
+impl Aria {
+    /// the cipher under `key`, or `InvalidLength` unless it is 16, 24 or 32 bytes long - use
+    /// this rather than `new` for keys from outside the program
+    pub fn try_new(key: &[u8]) -> Result<Self, KeySizeError> {
+        let (rounds, ck) = match key.len() {
+            16 => (12, [C[0], C[1], C[2]]),
+            24 => (14, [C[1], C[2], C[0]]),
+            32 => (16, [C[2], C[0], C[1]]),
+            _ => return Err(KeySizeError::InvalidLength),
+        };
+
+        // KL is the first 128 bits of the key and KR the rest, zero padded
+        let mut padded = [0u8; 32];
+        padded[..key.len()].copy_from_slice(key);
+        let kl = u128::from_be_bytes(padded[..16].try_into().unwrap());
+        let kr = u128::from_be_bytes(padded[16..].try_into().unwrap());
+
+        let w0 = kl;
+        let w1 = fo(w0, ck[0]) ^ kr;
+        let w2 = fe(w1, ck[1]) ^ w0;
+        let w3 = fo(w2, ck[2]) ^ w1;
+        let w = [w0, w1, w2, w3];
+
+        // ek1 to ek4 pair each W with the next rotated right by 19, then 31, then left by 61 and
+        // 31, and ek17 starts the cycle again with a left rotation by 19
+        let mut encrypt_keys = [0u128; 17];
+        for (i, ek) in encrypt_keys.iter_mut().enumerate() {
+            let next = w[(i + 1) % 4];
+            *ek = w[i % 4]
+                ^ match i / 4 {
+                    0 => next.rotate_right(19),
+                    1 => next.rotate_right(31),
+                    2 => next.rotate_left(61),
+                    3 => next.rotate_left(31),
+                    _ => next.rotate_left(19),
+                };
+        }
+
+        // decryption runs the same network over the round keys reversed, with the diffusion
+        // layer applied to all but the outermost two
+        let mut decrypt_keys = [0u128; 17];
+        decrypt_keys[0] = encrypt_keys[rounds];
+        for i in 1..rounds {
+            decrypt_keys[i] = a(encrypt_keys[rounds - i]);
+        }
+        decrypt_keys[rounds] = encrypt_keys[0];
+
+        Ok(Aria {
+            encrypt_keys,
+            decrypt_keys,
+            rounds,
+        })
+    }
+
+    fn crypt(block: &mut [u8], round_keys: &[u128]) {
+        let rounds = round_keys.len() - 1;
+        let mut p = u128::from_be_bytes((&*block).try_into().unwrap());
+
+        for (i, rk) in round_keys[..rounds - 1].iter().enumerate() {
+            p = if i % 2 == 0 { fo(p, *rk) } else { fe(p, *rk) };
+        }
+        // the last round swaps the diffusion layer for a final key addition
+        p = sl2(p ^ round_keys[rounds - 1]) ^ round_keys[rounds];
+
+        block.copy_from_slice(&p.to_be_bytes());
+    }
+}
+
+impl BlockCipher for Aria {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+
+    /// a 128, 192 or 256-bit key
+    type Key = [u8];
+
+    /// panics unless `key` is 16, 24 or 32 bytes long - see `try_new`
+    fn new(key: &[u8]) -> Self {
+        Self::try_new(key).expect("aria keys are 16, 24 or 32 bytes")
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        Self::crypt(block, &self.encrypt_keys[..=self.rounds]);
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        Self::crypt(block, &self.decrypt_keys[..=self.rounds]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    #[test]
+    fn test_rfc5794_vectors() {
+        // RFC 5794 appendix A
+        let key = hex("000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f");
+        let plaintext = hex("00112233445566778899aabbccddeeff");
+
+        for (key_len, ciphertext) in [
+            (16, "d718fbd6ab644c739da95f3be6451778"),
+            (24, "26449c1805dbe7aa25a468ce263a9e79"),
+            (32, "f92bd7c79fb72e2f2b8f80c1972d24fc"),
+        ] {
+            let cipher = Aria::new(&key[..key_len]);
+            let mut block = plaintext.clone();
+            cipher.encrypt_block(&mut block);
+            assert_eq!(block, hex(ciphertext));
+            cipher.decrypt_block(&mut block);
+            assert_eq!(block, plaintext);
+        }
+    }
+
+    #[test]
+    fn test_rejects_other_key_sizes() {
+        for len in [0, 8, 15, 17, 23, 25, 31, 33, 64] {
+            assert_eq!(
+                Aria::try_new(&vec![0u8; len]).err(),
+                Some(KeySizeError::InvalidLength)
+            );
+        }
+        for len in [16, 24, 32] {
+            assert!(Aria::try_new(&vec![0u8; len]).is_ok());
+        }
+    }
+}
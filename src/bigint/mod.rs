@@ -0,0 +1,4 @@
+pub mod modular;
+#[cfg(test)]
+mod reference;
+pub mod uint;
@@ -0,0 +1,407 @@
+use super::uint::Uint;
+
+/// `a + b mod m` for `a` and `b` already below `m`, in constant time
+pub fn add_mod<const LIMBS: usize>(
+    a: &Uint<LIMBS>,
+    b: &Uint<LIMBS>,
+    m: &Uint<LIMBS>,
+) -> Uint<LIMBS> {
+    let (sum, carry) = a.overflowing_add(b);
+    let (reduced, borrow) = sum.overflowing_sub(m);
+    // the sum is below 2m, so one subtraction is enough - it's wanted when the sum overflowed
+    // or when subtracting didn't
+    Uint::ct_select(&sum, &reduced, carry | !borrow)
+}
+
+/// `a - b mod m` for `a` and `b` already below `m`, in constant time
+pub fn sub_mod<const LIMBS: usize>(
+    a: &Uint<LIMBS>,
+    b: &Uint<LIMBS>,
+    m: &Uint<LIMBS>,
+) -> Uint<LIMBS> {
+    let (diff, borrow) = a.overflowing_sub(b);
+    Uint::ct_select(&diff, &diff.wrapping_add(m), borrow)
+}
+
+/// `a * b mod m` for any modulus, odd or even - panics if `m` is zero
+///
+/// variable time - prefer `Montgomery::mul_mod` for secrets under an odd modulus
+pub fn mul_mod_vartime<const LIMBS: usize>(
+    a: &Uint<LIMBS>,
+    b: &Uint<LIMBS>,
+    m: &Uint<LIMBS>,
+) -> Uint<LIMBS> {
+    let (lo, hi) = a.widening_mul(b);
+    Uint::rem_wide_vartime(&lo, &hi, m)
+}
+
+/// the greatest common divisor, by Euclid's algorithm - variable time
+pub fn gcd_vartime<const LIMBS: usize>(a: &Uint<LIMBS>, b: &Uint<LIMBS>) -> Uint<LIMBS> {
+    let (mut a, mut b) = (*a, *b);
+    while !b.is_zero() {
+        (a, b) = (b, a.div_rem_vartime(&b).1);
+    }
+    a
+}
+
+/// the inverse of `a` modulo `m`, for any `m` above one - `None` when they share a factor
+///
+/// variable time - the extended Euclidean algorithm. the coefficients alternate in sign, so only
+/// their magnitudes are tracked, each the previous but one plus the quotient times the previous
+pub fn mod_inverse_vartime<const LIMBS: usize>(
+    a: &Uint<LIMBS>,
+    m: &Uint<LIMBS>,
+) -> Option<Uint<LIMBS>> {
+    if *m <= Uint::ONE {
+        return None;
+    }
+
+    let (mut r0, mut r1) = (*m, a.div_rem_vartime(m).1);
+    let (mut t0, mut t1) = (Uint::ZERO, Uint::ONE);
+    let mut steps = 0u32;
+
+    while !r1.is_zero() {
+        let (q, r) = r0.div_rem_vartime(&r1);
+        (r0, r1) = (r1, r);
+        // |t| never exceeds m, so this can't wrap
+        (t0, t1) = (t1, t0.wrapping_add(&q.wrapping_mul(&t1)));
+        steps += 1;
+    }
+
+    if r0 != Uint::ONE {
+        return None;
+    }
+
+    // after an even number of steps the coefficient is negative
+    Some(if steps % 2 == 1 {
+        t0
+    } else {
+        m.wrapping_sub(&t0)
+    })
+}
+
+/// Montgomery arithmetic modulo an odd `modulus` - values in Montgomery form are `x * R mod m`
+/// with R = 2^BITS, which turns the reduction after every multiplication into shifts
+///
+/// every operation runs in constant time except `pow_vartime`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Montgomery<const LIMBS: usize> {
+    modulus: Uint<LIMBS>,
+    // -m^-1 mod 2^64
+    m_inv: u64,
+    // R mod m, the Montgomery form of one
+    one: Uint<LIMBS>,
+    // R^2 mod m, used to convert into Montgomery form
+    r2: Uint<LIMBS>,
+}
+
+impl<const LIMBS: usize> Montgomery<LIMBS> {
+    /// `None` unless `modulus` is odd and above one
+    ///
+    /// setting up takes variable time in the modulus, which is assumed public
+    pub fn new(modulus: &Uint<LIMBS>) -> Option<Self> {
+        if !modulus.is_odd() || *modulus == Uint::ONE {
+            return None;
+        }
+
+        // Newton's iteration doubles the number of correct low bits of m^-1 each time - an odd
+        // m is its own inverse to 3 bits, and 3 * 2^5 > 64
+        let m0 = modulus.limbs()[0];
+        let mut inv = m0;
+        for _ in 0..5 {
+            inv = inv.wrapping_mul(2u64.wrapping_sub(m0.wrapping_mul(inv)));
+        }
+
+        // R - m < R, so R mod m is (R - m) mod m
+        let one = Uint::ZERO.wrapping_sub(modulus).div_rem_vartime(modulus).1;
+        let r2 = Uint::rem_wide_vartime(&Uint::ZERO, &one, modulus);
+
+        Some(Montgomery {
+            modulus: *modulus,
+            m_inv: inv.wrapping_neg(),
+            one,
+            r2,
+        })
+    }
+
+    pub fn modulus(&self) -> &Uint<LIMBS> {
+        &self.modulus
+    }
+
+    /// Montgomery reduction (REDC) - `(hi * 2^BITS + lo) * R^-1 mod m` for a double-width
+    /// value below `m * R`
+    pub fn reduce(&self, lo: &Uint<LIMBS>, hi: &Uint<LIMBS>) -> Uint<LIMBS> {
+        let m = self.modulus.limbs();
+        let mut t = *lo.limbs();
+        let mut top = 0u64;
+
+        // each pass adds the multiple of m that clears the lowest limb, then shifts down a limb
+        // and brings in the next limb of the high half
+        for &next in hi.limbs() {
+            let u = t[0].wrapping_mul(self.m_inv);
+            let mut carry = 0u64;
+            for j in 0..LIMBS {
+                let s = t[j] as u128 + u as u128 * m[j] as u128 + carry as u128;
+                if j > 0 {
+                    t[j - 1] = s as u64;
+                }
+                carry = (s >> 64) as u64;
+            }
+            let s = carry as u128 + next as u128 + top as u128;
+            t[LIMBS - 1] = s as u64;
+            top = (s >> 64) as u64;
+        }
+
+        // the result is below 2m - `|` rather than `||` so picking it doesn't branch
+        let t = Uint::from_limbs(t);
+        let (reduced, borrow) = t.overflowing_sub(&self.modulus);
+        Uint::ct_select(&t, &reduced, (top == 1) | !borrow)
+    }
+
+    /// Montgomery multiplication - `a * b * R^-1 mod m`, so the product of two values in
+    /// Montgomery form stays in Montgomery form
+    pub fn mul(&self, a: &Uint<LIMBS>, b: &Uint<LIMBS>) -> Uint<LIMBS> {
+        let (lo, hi) = a.widening_mul(b);
+        self.reduce(&lo, &hi)
+    }
+
+    /// convert `x` into Montgomery form - `x` may be any value, not just one below `m`
+    pub fn to_montgomery(&self, x: &Uint<LIMBS>) -> Uint<LIMBS> {
+        self.mul(x, &self.r2)
+    }
+
+    pub fn from_montgomery(&self, x: &Uint<LIMBS>) -> Uint<LIMBS> {
+        self.reduce(x, &Uint::ZERO)
+    }
+
+    /// `a * b mod m` for ordinary values, with `b` below `m` - multiplying by the Montgomery form
+    /// of `a` cancels the R^-1 of a single reduction
+    pub fn mul_mod(&self, a: &Uint<LIMBS>, b: &Uint<LIMBS>) -> Uint<LIMBS> {
+        self.mul(&self.to_montgomery(a), b)
+    }
+
+    /// `base^exp mod m` in constant time - a fixed 4-bit window over every bit of `exp`'s
+    /// width, reading the table through selects so neither the exponent's bits nor its length
+    /// show in the timing
+    pub fn pow(&self, base: &Uint<LIMBS>, exp: &Uint<LIMBS>) -> Uint<LIMBS> {
+        self.pow_bounded(base, exp, Uint::<LIMBS>::BITS)
+    }
+
+    /// `base^exp mod m` for an `exp` known to be below `2^bits` - constant time in `exp`, with
+    /// only the public bound deciding how many windows are processed
+    pub fn pow_bounded(&self, base: &Uint<LIMBS>, exp: &Uint<LIMBS>, bits: u32) -> Uint<LIMBS> {
+        let windows = bits.min(Uint::<LIMBS>::BITS).div_ceil(4);
+        let base = self.to_montgomery(base);
+        let mut table = [self.one; 16];
+        for i in 1..16 {
+            table[i] = self.mul(&table[i - 1], &base);
+        }
+
+        let mut acc = self.one;
+        for window in (0..windows).rev() {
+            for _ in 0..4 {
+                acc = self.mul(&acc, &acc);
+            }
+
+            let index = (exp.limbs()[window as usize / 16] >> (4 * (window % 16))) & 0xf;
+            let mut entry = table[0];
+            for (i, candidate) in table.iter().enumerate().skip(1) {
+                entry = Uint::ct_select(&entry, candidate, i as u64 == index);
+            }
+            acc = self.mul(&acc, &entry);
+        }
+
+        self.from_montgomery(&acc)
+    }
+
+    /// `base^exp mod m` by plain square and multiply - only for public exponents
+    pub fn pow_vartime(&self, base: &Uint<LIMBS>, exp: &Uint<LIMBS>) -> Uint<LIMBS> {
+        let base = self.to_montgomery(base);
+        let mut acc = self.one;
+        for i in (0..exp.bits_vartime()).rev() {
+            acc = self.mul(&acc, &acc);
+            if exp.bit(i) {
+                acc = self.mul(&acc, &base);
+            }
+        }
+        self.from_montgomery(&acc)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::reference::{random_uint, Big};
+    use super::super::uint::{U256, U512};
+    use super::*;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    type U320 = Uint<5>;
+
+    const CASES: usize = 300;
+
+    fn random_odd_modulus(rng: &mut impl Rng) -> U320 {
+        loop {
+            let mut m = random_uint::<5>(rng);
+            m.set_bit(0);
+            if m > Uint::ONE {
+                return m;
+            }
+        }
+    }
+
+    #[test]
+    fn test_montgomery_against_reference() {
+        let mut rng = StdRng::seed_from_u64(5);
+
+        for _ in 0..CASES {
+            let m = random_odd_modulus(&mut rng);
+            let mont = Montgomery::new(&m).unwrap();
+            let rm = Big::from_uint(&m);
+
+            let a = random_uint::<5>(&mut rng);
+            let b = random_uint::<5>(&mut rng).div_rem_vartime(&m).1;
+            let (ra, rb) = (Big::from_uint(&a), Big::from_uint(&b));
+
+            let a_mont = mont.to_montgomery(&a);
+            assert_eq!(
+                Big::from_uint(&a_mont),
+                ra.mul(&Big::power_of_two(320)).div_rem(&rm).1
+            );
+            assert_eq!(
+                Big::from_uint(&mont.from_montgomery(&a_mont)),
+                ra.div_rem(&rm).1
+            );
+
+            let expected = ra.mul(&rb).div_rem(&rm).1;
+            assert_eq!(Big::from_uint(&mont.mul_mod(&a, &b)), expected);
+            assert_eq!(Big::from_uint(&mul_mod_vartime(&a, &b, &m)), expected);
+        }
+    }
+
+    #[test]
+    fn test_pow_against_reference() {
+        let mut rng = StdRng::seed_from_u64(6);
+
+        for _ in 0..40 {
+            let m = random_odd_modulus(&mut rng);
+            let mont = Montgomery::new(&m).unwrap();
+            let (base, exp) = (random_uint::<5>(&mut rng), random_uint::<5>(&mut rng));
+
+            let expected =
+                Big::from_uint(&base).pow_mod(&Big::from_uint(&exp), &Big::from_uint(&m));
+            assert_eq!(Big::from_uint(&mont.pow(&base, &exp)), expected);
+            assert_eq!(Big::from_uint(&mont.pow_vartime(&base, &exp)), expected);
+
+            // an exponent cut down to a bound that needn't be a multiple of the window
+            let bits = rng.gen_range(1..=Uint::<5>::BITS);
+            let bounded = if bits == Uint::<5>::BITS {
+                exp
+            } else {
+                exp.wrapping_sub(&((exp >> bits) << bits))
+            };
+            let expected =
+                Big::from_uint(&base).pow_mod(&Big::from_uint(&bounded), &Big::from_uint(&m));
+            assert_eq!(
+                Big::from_uint(&mont.pow_bounded(&base, &bounded, bits)),
+                expected
+            );
+        }
+    }
+
+    #[test]
+    fn test_fermat_known_primes() {
+        // a^(p-1) = 1 mod p for 2^255 - 19 and 2^521 - 1
+        let p = (U256::ONE << 255).wrapping_sub(&U256::from_u64(19));
+        let mont = Montgomery::new(&p).unwrap();
+        let exp = p.wrapping_sub(&U256::ONE);
+        for a in [2, 3, 0xdeadbeef] {
+            assert_eq!(mont.pow(&U256::from_u64(a), &exp), U256::ONE);
+        }
+        // and a^((p-1)/2) is the Legendre symbol - 2 is a non-residue mod 2^255 - 19
+        assert_eq!(
+            mont.pow_vartime(&U256::from_u64(2), &(exp >> 1)),
+            p.wrapping_sub(&U256::ONE)
+        );
+
+        let p = (Uint::<9>::ONE << 521).wrapping_sub(&Uint::ONE);
+        let mont = Montgomery::new(&p).unwrap();
+        let exp = p.wrapping_sub(&Uint::ONE);
+        assert_eq!(mont.pow(&Uint::from_u64(5), &exp), Uint::ONE);
+    }
+
+    #[test]
+    fn test_mod_inverse() {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        for _ in 0..CASES {
+            let (a, m) = (random_uint::<5>(&mut rng), random_uint::<5>(&mut rng));
+            if m <= Uint::ONE {
+                assert_eq!(mod_inverse_vartime(&a, &m), None);
+                continue;
+            }
+
+            let rm = Big::from_uint(&m);
+            match mod_inverse_vartime(&a, &m) {
+                Some(inv) => {
+                    assert!(inv < m);
+                    let product = Big::from_uint(&a).mul(&Big::from_uint(&inv));
+                    assert_eq!(product.div_rem(&rm).1, Big::from_u64(1));
+                }
+                // no inverse means a common factor - check it against the reference's gcd
+                None => assert_ne!(gcd(Big::from_uint(&a), rm), Big::from_u64(1)),
+            }
+
+            assert_eq!(
+                Big::from_uint(&gcd_vartime(&a, &m)),
+                gcd(Big::from_uint(&a), Big::from_uint(&m))
+            );
+        }
+
+        // the textbook RSA example - e = 17 inverted modulo the even totient 3120
+        let phi = U512::from_u64(3120);
+        assert_eq!(
+            mod_inverse_vartime(&U512::from_u64(17), &phi),
+            Some(U512::from_u64(2753))
+        );
+        assert_eq!(mod_inverse_vartime(&U512::from_u64(2), &phi), None);
+    }
+
+    fn gcd(mut a: Big, mut b: Big) -> Big {
+        while b != Big::from_u64(0) {
+            (a, b) = (b.clone(), a.div_rem(&b).1);
+        }
+        a
+    }
+
+    #[test]
+    fn test_add_sub_mod() {
+        let mut rng = StdRng::seed_from_u64(8);
+
+        for _ in 0..CASES {
+            let m = random_uint::<5>(&mut rng);
+            if m.is_zero() {
+                continue;
+            }
+            let a = random_uint::<5>(&mut rng).div_rem_vartime(&m).1;
+            let b = random_uint::<5>(&mut rng).div_rem_vartime(&m).1;
+            let (ra, rb, rm) = (Big::from_uint(&a), Big::from_uint(&b), Big::from_uint(&m));
+
+            assert_eq!(
+                Big::from_uint(&add_mod(&a, &b, &m)),
+                ra.add(&rb).div_rem(&rm).1
+            );
+            assert_eq!(
+                Big::from_uint(&sub_mod(&a, &b, &m)),
+                ra.add(&rm).sub(&rb).div_rem(&rm).1
+            );
+        }
+    }
+
+    #[test]
+    fn test_rejects_unusable_modulus() {
+        assert_eq!(Montgomery::new(&U320::from_u64(10)), None);
+        assert_eq!(Montgomery::new(&U320::ONE), None);
+        assert_eq!(Montgomery::new(&U320::ZERO), None);
+        assert!(Montgomery::new(&U320::MAX).is_some());
+    }
+}
@@ -0,0 +1,150 @@
+// a deliberately naive arbitrary-precision integer for the property tests to check against -
+// 32-bit digits, schoolbook multiplication and bit-at-a-time division, nothing shared with the
+// real implementation
+use super::uint::Uint;
+use rand::Rng;
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Big(Vec<u32>);
+
+impl Big {
+    fn trim(mut digits: Vec<u32>) -> Big {
+        while digits.last() == Some(&0) {
+            digits.pop();
+        }
+        Big(digits)
+    }
+
+    pub fn from_u64(value: u64) -> Big {
+        Big::trim(vec![value as u32, (value >> 32) as u32])
+    }
+
+    pub fn from_uint<const LIMBS: usize>(x: &Uint<LIMBS>) -> Big {
+        let mut digits = Vec::new();
+        for limb in x.limbs() {
+            digits.push(*limb as u32);
+            digits.push((*limb >> 32) as u32);
+        }
+        Big::trim(digits)
+    }
+
+    fn bit(&self, i: usize) -> bool {
+        self.0.get(i / 32).is_some_and(|d| (d >> (i % 32)) & 1 == 1)
+    }
+
+    fn bits(&self) -> usize {
+        self.0
+            .last()
+            .map_or(0, |d| self.0.len() * 32 - d.leading_zeros() as usize)
+    }
+
+    pub fn add(&self, other: &Big) -> Big {
+        let mut out = Vec::new();
+        let mut carry = 0u64;
+        for i in 0..self.0.len().max(other.0.len()) {
+            let s = *self.0.get(i).unwrap_or(&0) as u64 + *other.0.get(i).unwrap_or(&0) as u64;
+            let s = s + carry;
+            out.push(s as u32);
+            carry = s >> 32;
+        }
+        out.push(carry as u32);
+        Big::trim(out)
+    }
+
+    /// panics if `other` is larger
+    pub fn sub(&self, other: &Big) -> Big {
+        assert!(self.cmp(other) != Ordering::Less);
+        let mut out = Vec::new();
+        let mut borrow = 0i64;
+        for i in 0..self.0.len() {
+            let mut d = self.0[i] as i64 - *other.0.get(i).unwrap_or(&0) as i64 - borrow;
+            borrow = 0;
+            if d < 0 {
+                d += 1 << 32;
+                borrow = 1;
+            }
+            out.push(d as u32);
+        }
+        Big::trim(out)
+    }
+
+    pub fn mul(&self, other: &Big) -> Big {
+        let mut out = vec![0u64; self.0.len() + other.0.len() + 1];
+        for (i, a) in self.0.iter().enumerate() {
+            for (j, b) in other.0.iter().enumerate() {
+                out[i + j] += *a as u64 * *b as u64;
+                // keep every position below 2^32 so the sums above can't overflow
+                let mut k = i + j;
+                while out[k] >> 32 != 0 {
+                    out[k + 1] += out[k] >> 32;
+                    out[k] &= 0xffffffff;
+                    k += 1;
+                }
+            }
+        }
+        Big::trim(out.into_iter().map(|d| d as u32).collect())
+    }
+
+    pub fn div_rem(&self, other: &Big) -> (Big, Big) {
+        assert!(!other.0.is_empty());
+        let mut quotient = vec![0u32; self.0.len()];
+        let mut rem = Big(Vec::new());
+        for i in (0..self.bits()).rev() {
+            rem = rem.add(&rem);
+            if self.bit(i) {
+                rem = rem.add(&Big::from_u64(1));
+            }
+            if rem.cmp(other) != Ordering::Less {
+                rem = rem.sub(other);
+                quotient[i / 32] |= 1 << (i % 32);
+            }
+        }
+        (Big::trim(quotient), rem)
+    }
+
+    pub fn pow_mod(&self, exp: &Big, m: &Big) -> Big {
+        let mut acc = Big::from_u64(1).div_rem(m).1;
+        for i in (0..exp.bits()).rev() {
+            acc = acc.mul(&acc).div_rem(m).1;
+            if exp.bit(i) {
+                acc = acc.mul(self).div_rem(m).1;
+            }
+        }
+        acc
+    }
+
+    /// 2^n
+    pub fn power_of_two(n: usize) -> Big {
+        let mut digits = vec![0u32; n / 32 + 1];
+        digits[n / 32] = 1 << (n % 32);
+        Big(digits)
+    }
+}
+
+impl PartialOrd for Big {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for Big {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .len()
+            .cmp(&other.0.len())
+            .then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+}
+
+/// a random value of random bit length, so that short operands and runs of ones and zeros
+/// turn up as often as full-width noise
+pub fn random_uint<const LIMBS: usize>(rng: &mut impl Rng) -> Uint<LIMBS> {
+    let bits = rng.gen_range(0..=Uint::<LIMBS>::BITS);
+    let limbs = match rng.gen_range(0..4) {
+        0 => [u64::MAX; LIMBS],
+        1 => core::array::from_fn(|_| if rng.gen() { u64::MAX } else { 0 }),
+        _ => core::array::from_fn(|_| rng.gen()),
+    };
+    Uint::from_limbs(limbs) >> (Uint::<LIMBS>::BITS - bits)
+}
@@ -0,0 +1,488 @@
+use std::cmp::Ordering;
+use std::ops::{Shl, Shr};
+
+/// an all-ones mask for `true` and zero for `false`, kept opaque to the optimiser so that
+/// selects built on it stay branch-free
+fn mask(choice: bool) -> u64 {
+    std::hint::black_box((choice as u64).wrapping_neg())
+}
+
+/// a fixed-width unsigned integer of `LIMBS` 64-bit limbs, least significant limb first
+///
+/// arithmetic wraps or reports overflow rather than growing - pick a width with room for the
+/// largest intermediate value. add, sub, mul, the selects and the comparisons prefixed `ct_` run
+/// in time independent of the values; anything marked `vartime` doesn't and is only for public
+/// inputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Uint<const LIMBS: usize> {
+    limbs: [u64; LIMBS],
+}
+
+pub type U256 = Uint<4>;
+pub type U512 = Uint<8>;
+pub type U1024 = Uint<16>;
+pub type U2048 = Uint<32>;
+pub type U4096 = Uint<64>;
+
+impl<const LIMBS: usize> Uint<LIMBS> {
+    pub const BITS: u32 = 64 * LIMBS as u32;
+    pub const BYTES: usize = 8 * LIMBS;
+    pub const ZERO: Self = Uint { limbs: [0; LIMBS] };
+    pub const MAX: Self = Uint {
+        limbs: [u64::MAX; LIMBS],
+    };
+    pub const ONE: Self = Self::from_u64(1);
+
+    pub const fn from_u64(value: u64) -> Self {
+        let mut limbs = [0; LIMBS];
+        limbs[0] = value;
+        Uint { limbs }
+    }
+
+    pub const fn from_limbs(limbs: [u64; LIMBS]) -> Self {
+        Uint { limbs }
+    }
+
+    pub fn limbs(&self) -> &[u64; LIMBS] {
+        &self.limbs
+    }
+
+    /// parse a big-endian byte string of any length - `None` if the value doesn't fit
+    pub fn from_be_bytes(bytes: &[u8]) -> Option<Self> {
+        let excess = bytes.len().saturating_sub(Self::BYTES);
+        if bytes[..excess].iter().any(|&b| b != 0) {
+            return None;
+        }
+
+        let mut limbs = [0u64; LIMBS];
+        for (i, &byte) in bytes[excess..].iter().rev().enumerate() {
+            limbs[i / 8] |= (byte as u64) << (8 * (i % 8));
+        }
+        Some(Uint { limbs })
+    }
+
+    /// the full-width big-endian encoding, `BYTES` long
+    pub fn to_be_bytes(&self) -> Vec<u8> {
+        self.limbs
+            .iter()
+            .rev()
+            .flat_map(|limb| limb.to_be_bytes())
+            .collect()
+    }
+
+    /// the big-endian encoding left-padded with zeros to exactly `len` bytes - `None` if the
+    /// value needs more
+    pub fn to_be_bytes_padded(&self, len: usize) -> Option<Vec<u8>> {
+        let full = self.to_be_bytes();
+        if len >= full.len() {
+            let mut out = vec![0u8; len - full.len()];
+            out.extend_from_slice(&full);
+            return Some(out);
+        }
+
+        let (excess, rest) = full.split_at(full.len() - len);
+        excess.iter().all(|&b| b == 0).then(|| rest.to_vec())
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.iter().fold(0, |acc, limb| acc | limb) == 0
+    }
+
+    pub fn is_odd(&self) -> bool {
+        self.limbs[0] & 1 == 1
+    }
+
+    /// the bit at position `i`, counting from the least significant
+    pub fn bit(&self, i: u32) -> bool {
+        i < Self::BITS && (self.limbs[i as usize / 64] >> (i % 64)) & 1 == 1
+    }
+
+    pub fn set_bit(&mut self, i: u32) {
+        self.limbs[i as usize / 64] |= 1 << (i % 64);
+    }
+
+    /// the position of the highest set bit plus one - zero for zero
+    pub fn bits_vartime(&self) -> u32 {
+        match self.limbs.iter().rposition(|&limb| limb != 0) {
+            Some(i) => 64 * i as u32 + 64 - self.limbs[i].leading_zeros(),
+            None => 0,
+        }
+    }
+
+    pub fn overflowing_add(&self, rhs: &Self) -> (Self, bool) {
+        let mut out = [0u64; LIMBS];
+        let mut carry = false;
+        for ((out, a), b) in out.iter_mut().zip(&self.limbs).zip(&rhs.limbs) {
+            let (sum, c1) = a.overflowing_add(*b);
+            let (sum, c2) = sum.overflowing_add(carry as u64);
+            *out = sum;
+            carry = c1 | c2;
+        }
+        (Uint { limbs: out }, carry)
+    }
+
+    pub fn overflowing_sub(&self, rhs: &Self) -> (Self, bool) {
+        let mut out = [0u64; LIMBS];
+        let mut borrow = false;
+        for ((out, a), b) in out.iter_mut().zip(&self.limbs).zip(&rhs.limbs) {
+            let (diff, b1) = a.overflowing_sub(*b);
+            let (diff, b2) = diff.overflowing_sub(borrow as u64);
+            *out = diff;
+            borrow = b1 | b2;
+        }
+        (Uint { limbs: out }, borrow)
+    }
+
+    pub fn wrapping_add(&self, rhs: &Self) -> Self {
+        self.overflowing_add(rhs).0
+    }
+
+    pub fn wrapping_sub(&self, rhs: &Self) -> Self {
+        self.overflowing_sub(rhs).0
+    }
+
+    pub fn checked_add(&self, rhs: &Self) -> Option<Self> {
+        match self.overflowing_add(rhs) {
+            (sum, false) => Some(sum),
+            _ => None,
+        }
+    }
+
+    pub fn checked_sub(&self, rhs: &Self) -> Option<Self> {
+        match self.overflowing_sub(rhs) {
+            (diff, false) => Some(diff),
+            _ => None,
+        }
+    }
+
+    /// the full product, as its low and high halves
+    pub fn widening_mul(&self, rhs: &Self) -> (Self, Self) {
+        let mut lo = [0u64; LIMBS];
+        let mut hi = [0u64; LIMBS];
+
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, b) in rhs.limbs.iter().enumerate() {
+                let k = i + j;
+                let limb = if k < LIMBS {
+                    &mut lo[k]
+                } else {
+                    &mut hi[k - LIMBS]
+                };
+                let t = *a as u128 * *b as u128 + *limb as u128 + carry as u128;
+                *limb = t as u64;
+                carry = (t >> 64) as u64;
+            }
+            // the first free limb of this row - always in the high half
+            hi[i] = carry;
+        }
+
+        (Uint { limbs: lo }, Uint { limbs: hi })
+    }
+
+    pub fn wrapping_mul(&self, rhs: &Self) -> Self {
+        self.widening_mul(rhs).0
+    }
+
+    pub fn checked_mul(&self, rhs: &Self) -> Option<Self> {
+        let (lo, hi) = self.widening_mul(rhs);
+        hi.is_zero().then_some(lo)
+    }
+
+    /// `a` if `choice` is false and `b` if it's true, without branching on `choice`
+    pub fn ct_select(a: &Self, b: &Self, choice: bool) -> Self {
+        let mask = mask(choice);
+        let mut limbs = a.limbs;
+        for (out, b) in limbs.iter_mut().zip(&b.limbs) {
+            *out ^= (*out ^ b) & mask;
+        }
+        Uint { limbs }
+    }
+
+    pub fn ct_eq(&self, rhs: &Self) -> bool {
+        let diff = self
+            .limbs
+            .iter()
+            .zip(&rhs.limbs)
+            .fold(0, |acc, (a, b)| acc | (a ^ b));
+        std::hint::black_box(diff) == 0
+    }
+
+    pub fn ct_lt(&self, rhs: &Self) -> bool {
+        self.overflowing_sub(rhs).1
+    }
+
+    // shift left by one bit, returning the bit shifted out
+    pub(crate) fn shl1(&self) -> (Self, bool) {
+        let mut limbs = [0u64; LIMBS];
+        let mut carry = 0;
+        for (out, limb) in limbs.iter_mut().zip(&self.limbs) {
+            *out = (limb << 1) | carry;
+            carry = limb >> 63;
+        }
+        (Uint { limbs }, carry == 1)
+    }
+
+    /// quotient and remainder - panics if `rhs` is zero
+    ///
+    /// variable time - the work done depends on the bit lengths of both operands
+    pub fn div_rem_vartime(&self, rhs: &Self) -> (Self, Self) {
+        assert!(!rhs.is_zero(), "division by zero");
+
+        let num_bits = self.bits_vartime();
+        let den_bits = rhs.bits_vartime();
+        if num_bits < den_bits {
+            return (Self::ZERO, *self);
+        }
+
+        // the top den_bits - 1 bits of the numerator are already less than the divisor, so
+        // long division only has to bring down the rest one at a time
+        let steps = num_bits - den_bits + 1;
+        let mut quotient = Self::ZERO;
+        let mut rem = *self >> steps;
+        for i in (0..steps).rev() {
+            let (shifted, overflow) = rem.shl1();
+            rem = shifted;
+            rem.limbs[0] |= self.bit(i) as u64;
+
+            if overflow || rem >= *rhs {
+                rem = rem.wrapping_sub(rhs);
+                quotient.set_bit(i);
+            }
+        }
+
+        (quotient, rem)
+    }
+
+    /// the remainder after dividing by a single limb - panics if `d` is zero
+    ///
+    /// variable time in `d`, which is meant to be public, such as a small prime for trial division
+    pub fn rem_u64(&self, d: u64) -> u64 {
+        assert!(d != 0, "division by zero");
+        self.limbs.iter().rev().fold(0u64, |rem, &limb| {
+            ((((rem as u128) << 64) | limb as u128) % d as u128) as u64
+        })
+    }
+
+    /// the remainder of the double-width value `hi` * 2^BITS + `lo` - panics if `modulus` is
+    /// zero
+    ///
+    /// variable time
+    pub fn rem_wide_vartime(lo: &Self, hi: &Self, modulus: &Self) -> Self {
+        let mut rem = hi.div_rem_vartime(modulus).1;
+        for i in (0..Self::BITS).rev() {
+            let (shifted, overflow) = rem.shl1();
+            rem = shifted;
+            rem.limbs[0] |= lo.bit(i) as u64;
+
+            if overflow || rem >= *modulus {
+                rem = rem.wrapping_sub(modulus);
+            }
+        }
+        rem
+    }
+}
+
+impl<const LIMBS: usize> Default for Uint<LIMBS> {
+    fn default() -> Self {
+        Self::ZERO
+    }
+}
+
+impl<const LIMBS: usize> Ord for Uint<LIMBS> {
+    // variable time - use `ct_lt` and `ct_eq` on secrets
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.limbs.iter().rev().cmp(other.limbs.iter().rev())
+    }
+}
+
+impl<const LIMBS: usize> PartialOrd for Uint<LIMBS> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<const LIMBS: usize> Shl<u32> for Uint<LIMBS> {
+    type Output = Self;
+
+    /// shifts of `BITS` or more give zero
+    fn shl(self, shift: u32) -> Self {
+        let (limb_shift, bit_shift) = ((shift / 64) as usize, shift % 64);
+        let mut limbs = [0u64; LIMBS];
+        for (i, limb) in limbs.iter_mut().enumerate().skip(limb_shift) {
+            *limb = self.limbs[i - limb_shift] << bit_shift;
+            if bit_shift > 0 && i > limb_shift {
+                *limb |= self.limbs[i - limb_shift - 1] >> (64 - bit_shift);
+            }
+        }
+        Uint { limbs }
+    }
+}
+
+impl<const LIMBS: usize> Shr<u32> for Uint<LIMBS> {
+    type Output = Self;
+
+    /// shifts of `BITS` or more give zero
+    fn shr(self, shift: u32) -> Self {
+        let (limb_shift, bit_shift) = ((shift / 64) as usize, shift % 64);
+        let mut limbs = [0u64; LIMBS];
+        let kept = LIMBS.saturating_sub(limb_shift);
+        for (i, limb) in limbs.iter_mut().enumerate().take(kept) {
+            *limb = self.limbs[i + limb_shift] >> bit_shift;
+            if bit_shift > 0 && i + 1 < kept {
+                *limb |= self.limbs[i + limb_shift + 1] << (64 - bit_shift);
+            }
+        }
+        Uint { limbs }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::reference::{random_uint, Big};
+    use super::*;
+    use crate::test_util::hex;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    // an odd limb count, so nothing relies on the width being a power of two
+    type U320 = Uint<5>;
+
+    const CASES: usize = 500;
+
+    #[test]
+    fn test_byte_conversions() {
+        let bytes = hex("0102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f20");
+        let x = U256::from_be_bytes(&bytes).unwrap();
+        assert_eq!(x.limbs()[0], 0x191a1b1c1d1e1f20);
+        assert_eq!(x.limbs()[3], 0x0102030405060708);
+        assert_eq!(x.to_be_bytes(), bytes);
+
+        // leading zeros beyond the width are fine, anything else isn't
+        let mut padded = vec![0u8; 8];
+        padded.extend_from_slice(&bytes);
+        assert_eq!(U256::from_be_bytes(&padded), Some(x));
+        padded[7] = 1;
+        assert_eq!(U256::from_be_bytes(&padded), None);
+
+        let small = U256::from_u64(0x1234);
+        assert_eq!(small.to_be_bytes_padded(3), Some(vec![0x00, 0x12, 0x34]));
+        assert_eq!(small.to_be_bytes_padded(1), None);
+        assert_eq!(x.to_be_bytes_padded(40).unwrap()[8..], bytes[..]);
+        assert_eq!(U256::from_be_bytes(&[]), Some(U256::ZERO));
+    }
+
+    #[test]
+    fn test_add_sub_against_reference() {
+        let mut rng = StdRng::seed_from_u64(1);
+        let modulus = Big::power_of_two(320);
+
+        for _ in 0..CASES {
+            let (a, b) = (random_uint::<5>(&mut rng), random_uint::<5>(&mut rng));
+            let (ra, rb) = (Big::from_uint(&a), Big::from_uint(&b));
+
+            let (sum, carry) = a.overflowing_add(&b);
+            let expected = ra.add(&rb);
+            assert_eq!(carry, expected >= modulus);
+            assert_eq!(Big::from_uint(&sum), expected.div_rem(&modulus).1);
+
+            let (diff, borrow) = a.overflowing_sub(&b);
+            assert_eq!(borrow, ra < rb);
+            let expected = if borrow {
+                ra.add(&modulus).sub(&rb)
+            } else {
+                ra.sub(&rb)
+            };
+            assert_eq!(Big::from_uint(&diff), expected);
+
+            assert_eq!(a.cmp(&b), ra.cmp(&rb));
+            assert_eq!(a.ct_lt(&b), ra < rb);
+            assert_eq!(a.ct_eq(&b), a == b);
+        }
+    }
+
+    #[test]
+    fn test_mul_against_reference() {
+        let mut rng = StdRng::seed_from_u64(2);
+
+        for _ in 0..CASES {
+            let (a, b) = (random_uint::<5>(&mut rng), random_uint::<5>(&mut rng));
+            let (lo, hi) = a.widening_mul(&b);
+            let product =
+                Big::from_uint(&lo).add(&Big::from_uint(&hi).mul(&Big::power_of_two(320)));
+            assert_eq!(product, Big::from_uint(&a).mul(&Big::from_uint(&b)));
+
+            assert_eq!(a.wrapping_mul(&b), lo);
+            assert_eq!(a.checked_mul(&b), hi.is_zero().then_some(lo));
+        }
+
+        let (lo, hi) = U320::MAX.widening_mul(&U320::MAX);
+        assert_eq!(lo, U320::ONE);
+        assert_eq!(hi, U320::MAX.wrapping_sub(&U320::ONE));
+    }
+
+    #[test]
+    fn test_div_rem_against_reference() {
+        let mut rng = StdRng::seed_from_u64(3);
+
+        for _ in 0..CASES {
+            let (a, b) = (random_uint::<5>(&mut rng), random_uint::<5>(&mut rng));
+            if b.is_zero() {
+                continue;
+            }
+
+            let (q, r) = a.div_rem_vartime(&b);
+            let (rq, rr) = Big::from_uint(&a).div_rem(&Big::from_uint(&b));
+            assert_eq!((Big::from_uint(&q), Big::from_uint(&r)), (rq, rr));
+
+            let small_bits = rng.gen_range(0..64);
+            let small = rng.gen_range(1..=u64::MAX >> small_bits);
+            assert_eq!(
+                Big::from_u64(a.rem_u64(small)),
+                Big::from_uint(&a).div_rem(&Big::from_u64(small)).1
+            );
+
+            let hi = random_uint::<5>(&mut rng);
+            let wide = Big::from_uint(&a).add(&Big::from_uint(&hi).mul(&Big::power_of_two(320)));
+            assert_eq!(
+                Big::from_uint(&U320::rem_wide_vartime(&a, &hi, &b)),
+                wide.div_rem(&Big::from_uint(&b)).1
+            );
+        }
+
+        // a divisor with its top bit set, where the shifted remainder overflows the width
+        let top = U320::ONE << 319;
+        let (q, r) = U320::MAX.div_rem_vartime(&top.wrapping_add(&U320::ONE));
+        assert_eq!(q, U320::ONE);
+        assert_eq!(r, top.wrapping_sub(&U320::from_u64(2)));
+    }
+
+    #[test]
+    fn test_shifts_and_bits() {
+        let mut rng = StdRng::seed_from_u64(4);
+
+        for _ in 0..CASES {
+            let a = random_uint::<5>(&mut rng);
+            let shift = rng.gen_range(0..=330);
+            let ra = Big::from_uint(&a);
+
+            let expected = ra.mul(&Big::power_of_two(shift as usize));
+            let expected = expected.div_rem(&Big::power_of_two(320)).1;
+            assert_eq!(Big::from_uint(&(a << shift)), expected);
+            assert_eq!(
+                Big::from_uint(&(a >> shift)),
+                ra.div_rem(&Big::power_of_two(shift as usize)).0
+            );
+
+            let bits = a.bits_vartime();
+            assert!(bits == 0 || a.bit(bits - 1));
+            assert!(!a.bit(bits) && a >> bits == U320::ZERO);
+        }
+    }
+
+    #[test]
+    fn test_select() {
+        let (a, b) = (U320::from_u64(7), U320::MAX);
+        assert_eq!(U320::ct_select(&a, &b, false), a);
+        assert_eq!(U320::ct_select(&a, &b, true), b);
+    }
+}
@@ -0,0 +1,481 @@
+use super::constant::*;
+use crate::{ct::ct_eq, hash::HashFunction};
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Blake2Error {
+    /// the requested digest length is zero or longer than the function's maximum
+    InvalidOutputLength,
+    KeyTooLong,
+    SaltTooLong,
+    PersonalTooLong,
+}
+
+impl fmt::Display for Blake2Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Blake2Error::InvalidOutputLength => write!(f, "invalid BLAKE2 output length"),
+            Blake2Error::KeyTooLong => write!(f, "BLAKE2 key too long"),
+            Blake2Error::SaltTooLong => write!(f, "BLAKE2 salt too long"),
+            Blake2Error::PersonalTooLong => write!(f, "BLAKE2 personalization too long"),
+        }
+    }
+}
+
+impl std::error::Error for Blake2Error {}
+
+/// BLAKE2 parameters (RFC 7693 section 2.5 plus the salt and personalization fields of the
+/// BLAKE2 paper) - a zero `output_len` selects the maximum, and salt/personalization strings
+/// shorter than their field are zero padded
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Blake2Params<'a> {
+    pub output_len: usize,
+    pub key: &'a [u8],
+    pub salt: &'a [u8],
+    pub personal: &'a [u8],
+}
+
+/// define a BLAKE2 variant over the given word type - BLAKE2b and BLAKE2s only differ in word
+/// size, round count, rotation distances and the sizes derived from them
+macro_rules! blake2_variant {
+    (
+        $(#[$doc:meta])*
+        $name:ident,
+        $word:ty,
+        $counter:ty,
+        block_size: $block_size:expr,
+        output_size: $output_size:expr,
+        key_size: $key_size:expr,
+        salt_size: $salt_size:expr,
+        personal_size: $personal_size:expr,
+        rounds: $rounds:expr,
+        iv: $iv:expr,
+        rotations: ($r1:expr, $r2:expr, $r3:expr, $r4:expr)
+    ) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name {
+            h: [$word; 8],
+            buffer: [u8; $block_size],
+            buffer_len: usize,
+            // total number of bytes compressed so far
+            counter: $counter,
+            output_len: usize,
+        }
+
+        impl $name {
+            /// unkeyed hash with an `output_len`-byte digest
+            pub fn new(output_len: usize) -> Result<Self, Blake2Error> {
+                Self::with_params(&Blake2Params {
+                    output_len,
+                    ..Default::default()
+                })
+            }
+
+            /// keyed hash (the BLAKE2 MAC mode) with an `output_len`-byte tag
+            pub fn new_keyed(key: &[u8], output_len: usize) -> Result<Self, Blake2Error> {
+                Self::with_params(&Blake2Params {
+                    output_len,
+                    key,
+                    ..Default::default()
+                })
+            }
+
+            pub fn with_params(params: &Blake2Params) -> Result<Self, Blake2Error> {
+                let output_len = match params.output_len {
+                    0 => $output_size,
+                    len if len > $output_size => return Err(Blake2Error::InvalidOutputLength),
+                    len => len,
+                };
+                if params.key.len() > $key_size {
+                    return Err(Blake2Error::KeyTooLong);
+                }
+                if params.salt.len() > $salt_size {
+                    return Err(Blake2Error::SaltTooLong);
+                }
+                if params.personal.len() > $personal_size {
+                    return Err(Blake2Error::PersonalTooLong);
+                }
+
+                // parameter block - sequential mode, so fanout and depth are both 1 and every
+                // tree field is left zero
+                const WORD_BYTES: usize = std::mem::size_of::<$word>();
+                let mut param_block = [0u8; 8 * WORD_BYTES];
+                param_block[0] = output_len as u8;
+                param_block[1] = params.key.len() as u8;
+                param_block[2] = 1;
+                param_block[3] = 1;
+
+                let salt_offset = 4 * WORD_BYTES;
+                let personal_offset = salt_offset + $salt_size;
+                param_block[salt_offset..salt_offset + params.salt.len()]
+                    .copy_from_slice(params.salt);
+                param_block[personal_offset..personal_offset + params.personal.len()]
+                    .copy_from_slice(params.personal);
+
+                let mut h = $iv;
+                for (h, chunk) in h.iter_mut().zip(param_block.chunks_exact(WORD_BYTES)) {
+                    *h ^= <$word>::from_le_bytes(chunk.try_into().unwrap());
+                }
+
+                let mut hasher = $name {
+                    h,
+                    buffer: [0u8; $block_size],
+                    buffer_len: 0,
+                    counter: 0,
+                    output_len,
+                };
+
+                // a key is zero padded to a full block and processed as the first block
+                if !params.key.is_empty() {
+                    hasher.buffer[..params.key.len()].copy_from_slice(params.key);
+                    hasher.buffer_len = $block_size;
+                }
+
+                Ok(hasher)
+            }
+
+            pub fn output_len(&self) -> usize {
+                self.output_len
+            }
+
+            fn compress(&mut self, last: bool) {
+                const WORD_BYTES: usize = std::mem::size_of::<$word>();
+
+                let mut m = [0 as $word; 16];
+                for (m, chunk) in m.iter_mut().zip(self.buffer.chunks_exact(WORD_BYTES)) {
+                    *m = <$word>::from_le_bytes(chunk.try_into().unwrap());
+                }
+
+                let mut v = [0 as $word; 16];
+                v[..8].copy_from_slice(&self.h);
+                v[8..].copy_from_slice(&$iv);
+                v[12] ^= self.counter as $word;
+                v[13] ^= (self.counter >> (8 * WORD_BYTES)) as $word;
+                if last {
+                    v[14] = !v[14];
+                }
+
+                #[inline(always)]
+                fn g(v: &mut [$word; 16], a: usize, b: usize, c: usize, d: usize, x: $word, y: $word) {
+                    v[a] = v[a].wrapping_add(v[b]).wrapping_add(x);
+                    v[d] = (v[d] ^ v[a]).rotate_right($r1);
+                    v[c] = v[c].wrapping_add(v[d]);
+                    v[b] = (v[b] ^ v[c]).rotate_right($r2);
+                    v[a] = v[a].wrapping_add(v[b]).wrapping_add(y);
+                    v[d] = (v[d] ^ v[a]).rotate_right($r3);
+                    v[c] = v[c].wrapping_add(v[d]);
+                    v[b] = (v[b] ^ v[c]).rotate_right($r4);
+                }
+
+                for round in 0..$rounds {
+                    let s = &SIGMA[round % 10];
+
+                    // columns, then diagonals
+                    g(&mut v, 0, 4, 8, 12, m[s[0]], m[s[1]]);
+                    g(&mut v, 1, 5, 9, 13, m[s[2]], m[s[3]]);
+                    g(&mut v, 2, 6, 10, 14, m[s[4]], m[s[5]]);
+                    g(&mut v, 3, 7, 11, 15, m[s[6]], m[s[7]]);
+                    g(&mut v, 0, 5, 10, 15, m[s[8]], m[s[9]]);
+                    g(&mut v, 1, 6, 11, 12, m[s[10]], m[s[11]]);
+                    g(&mut v, 2, 7, 8, 13, m[s[12]], m[s[13]]);
+                    g(&mut v, 3, 4, 9, 14, m[s[14]], m[s[15]]);
+                }
+
+                for i in 0..8 {
+                    self.h[i] ^= v[i] ^ v[i + 8];
+                }
+            }
+
+            pub fn update(&mut self, mut data: &[u8]) {
+                while !data.is_empty() {
+                    // the final block is compressed with the last-block flag set, so a full
+                    // buffer is only flushed once we know more input follows it
+                    if self.buffer_len == $block_size {
+                        self.counter = self.counter.wrapping_add($block_size as $counter);
+                        self.compress(false);
+                        self.buffer_len = 0;
+                    }
+
+                    let take = ($block_size - self.buffer_len).min(data.len());
+                    self.buffer[self.buffer_len..self.buffer_len + take]
+                        .copy_from_slice(&data[..take]);
+                    self.buffer_len += take;
+                    data = &data[take..];
+                }
+            }
+
+            /// write the digest into `out`, which must be exactly `output_len` bytes long
+            pub fn finalize_into(mut self, out: &mut [u8]) {
+                assert_eq!(out.len(), self.output_len);
+                const WORD_BYTES: usize = std::mem::size_of::<$word>();
+
+                self.counter = self.counter.wrapping_add(self.buffer_len as $counter);
+                self.buffer[self.buffer_len..].fill(0);
+                self.compress(true);
+
+                let mut digest = [0u8; 8 * WORD_BYTES];
+                for (chunk, word) in digest.chunks_exact_mut(WORD_BYTES).zip(self.h.iter()) {
+                    chunk.copy_from_slice(&word.to_le_bytes());
+                }
+
+                out.copy_from_slice(&digest[..self.output_len]);
+            }
+
+            pub fn finalize(self) -> Vec<u8> {
+                let mut out = vec![0u8; self.output_len];
+                self.finalize_into(&mut out);
+                out
+            }
+
+            /// recompute the (keyed) digest and compare it against `tag` in constant time
+            pub fn verify(self, tag: &[u8]) -> bool {
+                tag.len() == self.output_len && ct_eq(&self.finalize(), tag)
+            }
+        }
+    };
+}
+
+blake2_variant!(
+    /// BLAKE2b (RFC 7693) - 64-bit words, digests of 1 to 64 bytes
+    Blake2b,
+    u64,
+    u128,
+    block_size: BLAKE2B_BLOCK_SIZE,
+    output_size: BLAKE2B_OUTPUT_SIZE,
+    key_size: BLAKE2B_KEY_SIZE,
+    salt_size: BLAKE2B_SALT_SIZE,
+    personal_size: BLAKE2B_PERSONAL_SIZE,
+    rounds: BLAKE2B_ROUNDS,
+    iv: BLAKE2B_IV,
+    rotations: (32, 24, 16, 63)
+);
+
+blake2_variant!(
+    /// BLAKE2s (RFC 7693) - 32-bit words, digests of 1 to 32 bytes
+    Blake2s,
+    u32,
+    u64,
+    block_size: BLAKE2S_BLOCK_SIZE,
+    output_size: BLAKE2S_OUTPUT_SIZE,
+    key_size: BLAKE2S_KEY_SIZE,
+    salt_size: BLAKE2S_SALT_SIZE,
+    personal_size: BLAKE2S_PERSONAL_SIZE,
+    rounds: BLAKE2S_ROUNDS,
+    iv: BLAKE2S_IV,
+    rotations: (16, 12, 8, 7)
+);
+
+/// unkeyed BLAKE2b with its full 512-bit digest, for use wherever a fixed-output hash is needed
+#[derive(Clone)]
+pub struct Blake2b512(Blake2b);
+
+/// unkeyed BLAKE2s with its full 256-bit digest, for use wherever a fixed-output hash is needed
+#[derive(Clone)]
+pub struct Blake2s256(Blake2s);
+
+impl HashFunction for Blake2b512 {
+    const BLOCK_SIZE: usize = BLAKE2B_BLOCK_SIZE;
+    const OUTPUT_SIZE: usize = BLAKE2B_OUTPUT_SIZE;
+
+    type Output = [u8; BLAKE2B_OUTPUT_SIZE];
+
+    fn new() -> Self {
+        Blake2b512(Blake2b::new(BLAKE2B_OUTPUT_SIZE).unwrap())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        let mut out = [0u8; BLAKE2B_OUTPUT_SIZE];
+        self.0.finalize_into(&mut out);
+        out
+    }
+}
+
+impl HashFunction for Blake2s256 {
+    const BLOCK_SIZE: usize = BLAKE2S_BLOCK_SIZE;
+    const OUTPUT_SIZE: usize = BLAKE2S_OUTPUT_SIZE;
+
+    type Output = [u8; BLAKE2S_OUTPUT_SIZE];
+
+    fn new() -> Self {
+        Blake2s256(Blake2s::new(BLAKE2S_OUTPUT_SIZE).unwrap())
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        self.0.update(data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        let mut out = [0u8; BLAKE2S_OUTPUT_SIZE];
+        self.0.finalize_into(&mut out);
+        out
+    }
+}
+
+/// one-shot BLAKE2b with an `output_len`-byte digest, optionally keyed
+pub fn blake2b(data: &[u8], key: &[u8], output_len: usize) -> Result<Vec<u8>, Blake2Error> {
+    let mut hasher = Blake2b::new_keyed(key, output_len)?;
+    hasher.update(data);
+    Ok(hasher.finalize())
+}
+
+/// one-shot BLAKE2s with an `output_len`-byte digest, optionally keyed
+pub fn blake2s(data: &[u8], key: &[u8], output_len: usize) -> Result<Vec<u8>, Blake2Error> {
+    let mut hasher = Blake2s::new_keyed(key, output_len)?;
+    hasher.update(data);
+    Ok(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    #[test]
+    fn test_rfc7693_vectors() {
+        // RFC 7693 appendices A and B
+        assert_eq!(
+            blake2b(b"abc", &[], 64).unwrap(),
+            hex(
+                "ba80a53f981c4d0d6a2797b69f12f6e94c212f14685ac4b74b12bb6fdbffa2d1
+                 7d87c5392aab792dc252d5de4533cc9518d38aa8dbf1925ab92386edd4009923"
+            )
+        );
+        assert_eq!(
+            blake2s(b"abc", &[], 32).unwrap(),
+            hex("508c5e8c327c14e2e1a72ba34eeb452f37458b209ed63a294d999b4c86675982")
+        );
+    }
+
+    // keyed entries from the reference blake2b-kat.txt / blake2s-kat.txt, where the key is
+    // 00 01 02 .. and the input for entry n is the n bytes 00 01 .. n-1
+    #[test]
+    fn test_blake2b_keyed_kat() {
+        let key: Vec<u8> = (0..64).collect();
+        let cases = [
+            (
+                0,
+                "10ebb67700b1868efb4417987acf4690ae9d972fb7a590c2f02871799aaa4786
+                 b5e996e8f0f4eb981fc214b005f42d2ff4233499391653df7aefcbc13fc51568",
+            ),
+            (
+                1,
+                "961f6dd1e4dd30f63901690c512e78e4b45e4742ed197c3c5e45c549fd25f2e4
+                 187b0bc9fe30492b16b0d0bc4ef9b0f34c7003fac09a5ef1532e69430234cebd",
+            ),
+            (
+                255,
+                "142709d62e28fcccd0af97fad0f8465b971e82201dc51070faa0372aa43e9248
+                 4be1c1e73ba10906d5d1853db6a4106e0a7bf9800d373d6dee2d46d62ef2a461",
+            ),
+        ];
+
+        for (len, expected) in cases {
+            let input: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            assert_eq!(blake2b(&input, &key, 64).unwrap(), hex(expected));
+        }
+    }
+
+    #[test]
+    fn test_blake2s_keyed_kat() {
+        let key: Vec<u8> = (0..32).collect();
+        let cases = [
+            (
+                0,
+                "48a8997da407876b3d79c0d92325ad3b89cbb754d86ab71aee047ad345fd2c49",
+            ),
+            (
+                1,
+                "40d15fee7c328830166ac3f918650f807e7e01e177258cdc0a39b11f598066f1",
+            ),
+            (
+                255,
+                "3fb735061abc519dfe979e54c1ee5bfad0a9d858b3315bad34bde999efd724dd",
+            ),
+        ];
+
+        for (len, expected) in cases {
+            let input: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            assert_eq!(blake2s(&input, &key, 32).unwrap(), hex(expected));
+        }
+    }
+
+    #[test]
+    fn test_salt_personal_and_output_length() {
+        let mut hasher = Blake2b::with_params(&Blake2Params {
+            output_len: 20,
+            salt: b"saltsaltsaltsalt",
+            personal: b"personalization!",
+            ..Default::default()
+        })
+        .unwrap();
+        hasher.update(b"abc");
+        assert_eq!(
+            hasher.finalize(),
+            hex("dcb4441139a31259813e7aa0b2a5bc5f86a328fe")
+        );
+
+        let mut hasher = Blake2s::with_params(&Blake2Params {
+            output_len: 20,
+            key: b"key",
+            salt: b"saltsalt",
+            personal: b"personal",
+        })
+        .unwrap();
+        hasher.update(b"abc");
+        assert_eq!(
+            hasher.finalize(),
+            hex("b22efd5adb18e1ac152338bcea7a2e232a2f9c1c")
+        );
+
+        assert_eq!(blake2b(b"abc", &[], 1).unwrap(), hex("6b"));
+    }
+
+    #[test]
+    fn test_invalid_params() {
+        assert_eq!(
+            Blake2b::new(65).err(),
+            Some(Blake2Error::InvalidOutputLength)
+        );
+        assert_eq!(
+            Blake2s::new_keyed(&[0u8; 33], 32).err(),
+            Some(Blake2Error::KeyTooLong)
+        );
+        assert_eq!(
+            Blake2s::with_params(&Blake2Params {
+                salt: &[0u8; 9],
+                ..Default::default()
+            })
+            .err(),
+            Some(Blake2Error::SaltTooLong)
+        );
+    }
+
+    #[test]
+    fn test_streaming_and_verify() {
+        let key = [0x61; 32];
+        let data: Vec<u8> = (0..300).map(|i| i as u8).collect();
+        let expected = blake2b(&data, &key, 32).unwrap();
+
+        for split in [0, 1, 127, 128, 129, 256] {
+            let mut mac = Blake2b::new_keyed(&key, 32).unwrap();
+            mac.update(&data[..split]);
+            mac.update(&data[split..]);
+            assert!(mac.clone().verify(&expected));
+            assert!(!mac.verify(&expected[..16]));
+        }
+
+        assert_eq!(
+            Blake2b512::digest(b"abc").to_vec(),
+            blake2b(b"abc", &[], 64).unwrap()
+        );
+        assert_eq!(
+            Blake2s256::digest(b"abc").to_vec(),
+            blake2s(b"abc", &[], 32).unwrap()
+        );
+    }
+}
@@ -0,0 +1,955 @@
+use super::constant::*;
+use crate::ct::ct_eq;
+use crate::hash::{HashFunction, XofReader};
+use std::thread;
+
+pub type Blake3Digest = [u8; OUT_LEN];
+
+// below this many bytes `update_parallel` isn't worth spawning threads for
+const PARALLEL_THRESHOLD: usize = 128 * 1024;
+
+fn g(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize, mx: u32, my: u32) {
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(mx);
+    state[d] = (state[d] ^ state[a]).rotate_right(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(12);
+    state[a] = state[a].wrapping_add(state[b]).wrapping_add(my);
+    state[d] = (state[d] ^ state[a]).rotate_right(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_right(7);
+}
+
+fn round(state: &mut [u32; 16], m: &[u32; 16]) {
+    // columns
+    g(state, 0, 4, 8, 12, m[0], m[1]);
+    g(state, 1, 5, 9, 13, m[2], m[3]);
+    g(state, 2, 6, 10, 14, m[4], m[5]);
+    g(state, 3, 7, 11, 15, m[6], m[7]);
+    // diagonals
+    g(state, 0, 5, 10, 15, m[8], m[9]);
+    g(state, 1, 6, 11, 12, m[10], m[11]);
+    g(state, 2, 7, 8, 13, m[12], m[13]);
+    g(state, 3, 4, 9, 14, m[14], m[15]);
+}
+
+fn compress(
+    chaining_value: &[u32; 8],
+    block_words: &[u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+) -> [u32; 16] {
+    let mut state = [0u32; 16];
+    state[..8].copy_from_slice(chaining_value);
+    state[8..12].copy_from_slice(&IV[..4]);
+    state[12] = counter as u32;
+    state[13] = (counter >> 32) as u32;
+    state[14] = block_len;
+    state[15] = flags;
+
+    let mut m = *block_words;
+    for r in 0..7 {
+        round(&mut state, &m);
+        // no permutation is needed after the last round
+        if r < 6 {
+            m = MSG_PERMUTATION.map(|i| m[i]);
+        }
+    }
+
+    for i in 0..8 {
+        state[i] ^= state[i + 8];
+        state[i + 8] ^= chaining_value[i];
+    }
+    state
+}
+
+fn words_from_bytes<const N: usize>(bytes: &[u8]) -> [u32; N] {
+    let mut words = [0u32; N];
+    for (word, chunk) in words.iter_mut().zip(bytes.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    words
+}
+
+fn first_8_words(words: [u32; 16]) -> [u32; 8] {
+    words[..8].try_into().unwrap()
+}
+
+/// the inputs to a compression that hasn't been performed yet - the last block of a chunk or a
+/// parent node, which is either reduced to a chaining value or, for the root, expanded into
+/// output
+#[derive(Clone)]
+struct Output {
+    input_chaining_value: [u32; 8],
+    block_words: [u32; 16],
+    counter: u64,
+    block_len: u32,
+    flags: u32,
+}
+
+impl Output {
+    fn chaining_value(&self) -> [u32; 8] {
+        first_8_words(compress(
+            &self.input_chaining_value,
+            &self.block_words,
+            self.counter,
+            self.block_len,
+            self.flags,
+        ))
+    }
+
+    // the root compression with `counter` as its output block index
+    fn root_output_block(&self, counter: u64) -> [u8; 2 * OUT_LEN] {
+        let words = compress(
+            &self.input_chaining_value,
+            &self.block_words,
+            counter,
+            self.block_len,
+            self.flags | ROOT,
+        );
+
+        let mut block = [0u8; 2 * OUT_LEN];
+        for (chunk, word) in block.chunks_exact_mut(4).zip(words.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        block
+    }
+}
+
+fn parent_output(left: [u32; 8], right: [u32; 8], key_words: &[u32; 8], flags: u32) -> Output {
+    let mut block_words = [0u32; 16];
+    block_words[..8].copy_from_slice(&left);
+    block_words[8..].copy_from_slice(&right);
+
+    Output {
+        input_chaining_value: *key_words,
+        block_words,
+        counter: 0,
+        block_len: BLOCK_LEN as u32,
+        flags: PARENT | flags,
+    }
+}
+
+fn parent_cv(left: [u32; 8], right: [u32; 8], key_words: &[u32; 8], flags: u32) -> [u32; 8] {
+    parent_output(left, right, key_words, flags).chaining_value()
+}
+
+/// the state of a single 1 KiB chunk - the last block is held back, since it's compressed with
+/// the CHUNK_END flag and, if this chunk turns out to be the whole input, the ROOT flag too
+#[derive(Clone)]
+struct ChunkState {
+    chaining_value: [u32; 8],
+    chunk_counter: u64,
+    block: [u8; BLOCK_LEN],
+    block_len: usize,
+    blocks_compressed: usize,
+    flags: u32,
+}
+
+impl ChunkState {
+    fn new(key_words: &[u32; 8], chunk_counter: u64, flags: u32) -> Self {
+        ChunkState {
+            chaining_value: *key_words,
+            chunk_counter,
+            block: [0u8; BLOCK_LEN],
+            block_len: 0,
+            blocks_compressed: 0,
+            flags,
+        }
+    }
+
+    fn len(&self) -> usize {
+        BLOCK_LEN * self.blocks_compressed + self.block_len
+    }
+
+    fn start_flag(&self) -> u32 {
+        if self.blocks_compressed == 0 {
+            CHUNK_START
+        } else {
+            0
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // only compress a full block once we know another one follows it
+            if self.block_len == BLOCK_LEN {
+                let block_words = words_from_bytes(&self.block);
+                self.chaining_value = first_8_words(compress(
+                    &self.chaining_value,
+                    &block_words,
+                    self.chunk_counter,
+                    BLOCK_LEN as u32,
+                    self.flags | self.start_flag(),
+                ));
+                self.blocks_compressed += 1;
+                self.block = [0u8; BLOCK_LEN];
+                self.block_len = 0;
+            }
+
+            let take = (BLOCK_LEN - self.block_len).min(data.len());
+            self.block[self.block_len..self.block_len + take].copy_from_slice(&data[..take]);
+            self.block_len += take;
+            data = &data[take..];
+        }
+    }
+
+    fn output(&self) -> Output {
+        Output {
+            input_chaining_value: self.chaining_value,
+            block_words: words_from_bytes(&self.block),
+            counter: self.chunk_counter,
+            block_len: self.block_len as u32,
+            flags: self.flags | self.start_flag() | CHUNK_END,
+        }
+    }
+}
+
+/// chaining value of a complete (non-root) subtree of `data.len() / CHUNK_LEN` chunks, which
+/// must be a power of two - the two halves are hashed on separate threads while `threads` allows
+fn subtree_cv(
+    data: &[u8],
+    key_words: &[u32; 8],
+    chunk_counter: u64,
+    flags: u32,
+    threads: usize,
+) -> [u32; 8] {
+    if data.len() == CHUNK_LEN {
+        let mut chunk = ChunkState::new(key_words, chunk_counter, flags);
+        chunk.update(data);
+        return chunk.output().chaining_value();
+    }
+
+    let (left, right) = data.split_at(data.len() / 2);
+    let right_counter = chunk_counter + (left.len() / CHUNK_LEN) as u64;
+
+    let (left_cv, right_cv) = if threads > 1 {
+        thread::scope(|s| {
+            let right_handle =
+                s.spawn(|| subtree_cv(right, key_words, right_counter, flags, threads / 2));
+            let left_cv = subtree_cv(left, key_words, chunk_counter, flags, threads - threads / 2);
+            (left_cv, right_handle.join().unwrap())
+        })
+    } else {
+        (
+            subtree_cv(left, key_words, chunk_counter, flags, 1),
+            subtree_cv(right, key_words, right_counter, flags, 1),
+        )
+    };
+
+    parent_cv(left_cv, right_cv, key_words, flags)
+}
+
+/// BLAKE3 with a streaming `update`/`finalize` interface - plain hashing, keyed hashing and key
+/// derivation are the same tree hash under different keys and domain flags
+#[derive(Clone)]
+pub struct Blake3 {
+    chunk_state: ChunkState,
+    key_words: [u32; 8],
+    // chaining values of completed subtrees, one per set bit of the number of chunks so far -
+    // 54 entries are enough for the full 2^64 byte input space
+    cv_stack: Vec<[u32; 8]>,
+    flags: u32,
+}
+
+impl Blake3 {
+    fn new_internal(key_words: [u32; 8], flags: u32) -> Self {
+        Blake3 {
+            chunk_state: ChunkState::new(&key_words, 0, flags),
+            key_words,
+            cv_stack: Vec::with_capacity(54),
+            flags,
+        }
+    }
+
+    /// plain BLAKE3 hashing
+    pub fn new() -> Self {
+        Self::new_internal(IV, 0)
+    }
+
+    /// keyed hashing - a MAC/PRF under a 32-byte key
+    pub fn new_keyed(key: &[u8; KEY_LEN]) -> Self {
+        Self::new_internal(words_from_bytes(key), KEYED_HASH)
+    }
+
+    /// key derivation - `context` should be a hardcoded, globally unique and application-specific
+    /// string, and the key material is then fed in through `update`
+    pub fn new_derive_key(context: &str) -> Self {
+        let mut context_hasher = Self::new_internal(IV, DERIVE_KEY_CONTEXT);
+        context_hasher.update(context.as_bytes());
+        let context_key = context_hasher.finalize();
+
+        Self::new_internal(words_from_bytes(&context_key), DERIVE_KEY_MATERIAL)
+    }
+
+    // fold the chaining value of a completed subtree of 2^k chunks into the stack - `total` is
+    // the number of such subtrees seen so far including this one, and each trailing zero bit
+    // means a left sibling on the stack is waiting to be merged
+    fn push_subtree_cv(&mut self, mut cv: [u32; 8], mut total: u64) {
+        while total & 1 == 0 {
+            let left = self.cv_stack.pop().unwrap();
+            cv = parent_cv(left, cv, &self.key_words, self.flags);
+            total >>= 1;
+        }
+        self.cv_stack.push(cv);
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        while !data.is_empty() {
+            // as within a chunk, only finish a full chunk once more input arrives, since the last
+            // chunk may be the root
+            if self.chunk_state.len() == CHUNK_LEN {
+                let chunk_cv = self.chunk_state.output().chaining_value();
+                let total_chunks = self.chunk_state.chunk_counter + 1;
+                self.push_subtree_cv(chunk_cv, total_chunks);
+                self.chunk_state = ChunkState::new(&self.key_words, total_chunks, self.flags);
+            }
+
+            let take = (CHUNK_LEN - self.chunk_state.len()).min(data.len());
+            self.chunk_state.update(&data[..take]);
+            data = &data[take..];
+        }
+    }
+
+    /// like `update`, but hashes large inputs as whole subtrees spread over the available cores -
+    /// the result is identical to `update`
+    pub fn update_parallel(&mut self, data: &[u8]) {
+        if data.len() < PARALLEL_THRESHOLD {
+            self.update(data);
+            return;
+        }
+
+        let threads = thread::available_parallelism().map_or(1, |n| n.get());
+        self.update_threaded(data, threads);
+    }
+
+    fn update_threaded(&mut self, mut data: &[u8], threads: usize) {
+        // finish the current chunk first so that the subtrees start on a chunk boundary
+        if self.chunk_state.len() > 0 {
+            let take = (CHUNK_LEN - self.chunk_state.len()).min(data.len());
+            self.chunk_state.update(&data[..take]);
+            data = &data[take..];
+        }
+
+        // hand over whole subtrees, always keeping at least one byte back for the chunk state
+        // so the root is never among them
+        while data.len() > CHUNK_LEN {
+            if self.chunk_state.len() == CHUNK_LEN {
+                // the buffered chunk is followed by more input, so it can be finished
+                let chunk_cv = self.chunk_state.output().chaining_value();
+                let total_chunks = self.chunk_state.chunk_counter + 1;
+                self.push_subtree_cv(chunk_cv, total_chunks);
+                self.chunk_state = ChunkState::new(&self.key_words, total_chunks, self.flags);
+            }
+
+            // the largest power-of-two number of chunks that fits and keeps the subtree aligned
+            // within the tree
+            let counter = self.chunk_state.chunk_counter;
+            let max_chunks = ((data.len() - 1) / CHUNK_LEN) as u64;
+            let mut subtree_chunks = 1u64 << (63 - max_chunks.leading_zeros());
+            while !counter.is_multiple_of(subtree_chunks) {
+                subtree_chunks /= 2;
+            }
+
+            let subtree_len = subtree_chunks as usize * CHUNK_LEN;
+            let cv = subtree_cv(
+                &data[..subtree_len],
+                &self.key_words,
+                counter,
+                self.flags,
+                threads,
+            );
+            self.push_subtree_cv(cv, (counter + subtree_chunks) / subtree_chunks);
+
+            self.chunk_state =
+                ChunkState::new(&self.key_words, counter + subtree_chunks, self.flags);
+            data = &data[subtree_len..];
+        }
+
+        self.update(data);
+    }
+
+    fn root_output(&self) -> Output {
+        // merge the stack from the right, with the current chunk as the right-most node
+        let mut output = self.chunk_state.output();
+        for left in self.cv_stack.iter().rev() {
+            output = parent_output(*left, output.chaining_value(), &self.key_words, self.flags);
+        }
+        output
+    }
+
+    pub fn finalize(&self) -> Blake3Digest {
+        let mut digest = [0u8; OUT_LEN];
+        self.finalize_xof().read(&mut digest);
+        digest
+    }
+
+    /// extendable output - the first 32 bytes are the regular digest
+    pub fn finalize_xof(&self) -> Blake3Reader {
+        Blake3Reader {
+            output: self.root_output(),
+            position: 0,
+        }
+    }
+
+    /// compare the 32-byte digest against `tag` in constant time - shorter prefixes of it are
+    /// rejected, since a keyed-mode tag is only as strong as its length
+    pub fn verify(&self, tag: &[u8]) -> bool {
+        ct_eq(&self.finalize(), tag)
+    }
+}
+
+impl Default for Blake3 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Blake3 {
+    const BLOCK_SIZE: usize = BLOCK_LEN;
+    const OUTPUT_SIZE: usize = OUT_LEN;
+
+    type Output = Blake3Digest;
+
+    fn new() -> Self {
+        Blake3::new()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        Blake3::update(self, data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        Blake3::finalize(&self)
+    }
+}
+
+/// reads the BLAKE3 extended output - each 64-byte output block is an independent compression,
+/// so the reader can be positioned anywhere in the stream
+#[derive(Clone)]
+pub struct Blake3Reader {
+    output: Output,
+    position: u64,
+}
+
+impl Blake3Reader {
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    pub fn set_position(&mut self, position: u64) {
+        self.position = position;
+    }
+}
+
+impl XofReader for Blake3Reader {
+    fn read(&mut self, mut out: &mut [u8]) {
+        const OUTPUT_BLOCK_LEN: u64 = 2 * OUT_LEN as u64;
+
+        while !out.is_empty() {
+            let block = self
+                .output
+                .root_output_block(self.position / OUTPUT_BLOCK_LEN);
+            let offset = (self.position % OUTPUT_BLOCK_LEN) as usize;
+            let take = (block.len() - offset).min(out.len());
+
+            out[..take].copy_from_slice(&block[offset..offset + take]);
+            self.position += take as u64;
+            out = &mut out[take..];
+        }
+    }
+}
+
+/// one-shot BLAKE3
+pub fn blake3(data: &[u8]) -> Blake3Digest {
+    Blake3::digest(data)
+}
+
+/// one-shot keyed BLAKE3
+pub fn blake3_keyed(key: &[u8; KEY_LEN], data: &[u8]) -> Blake3Digest {
+    let mut hasher = Blake3::new_keyed(key);
+    hasher.update(data);
+    hasher.finalize()
+}
+
+/// one-shot BLAKE3 key derivation, filling `out` with key material
+pub fn blake3_derive_key(context: &str, key_material: &[u8], out: &mut [u8]) {
+    let mut hasher = Blake3::new_derive_key(context);
+    hasher.update(key_material);
+    hasher.finalize_xof().read(out);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    // from the official BLAKE3 test_vectors.json - input byte i is i % 251 and every output is
+    // 131 bytes of extended output
+    const TEST_KEY: &[u8; KEY_LEN] = b"whats the Elvish word for friend";
+    const TEST_CONTEXT: &str = "BLAKE3 2019-12-27 16:29:52 test vectors context";
+
+    // (input length, hash, keyed_hash, derive_key)
+    const TEST_VECTORS: &[(usize, &str, &str, &str)] = &[
+        (
+            0,
+            "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262
+             e00f03e7b69af26b7faaf09fcd333050338ddfe085b8cc869ca98b206c08243a
+             26f5487789e8f660afe6c99ef9e0c52b92e7393024a80459cf91f476f9ffdbda
+             7001c22e159b402631f277ca96f2defdf1078282314e763699a31c5363165421
+             cce14d",
+            "92b2b75604ed3c761f9d6f62392c8a9227ad0ea3f09573e783f1498a4ed60d26
+             b18171a2f22a4b94822c701f107153dba24918c4bae4d2945c20ece13387627d
+             3b73cbf97b797d5e59948c7ef788f54372df45e45e4293c7dc18c1d41144a975
+             8be58960856be1eabbe22c2653190de560ca3b2ac4aa692a9210694254c371e8
+             51bc8f",
+            "2cc39783c223154fea8dfb7c1b1660f2ac2dcbd1c1de8277b0b0dd39b7e50d7d
+             905630c8be290dfcf3e6842f13bddd573c098c3f17361f1f206b8cad9d088aa4
+             a3f746752c6b0ce6a83b0da81d59649257cdf8eb3e9f7d4998e41021fac119de
+             efb896224ac99f860011f73609e6e0e4540f93b273e56547dfd3aa1a035ba668
+             9d89a0",
+        ),
+        (
+            1,
+            "2d3adedff11b61f14c886e35afa036736dcd87a74d27b5c1510225d0f592e213
+             c3a6cb8bf623e20cdb535f8d1a5ffb86342d9c0b64aca3bce1d31f60adfa137b
+             358ad4d79f97b47c3d5e79f179df87a3b9776ef8325f8329886ba42f07fb138b
+             b502f4081cbcec3195c5871e6c23e2cc97d3c69a613eba131e5f1351f3f1da78
+             6545e5",
+            "6d7878dfff2f485635d39013278ae14f1454b8c0a3a2d34bc1ab38228a80c95b
+             6568c0490609413006fbd428eb3fd14e7756d90f73a4725fad147f7bf70fd61c
+             4e0cf7074885e92b0e3f125978b4154986d4fb202a3f331a3fb6cf349a3a70e4
+             9990f98fe4289761c8602c4e6ab1138d31d3b62218078b2f3ba9a88e1d08d0dd
+             4cea11",
+            "b3e2e340a117a499c6cf2398a19ee0d29cca2bb7404c73063382693bf66cb06c
+             5827b91bf889b6b97c5477f535361caefca0b5d8c4746441c576171119331589
+             50670f9aa8a05d791daae10ac683cbef8faf897c84e6114a59d2173c3f417023
+             a35d6983f2c7dfa57e7fc559ad751dbfb9ffab39c2ef8c4aafebc9ae973a64f0
+             c76551",
+        ),
+        (
+            63,
+            "e9bc37a594daad83be9470df7f7b3798297c3d834ce80ba85d6e207627b7db7b
+             1197012b1e7d9af4d7cb7bdd1f3bb49a90a9b5dec3ea2bbc6eaebce77f4e470c
+             bf4687093b5352f04e4a4570fba233164e6acc36900e35d185886a827f7ea9bd
+             c1e5c3ce88b095a200e62c10c043b3e9bc6cb9b6ac4dfa51794b02ace9f98779
+             040755",
+            "bb1eb5d4afa793c1ebdd9fb08def6c36d10096986ae0cfe148cd101170ce37ae
+             a05a63d74a840aecd514f654f080e51ac50fd617d22610d91780fe6b07a26b08
+             47abb38291058c97474ef6ddd190d30fc318185c09ca1589d2024f0a6f16d45f
+             11678377483fa5c005b2a107cb9943e5da634e7046855eaa888663de55d64713
+             71d55d",
+            "b6451e30b953c206e34644c6803724e9d2725e0893039cfc49584f991f451af3
+             b89e8ff572d3da4f4022199b9563b9d70ebb616efff0763e9abec71b550f1371
+             e233319c4c4e74da936ba8e5bbb29a598e007a0bbfa929c99738ca2cc098d591
+             34d11ff300c39f82e2fce9f7f0fa266459503f64ab9913befc65fddc474f6dc1
+             c67669",
+        ),
+        (
+            64,
+            "4eed7141ea4a5cd4b788606bd23f46e212af9cacebacdc7d1f4c6dc7f2511b98
+             fc9cc56cb831ffe33ea8e7e1d1df09b26efd2767670066aa82d023b1dfe8ab1b
+             2b7fbb5b97592d46ffe3e05a6a9b592e2949c74160e4674301bc3f97e04903f8
+             c6cf95b863174c33228924cdef7ae47559b10b294acd660666c4538833582b43
+             f82d74",
+            "ba8ced36f327700d213f120b1a207a3b8c04330528586f414d09f2f7d9ccb7e6
+             8244c26010afc3f762615bbac552a1ca909e67c83e2fd5478cf46b9e811efccc
+             93f77a21b17a152ebaca1695733fdb086e23cd0eb48c41c034d52523fc21236e
+             5d8c9255306e48d52ba40b4dac24256460d56573d1312319afcf3ed39d72d0bf
+             c69acb",
+            "a5c4a7053fa86b64746d4bb688d06ad1f02a18fce9afd3e818fefaa7126bf73e
+             9b9493a9befebe0bf0c9509fb3105cfa0e262cde141aa8e3f2c2f77890bb64a4
+             cca96922a21ead111f6338ad5244f2c15c44cb595443ac2ac294231e31be4a43
+             07d0a91e874d36fc9852aeb1265c09b6e0cda7c37ef686fbbcab97e8ff66718b
+             e048bb",
+        ),
+        (
+            65,
+            "de1e5fa0be70df6d2be8fffd0e99ceaa8eb6e8c93a63f2d8d1c30ecb6b263dee
+             0e16e0a4749d6811dd1d6d1265c29729b1b75a9ac346cf93f0e1d7296dfcfd43
+             13b3a227faaaaf7757cc95b4e87a49be3b8a270a12020233509b1c3632b3485e
+             ef309d0abc4a4a696c9decc6e90454b53b000f456a3f10079072baaf7a981653
+             221f2c",
+            "c0a4edefa2d2accb9277c371ac12fcdbb52988a86edc54f0716e1591b4326e72
+             d5e795f46a596b02d3d4bfb43abad1e5d19211152722ec1f20fef2cd413e3c22
+             f2fc5da3d73041275be6ede3517b3b9f0fc67ade5956a672b8b75d96cb43294b
+             9041497de92637ed3f2439225e683910cb3ae923374449ca788fb0f9bea92731
+             bc26ad",
+            "51fd05c3c1cfbc8ed67d139ad76f5cf8236cd2acd26627a30c104dfd9d3ff8a8
+             2b02e8bd36d8498a75ad8c8e9b15eb386970283d6dd42c8ae7911cc592887fdb
+             e26a0a5f0bf821cd92986c60b2502c9be3f98a9c133a7e8045ea867e0828c725
+             2e739321f7c2d65daee4468eb4429efae469a42763f1f94977435d10dccae3e3
+             dce88d",
+        ),
+        (
+            1023,
+            "10108970eeda3eb932baac1428c7a2163b0e924c9a9e25b35bba72b28f70bd11
+             a182d27a591b05592b15607500e1e8dd56bc6c7fc063715b7a1d737df5bad333
+             9c56778957d870eb9717b57ea3d9fb68d1b55127bba6a906a4a24bbd5acb2d12
+             3a37b28f9e9a81bbaae360d58f85e5fc9d75f7c370a0cc09b6522d9c8d822f2f
+             28f485",
+            "c951ecdf03288d0fcc96ee3413563d8a6d3589547f2c2fb36d9786470f1b9d6e
+             890316d2e6d8b8c25b0a5b2180f94fb1a158ef508c3cde45e2966bd796a696d3
+             e13efd86259d756387d9becf5c8bf1ce2192b87025152907b6d8cc33d17826d8
+             b7b9bc97e38c3c85108ef09f013e01c229c20a83d9e8efac5b37470da28575fd
+             755a10",
+            "74a16c1c3d44368a86e1ca6df64be6a2f64cce8f09220787450722d85725dea5
+             9c413264404661e9e4d955409dfe4ad3aa487871bcd454ed12abfe2c2b1eb775
+             7588cf6cb18d2eccad49e018c0d0fec323bec82bf1644c6325717d13ea712e68
+             40d3e6e730d35553f59eff5377a9c350bcc1556694b924b858f329c44ee64b88
+             4ef00d",
+        ),
+        (
+            1024,
+            "42214739f095a406f3fc83deb889744ac00df831c10daa55189b5d121c855af7
+             1cf8107265ecdaf8505b95d8fcec83a98a6a96ea5109d2c179c47a387ffbb404
+             756f6eeae7883b446b70ebb144527c2075ab8ab204c0086bb22b7c93d465efc5
+             7f8d917f0b385c6df265e77003b85102967486ed57db5c5ca170ba441427ed9a
+             fa684e",
+            "75c46f6f3d9eb4f55ecaaee480db732e6c2105546f1e675003687c31719c7ba4
+             a78bc838c72852d4f49c864acb7adafe2478e824afe51c8919d06168414c265f
+             298a8094b1ad813a9b8614acabac321f24ce61c5a5346eb519520d38ecc43e89
+             b5000236df0597243e4d2493fd626730e2ba17ac4d8824d09d1a4a8f57b82277
+             78e2de",
+            "7356cd7720d5b66b6d0697eb3177d9f8d73a4a5c5e968896eb6a689684302706
+             6c23b601d3ddfb391e90d5c8eccdef4ae2a264bce9e612ba15e2bc9d654af148
+             1b2e75dbabe615974f1070bba84d56853265a34330b4766f8e75edd1f4a16504
+             76c10802f22b64bd3919d246ba20a17558bc51c199efdec67e80a227251808d8
+             ce5bad",
+        ),
+        (
+            1025,
+            "d00278ae47eb27b34faecf67b4fe263f82d5412916c1ffd97c8cb7fb814b8444
+             f4c4a22b4b399155358a994e52bf255de60035742ec71bd08ac275a1b51cc6bf
+             e332b0ef84b409108cda080e6269ed4b3e2c3f7d722aa4cdc98d16deb554e562
+             7be8f955c98e1d5f9565a9194cad0c4285f93700062d9595adb992ae68ff1280
+             0ab67a",
+            "357dc55de0c7e382c900fd6e320acc04146be01db6a8ce7210b7189bd664ea69
+             362396b77fdc0d2634a552970843722066c3c15902ae5097e00ff53f1e116f1c
+             d5352720113a837ab2452cafbde4d54085d9cf5d21ca613071551b25d52e69d6
+             c81123872b6f19cd3bc1333edf0c52b94de23ba772cf82636cff4542540a7738
+             d5b930",
+            "effaa245f065fbf82ac186839a249707c3bddf6d3fdda22d1b95a3c970379bcb
+             5d31013a167509e9066273ab6e2123bc835b408b067d88f96addb550d96b6852
+             dad38e320b9d940f86db74d398c770f462118b35d2724efa13da97194491d96d
+             d37c3c09cbef665953f2ee85ec83d88b88d11547a6f911c8217cca46defa2751
+             e7f3ad",
+        ),
+        (
+            2048,
+            "e776b6028c7cd22a4d0ba182a8bf62205d2ef576467e838ed6f2529b85fba24a
+             9a60bf80001410ec9eea6698cd537939fad4749edd484cb541aced55cd9bf547
+             64d063f23f6f1e32e12958ba5cfeb1bf618ad094266d4fc3c968c2088f677454
+             c288c67ba0dba337b9d91c7e1ba586dc9a5bc2d5e90c14f53a8863ac75655461
+             cea8f9",
+            "879cf1fa2ea0e79126cb1063617a05b6ad9d0b696d0d757cf053439f60a99dd1
+             0173b961cd574288194b23ece278c330fbb8585485e74967f31352a8183aa782
+             b2b22f26cdcadb61eed1a5bc144b8198fbb0c13abbf8e3192c145d0a5c21633b
+             0ef86054f42809df823389ee40811a5910dcbd1018af31c3b43aa55201ed4eda
+             ac74fe",
+            "7b2945cb4fef70885cc5d78a87bf6f6207dd901ff239201351ffac04e1088a23
+             e2c11a1ebffcea4d80447867b61badb1383d842d4e79645d48dd82ccba290769
+             caa7af8eaa1bd78a2a5e6e94fbdab78d9c7b74e894879f6a515257ccf6f95056
+             f4e25390f24f6b35ffbb74b766202569b1d797f2d4bd9d17524c720107f985f4
+             ddc583",
+        ),
+        (
+            2049,
+            "5f4d72f40d7a5f82b15ca2b2e44b1de3c2ef86c426c95c1af0b6879522563030
+             96de31d71d74103403822a2e0bc1eb193e7aecc9643a76b7bbc0c9f9c52e8783
+             aae98764ca468962b5c2ec92f0c74eb5448d519713e09413719431c802f948dd
+             5d90425a4ecdadece9eb178d80f26efccae630734dff63340285adec2aed3b51
+             073ad3",
+            "9f29700902f7c86e514ddc4df1e3049f258b2472b6dd5267f61bf13983b78dd5
+             f9a88abfefdfa1e00b418971f2b39c64ca621e8eb37fceac57fd0c8fc8e117d4
+             3b81447be22d5d8186f8f5919ba6bcc6846bd7d50726c06d245672c2ad4f6170
+             2c646499ee1173daa061ffe15bf45a631e2946d616a4c345822f1151284712f7
+             6b2b0e",
+            "2ea477c5515cc3dd606512ee72bb3e0e758cfae7232826f35fb98ca1bcbdf273
+             16d8e9e79081a80b046b60f6a263616f33ca464bd78d79fa18200d06c7fc9bff
+             d808cc4755277a7d5e09da0f29ed150f6537ea9bed946227ff184cc66a72a5f8
+             c1e4bd8b04e81cf40fe6dc4427ad5678311a61f4ffc39d195589bdbc670f63ae
+             70f4b6",
+        ),
+        (
+            3072,
+            "b98cb0ff3623be03326b373de6b9095218513e64f1ee2edd2525c7ad1e5cffd2
+             9a3f6b0b978d6608335c09dc94ccf682f9951cdfc501bfe47b9c9189a6fc7b40
+             4d120258506341a6d802857322fbd20d3e5dae05b95c88793fa83db1cb08e7d8
+             008d1599b6209d78336e24839724c191b2a52a80448306e0daa84a3fdb566661
+             a37e11",
+            "044a0e7b172a312dc02a4c9a818c036ffa2776368d7f528268d2e6b5df191770
+             22f302d0529e4174cc507c463671217975e81dab02b8fdeb0d7ccc7568dd2257
+             4c783a76be215441b32e91b9a904be8ea81f7a0afd14bad8ee7c8efc305ace5d
+             3dd61b996febe8da4f56ca0919359a7533216e2999fc87ff7d8f176fbecb3d6f
+             34278b",
+            "050df97f8c2ead654d9bb3ab8c9178edcd902a32f8495949feadcc1e0480c46b
+             3604131bbd6e3ba573b6dd682fa0a63e5b165d39fc43a625d00207607a2bfeb6
+             5ff1d29292152e26b298868e3b87be95d6458f6f2ce6118437b632415abe6ad5
+             22874bcd79e4030a5e7bad2efa90a7a7c67e93f0a18fb28369d0a9329ab5c241
+             34ccb0",
+        ),
+        (
+            3073,
+            "7124b49501012f81cc7f11ca069ec9226cecb8a2c850cfe644e327d22d3e1cd3
+             9a27ae3b79d68d89da9bf25bc27139ae65a324918a5f9b7828181e52cf373c84
+             f35b639b7fccbb985b6f2fa56aea0c18f531203497b8bbd3a07ceb5926f1cab7
+             4d14bd66486d9a91eba99059a98bd1cd25876b2af5a76c3e9eed554ed72ea952
+             b603bf",
+            "68dede9bef00ba89e43f31a6825f4cf433389fedae75c04ee9f0cf16a427c95a
+             96d6da3fe985054d3478865be9a092250839a697bbda74e279e8a9e69f0025e4
+             cfddd6cfb434b1cd9543aaf97c635d1b451a4386041e4bb100f5e45407cbbc24
+             fa53ea2de3536ccb329e4eb9466ec37093a42cf62b82903c696a93a50b702c80
+             f3c3c5",
+            "72613c9ec9ff7e40f8f5c173784c532ad852e827dba2bf85b2ab4b76f7079081
+             576288e552647a9d86481c2cae75c2dd4e7c5195fb9ada1ef50e9c5098c249d7
+             43929191441301c69e1f48505a4305ec1778450ee48b8e69dc23a25960fe3307
+             0ea549119599760a8a2d28aeca06b8c5e9ba58bc19e11fe57b6ee98aa44b2a8e
+             6b14a5",
+        ),
+        (
+            4096,
+            "015094013f57a5277b59d8475c0501042c0b642e531b0a1c8f58d2163229e969
+             0289e9409ddb1b99768eafe1623da896faf7e1114bebeadc1be30829b6f8af70
+             7d85c298f4f0ff4d9438aef948335612ae921e76d411c3a9111df62d27eaf871
+             959ae0062b5492a0feb98ef3ed4af277f5395172dbe5c311918ea0074ce00364
+             54f620",
+            "befc660aea2f1718884cd8deb9902811d332f4fc4a38cf7c7300d597a081bfc0
+             bbb64a36edb564e01e4b4aaf3b060092a6b838bea44afebd2deb8298fa562b7b
+             597c757b9df4c911c3ca462e2ac89e9a787357aaf74c3b56d5c07bc93ce89956
+             8a3eb17d9250c20f6c5f6c1e792ec9a2dcb715398d5a6ec6d5c54f586a00403a
+             1af1de",
+            "1e0d7f3db8c414c97c6307cbda6cd27ac3b030949da8e23be1a1a924ad2f25b9
+             d78038f7b198596c6cc4a9ccf93223c08722d684f240ff6569075ed81591fd93
+             f9fff1110b3a75bc67e426012e5588959cc5a4c192173a03c00731cf84544f65
+             a2fb9378989f72e9694a6a394a8a30997c2e67f95a504e631cd2c5f552460247
+             61b245",
+        ),
+        (
+            4097,
+            "9b4052b38f1c5fc8b1f9ff7ac7b27cd242487b3d890d15c96a1c25b8aa0fb995
+             05f91b0b5600a11251652eacfa9497b31cd3c409ce2e45cfe6c0a016967316c4
+             26bd26f619eab5d70af9a418b845c608840390f361630bd497b1ab4401931635
+             7c61dbe091ce72fc16dc340ac3d6e009e050b3adac4b5b2c92e722cffdc46501
+             531956",
+            "00df940cd36bb9fa7cbbc3556744e0dbc8191401afe70520ba292ee3ca80abbc
+             606db4976cfdd266ae0abf667d9481831ff12e0caa268e7d3e57260c0824115a
+             54ce595ccc897786d9dcbf495599cfd90157186a46ec800a6763f1c59e36197e
+             9939e900809f7077c102f888caaf864b253bc41eea812656d46742e4ea42769f
+             89b83f",
+            "aca51029626b55fda7117b42a7c211f8c6e9ba4fe5b7a8ca922f34299500ead8
+             a897f66a400fed9198fd61dd2d58d382458e64e100128075fc54b860934e8de2
+             e84170734b06e1d212a117100820dbc48292d148afa50567b8b84b1ec336ae10
+             d40c8c975a624996e12de31abbe135d9d159375739c333798a80c64ae895e51e
+             22f3ad",
+        ),
+        (
+            8193,
+            "bab6c09cb8ce8cf459261398d2e7aef35700bf488116ceb94a36d0f5f1b7bc3b
+             b2282aa69be089359ea1154b9a9286c4a56af4de975a9aa4a5c497654914d279
+             bea60bb6d2cf7225a2fa0ff5ef56bbe4b149f3ed15860f78b4e2ad04e158e375
+             c1e0c0b551cd7dfc82f1b155c11b6b3ed51ec9edb30d133653bb5709d1dbd55f
+             4e1ff6",
+            "954a2a75420c8d6547e3ba5b98d963e6fa6491addc8c023189cc519821b4a1f5
+             f03228648fd983aef045c2fa8290934b0866b615f585149587dda22990399653
+             28835a2b18f1d63b7e300fc76ff260b571839fe44876a4eae66cbac8c6769441
+             1ed7e09df51068a22c6e67d6d3dd2cca8ff12e3275384006c80f4db68023f24e
+             ebba57",
+            "af1e0346e389b17c23200270a64aa4e1ead98c61695d917de7d5b00491c9b0f1
+             2f20a01d6d622edf3de026a4db4e4526225debb93c1237934d71c7340bb59161
+             58cbdafe9ac3225476b6ab57a12357db3abbad7a26c6e66290e44034fb08a20a
+             8d0ec264f309994d2810c49cfba6989d7abb095897459f5425adb48aba07c5fb
+             3c83c0",
+        ),
+        (
+            16384,
+            "f875d6646de28985646f34ee13be9a576fd515f76b5b0a26bb324735041ddde4
+             9d764c270176e53e97bdffa58d549073f2c660be0e81293767ed4e4929f9ad34
+             bbb39a529334c57c4a381ffd2a6d4bfdbf1482651b172aa883cc13408fa67758
+             a3e47503f93f87720a3177325f7823251b85275f64636a8f1d599c2e49722f42
+             e93893",
+            "9e9fc4eb7cf081ea7c47d1807790ed211bfec56aa25bb7037784c13c4b707b0d
+             f9e601b101e4cf63a404dfe50f2e1865bb12edc8fca166579ce0c70dba5a5c0f
+             c960ad6f3772183416a00bd29d4c6e651ea7620bb100c9449858bf14e1ddc9ec
+             d35725581ca5b9160de04060045993d972571c3e8f71e9d0496bfa744656861b
+             169d65",
+            "160e18b5878cd0df1c3af85eb25a0db5344d43a6fbd7a8ef4ed98d0714c3f7e1
+             60dc0b1f09caa35f2f417b9ef309dfe5ebd67f4c9507995a531374d099cf8ae3
+             17542e885ec6f589378864d3ea98716b3bbb65ef4ab5e0ab5bb298a501f19a41
+             ec19af84a5e6b428ecd813b1a47ed91c9657c3fba11c406bc316768b58f6802c
+             9e9b57",
+        ),
+        (
+            31744,
+            "62b6960e1a44bcc1eb1a611a8d6235b6b4b78f32e7abc4fb4c6cdcce94895c47
+             860cc51f2b0c28a7b77304bd55fe73af663c02d3f52ea053ba43431ca5bab7bf
+             ea2f5e9d7121770d88f70ae9649ea713087d1914f7f312147e247f87eb2d4ffe
+             f0ac978bf7b6579d57d533355aa20b8b77b13fd09748728a5cc327a8ec470f40
+             13226f",
+            "efa53b389ab67c593dba624d898d0f7353ab99e4ac9d42302ee64cbf9939a419
+             3a7258db2d9cd32a7a3ecfce46144114b15c2fcb68a618a976bd74515d47be08
+             b628be420b5e830fade7c080e351a076fbc38641ad80c736c8a18fe3c66ce12f
+             95c61c2462a9770d60d0f77115bbcd3782b593016a4e728d4c06cee4505cb0c0
+             8a42ec",
+            "39772aef80e0ebe60596361e45b061e8f417429d529171b6764468c22928e28e
+             9759adeb797a3fbf771b1bcea30150a020e317982bf0d6e7d14dd9f064bc1102
+             5c25f31e81bd78a921db0174f03dd481d30e93fd8e90f8b2fee209f849f2d2a5
+             2f31719a490fb0ba7aea1e09814ee912eba111a9fde9d5c274185f7bae8ba85d
+             300a2b",
+        ),
+        (
+            102400,
+            "bc3e3d41a1146b069abffad3c0d44860cf664390afce4d9661f7902e7943e085
+             e01c59dab908c04c3342b816941a26d69c2605ebee5ec5291cc55e15b76146e6
+             745f0601156c3596cb75065a9c57f35585a52e1ac70f69131c23d611ce11ee4a
+             b1ec2c009012d236648e77be9295dd0426f29b764d65de58eb7d01dd42248204
+             f45f8e",
+            "1c35d1a5811083fd7119f5d5d1ba027b4d01c0c6c49fb6ff2cf75393ea5db4a7
+             f9dbdd3e1d81dcbca3ba241bb18760f207710b751846faaeb9dff8262710999a
+             59b2aa1aca298a032d94eacfadf1aa192418eb54808db23b56e34213266aa084
+             99a16b354f018fc4967d05f8b9d2ad87a7278337be9693fc638a3bfdbe314574
+             ee6fc4",
+            "4652cff7a3f385a6103b5c260fc1593e13c778dbe608efb092fe7ee69df6e9c6
+             d83a3e041bc3a48df2879f4a0a3ed40e7c961c73eff740f3117a0504c2dff478
+             6d44fb17f1549eb0ba585e40ec29bf7732f0b7e286ff8acddc4cb1e23b87ff5d
+             824a986458dcc6a04ac83969b80637562953df51ed1a7e90a7926924d2763778
+             be8560",
+        ),
+    ];
+
+    fn test_input(len: usize) -> Vec<u8> {
+        (0..len).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn read_xof(mut reader: Blake3Reader, len: usize) -> Vec<u8> {
+        let mut out = vec![0u8; len];
+        reader.read(&mut out);
+        out
+    }
+
+    #[test]
+    fn test_official_vectors() {
+        for &(len, hash, keyed_hash, derive_key) in TEST_VECTORS {
+            let input = test_input(len);
+            let hash = hex(hash);
+            let keyed_hash = hex(keyed_hash);
+            let derive_key = hex(derive_key);
+
+            let mut hasher = Blake3::new();
+            hasher.update(&input);
+            assert_eq!(
+                read_xof(hasher.finalize_xof(), 131),
+                hash,
+                "hash, len {len}"
+            );
+            assert_eq!(blake3(&input).to_vec(), hash[..OUT_LEN]);
+
+            let mut hasher = Blake3::new_keyed(TEST_KEY);
+            hasher.update(&input);
+            assert_eq!(read_xof(hasher.finalize_xof(), 131), keyed_hash);
+            assert_eq!(
+                blake3_keyed(TEST_KEY, &input).to_vec(),
+                keyed_hash[..OUT_LEN]
+            );
+
+            let mut out = vec![0u8; 131];
+            blake3_derive_key(TEST_CONTEXT, &input, &mut out);
+            assert_eq!(out, derive_key);
+        }
+    }
+
+    #[test]
+    fn test_streaming_matches_one_shot() {
+        let input = test_input(5000);
+        let expected = blake3(&input);
+
+        for piece in [1, 63, 64, 65, 1023, 1024, 1025, 4096] {
+            let mut hasher = Blake3::new();
+            for chunk in input.chunks(piece) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finalize(), expected, "piece size {piece}");
+        }
+    }
+
+    #[test]
+    fn test_threaded_update_matches_serial() {
+        let input = test_input(102400);
+        let (_, hash, keyed_hash, _) = TEST_VECTORS[TEST_VECTORS.len() - 1];
+
+        for threads in [1, 2, 3, 4] {
+            let mut hasher = Blake3::new();
+            hasher.update_threaded(&input, threads);
+            assert_eq!(hasher.finalize().to_vec(), hex(hash)[..OUT_LEN]);
+
+            let mut hasher = Blake3::new_keyed(TEST_KEY);
+            hasher.update_threaded(&input, threads);
+            assert_eq!(hasher.finalize().to_vec(), hex(keyed_hash)[..OUT_LEN]);
+        }
+
+        // subtrees have to stay aligned when the threaded path starts mid-chunk or mid-tree
+        for prefix in [1, 1024, 3000, 5120] {
+            let mut hasher = Blake3::new();
+            hasher.update(&input[..prefix]);
+            hasher.update_threaded(&input[prefix..], 4);
+            assert_eq!(hasher.finalize().to_vec(), hex(hash)[..OUT_LEN]);
+        }
+
+        let mut hasher = Blake3::new();
+        hasher.update_parallel(&input);
+        assert_eq!(hasher.finalize().to_vec(), hex(hash)[..OUT_LEN]);
+    }
+
+    #[test]
+    fn test_xof_reader_seeking() {
+        let mut hasher = Blake3::new();
+        hasher.update(b"abc");
+        let full = read_xof(hasher.finalize_xof(), 300);
+
+        // reading in odd pieces continues the stream
+        let mut reader = hasher.finalize_xof();
+        let mut pieces = Vec::new();
+        for len in [1, 63, 64, 100, 72] {
+            let mut buf = vec![0u8; len];
+            reader.read(&mut buf);
+            pieces.extend_from_slice(&buf);
+        }
+        assert_eq!(pieces, full);
+        assert_eq!(reader.position(), 300);
+
+        reader.set_position(130);
+        let mut buf = [0u8; 50];
+        reader.read(&mut buf);
+        assert_eq!(buf[..], full[130..180]);
+    }
+
+    #[test]
+    fn test_hash_function_trait_and_verify() {
+        let mut hasher = <Blake3 as HashFunction>::new();
+        HashFunction::update(&mut hasher, b"abc");
+        let digest = HashFunction::finalize(hasher.clone());
+        assert_eq!(digest, blake3(b"abc"));
+        assert_eq!(Blake3::OUTPUT_SIZE, 32);
+
+        assert!(hasher.verify(&digest));
+        for len in [0, 1, 16, 31] {
+            assert!(!hasher.verify(&digest[..len]));
+        }
+
+        // nor is a longer read of the extended output
+        let mut extended = [0u8; 64];
+        hasher.finalize_xof().read(&mut extended);
+        assert!(!hasher.verify(&extended));
+
+        // in keyed mode a 1-byte tag would be forged one time in 256
+        let mut mac = Blake3::new_keyed(&[0x61; KEY_LEN]);
+        mac.update(b"abc");
+        let tag = blake3_keyed(&[0x61; KEY_LEN], b"abc");
+        assert!(mac.verify(&tag));
+        assert!(!mac.verify(&tag[..1]));
+
+        let mut bad = digest;
+        bad[0] ^= 1;
+        assert!(!hasher.verify(&bad));
+    }
+}
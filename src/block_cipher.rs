@@ -0,0 +1,42 @@
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySizeError {
+    /// the key isn't one of the lengths the cipher accepts
+    InvalidLength,
+}
+
+impl fmt::Display for KeySizeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeySizeError::InvalidLength => write!(f, "invalid key length"),
+        }
+    }
+}
+
+impl std::error::Error for KeySizeError {}
+
+/// a keyed block cipher - the interface the block modes are written against, so that ECB and
+/// CBC can be shared between AES, DES and friends
+pub trait BlockCipher: Clone + Sync {
+    /// size in bytes of a block
+    const BLOCK_SIZE: usize;
+
+    type Key: ?Sized;
+
+    fn new(key: &Self::Key) -> Self;
+
+    /// encrypt a single `BLOCK_SIZE` block in place
+    fn encrypt_block(&self, block: &mut [u8]);
+
+    /// decrypt a single `BLOCK_SIZE` block in place
+    fn decrypt_block(&self, block: &mut [u8]);
+
+    /// decrypt a run of independent blocks in place - ciphers with a batched implementation
+    /// override this
+    fn decrypt_blocks(&self, blocks: &mut [u8]) {
+        for block in blocks.chunks_exact_mut(Self::BLOCK_SIZE) {
+            self.decrypt_block(block);
+        }
+    }
+}
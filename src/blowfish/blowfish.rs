@@ -0,0 +1,211 @@
+use super::constant::*;
+use crate::block_cipher::{BlockCipher, KeySizeError};
+
+// read the next big-endian word of `data`, cycling back to the start whenever it runs out
+fn next_word(data: &[u8], pos: &mut usize) -> u32 {
+    let mut word = 0u32;
+    for _ in 0..4 {
+        word = (word << 8) | data[*pos] as u32;
+        *pos = (*pos + 1) % data.len();
+    }
+    word
+}
+
+/// the Blowfish block cipher - a 16-round Feistel network over 64-bit blocks whose S-boxes are
+/// generated from the key, which is 32 to 448 bits long
+///
+/// kept for legacy interoperability and bcrypt - its 64-bit block makes it unsuitable for
+/// encrypting much data under one key
+#[derive(Clone)]
+pub struct Blowfish {
+    p: [u32; NUM_ROUNDS + 2],
+    s: [[u32; 256]; 4],
+}
+
+impl Blowfish {
+    /// the unkeyed state, with the P-array and S-boxes holding the digits of pi
+    pub fn initial_state() -> Self {
+        Blowfish {
+            p: P_INIT,
+            s: S_INIT,
+        }
+    }
+
+    /// the cipher under `key`, or `InvalidLength` unless it is 4 to 56 bytes long - use this
+    /// rather than `new` for keys from outside the program
+    pub fn try_new(key: &[u8]) -> Result<Self, KeySizeError> {
+        if !(MIN_KEY_SIZE..=MAX_KEY_SIZE).contains(&key.len()) {
+            return Err(KeySizeError::InvalidLength);
+        }
+
+        let mut cipher = Blowfish::initial_state();
+        cipher.expand_key(key, &[]);
+        Ok(cipher)
+    }
+
+    /// the first, salted key schedule of bcrypt's EksBlowfishSetup, or `InvalidLength` unless
+    /// `key` is 1 to 72 bytes long - the caller runs the expensive rounds with `expand_key`
+    pub fn try_new_salted(key: &[u8], salt: &[u8; EKS_SALT_SIZE]) -> Result<Self, KeySizeError> {
+        if !(1..=EKS_MAX_KEY_SIZE).contains(&key.len()) {
+            return Err(KeySizeError::InvalidLength);
+        }
+
+        let mut state = Blowfish::initial_state();
+        state.expand_key(key, salt);
+        Ok(state)
+    }
+
+    fn f(&self, x: u32) -> u32 {
+        let [a, b, c, d] = x.to_be_bytes();
+        (self.s[0][a as usize].wrapping_add(self.s[1][b as usize]) ^ self.s[2][c as usize])
+            .wrapping_add(self.s[3][d as usize])
+    }
+
+    pub fn encrypt_words(&self, [mut l, mut r]: [u32; 2]) -> [u32; 2] {
+        for pair in self.p[..NUM_ROUNDS].chunks_exact(2) {
+            l ^= pair[0];
+            r ^= self.f(l);
+            r ^= pair[1];
+            l ^= self.f(r);
+        }
+        [r ^ self.p[NUM_ROUNDS + 1], l ^ self.p[NUM_ROUNDS]]
+    }
+
+    pub fn decrypt_words(&self, [mut l, mut r]: [u32; 2]) -> [u32; 2] {
+        for pair in self.p[2..].chunks_exact(2).rev() {
+            l ^= pair[1];
+            r ^= self.f(l);
+            r ^= pair[0];
+            l ^= self.f(r);
+        }
+        [r ^ self.p[0], l ^ self.p[1]]
+    }
+
+    /// the key schedule - xor `key` (cycled) into the P-array, then replace the P-array and
+    /// S-boxes with the successive encryptions of a running block, which starts at zero
+    ///
+    /// with a `salt`, the block is xored with the salt's words (cycled) before each encryption -
+    /// the salted variant used by bcrypt's EksBlowfishSetup
+    ///
+    /// `key` must not be empty, and `salt` must be empty or a multiple of 8 bytes
+    pub fn expand_key(&mut self, key: &[u8], salt: &[u8]) {
+        let mut pos = 0;
+        for p in self.p.iter_mut() {
+            *p ^= next_word(key, &mut pos);
+        }
+
+        let mut pos = 0;
+        let mut block = [0u32; 2];
+        let mut next = |state: &Self, block: &mut [u32; 2]| {
+            if !salt.is_empty() {
+                block[0] ^= next_word(salt, &mut pos);
+                block[1] ^= next_word(salt, &mut pos);
+            }
+            *block = state.encrypt_words(*block);
+            *block
+        };
+
+        for i in (0..NUM_ROUNDS + 2).step_by(2) {
+            [self.p[i], self.p[i + 1]] = next(self, &mut block);
+        }
+        for sbox in 0..4 {
+            for i in (0..256).step_by(2) {
+                [self.s[sbox][i], self.s[sbox][i + 1]] = next(self, &mut block);
+            }
+        }
+    }
+}
+
+impl BlockCipher for Blowfish {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+
+    /// a 4 to 56-byte key
+    type Key = [u8];
+
+    /// panics unless `key` is 4 to 56 bytes long - see `try_new`
+    fn new(key: &[u8]) -> Self {
+        Self::try_new(key).expect("blowfish keys are 4 to 56 bytes")
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let l = u32::from_be_bytes(block[..4].try_into().unwrap());
+        let r = u32::from_be_bytes(block[4..].try_into().unwrap());
+        let [l, r] = self.encrypt_words([l, r]);
+        block[..4].copy_from_slice(&l.to_be_bytes());
+        block[4..].copy_from_slice(&r.to_be_bytes());
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let l = u32::from_be_bytes(block[..4].try_into().unwrap());
+        let r = u32::from_be_bytes(block[4..].try_into().unwrap());
+        let [l, r] = self.decrypt_words([l, r]);
+        block[..4].copy_from_slice(&l.to_be_bytes());
+        block[4..].copy_from_slice(&r.to_be_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    #[test]
+    fn test_eric_young_vectors() {
+        // from Eric Young's Blowfish test vectors, as published with the cipher
+        for (key, plaintext, ciphertext) in [
+            ("0000000000000000", "0000000000000000", "4ef997456198dd78"),
+            ("ffffffffffffffff", "ffffffffffffffff", "51866fd5b85ecb8a"),
+            ("3000000000000000", "1000000000000001", "7d856f9a613063f2"),
+            ("0123456789abcdef", "1111111111111111", "61f9c3802281b096"),
+            ("fedcba9876543210", "0123456789abcdef", "0aceab0fc6a0a28d"),
+        ] {
+            let cipher = Blowfish::new(&hex(key));
+            let mut block = hex(plaintext);
+            cipher.encrypt_block(&mut block);
+            assert_eq!(block, hex(ciphertext));
+            cipher.decrypt_block(&mut block);
+            assert_eq!(block, hex(plaintext));
+        }
+    }
+
+    #[test]
+    fn test_variable_key_length() {
+        // the same vectors' set-key tests - prefixes of one key, all encrypting fedcba9876543210
+        let key = hex("f0e1d2c3b4a5968778695a4b3c2d1e0f0011223344556677");
+        for (len, ciphertext) in [
+            (4, "be1e639408640f05"),
+            (8, "e87a244e2cc85e82"),
+            (16, "93142887ee3be15c"),
+            (24, "05044b62fa52d080"),
+        ] {
+            let cipher = Blowfish::new(&key[..len]);
+            let mut block = hex("fedcba9876543210");
+            cipher.encrypt_block(&mut block);
+            assert_eq!(block, hex(ciphertext), "key length {len}");
+        }
+    }
+
+    #[test]
+    fn test_rejects_other_key_sizes() {
+        for len in [0, 1, 3, 57, 64] {
+            assert_eq!(
+                Blowfish::try_new(&vec![0u8; len]).err(),
+                Some(KeySizeError::InvalidLength)
+            );
+        }
+        for len in [4, 56] {
+            assert!(Blowfish::try_new(&vec![0u8; len]).is_ok());
+        }
+
+        let salt = [0u8; EKS_SALT_SIZE];
+        for len in [0, 73, 100] {
+            assert_eq!(
+                Blowfish::try_new_salted(&vec![0u8; len], &salt).err(),
+                Some(KeySizeError::InvalidLength)
+            );
+        }
+        for len in [1, 72] {
+            assert!(Blowfish::try_new_salted(&vec![0u8; len], &salt).is_ok());
+        }
+    }
+}
@@ -0,0 +1,271 @@
+use super::constant::*;
+use crate::block_cipher::{BlockCipher, KeySizeError};
+
+// SBOX2 to SBOX4 are defined in RFC 3713 as rotations of SBOX1's output or input
+fn sbox(n: usize, x: u8) -> u8 {
+    match n {
+        1 => SBOX1[x as usize],
+        2 => SBOX1[x as usize].rotate_left(1),
+        3 => SBOX1[x as usize].rotate_left(7),
+        _ => SBOX1[x.rotate_left(1) as usize],
+    }
+}
+
+// the F-function - the S-boxes followed by the byte-oriented P-function
+fn f(input: u64, subkey: u64) -> u64 {
+    let x = (input ^ subkey).to_be_bytes();
+    let t = [
+        sbox(1, x[0]),
+        sbox(2, x[1]),
+        sbox(3, x[2]),
+        sbox(4, x[3]),
+        sbox(2, x[4]),
+        sbox(3, x[5]),
+        sbox(4, x[6]),
+        sbox(1, x[7]),
+    ];
+
+    u64::from_be_bytes([
+        t[0] ^ t[2] ^ t[3] ^ t[5] ^ t[6] ^ t[7],
+        t[0] ^ t[1] ^ t[3] ^ t[4] ^ t[6] ^ t[7],
+        t[0] ^ t[1] ^ t[2] ^ t[4] ^ t[5] ^ t[7],
+        t[1] ^ t[2] ^ t[3] ^ t[4] ^ t[5] ^ t[6],
+        t[0] ^ t[1] ^ t[5] ^ t[6] ^ t[7],
+        t[1] ^ t[2] ^ t[4] ^ t[6] ^ t[7],
+        t[2] ^ t[3] ^ t[4] ^ t[5] ^ t[7],
+        t[0] ^ t[3] ^ t[4] ^ t[5] ^ t[6],
+    ])
+}
+
+fn fl(input: u64, subkey: u64) -> u64 {
+    let (mut x1, mut x2) = ((input >> 32) as u32, input as u32);
+    let (k1, k2) = ((subkey >> 32) as u32, subkey as u32);
+    x2 ^= (x1 & k1).rotate_left(1);
+    x1 ^= x2 | k2;
+    ((x1 as u64) << 32) | x2 as u64
+}
+
+fn fl_inv(input: u64, subkey: u64) -> u64 {
+    let (mut y1, mut y2) = ((input >> 32) as u32, input as u32);
+    let (k1, k2) = ((subkey >> 32) as u32, subkey as u32);
+    y1 ^= y2 | k2;
+    y2 ^= (y1 & k1).rotate_left(1);
+    ((y1 as u64) << 32) | y2 as u64
+}
+
+// split a 128-bit value rotated left by `n` into its two 64-bit subkeys
+fn halves(x: u128, n: u32) -> [u64; 2] {
+    let x = x.rotate_left(n);
+    [(x >> 64) as u64, x as u64]
+}
+
+/// the Camellia block cipher (RFC 3713) - an 18-round Feistel network for 128-bit keys, or 24
+/// rounds for 192 and 256-bit keys, with FL layers every six rounds
+#[derive(Clone)]
+pub struct Camellia {
+    // pre- and post-whitening subkeys kw1 to kw4
+    kw: [u64; 4],
+    // round subkeys - only the first 18 are used with a 128-bit key
+    k: [u64; 24],
+    // FL and FL^-1 subkeys, in pairs
+    ke: [u64; 6],
+    rounds: usize,
+}
+
+impl Camellia {
+    /// the cipher under `key`, or `InvalidLength` unless it is 16, 24 or 32 bytes long - use
+    /// this rather than `new` for keys from outside the program
+    pub fn try_new(key: &[u8]) -> Result<Self, KeySizeError> {
+        let (kl, kr) = match key.len() {
+            16 => (u128::from_be_bytes(key.try_into().unwrap()), 0),
+            24 => {
+                let right = u64::from_be_bytes(key[16..].try_into().unwrap());
+                (
+                    u128::from_be_bytes(key[..16].try_into().unwrap()),
+                    ((right as u128) << 64) | !right as u128,
+                )
+            }
+            32 => (
+                u128::from_be_bytes(key[..16].try_into().unwrap()),
+                u128::from_be_bytes(key[16..].try_into().unwrap()),
+            ),
+            _ => return Err(KeySizeError::InvalidLength),
+        };
+
+        let mut d1 = ((kl ^ kr) >> 64) as u64;
+        let mut d2 = (kl ^ kr) as u64;
+        d2 ^= f(d1, SIGMA[0]);
+        d1 ^= f(d2, SIGMA[1]);
+        d1 ^= (kl >> 64) as u64;
+        d2 ^= kl as u64;
+        d2 ^= f(d1, SIGMA[2]);
+        d1 ^= f(d2, SIGMA[3]);
+        let ka = ((d1 as u128) << 64) | d2 as u128;
+
+        let mut kw = [0u64; 4];
+        let mut k = [0u64; 24];
+        let mut ke = [0u64; 6];
+
+        if key.len() == 16 {
+            [kw[0], kw[1]] = halves(kl, 0);
+            [k[0], k[1]] = halves(ka, 0);
+            [k[2], k[3]] = halves(kl, 15);
+            [k[4], k[5]] = halves(ka, 15);
+            [ke[0], ke[1]] = halves(ka, 30);
+            [k[6], k[7]] = halves(kl, 45);
+            [k[8], _] = halves(ka, 45);
+            [_, k[9]] = halves(kl, 60);
+            [k[10], k[11]] = halves(ka, 60);
+            [ke[2], ke[3]] = halves(kl, 77);
+            [k[12], k[13]] = halves(kl, 94);
+            [k[14], k[15]] = halves(ka, 94);
+            [k[16], k[17]] = halves(kl, 111);
+            [kw[2], kw[3]] = halves(ka, 111);
+
+            return Ok(Camellia {
+                kw,
+                k,
+                ke,
+                rounds: 18,
+            });
+        }
+
+        let mut d1 = ((ka ^ kr) >> 64) as u64;
+        let mut d2 = (ka ^ kr) as u64;
+        d2 ^= f(d1, SIGMA[4]);
+        d1 ^= f(d2, SIGMA[5]);
+        let kb = ((d1 as u128) << 64) | d2 as u128;
+
+        [kw[0], kw[1]] = halves(kl, 0);
+        [k[0], k[1]] = halves(kb, 0);
+        [k[2], k[3]] = halves(kr, 15);
+        [k[4], k[5]] = halves(ka, 15);
+        [ke[0], ke[1]] = halves(kr, 30);
+        [k[6], k[7]] = halves(kb, 30);
+        [k[8], k[9]] = halves(kl, 45);
+        [k[10], k[11]] = halves(ka, 45);
+        [ke[2], ke[3]] = halves(kl, 60);
+        [k[12], k[13]] = halves(kr, 60);
+        [k[14], k[15]] = halves(kb, 60);
+        [k[16], k[17]] = halves(kl, 77);
+        [ke[4], ke[5]] = halves(ka, 77);
+        [k[18], k[19]] = halves(kr, 94);
+        [k[20], k[21]] = halves(ka, 94);
+        [k[22], k[23]] = halves(kl, 111);
+        [kw[2], kw[3]] = halves(kb, 111);
+
+        Ok(Camellia {
+            kw,
+            k,
+            ke,
+            rounds: 24,
+        })
+    }
+
+    fn crypt(&self, block: &mut [u8], decrypt: bool) {
+        let rounds = self.rounds;
+        let layers = rounds / 6 - 1;
+        // decryption runs the same network with every subkey list reversed
+        let kw = if decrypt {
+            [self.kw[2], self.kw[3], self.kw[0], self.kw[1]]
+        } else {
+            self.kw
+        };
+        let k = |i: usize| {
+            if decrypt {
+                self.k[rounds - 1 - i]
+            } else {
+                self.k[i]
+            }
+        };
+        let ke = |i: usize| {
+            if decrypt {
+                self.ke[2 * layers - 1 - i]
+            } else {
+                self.ke[i]
+            }
+        };
+
+        let m = u128::from_be_bytes((&*block).try_into().unwrap());
+        let mut d1 = (m >> 64) as u64 ^ kw[0];
+        let mut d2 = m as u64 ^ kw[1];
+
+        for i in (0..rounds).step_by(2) {
+            if i > 0 && i % 6 == 0 {
+                let layer = i / 6 - 1;
+                d1 = fl(d1, ke(2 * layer));
+                d2 = fl_inv(d2, ke(2 * layer + 1));
+            }
+            d2 ^= f(d1, k(i));
+            d1 ^= f(d2, k(i + 1));
+        }
+
+        let c = ((((d2 ^ kw[2]) as u128) << 64) | (d1 ^ kw[3]) as u128).to_be_bytes();
+        block.copy_from_slice(&c);
+    }
+}
+
+impl BlockCipher for Camellia {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+
+    /// a 128, 192 or 256-bit key
+    type Key = [u8];
+
+    /// panics unless `key` is 16, 24 or 32 bytes long - see `try_new`
+    fn new(key: &[u8]) -> Self {
+        Self::try_new(key).expect("camellia keys are 16, 24 or 32 bytes")
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        self.crypt(block, false);
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        self.crypt(block, true);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    fn check(key: &str, plaintext: &str, ciphertext: &str) {
+        let cipher = Camellia::new(&hex(key));
+        let mut block = hex(plaintext);
+        cipher.encrypt_block(&mut block);
+        assert_eq!(block, hex(ciphertext));
+        cipher.decrypt_block(&mut block);
+        assert_eq!(block, hex(plaintext));
+    }
+
+    #[test]
+    fn test_rfc3713_vectors() {
+        // RFC 3713 appendix A
+        let plaintext = "0123456789abcdeffedcba9876543210";
+        check(plaintext, plaintext, "67673138549669730857065648eabe43");
+        check(
+            "0123456789abcdeffedcba98765432100011223344556677",
+            plaintext,
+            "b4993401b3e996f84ee5cee7d79b09b9",
+        );
+        check(
+            "0123456789abcdeffedcba987654321000112233445566778899aabbccddeeff",
+            plaintext,
+            "9acc237dff16d76c20ef7c919e3a7509",
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_key_sizes() {
+        for len in [0, 8, 15, 17, 23, 25, 31, 33, 64] {
+            assert_eq!(
+                Camellia::try_new(&vec![0u8; len]).err(),
+                Some(KeySizeError::InvalidLength)
+            );
+        }
+        for len in [16, 24, 32] {
+            assert!(Camellia::try_new(&vec![0u8; len]).is_ok());
+        }
+    }
+}
@@ -0,0 +1,494 @@
+use super::constant::*;
+use std::fmt;
+
+pub type ChaChaKey = [u8; KEY_SIZE];
+pub type ChaChaNonce = [u8; NONCE_SIZE];
+pub type XChaChaNonce = [u8; XNONCE_SIZE];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChaChaError {
+    /// the 32-bit block counter would wrap - a single nonce covers at most 256 GiB of keystream
+    KeystreamExhausted,
+}
+
+impl fmt::Display for ChaChaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChaChaError::KeystreamExhausted => write!(f, "chacha20 keystream exhausted"),
+        }
+    }
+}
+
+impl std::error::Error for ChaChaError {}
+
+pub fn quarter_round(state: &mut [u32; 16], a: usize, b: usize, c: usize, d: usize) {
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(16);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(12);
+    state[a] = state[a].wrapping_add(state[b]);
+    state[d] = (state[d] ^ state[a]).rotate_left(8);
+    state[c] = state[c].wrapping_add(state[d]);
+    state[b] = (state[b] ^ state[c]).rotate_left(7);
+}
+
+// the 20 rounds without the final feed-forward, which HChaCha20 leaves out
+fn rounds(state: &mut [u32; 16]) {
+    for _ in 0..10 {
+        // column round
+        quarter_round(state, 0, 4, 8, 12);
+        quarter_round(state, 1, 5, 9, 13);
+        quarter_round(state, 2, 6, 10, 14);
+        quarter_round(state, 3, 7, 11, 15);
+        // diagonal round
+        quarter_round(state, 0, 5, 10, 15);
+        quarter_round(state, 1, 6, 11, 12);
+        quarter_round(state, 2, 7, 8, 13);
+        quarter_round(state, 3, 4, 9, 14);
+    }
+}
+
+fn initial_state(key: &[u8; KEY_SIZE], input: &[u8; HNONCE_SIZE]) -> [u32; 16] {
+    let mut state = [0u32; 16];
+    state[..4].copy_from_slice(&SIGMA);
+    for (word, chunk) in state[4..12].iter_mut().zip(key.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    for (word, chunk) in state[12..].iter_mut().zip(input.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    state
+}
+
+/// the ChaCha20 block function (RFC 8439 section 2.3) - `state` holds the counter in word 12
+pub fn chacha20_block(state: &[u32; 16]) -> [u8; BLOCK_SIZE] {
+    let mut working = *state;
+    rounds(&mut working);
+
+    let mut out = [0u8; BLOCK_SIZE];
+    for ((chunk, w), s) in out
+        .chunks_exact_mut(4)
+        .zip(working.iter())
+        .zip(state.iter())
+    {
+        chunk.copy_from_slice(&w.wrapping_add(*s).to_le_bytes());
+    }
+    out
+}
+
+// the quarter round on PARALLEL_BLOCKS independent states at once, laid out so that each word
+// position is a vector with one lane per block - this is the form compilers turn into SIMD
+fn quarter_round_wide(
+    state: &mut [[u32; PARALLEL_BLOCKS]; 16],
+    a: usize,
+    b: usize,
+    c: usize,
+    d: usize,
+) {
+    let (mut va, mut vb, mut vc, mut vd) = (state[a], state[b], state[c], state[d]);
+    for (((a, b), c), d) in va
+        .iter_mut()
+        .zip(vb.iter_mut())
+        .zip(vc.iter_mut())
+        .zip(vd.iter_mut())
+    {
+        *a = a.wrapping_add(*b);
+        *d = (*d ^ *a).rotate_left(16);
+        *c = c.wrapping_add(*d);
+        *b = (*b ^ *c).rotate_left(12);
+        *a = a.wrapping_add(*b);
+        *d = (*d ^ *a).rotate_left(8);
+        *c = c.wrapping_add(*d);
+        *b = (*b ^ *c).rotate_left(7);
+    }
+    (state[a], state[b], state[c], state[d]) = (va, vb, vc, vd);
+}
+
+/// PARALLEL_BLOCKS consecutive keystream blocks starting at the counter in `state`
+pub fn chacha20_blocks(state: &[u32; 16]) -> [u8; BLOCK_SIZE * PARALLEL_BLOCKS] {
+    let mut input = [[0u32; PARALLEL_BLOCKS]; 16];
+    for (word, s) in input.iter_mut().zip(state.iter()) {
+        *word = [*s; PARALLEL_BLOCKS];
+    }
+    for (lane, counter) in input[12].iter_mut().enumerate() {
+        *counter = counter.wrapping_add(lane as u32);
+    }
+
+    let mut working = input;
+    for _ in 0..10 {
+        quarter_round_wide(&mut working, 0, 4, 8, 12);
+        quarter_round_wide(&mut working, 1, 5, 9, 13);
+        quarter_round_wide(&mut working, 2, 6, 10, 14);
+        quarter_round_wide(&mut working, 3, 7, 11, 15);
+        quarter_round_wide(&mut working, 0, 5, 10, 15);
+        quarter_round_wide(&mut working, 1, 6, 11, 12);
+        quarter_round_wide(&mut working, 2, 7, 8, 13);
+        quarter_round_wide(&mut working, 3, 4, 9, 14);
+    }
+
+    let mut out = [0u8; BLOCK_SIZE * PARALLEL_BLOCKS];
+    for (lane, block) in out.chunks_exact_mut(BLOCK_SIZE).enumerate() {
+        for (i, chunk) in block.chunks_exact_mut(4).enumerate() {
+            let word = working[i][lane].wrapping_add(input[i][lane]);
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+    }
+    out
+}
+
+/// HChaCha20 - derives a subkey from a key and the first 16 bytes of an extended nonce
+pub fn hchacha20(key: &ChaChaKey, input: &[u8; HNONCE_SIZE]) -> ChaChaKey {
+    let mut state = initial_state(key, input);
+    rounds(&mut state);
+
+    let mut subkey = [0u8; KEY_SIZE];
+    for (chunk, word) in subkey
+        .chunks_exact_mut(4)
+        .zip(state[..4].iter().chain(state[12..].iter()))
+    {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+    subkey
+}
+
+fn xor_into(data: &mut [u8], keystream: &[u8]) {
+    for (d, k) in data.iter_mut().zip(keystream.iter()) {
+        *d ^= k;
+    }
+}
+
+/// the ChaCha20 stream cipher (RFC 8439) with a 96-bit nonce and 32-bit block counter -
+/// encryption and decryption are the same operation
+#[derive(Clone)]
+pub struct ChaCha20 {
+    state: [u32; 16],
+    // byte offset into the keystream, counted from block 0
+    position: u64,
+    // the most recently generated block, kept for calls that end part way through it
+    buffer: [u8; BLOCK_SIZE],
+    buffer_block: Option<u64>,
+}
+
+impl ChaCha20 {
+    pub fn new(key: &ChaChaKey, nonce: &ChaChaNonce) -> Self {
+        let mut input = [0u8; HNONCE_SIZE];
+        input[4..].copy_from_slice(nonce);
+
+        ChaCha20 {
+            state: initial_state(key, &input),
+            position: 0,
+            buffer: [0u8; BLOCK_SIZE],
+            buffer_block: None,
+        }
+    }
+
+    /// start the keystream at block `counter` - RFC 8439 uses 1 for encryption, leaving block 0
+    /// for the Poly1305 key
+    pub fn with_counter(key: &ChaChaKey, nonce: &ChaChaNonce, counter: u32) -> Self {
+        let mut cipher = Self::new(key, nonce);
+        cipher.position = counter as u64 * BLOCK_SIZE as u64;
+        cipher
+    }
+
+    /// the current keystream position in bytes
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// move to an arbitrary keystream byte position
+    pub fn seek(&mut self, position: u64) -> Result<(), ChaChaError> {
+        if position > MAX_KEYSTREAM_LEN {
+            return Err(ChaChaError::KeystreamExhausted);
+        }
+        self.position = position;
+        Ok(())
+    }
+
+    fn state_at(&self, block: u64) -> [u32; 16] {
+        let mut state = self.state;
+        state[12] = block as u32;
+        state
+    }
+
+    /// xor the keystream into `data`, advancing the position - fails without touching `data` if
+    /// it would run past the end of the keystream
+    pub fn apply_keystream(&mut self, mut data: &mut [u8]) -> Result<(), ChaChaError> {
+        if self.position + data.len() as u64 > MAX_KEYSTREAM_LEN {
+            return Err(ChaChaError::KeystreamExhausted);
+        }
+
+        while !data.is_empty() {
+            let block = self.position / BLOCK_SIZE as u64;
+            let offset = (self.position % BLOCK_SIZE as u64) as usize;
+
+            // whole groups of blocks take the wide path, which mustn't run past the last block
+            let wide_len = BLOCK_SIZE * PARALLEL_BLOCKS;
+            if offset == 0
+                && data.len() >= wide_len
+                && block + PARALLEL_BLOCKS as u64 <= MAX_KEYSTREAM_LEN / BLOCK_SIZE as u64
+            {
+                let keystream = chacha20_blocks(&self.state_at(block));
+                xor_into(&mut data[..wide_len], &keystream);
+                self.position += wide_len as u64;
+                data = &mut data[wide_len..];
+                continue;
+            }
+
+            if self.buffer_block != Some(block) {
+                self.buffer = chacha20_block(&self.state_at(block));
+                self.buffer_block = Some(block);
+            }
+
+            let take = (BLOCK_SIZE - offset).min(data.len());
+            xor_into(&mut data[..take], &self.buffer[offset..offset + take]);
+            self.position += take as u64;
+            data = &mut data[take..];
+        }
+
+        Ok(())
+    }
+}
+
+/// XChaCha20 - ChaCha20 with a 192-bit nonce, long enough to be picked at random, using a
+/// subkey derived by HChaCha20 from the key and the first 16 bytes of the nonce
+#[derive(Clone)]
+pub struct XChaCha20(ChaCha20);
+
+impl XChaCha20 {
+    pub fn new(key: &ChaChaKey, nonce: &XChaChaNonce) -> Self {
+        Self::with_counter(key, nonce, 0)
+    }
+
+    pub fn with_counter(key: &ChaChaKey, nonce: &XChaChaNonce, counter: u32) -> Self {
+        let (subkey, chacha_nonce) = xchacha20_subkey_and_nonce(key, nonce);
+        XChaCha20(ChaCha20::with_counter(&subkey, &chacha_nonce, counter))
+    }
+
+    pub fn position(&self) -> u64 {
+        self.0.position()
+    }
+
+    pub fn seek(&mut self, position: u64) -> Result<(), ChaChaError> {
+        self.0.seek(position)
+    }
+
+    pub fn apply_keystream(&mut self, data: &mut [u8]) -> Result<(), ChaChaError> {
+        self.0.apply_keystream(data)
+    }
+}
+
+/// the subkey and 96-bit ChaCha20 nonce an extended nonce expands to
+pub fn xchacha20_subkey_and_nonce(
+    key: &ChaChaKey,
+    nonce: &XChaChaNonce,
+) -> (ChaChaKey, ChaChaNonce) {
+    let subkey = hchacha20(key, nonce[..HNONCE_SIZE].try_into().unwrap());
+
+    let mut chacha_nonce = [0u8; NONCE_SIZE];
+    chacha_nonce[4..].copy_from_slice(&nonce[HNONCE_SIZE..]);
+    (subkey, chacha_nonce)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    const SUNSCREEN: &[u8] =
+        b"Ladies and Gentlemen of the class of '99: If I could offer you only \
+        one tip for the future, sunscreen would be it.";
+
+    fn sequential_key() -> ChaChaKey {
+        core::array::from_fn(|i| i as u8)
+    }
+
+    #[test]
+    fn test_quarter_round_rfc8439() {
+        // RFC 8439 section 2.1.1
+        let mut state = [0u32; 16];
+        state[..4].copy_from_slice(&[0x11111111, 0x01020304, 0x9b8d6f43, 0x01234567]);
+        quarter_round(&mut state, 0, 1, 2, 3);
+        assert_eq!(state[..4], [0xea2a92f4, 0xcb1cf8ce, 0x4581472e, 0x5881c4bb]);
+    }
+
+    #[test]
+    fn test_block_function_rfc8439() {
+        // RFC 8439 section 2.3.2
+        let nonce: ChaChaNonce = hex("000000090000004a00000000").try_into().unwrap();
+        let mut keystream = [0u8; 64];
+        ChaCha20::with_counter(&sequential_key(), &nonce, 1)
+            .apply_keystream(&mut keystream)
+            .unwrap();
+
+        assert_eq!(
+            keystream.to_vec(),
+            hex(
+                "10f1e7e4d13b5915500fdd1fa32071c4c7d1f4c733c068030422aa9ac3d46c4e
+                 d2826446079faa0914c2d705d98b02a2b5129cd1de164eb9cbd083e8a2503c4e"
+            )
+        );
+    }
+
+    #[test]
+    fn test_encryption_rfc8439() {
+        // RFC 8439 section 2.4.2
+        let nonce: ChaChaNonce = hex("000000000000004a00000000").try_into().unwrap();
+        let expected = hex(
+            "6e2e359a2568f98041ba0728dd0d6981e97e7aec1d4360c20a27afccfd9fae0b
+             f91b65c5524733ab8f593dabcd62b3571639d624e65152ab8f530c359f0861d8
+             07ca0dbf500d6a6156a38e088a22b65e52bc514d16ccf806818ce91ab7793736
+             5af90bbf74a35be6b40b8eedf2785e42874d",
+        );
+
+        let mut data = SUNSCREEN.to_vec();
+        ChaCha20::with_counter(&sequential_key(), &nonce, 1)
+            .apply_keystream(&mut data)
+            .unwrap();
+        assert_eq!(data, expected);
+
+        // and back again, in uneven pieces
+        let mut cipher = ChaCha20::with_counter(&sequential_key(), &nonce, 1);
+        for chunk in data.chunks_mut(7) {
+            cipher.apply_keystream(chunk).unwrap();
+        }
+        assert_eq!(data, SUNSCREEN);
+    }
+
+    #[test]
+    fn test_zero_key_keystream() {
+        // RFC 8439 appendix A.1 test vector 1
+        let mut keystream = [0u8; 64];
+        ChaCha20::new(&[0u8; 32], &[0u8; 12])
+            .apply_keystream(&mut keystream)
+            .unwrap();
+        assert_eq!(
+            keystream.to_vec(),
+            hex(
+                "76b8e0ada0f13d90405d6ae55386bd28bdd219b8a08ded1aa836efcc8b770dc7
+                 da41597c5157488d7724e03fb8d84a376a43b8f41518a11cc387b669b2ee6586"
+            )
+        );
+    }
+
+    #[test]
+    fn test_wide_path_matches_single_blocks() {
+        let key = sequential_key();
+        let nonce = [7u8; 12];
+
+        let mut wide = vec![0u8; 1000];
+        ChaCha20::new(&key, &nonce)
+            .apply_keystream(&mut wide)
+            .unwrap();
+
+        let mut single = vec![0u8; 1000];
+        let mut cipher = ChaCha20::new(&key, &nonce);
+        for chunk in single.chunks_mut(BLOCK_SIZE) {
+            cipher.apply_keystream(chunk).unwrap();
+        }
+        assert_eq!(wide, single);
+    }
+
+    #[test]
+    fn test_seek() {
+        let key = sequential_key();
+        let nonce = [3u8; 12];
+
+        let mut full = vec![0u8; 700];
+        ChaCha20::new(&key, &nonce)
+            .apply_keystream(&mut full)
+            .unwrap();
+
+        for position in [0, 1, 63, 64, 65, 300, 699] {
+            let mut cipher = ChaCha20::new(&key, &nonce);
+            cipher.seek(position).unwrap();
+
+            let mut part = vec![0u8; 700 - position as usize];
+            cipher.apply_keystream(&mut part).unwrap();
+            assert_eq!(part, full[position as usize..]);
+            assert_eq!(cipher.position(), 700);
+        }
+    }
+
+    #[test]
+    fn test_keystream_limit() {
+        let mut cipher = ChaCha20::new(&[0u8; 32], &[0u8; 12]);
+        assert_eq!(
+            cipher.seek(MAX_KEYSTREAM_LEN + 1),
+            Err(ChaChaError::KeystreamExhausted)
+        );
+
+        // the last block can be used, but not a byte past it
+        cipher.seek(MAX_KEYSTREAM_LEN - 64).unwrap();
+        let mut data = [0u8; 65];
+        assert_eq!(
+            cipher.apply_keystream(&mut data),
+            Err(ChaChaError::KeystreamExhausted)
+        );
+        assert_eq!(cipher.apply_keystream(&mut data[..64]), Ok(()));
+        assert_eq!(cipher.position(), MAX_KEYSTREAM_LEN);
+    }
+
+    #[test]
+    fn test_hchacha20() {
+        // draft-irtf-cfrg-xchacha section 2.2.1
+        let input: [u8; 16] = hex("000000090000004a0000000031415927").try_into().unwrap();
+        assert_eq!(
+            hchacha20(&sequential_key(), &input).to_vec(),
+            hex("82413b4227b27bfed30e42508a877d73a0f9e4d58a74a853c12ec41326d3ecdc")
+        );
+    }
+
+    #[test]
+    fn test_xchacha20() {
+        // the inputs of draft-irtf-cfrg-xchacha appendix A.3.2
+        let key: ChaChaKey = core::array::from_fn(|i| 0x80 + i as u8);
+        let nonce: XChaChaNonce = hex("404142434445464748494a4b4c4d4e4f5051525354555658")
+            .try_into()
+            .unwrap();
+        let plaintext = b"The dhole (pronounced \"dole\") is also known as the Asiatic wild dog, \
+            red dog, and whistling dog. It is about the size of a German shepherd but looks more \
+            like a long-legged fox. This highly elusive and skilled jumper is classified with \
+            wolves, coyotes, jackals, and foxes in the taxonomic family Canidae.";
+
+        let mut data = plaintext.to_vec();
+        XChaCha20::new(&key, &nonce)
+            .apply_keystream(&mut data)
+            .unwrap();
+        assert_eq!(
+            data,
+            hex(
+                "4559abba4e48c16102e8bb2c05e6947f50a786de162f9b0b7e592a9b53d0d4e9
+                 8d8d6410d540a1a6375b26d80dace4fab52384c731acbf16a5923c0c48d3575d
+                 4d0d2c673b666faa731061277701093a6bf7a158a8864292a41c48e3a9b4c0da
+                 ece0f8d98d0d7e05b37a307bbb66333164ec9e1b24ea0d6c3ffddcec4f68e744
+                 3056193a03c810e11344ca06d8ed8a2bfb1e8d48cfa6bc0eb4e2464b74814240
+                 7c9f431aee769960e15ba8b96890466ef2457599852385c661f752ce20f9da0c
+                 09ab6b19df74e76a95967446f8d0fd415e7bee2a12a114c20eb5292ae7a349ae
+                 577820d5520a1f3fb62a17ce6a7e68fa7c79111d8860920bc048ef43fe84486c
+                 cb87c25f0ae045f0cce1e7989a9aa220a28bdd4827e751a24a6d5c62d790a663
+                 93b93111c1a55dd7421a10184974c7c5"
+            )
+        );
+
+        // starting at block 1, as the draft's example does, is the same stream seeked forward
+        let mut data = plaintext.to_vec();
+        XChaCha20::with_counter(&key, &nonce, 1)
+            .apply_keystream(&mut data)
+            .unwrap();
+        assert_eq!(
+            data,
+            hex(
+                "7d0a2e6b7f7c65a236542630294e063b7ab9b555a5d5149aa21e4ae1e4fbce87
+                 ecc8e08a8b5e350abe622b2ffa617b202cfad72032a3037e76ffdcdc4376ee05
+                 3a190d7e46ca1de04144850381b9cb29f051915386b8a710b8ac4d027b8b050f
+                 7cba5854e028d564e453b8a968824173fc16488b8970cac828f11ae53cabd201
+                 12f87107df24ee6183d2274fe4c8b1485534ef2c5fbc1ec24bfc3663efaa08bc
+                 047d29d25043532db8391a8a3d776bf4372a6955827ccb0cdd4af403a7ce4c63
+                 d595c75a43e045f0cce1f29c8b93bd65afc5974922f214a40b7c402cdb91ae73
+                 c0b63615cdad0480680f16515a7ace9d39236464328a37743ffc28f4ddb324f4
+                 d0f5bbdc270c65b1749a6efff1fbaa09536175ccd29fb9e6057b307320d31683
+                 8a9c71f70b5b5907a66f7ea49aadc409"
+            )
+        );
+    }
+}
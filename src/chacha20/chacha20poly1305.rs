@@ -0,0 +1,322 @@
+use super::chacha20::{xchacha20_subkey_and_nonce, ChaCha20, ChaChaKey, ChaChaNonce, XChaChaNonce};
+use super::constant::*;
+use super::poly1305::{Poly1305, Poly1305Key, Poly1305Tag};
+use crate::aead::{Aead, AeadError};
+use crate::ct::ct_eq;
+
+/// the one-time Poly1305 key for a nonce - the first half of keystream block 0
+pub fn poly1305_key_gen(key: &ChaChaKey, nonce: &ChaChaNonce) -> Poly1305Key {
+    let mut otk = [0u8; POLY1305_KEY_SIZE];
+    ChaCha20::new(key, nonce)
+        .apply_keystream(&mut otk)
+        .expect("block 0 is always available");
+    otk
+}
+
+/// the tag over aad || pad16 || ciphertext || pad16 || len(aad) || len(ciphertext)
+fn compute_tag(otk: &Poly1305Key, aad: &[u8], ciphertext: &[u8]) -> Poly1305Tag {
+    let zeros = [0u8; POLY1305_BLOCK_SIZE];
+    let pad = |len: usize| {
+        &zeros[..(POLY1305_BLOCK_SIZE - len % POLY1305_BLOCK_SIZE) % POLY1305_BLOCK_SIZE]
+    };
+
+    let mut mac = Poly1305::new(otk);
+    mac.update(aad);
+    mac.update(pad(aad.len()));
+    mac.update(ciphertext);
+    mac.update(pad(ciphertext.len()));
+    mac.update(&(aad.len() as u64).to_le_bytes());
+    mac.update(&(ciphertext.len() as u64).to_le_bytes());
+    mac.finalize()
+}
+
+// block 0 goes to the Poly1305 key, leaving 2^32 - 1 blocks for the message
+fn check_len(len: usize) -> Result<(), AeadError> {
+    if len as u64 > MAX_KEYSTREAM_LEN - BLOCK_SIZE as u64 {
+        return Err(AeadError::MessageTooLong);
+    }
+    Ok(())
+}
+
+fn seal(
+    key: &ChaChaKey,
+    nonce: &ChaChaNonce,
+    aad: &[u8],
+    buffer: &mut [u8],
+) -> Result<Poly1305Tag, AeadError> {
+    check_len(buffer.len())?;
+
+    ChaCha20::with_counter(key, nonce, 1)
+        .apply_keystream(buffer)
+        .map_err(|_| AeadError::MessageTooLong)?;
+    Ok(compute_tag(&poly1305_key_gen(key, nonce), aad, buffer))
+}
+
+fn open(
+    key: &ChaChaKey,
+    nonce: &ChaChaNonce,
+    aad: &[u8],
+    buffer: &mut [u8],
+    tag: &[u8],
+) -> Result<(), AeadError> {
+    check_len(buffer.len())?;
+
+    let expected = compute_tag(&poly1305_key_gen(key, nonce), aad, buffer);
+    if tag.len() != POLY1305_TAG_SIZE || !ct_eq(&expected, tag) {
+        return Err(AeadError::AuthenticationFailed);
+    }
+
+    ChaCha20::with_counter(key, nonce, 1)
+        .apply_keystream(buffer)
+        .map_err(|_| AeadError::MessageTooLong)
+}
+
+/// the ChaCha20-Poly1305 AEAD (RFC 8439 section 2.8)
+#[derive(Clone)]
+pub struct ChaCha20Poly1305 {
+    key: ChaChaKey,
+}
+
+impl Aead for ChaCha20Poly1305 {
+    const KEY_SIZE: usize = KEY_SIZE;
+    const NONCE_SIZE: usize = NONCE_SIZE;
+    const TAG_SIZE: usize = POLY1305_TAG_SIZE;
+
+    type Key = ChaChaKey;
+    type Nonce = ChaChaNonce;
+    type Tag = Poly1305Tag;
+
+    fn new(key: &ChaChaKey) -> Self {
+        ChaCha20Poly1305 { key: *key }
+    }
+
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &ChaChaNonce,
+        aad: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<Poly1305Tag, AeadError> {
+        seal(&self.key, nonce, aad, buffer)
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &ChaChaNonce,
+        aad: &[u8],
+        buffer: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), AeadError> {
+        open(&self.key, nonce, aad, buffer, tag)
+    }
+}
+
+/// XChaCha20-Poly1305 - ChaCha20-Poly1305 under the HChaCha20 subkey for a 192-bit nonce, which
+/// is long enough to be picked at random for every message
+#[derive(Clone)]
+pub struct XChaCha20Poly1305 {
+    key: ChaChaKey,
+}
+
+impl Aead for XChaCha20Poly1305 {
+    const KEY_SIZE: usize = KEY_SIZE;
+    const NONCE_SIZE: usize = XNONCE_SIZE;
+    const TAG_SIZE: usize = POLY1305_TAG_SIZE;
+
+    type Key = ChaChaKey;
+    type Nonce = XChaChaNonce;
+    type Tag = Poly1305Tag;
+
+    fn new(key: &ChaChaKey) -> Self {
+        XChaCha20Poly1305 { key: *key }
+    }
+
+    fn encrypt_in_place_detached(
+        &self,
+        nonce: &XChaChaNonce,
+        aad: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<Poly1305Tag, AeadError> {
+        let (subkey, chacha_nonce) = xchacha20_subkey_and_nonce(&self.key, nonce);
+        seal(&subkey, &chacha_nonce, aad, buffer)
+    }
+
+    fn decrypt_in_place_detached(
+        &self,
+        nonce: &XChaChaNonce,
+        aad: &[u8],
+        buffer: &mut [u8],
+        tag: &[u8],
+    ) -> Result<(), AeadError> {
+        let (subkey, chacha_nonce) = xchacha20_subkey_and_nonce(&self.key, nonce);
+        open(&subkey, &chacha_nonce, aad, buffer, tag)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha2::sha256::Sha256;
+    use crate::test_util::{hex, wycheproof_aead};
+
+    const SUNSCREEN: &[u8] =
+        b"Ladies and Gentlemen of the class of '99: If I could offer you only \
+        one tip for the future, sunscreen would be it.";
+
+    fn test_key() -> ChaChaKey {
+        core::array::from_fn(|i| 0x80 + i as u8)
+    }
+
+    #[test]
+    fn test_rfc8439_poly1305_key_gen() {
+        // section 2.6.2
+        let nonce: ChaChaNonce = hex("000000000001020304050607").try_into().unwrap();
+        assert_eq!(
+            poly1305_key_gen(&test_key(), &nonce).to_vec(),
+            hex("8ad5a08b905f81cc815040274ab29471a833b637e3fd0da508dbb8e2fdd1a646")
+        );
+    }
+
+    #[test]
+    fn test_rfc8439_aead_vector() {
+        // section 2.8.2
+        let nonce: ChaChaNonce = hex("070000004041424344454647").try_into().unwrap();
+        let aad = hex("50515253c0c1c2c3c4c5c6c7");
+        let expected = hex(
+            "d31a8d34648e60db7b86afbc53ef7ec2a4aded51296e08fea9e2b5a736ee62d6
+             3dbea45e8ca9671282fafb69da92728b1a71de0a9e060b2905d6a5b67ecd3b36
+             92ddbd7f2d778b8c9803aee328091b58fab324e4fad675945585808b4831d7bc
+             3ff4def08e4b7a9de576d26586cec64b6116
+             1ae10b594f09e26a7e902ecbd0600691",
+        );
+
+        let aead = ChaCha20Poly1305::new(&test_key());
+        let sealed = aead.encrypt(&nonce, &aad, SUNSCREEN).unwrap();
+        assert_eq!(sealed, expected);
+        assert_eq!(aead.decrypt(&nonce, &aad, &sealed).unwrap(), SUNSCREEN);
+    }
+
+    #[test]
+    fn test_xchacha20_poly1305_vector() {
+        // draft-irtf-cfrg-xchacha appendix A.3.1
+        let nonce: XChaChaNonce = core::array::from_fn(|i| 0x40 + i as u8);
+        let aad = hex("50515253c0c1c2c3c4c5c6c7");
+        let expected = hex(
+            "bd6d179d3e83d43b9576579493c0e939572a1700252bfaccbed2902c21396cbb
+             731c7f1b0b4aa6440bf3a82f4eda7e39ae64c6708c54c216cb96b72e1213b452
+             2f8c9ba40db5d945b11b69b982c1bb9e3f3fac2bc369488f76b2383565d3fff9
+             21f9664c97637da9768812f615c68b13b52e
+             c0875924c1c7987947deafd8780acf49",
+        );
+
+        let aead = XChaCha20Poly1305::new(&test_key());
+        let sealed = aead.encrypt(&nonce, &aad, SUNSCREEN).unwrap();
+        assert_eq!(sealed, expected);
+        assert_eq!(aead.decrypt(&nonce, &aad, &sealed).unwrap(), SUNSCREEN);
+    }
+
+    #[test]
+    fn test_wycheproof() {
+        assert_eq!(
+            wycheproof_aead::<ChaCha20Poly1305>(include_str!(
+                "wycheproof/chacha20_poly1305_test.txt"
+            )),
+            293
+        );
+        assert_eq!(
+            wycheproof_aead::<XChaCha20Poly1305>(include_str!(
+                "wycheproof/xchacha20_poly1305_test.txt"
+            )),
+            280
+        );
+    }
+
+    // seal every plaintext length up to 300 bytes, with associated data of length len % 37,
+    // and hash the results together
+    fn length_sweep<A: Aead>(aead: &A, nonce: &A::Nonce) -> Vec<u8> {
+        let mut digest = Sha256::new();
+        for len in 0..300usize {
+            let plaintext: Vec<u8> = (0..len).map(|i| (i * 7 + len) as u8).collect();
+            let aad: Vec<u8> = (0..(len % 37) as u8).collect();
+
+            let sealed = aead.encrypt(nonce, &aad, &plaintext).unwrap();
+            assert_eq!(aead.decrypt(nonce, &aad, &sealed).unwrap(), plaintext);
+            digest.update(&sealed);
+        }
+        digest.finalize().to_vec()
+    }
+
+    #[test]
+    fn test_length_sweep() {
+        // digests computed with an independent ChaCha20-Poly1305 implementation - the sweep
+        // crosses every Poly1305 padding and keystream block boundary
+        let key: ChaChaKey = core::array::from_fn(|i| i as u8);
+
+        let nonce: ChaChaNonce = core::array::from_fn(|i| i as u8);
+        assert_eq!(
+            length_sweep(&ChaCha20Poly1305::new(&key), &nonce),
+            hex("3aab8bd6c9e76db490704d1dea02b7417d0c0ff60ff0498fba76e3a19afae124")
+        );
+
+        let nonce: XChaChaNonce = core::array::from_fn(|i| i as u8);
+        assert_eq!(
+            length_sweep(&XChaCha20Poly1305::new(&key), &nonce),
+            hex("ff76fff1b1ececeb97833d158f0b8e47ccb9b1b97a8214c5ece7e50f3593afbd")
+        );
+    }
+
+    #[test]
+    fn test_rejects_tampering() {
+        let aead = ChaCha20Poly1305::new(&test_key());
+        let nonce = [0x07; NONCE_SIZE];
+        let aad = b"header";
+        let sealed = aead.encrypt(&nonce, aad, b"attack at dawn").unwrap();
+
+        // every single-bit flip in the ciphertext or tag is caught
+        for i in 0..sealed.len() * 8 {
+            let mut bad = sealed.clone();
+            bad[i / 8] ^= 1 << (i % 8);
+            assert_eq!(
+                aead.decrypt(&nonce, aad, &bad),
+                Err(AeadError::AuthenticationFailed)
+            );
+        }
+
+        assert_eq!(
+            aead.decrypt(&nonce, b"heades", &sealed),
+            Err(AeadError::AuthenticationFailed)
+        );
+        assert_eq!(
+            aead.decrypt(&[0x08; NONCE_SIZE], aad, &sealed),
+            Err(AeadError::AuthenticationFailed)
+        );
+        assert_eq!(
+            aead.decrypt(&nonce, aad, &sealed[..15]),
+            Err(AeadError::CiphertextTooShort)
+        );
+
+        // a failed open leaves the ciphertext alone
+        let (ciphertext, tag) = sealed.split_at(sealed.len() - POLY1305_TAG_SIZE);
+        let mut buffer = ciphertext.to_vec();
+        let mut bad_tag = tag.to_vec();
+        bad_tag[0] ^= 1;
+        assert!(aead
+            .decrypt_in_place_detached(&nonce, aad, &mut buffer, &bad_tag)
+            .is_err());
+        assert_eq!(buffer, ciphertext);
+        assert!(aead
+            .decrypt_in_place_detached(&nonce, aad, &mut buffer, &tag[..8])
+            .is_err());
+    }
+
+    #[test]
+    fn test_empty_message() {
+        // an empty plaintext still authenticates the associated data
+        let aead = ChaCha20Poly1305::new(&test_key());
+        let nonce = [0u8; NONCE_SIZE];
+
+        let sealed = aead.encrypt(&nonce, b"only aad", &[]).unwrap();
+        assert_eq!(sealed.len(), POLY1305_TAG_SIZE);
+        assert_eq!(aead.decrypt(&nonce, b"only aad", &sealed).unwrap(), b"");
+        assert!(aead.decrypt(&nonce, b"other aad", &sealed).is_err());
+    }
+}
@@ -0,0 +1,16 @@
+pub const KEY_SIZE: usize = 32;
+pub const NONCE_SIZE: usize = 12;
+pub const HNONCE_SIZE: usize = 16;
+pub const XNONCE_SIZE: usize = 24;
+pub const BLOCK_SIZE: usize = 64;
+pub const PARALLEL_BLOCKS: usize = 4;
+
+// "expand 32-byte k" as little-endian words
+pub const SIGMA: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+
+// the 32-bit block counter allows 2^32 blocks of keystream per nonce
+pub const MAX_KEYSTREAM_LEN: u64 = (1 << 32) * BLOCK_SIZE as u64;
+
+pub const POLY1305_KEY_SIZE: usize = 32;
+pub const POLY1305_BLOCK_SIZE: usize = 16;
+pub const POLY1305_TAG_SIZE: usize = 16;
@@ -0,0 +1,5 @@
+#[allow(clippy::module_inception)]
+pub mod chacha20;
+pub mod chacha20poly1305;
+pub mod constant;
+pub mod poly1305;
@@ -0,0 +1,327 @@
+use super::constant::{POLY1305_BLOCK_SIZE, POLY1305_KEY_SIZE, POLY1305_TAG_SIZE};
+use crate::ct::ct_eq;
+use crate::mac::Mac;
+
+pub type Poly1305Key = [u8; POLY1305_KEY_SIZE];
+pub type Poly1305Tag = [u8; POLY1305_TAG_SIZE];
+
+const LIMB_MASK: u32 = 0x3ffffff;
+
+fn le32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes(bytes[..4].try_into().unwrap())
+}
+
+/// the Poly1305 one-time authenticator (RFC 8439 section 2.5) - a key must never be used for
+/// more than one message, which is why the AEAD constructions derive a fresh one per nonce
+///
+/// the accumulator and `r` are held as five 26-bit limbs of a 130-bit number, so the products
+/// in a block multiply fit in a u64 with room for the carries
+#[derive(Clone)]
+pub struct Poly1305 {
+    r: [u32; 5],
+    // r[1..] * 5, folding the 2^130 = 5 (mod p) reduction into the multiply
+    s: [u32; 4],
+    h: [u32; 5],
+    pad: [u32; 4],
+    buffer: [u8; POLY1305_BLOCK_SIZE],
+    buffer_len: usize,
+}
+
+impl Poly1305 {
+    /// `key` is r || s, with r clamped as the RFC requires
+    pub fn new(key: &Poly1305Key) -> Self {
+        // clamping clears the top four bits of every 32-bit word of r and the bottom two bits of
+        // all but the first - folded into the masks used to split r into limbs
+        let r = [
+            le32(&key[0..]) & 0x3ffffff,
+            (le32(&key[3..]) >> 2) & 0x3ffff03,
+            (le32(&key[6..]) >> 4) & 0x3ffc0ff,
+            (le32(&key[9..]) >> 6) & 0x3f03fff,
+            (le32(&key[12..]) >> 8) & 0x00fffff,
+        ];
+
+        Poly1305 {
+            r,
+            s: [r[1] * 5, r[2] * 5, r[3] * 5, r[4] * 5],
+            h: [0; 5],
+            pad: [
+                le32(&key[16..]),
+                le32(&key[20..]),
+                le32(&key[24..]),
+                le32(&key[28..]),
+            ],
+            buffer: [0u8; POLY1305_BLOCK_SIZE],
+            buffer_len: 0,
+        }
+    }
+
+    /// h = (h + block) * r mod 2^130 - 5, where `hibit` is the 2^128 bit appended to full blocks
+    fn process_block(&mut self, block: &[u8; POLY1305_BLOCK_SIZE], hibit: u32) {
+        let [r0, r1, r2, r3, r4] = self.r.map(u64::from);
+        let [s1, s2, s3, s4] = self.s.map(u64::from);
+
+        let h0 = (self.h[0] + (le32(&block[0..]) & LIMB_MASK)) as u64;
+        let h1 = (self.h[1] + ((le32(&block[3..]) >> 2) & LIMB_MASK)) as u64;
+        let h2 = (self.h[2] + ((le32(&block[6..]) >> 4) & LIMB_MASK)) as u64;
+        let h3 = (self.h[3] + ((le32(&block[9..]) >> 6) & LIMB_MASK)) as u64;
+        let h4 = (self.h[4] + ((le32(&block[12..]) >> 8) | hibit)) as u64;
+
+        let d0 = h0 * r0 + h1 * s4 + h2 * s3 + h3 * s2 + h4 * s1;
+        let mut d1 = h0 * r1 + h1 * r0 + h2 * s4 + h3 * s3 + h4 * s2;
+        let mut d2 = h0 * r2 + h1 * r1 + h2 * r0 + h3 * s4 + h4 * s3;
+        let mut d3 = h0 * r3 + h1 * r2 + h2 * r1 + h3 * r0 + h4 * s4;
+        let mut d4 = h0 * r4 + h1 * r3 + h2 * r2 + h3 * r1 + h4 * r0;
+
+        // partial carry propagation - leaves every limb within a couple of bits of 26, which is
+        // all the next multiply needs
+        d1 += d0 >> 26;
+        d2 += d1 >> 26;
+        d3 += d2 >> 26;
+        d4 += d3 >> 26;
+        let h0 = (d0 & LIMB_MASK as u64) + (d4 >> 26) * 5;
+        let h1 = (d1 & LIMB_MASK as u64) + (h0 >> 26);
+
+        self.h = [
+            h0 as u32 & LIMB_MASK,
+            h1 as u32,
+            d2 as u32 & LIMB_MASK,
+            d3 as u32 & LIMB_MASK,
+            d4 as u32 & LIMB_MASK,
+        ];
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        if self.buffer_len > 0 {
+            let take = (POLY1305_BLOCK_SIZE - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+
+            if self.buffer_len < POLY1305_BLOCK_SIZE {
+                return;
+            }
+            let block = self.buffer;
+            self.process_block(&block, 1 << 24);
+            self.buffer_len = 0;
+        }
+
+        let mut blocks = data.chunks_exact(POLY1305_BLOCK_SIZE);
+        for block in &mut blocks {
+            self.process_block(block.try_into().unwrap(), 1 << 24);
+        }
+
+        let rest = blocks.remainder();
+        self.buffer[..rest.len()].copy_from_slice(rest);
+        self.buffer_len = rest.len();
+    }
+
+    pub fn finalize(mut self) -> Poly1305Tag {
+        // a trailing partial block gets its 0x01 byte in place of the 2^128 bit
+        if self.buffer_len > 0 {
+            let mut block = [0u8; POLY1305_BLOCK_SIZE];
+            block[..self.buffer_len].copy_from_slice(&self.buffer[..self.buffer_len]);
+            block[self.buffer_len] = 1;
+            self.process_block(&block, 0);
+        }
+
+        // fully carry h
+        let [mut h0, mut h1, mut h2, mut h3, mut h4] = self.h;
+        h2 += h1 >> 26;
+        h1 &= LIMB_MASK;
+        h3 += h2 >> 26;
+        h2 &= LIMB_MASK;
+        h4 += h3 >> 26;
+        h3 &= LIMB_MASK;
+        h0 += (h4 >> 26) * 5;
+        h4 &= LIMB_MASK;
+        h1 += h0 >> 26;
+        h0 &= LIMB_MASK;
+
+        // g = h + 5 - 2^130, and h is replaced by g if that didn't go negative - selected with a
+        // mask so the final reduction doesn't branch on the tag
+        let mut g0 = h0 + 5;
+        let mut g1 = h1 + (g0 >> 26);
+        g0 &= LIMB_MASK;
+        let mut g2 = h2 + (g1 >> 26);
+        g1 &= LIMB_MASK;
+        let mut g3 = h3 + (g2 >> 26);
+        g2 &= LIMB_MASK;
+        let g4 = (h4 + (g3 >> 26)).wrapping_sub(1 << 26);
+        g3 &= LIMB_MASK;
+
+        let use_g = (g4 >> 31).wrapping_sub(1);
+        let h0 = (h0 & !use_g) | (g0 & use_g);
+        let h1 = (h1 & !use_g) | (g1 & use_g);
+        let h2 = (h2 & !use_g) | (g2 & use_g);
+        let h3 = (h3 & !use_g) | (g3 & use_g);
+        let h4 = (h4 & !use_g) | (g4 & use_g);
+
+        // repack into 32-bit words and add s mod 2^128
+        let words = [
+            h0 | (h1 << 26),
+            (h1 >> 6) | (h2 << 20),
+            (h2 >> 12) | (h3 << 14),
+            (h3 >> 18) | (h4 << 8),
+        ];
+
+        let mut tag = [0u8; POLY1305_TAG_SIZE];
+        let mut carry = 0u64;
+        for ((out, word), pad) in tag.chunks_exact_mut(4).zip(words).zip(self.pad) {
+            let sum = word as u64 + pad as u64 + carry;
+            out.copy_from_slice(&(sum as u32).to_le_bytes());
+            carry = sum >> 32;
+        }
+        tag
+    }
+
+    /// compare the computed tag against `tag` in constant time - only the full 16-byte tag is
+    /// accepted
+    pub fn verify(self, tag: &[u8]) -> bool {
+        let computed = self.finalize();
+        ct_eq(&computed, tag)
+    }
+}
+
+impl Mac for Poly1305 {
+    const OUTPUT_SIZE: usize = POLY1305_TAG_SIZE;
+
+    type Key = Poly1305Key;
+    type Output = Poly1305Tag;
+
+    fn new(key: &Self::Key) -> Self {
+        Poly1305::new(key)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        Poly1305::update(self, data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        Poly1305::finalize(self)
+    }
+
+    fn verify(self, tag: &[u8]) -> bool {
+        Poly1305::verify(self, tag)
+    }
+}
+
+/// one-shot Poly1305
+pub fn poly1305(key: &Poly1305Key, data: &[u8]) -> Poly1305Tag {
+    let mut mac = Poly1305::new(key);
+    mac.update(data);
+    mac.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha2::sha256::Sha256;
+    use crate::test_util::hex;
+
+    #[test]
+    fn test_rfc8439_vector() {
+        // section 2.5.2
+        let key: Poly1305Key =
+            hex("85d6be7857556d337f4452fe42d506a80103808afb0db2fd4abff6af4149f51b")
+                .try_into()
+                .unwrap();
+        let tag = poly1305(&key, b"Cryptographic Forum Research Group");
+        assert_eq!(tag.to_vec(), hex("a8061dc1305136c6c22b8baf0c0127a9"));
+    }
+
+    #[test]
+    fn test_rfc8439_edge_vectors() {
+        // appendix A.3 #5-#7 - accumulators that land on or just past p, and a carry out of
+        // the final addition of s
+        let mut two = [0u8; 32];
+        two[0] = 2;
+        let mut one = [0u8; 32];
+        one[0] = 1;
+
+        let cases: [(&Poly1305Key, &str, &str); 3] = [
+            (
+                &two,
+                "ffffffffffffffffffffffffffffffff",
+                "03000000000000000000000000000000",
+            ),
+            (
+                &{
+                    let mut key = two;
+                    key[16..].fill(0xff);
+                    key
+                },
+                "02000000000000000000000000000000",
+                "03000000000000000000000000000000",
+            ),
+            (
+                &one,
+                "ffffffffffffffffffffffffffffffff
+                 f0ffffffffffffffffffffffffffffff
+                 11000000000000000000000000000000",
+                "05000000000000000000000000000000",
+            ),
+        ];
+
+        for (key, message, tag) in cases {
+            assert_eq!(poly1305(key, &hex(message)).to_vec(), hex(tag));
+        }
+    }
+
+    #[test]
+    fn test_streaming_matches_one_shot() {
+        let key: Poly1305Key = core::array::from_fn(|i| i as u8);
+        let message: Vec<u8> = (0..200u32).map(|i| (i * 13) as u8).collect();
+        let expected = poly1305(&key, &message);
+
+        for chunk_size in [1, 7, 15, 16, 17, 33] {
+            let mut mac = <Poly1305 as Mac>::new(&key);
+            for chunk in message.chunks(chunk_size) {
+                Mac::update(&mut mac, chunk);
+            }
+            assert!(mac.clone().verify(&expected));
+            assert_eq!(mac.finalize(), expected);
+        }
+    }
+
+    #[test]
+    fn test_length_sweep() {
+        // tags for every message length up to 300 bytes, alternating an all-ones key (the
+        // largest clamped r) with a counting one, hashed together - the digest was computed
+        // with an independent Poly1305 implementation
+        let mut digest = Sha256::new();
+        for len in 0..300usize {
+            let key: Poly1305Key = if len % 2 == 1 {
+                [0xff; 32]
+            } else {
+                core::array::from_fn(|i| i as u8)
+            };
+            let message: Vec<u8> = (0..len).map(|i| 0xff - i as u8).collect();
+            digest.update(&poly1305(&key, &message));
+        }
+
+        assert_eq!(
+            digest.finalize().to_vec(),
+            hex("abf56e710fd5dfc0d244edecf2009b72d014c56bf750ebd4f50b2bc7b411fcc5")
+        );
+    }
+
+    #[test]
+    fn test_verify_rejects_bad_tags() {
+        let key = [0x42; 32];
+        let tag = poly1305(&key, b"message");
+
+        let mut bad = tag;
+        bad[15] ^= 0x80;
+        let mut mac = Poly1305::new(&key);
+        mac.update(b"message");
+        assert!(mac.clone().verify(&tag));
+        assert!(!mac.clone().verify(&bad));
+
+        // a prefix of the right tag is not a tag
+        for len in [0, 1, 8, 15] {
+            assert!(!mac.clone().verify(&tag[..len]));
+            assert!(!Mac::verify(mac.clone(), &tag[..len]));
+        }
+        assert!(!mac.verify(&[&tag[..], &[0]].concat()));
+    }
+}
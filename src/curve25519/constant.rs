@@ -0,0 +1,84 @@
+pub const KEY_SIZE: usize = 32;
+pub const FIELD_ELEMENT_SIZE: usize = 32;
+
+// limbs of a field element hold 51 bits each, 5 * 51 = 255
+pub const LIMB_BITS: u32 = 51;
+pub const LIMB_MASK: u64 = (1 << LIMB_BITS) - 1;
+
+// 2p limb by limb, added before subtracting so no limb goes negative
+pub const TWO_P: [u64; 5] = [
+    0xfffffffffffda,
+    0xffffffffffffe,
+    0xffffffffffffe,
+    0xffffffffffffe,
+    0xffffffffffffe,
+];
+
+// (A - 2) / 4 for the Montgomery curve y^2 = x^3 + 486662x^2 + x, as used by the RFC 7748
+// ladder
+pub const A24: u32 = 121665;
+
+// the u-coordinate of the base point
+pub const X25519_BASEPOINT: [u8; KEY_SIZE] = {
+    let mut u = [0u8; KEY_SIZE];
+    u[0] = 9;
+    u
+};
+
+pub const ED25519_SEED_SIZE: usize = 32;
+pub const ED25519_PUBLIC_KEY_SIZE: usize = 32;
+pub const ED25519_SIGNATURE_SIZE: usize = 64;
+// the context of Ed25519ctx and Ed25519ph is length-prefixed with a single byte
+pub const ED25519_MAX_CONTEXT_LEN: usize = 255;
+// the start of dom2 (RFC 8032 section 5.1), which separates Ed25519ctx and Ed25519ph from plain
+// Ed25519 and from each other
+pub const DOM2_PREFIX: &[u8] = b"SigEd25519 no Ed25519 collisions";
+
+// d = -121665 / 121666 for the twisted Edwards curve -x^2 + y^2 = 1 + d x^2 y^2, and 2d, as
+// 51-bit limbs
+pub const EDWARDS_D: [u64; 5] = [
+    0x34dca135978a3,
+    0x1a8283b156ebd,
+    0x5e7a26001c029,
+    0x739c663a03cbb,
+    0x52036cee2b6ff,
+];
+pub const EDWARDS_D2: [u64; 5] = [
+    0x69b9426b2f159,
+    0x35050762add7a,
+    0x3cf44c0038052,
+    0x6738cc7407977,
+    0x2406d9dc56dff,
+];
+
+// a square root of -1 mod p, 2^((p - 1) / 4)
+pub const SQRT_M1: [u64; 5] = [
+    0x61b274a0ea0b0,
+    0x0d5a5fc8f189d,
+    0x7ef5e9cbd0c60,
+    0x78595a6804c9e,
+    0x2b8324804fc1d,
+];
+
+// the compressed base point, y = 4/5 with x even
+pub const ED25519_BASEPOINT: [u8; 32] = [
+    0x58, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+    0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66, 0x66,
+];
+
+// L = 2^252 + 27742317777372353535851937790883648493, the order of the base point, as 64-bit
+// limbs
+pub const ED25519_ORDER: [u64; 4] = [
+    0x5812631a5cf5d3ed,
+    0x14def9dea2f79cd6,
+    0x0000000000000000,
+    0x1000000000000000,
+];
+
+// 2^256 mod L, for reducing the top half of a 512-bit hash
+pub const ED25519_R_MOD_ORDER: [u64; 4] = [
+    0xd6ec31748d98951d,
+    0xc6ef5bf4737dcf70,
+    0xfffffffffffffffe,
+    0x0fffffffffffffff,
+];
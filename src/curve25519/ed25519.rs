@@ -0,0 +1,457 @@
+use super::constant::*;
+use super::edwards::EdwardsPoint;
+use super::scalar::Scalar;
+use super::CurveError;
+use crate::hash::HashFunction;
+use crate::sha2::sha512::Sha512;
+use rand::{thread_rng, Rng};
+
+pub type Ed25519Seed = [u8; ED25519_SEED_SIZE];
+pub type Ed25519PublicKey = [u8; ED25519_PUBLIC_KEY_SIZE];
+pub type Ed25519Signature = [u8; ED25519_SIGNATURE_SIZE];
+
+// dom2(phflag, context) for Ed25519ctx and Ed25519ph - plain Ed25519 uses no prefix at all
+fn dom2(prehashed: bool, context: &[u8]) -> Result<Vec<u8>, CurveError> {
+    if context.len() > ED25519_MAX_CONTEXT_LEN {
+        return Err(CurveError::ContextTooLong);
+    }
+
+    let mut dom = DOM2_PREFIX.to_vec();
+    dom.push(prehashed as u8);
+    dom.push(context.len() as u8);
+    dom.extend_from_slice(context);
+    Ok(dom)
+}
+
+// SHA-512 of the concatenated parts, reduced modulo L
+fn hash_to_scalar(parts: &[&[u8]]) -> Scalar {
+    let mut hasher = Sha512::new();
+    for part in parts {
+        hasher.update(part);
+    }
+    Scalar::from_bytes_mod_order_wide(&hasher.finalize())
+}
+
+/// an Ed25519 signing key (RFC 8032 section 5.1.5) - the 32-byte seed is the private key, and
+/// the secret scalar, the nonce prefix and the public key are all derived from it
+#[derive(Clone)]
+pub struct Ed25519SigningKey {
+    seed: Ed25519Seed,
+    scalar: Scalar,
+    // the second half of the seed's hash, which makes the per-signature nonces deterministic
+    prefix: [u8; 32],
+    public: Ed25519PublicKey,
+}
+
+impl Ed25519SigningKey {
+    pub fn from_seed(seed: &Ed25519Seed) -> Self {
+        let h = Sha512::digest(seed);
+
+        // clamped as in X25519, though only the value mod L matters for signing
+        let mut scalar_bytes: [u8; 32] = h[..32].try_into().unwrap();
+        scalar_bytes[0] &= 0xf8;
+        scalar_bytes[31] &= 0x7f;
+        scalar_bytes[31] |= 0x40;
+        let scalar = Scalar::from_bytes_mod_order(&scalar_bytes);
+
+        Ed25519SigningKey {
+            seed: *seed,
+            scalar,
+            prefix: h[32..].try_into().unwrap(),
+            public: EdwardsPoint::basepoint().mul(&scalar).compress(),
+        }
+    }
+
+    /// a key from a fresh random seed
+    pub fn generate() -> Self {
+        let mut seed = [0u8; ED25519_SEED_SIZE];
+        thread_rng().fill(&mut seed);
+        Self::from_seed(&seed)
+    }
+
+    pub fn seed(&self) -> &Ed25519Seed {
+        &self.seed
+    }
+
+    pub fn public_key(&self) -> &Ed25519PublicKey {
+        &self.public
+    }
+
+    // R = [r]B with r = H(dom || prefix || M), then S = r + H(dom || R || A || M) * a
+    fn sign_with_dom(&self, dom: &[u8], message: &[u8]) -> Ed25519Signature {
+        let r = hash_to_scalar(&[dom, &self.prefix, message]);
+        let big_r = EdwardsPoint::basepoint().mul(&r).compress();
+        let k = hash_to_scalar(&[dom, &big_r, &self.public, message]);
+        let s = k.mul_add(&self.scalar, &r);
+
+        let mut signature = [0u8; ED25519_SIGNATURE_SIZE];
+        signature[..32].copy_from_slice(&big_r);
+        signature[32..].copy_from_slice(&s.to_bytes());
+        signature
+    }
+
+    /// a plain Ed25519 signature - deterministic, so signing the same message twice gives the
+    /// same signature
+    pub fn sign(&self, message: &[u8]) -> Ed25519Signature {
+        self.sign_with_dom(&[], message)
+    }
+
+    /// an Ed25519ctx signature, bound to a `context` of up to 255 bytes that the verifier has
+    /// to supply too - RFC 8032 advises against an empty context here
+    pub fn sign_ctx(&self, message: &[u8], context: &[u8]) -> Result<Ed25519Signature, CurveError> {
+        Ok(self.sign_with_dom(&dom2(false, context)?, message))
+    }
+
+    /// an Ed25519ph signature over `digest`, the SHA-512 of the message - lets a large message
+    /// be hashed in a single streaming pass
+    pub fn sign_prehashed(
+        &self,
+        digest: &[u8; 64],
+        context: &[u8],
+    ) -> Result<Ed25519Signature, CurveError> {
+        Ok(self.sign_with_dom(&dom2(true, context)?, digest))
+    }
+}
+
+// checks the encoding of [S]B - [k]A against R byte for byte
+fn verify_with_dom(
+    public: &Ed25519PublicKey,
+    dom: &[u8],
+    message: &[u8],
+    signature: &[u8],
+) -> bool {
+    let Ok(signature) = <&Ed25519Signature>::try_from(signature) else {
+        return false;
+    };
+    let Some(a) = EdwardsPoint::decompress(public) else {
+        return false;
+    };
+    let Some(s) = Scalar::from_canonical_bytes(signature[32..].try_into().unwrap()) else {
+        return false;
+    };
+
+    let big_r = &signature[..32];
+    let k = hash_to_scalar(&[dom, big_r, public, message]);
+    let expected = EdwardsPoint::basepoint().mul(&s) - a.mul(&k);
+    expected.compress() == big_r
+}
+
+/// verify a plain Ed25519 signature (RFC 8032 section 5.1.7)
+///
+/// this is cofactorless verification - [S]B - [k]A has to encode to exactly R, as in ref10 and
+/// libsodium. S has to be below L and A has to decode, and since R is compared as bytes a
+/// non-canonical R is rejected too. RFC 8032 also allows the cofactored check
+/// [8][S]B = [8]R + [8][k]A used by batch verifiers, which accepts everything this does plus
+/// signatures whose R or A carry a small-order component - only crafted signatures can tell
+/// the two apart. small-order public keys aren't rejected, so a caller that needs a signature
+/// to be valid under one key only should check `EdwardsPoint::is_small_order` itself
+pub fn ed25519_verify(public: &Ed25519PublicKey, message: &[u8], signature: &[u8]) -> bool {
+    verify_with_dom(public, &[], message, signature)
+}
+
+/// verify an Ed25519ctx signature made with `context` - as `ed25519_verify` otherwise
+pub fn ed25519_verify_ctx(
+    public: &Ed25519PublicKey,
+    message: &[u8],
+    signature: &[u8],
+    context: &[u8],
+) -> bool {
+    dom2(false, context).is_ok_and(|dom| verify_with_dom(public, &dom, message, signature))
+}
+
+/// verify an Ed25519ph signature over `digest`, the SHA-512 of the message - as
+/// `ed25519_verify` otherwise
+pub fn ed25519_verify_prehashed(
+    public: &Ed25519PublicKey,
+    digest: &[u8; 64],
+    signature: &[u8],
+    context: &[u8],
+) -> bool {
+    dom2(true, context).is_ok_and(|dom| verify_with_dom(public, &dom, digest, signature))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{hex, vector_blocks};
+
+    fn seed(s: &str) -> Ed25519Seed {
+        hex(s).try_into().unwrap()
+    }
+
+    fn digest(message: &[u8]) -> [u8; 64] {
+        Sha512::digest(message)
+    }
+
+    // (secret seed, public key, message, signature)
+    const RFC8032_ED25519: [(&str, &str, &str, &str); 4] = [
+        (
+            "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+            "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a",
+            "",
+            "e5564300c360ac729086e2cc806e828a84877f1eb8e5d974d873e06522490155
+             5fb8821590a33bacc61e39701cf9b46bd25bf5f0595bbe24655141438e7a100b",
+        ),
+        (
+            "4ccd089b28ff96da9db6c346ec114e0f5b8a319f35aba624da8cf6ed4fb8a6fb",
+            "3d4017c3e843895a92b70aa74d1b7ebc9c982ccf2ec4968cc0cd55f12af4660c",
+            "72",
+            "92a009a9f0d4cab8720e820b5f642540a2b27b5416503f8fb3762223ebdb69da
+             085ac1e43e15996e458f3613d0f11d8c387b2eaeb4302aeeb00d291612bb0c00",
+        ),
+        (
+            "c5aa8df43f9f837bedb7442f31dcb7b166d38535076f094b85ce3a2e0b4458f7",
+            "fc51cd8e6218a1a38da47ed00230f0580816ed13ba3303ac5deb911548908025",
+            "af82",
+            "6291d657deec24024827e69c3abe01a30ce548a284743a445e3680d7db5ac3ac
+             18ff9b538d16f290ae67f760984dc6594a7c15e9716ed28dc027beceea1ec40a",
+        ),
+        // TEST SHA(abc), whose message is the SHA-512 of "abc"
+        (
+            "833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42",
+            "ec172b93ad5e563bf4932c70e1245034c35467ef2efd4d64ebf819683467e2bf",
+            "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a
+             2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f",
+            "dc2a4459e7369633a52b1bf277839a00201009a3efbf3ecb69bea2186c26b589
+             09351fc9ac90b3ecfdfbc7c66431e0303dca179c138ac17ad9bef1177331a704",
+        ),
+    ];
+
+    #[test]
+    fn test_rfc8032_ed25519() {
+        // RFC 8032 section 7.1
+        for (secret, public, message, signature) in RFC8032_ED25519 {
+            let key = Ed25519SigningKey::from_seed(&seed(secret));
+            assert_eq!(key.public_key().to_vec(), hex(public));
+
+            let (message, signature) = (hex(message), hex(signature));
+            assert_eq!(key.sign(&message).to_vec(), signature);
+            assert!(ed25519_verify(key.public_key(), &message, &signature));
+        }
+    }
+
+    #[test]
+    fn test_supercop_vectors() {
+        // from the SUPERCOP sign.input set that RFC 8032's first vectors are taken from
+        for (secret, public, message, signature) in [
+            (
+                "0d4a05b07352a5436e180356da0ae6efa0345ff7fb1572575772e8005ed978e9",
+                "e61a185bcef2613a6c7cb79763ce945d3b245d76114dd440bcf5f2dc1aa57057",
+                "cbc77b",
+                "d9868d52c2bebce5f3fa5a79891970f309cb6591e3e1702a70276fa97c24b3a8
+                 e58606c38c9758529da50ee31b8219cba45271c689afa60b0ea26c99db19b00c",
+            ),
+            (
+                "ba4d6e67b2ce67a1e44326494044f37a442f3b81725bc1f9341462718b55ee20",
+                "f73fa076f84b6db675a5fda5ad67e351a41e8e7f29add16809ca010387e9c6cc",
+                "4bafdac9099d4057ed6dd08bcaee8756e9a40f2cb9598020eb95019528409bbe
+                 a38b384a59f119f57297bfb2fa142fc7bb1d90dbddde772bcde48c5670d5fa13",
+                "57b9d2a711207f837421bae7dd48eaa18eab1a9a70a0f1305806fee17b458f3a
+                 0964b302d1834d3e0ac9e8496f000b77f0083b41f8a957e632fbc7840eee6a06",
+            ),
+        ] {
+            let key = Ed25519SigningKey::from_seed(&seed(secret));
+            assert_eq!(key.public_key().to_vec(), hex(public));
+            assert_eq!(key.sign(&hex(message)).to_vec(), hex(signature));
+        }
+    }
+
+    #[test]
+    fn test_rfc8032_ed25519ctx() {
+        // RFC 8032 section 7.2
+        let key = Ed25519SigningKey::from_seed(&seed(
+            "0305334e381af78f141cb666f6199f57bc3495335a256a95bd2a55bf546663f6",
+        ));
+        let other = Ed25519SigningKey::from_seed(&seed(
+            "ab9c2853ce297ddab85c993b3ae14bcad39b2c682beabc27d6d4eb20711d6560",
+        ));
+        assert_eq!(
+            key.public_key().to_vec(),
+            hex("dfc9425e4f968f7f0c29f0259cf5f9aed6851c2bb4ad8bfb860cfee0ab248292")
+        );
+        assert_eq!(
+            other.public_key().to_vec(),
+            hex("0f1d1274943b91415889152e893d80e93275a1fc0b65fd71b4b0dda10ad7d772")
+        );
+
+        for (key, message, context, signature) in [
+            (
+                &key,
+                "f726936d19c800494e3fdaff20b276a8",
+                "666f6f",
+                "55a4cc2f70a54e04288c5f4cd1e45a7bb520b36292911876cada7323198dd87a
+                 8b36950b95130022907a7fb7c4e9b2d5f6cca685a587b4b21f4b888e4e7edb0d",
+            ),
+            (
+                &key,
+                "f726936d19c800494e3fdaff20b276a8",
+                "626172",
+                "fc60d5872fc46b3aa69f8b5b4351d5808f92bcc044606db097abab6dbcb1aee3
+                 216c48e8b3b66431b5b186d1d28f8ee15a5ca2df6668346291c2043d4eb3e90d",
+            ),
+            (
+                &key,
+                "508e9e6882b979fea900f62adceaca35",
+                "666f6f",
+                "8b70c1cc8310e1de20ac53ce28ae6e7207f33c3295e03bb5c0732a1d20dc6490
+                 8922a8b052cf99b7c4fe107a5abb5b2c4085ae75890d02df26269d8945f84b0b",
+            ),
+            (
+                &other,
+                "f726936d19c800494e3fdaff20b276a8",
+                "666f6f",
+                "21655b5f1aa965996b3f97b3c849eafba922a0a62992f73b3d1b73106a84ad85
+                 e9b86a7b6005ea868337ff2d20a7f5fbd4cd10b0be49a68da2b2e0dc0ad8960f",
+            ),
+        ] {
+            let (message, context, signature) = (hex(message), hex(context), hex(signature));
+            assert_eq!(
+                key.sign_ctx(&message, &context).unwrap().to_vec(),
+                signature
+            );
+            assert!(ed25519_verify_ctx(
+                key.public_key(),
+                &message,
+                &signature,
+                &context
+            ));
+        }
+    }
+
+    #[test]
+    fn test_rfc8032_ed25519ph() {
+        // RFC 8032 section 7.3
+        let key = Ed25519SigningKey::from_seed(&seed(
+            "833fe62409237b9d62ec77587520911e9a759cec1d19755b7da901b96dca3d42",
+        ));
+        let signature = hex(
+            "98a70222f0b8121aa9d30f813d683f809e462b469c7ff87639499bb94e6dae41
+             31f85042463c2a355a2003d062adf5aaa10b8c61e636062aaad11c2a26083406",
+        );
+
+        assert_eq!(
+            key.sign_prehashed(&digest(b"abc"), b"").unwrap().to_vec(),
+            signature
+        );
+        assert!(ed25519_verify_prehashed(
+            key.public_key(),
+            &digest(b"abc"),
+            &signature,
+            b""
+        ));
+    }
+
+    #[test]
+    fn test_variants_are_separated() {
+        // the same key and message under each variant gives signatures that only verify
+        // under that variant and context
+        let key = Ed25519SigningKey::generate();
+        let public = key.public_key();
+        let message = b"encrypted file header";
+
+        let plain = key.sign(message);
+        let ctx = key.sign_ctx(message, b"").unwrap();
+        let ph = key.sign_prehashed(&digest(message), b"").unwrap();
+        assert_ne!(plain, ctx);
+        assert_ne!(ctx, ph);
+
+        assert!(ed25519_verify(public, message, &plain));
+        assert!(!ed25519_verify(public, message, &ctx));
+        assert!(!ed25519_verify(public, &digest(message), &ph));
+
+        assert!(ed25519_verify_ctx(public, message, &ctx, b""));
+        assert!(!ed25519_verify_ctx(public, message, &ctx, b"other"));
+        assert!(!ed25519_verify_ctx(public, message, &plain, b""));
+
+        assert!(ed25519_verify_prehashed(public, &digest(message), &ph, b""));
+        assert!(!ed25519_verify_prehashed(
+            public,
+            &digest(message),
+            &ctx,
+            b""
+        ));
+
+        let long = [0u8; 256];
+        assert_eq!(
+            key.sign_ctx(message, &long),
+            Err(CurveError::ContextTooLong)
+        );
+        assert_eq!(
+            key.sign_prehashed(&digest(message), &long),
+            Err(CurveError::ContextTooLong)
+        );
+        assert!(key.sign_ctx(message, &long[..255]).is_ok());
+        assert!(!ed25519_verify_ctx(public, message, &ctx, &long));
+    }
+
+    #[test]
+    fn test_generated_verify_vectors() {
+        // generated cases with OpenSSL's verdicts, not Wycheproof's - see the file's header
+        let cases = vector_blocks(include_str!("vectors/ed25519_verify_generated.txt"));
+        for case in &cases {
+            let Ok(public) = Ed25519PublicKey::try_from(hex(case["key"])) else {
+                panic!("tcId {}: bad public key size", case["tcId"]);
+            };
+            let verified = ed25519_verify(&public, &hex(case["msg"]), &hex(case["sig"]));
+
+            let expected = match case["result"] {
+                "valid" => true,
+                "invalid" => false,
+                result => panic!("tcId {}: unknown result {:?}", case["tcId"], result),
+            };
+            assert_eq!(
+                verified, expected,
+                "tcId {} ({})",
+                case["tcId"], case["comment"]
+            );
+        }
+        assert_eq!(cases.len(), 132);
+    }
+
+    #[test]
+    fn test_verification_is_cofactorless() {
+        // a signature whose R carries a point of order 8 - it satisfies the cofactored
+        // equation but not the cofactorless one this crate checks
+        let key = Ed25519SigningKey::from_seed(&[7u8; 32]);
+        let a = EdwardsPoint::decompress(key.public_key()).unwrap();
+        let torsion = EdwardsPoint::decompress(
+            &hex("c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac037a")
+                .try_into()
+                .unwrap(),
+        )
+        .unwrap();
+        let message = b"header";
+
+        let r = Scalar::from_bytes_mod_order(&[3u8; 32]);
+        let big_r = (EdwardsPoint::basepoint().mul(&r) + torsion).compress();
+        let k = hash_to_scalar(&[&big_r, key.public_key(), message]);
+        let s = k.mul_add(&key.scalar, &r);
+
+        let mut signature = [0u8; 64];
+        signature[..32].copy_from_slice(&big_r);
+        signature[32..].copy_from_slice(&s.to_bytes());
+
+        let big_r = EdwardsPoint::decompress(&big_r).unwrap();
+        assert_eq!(
+            EdwardsPoint::basepoint().mul(&s).mul_by_cofactor(),
+            (big_r + a.mul(&k)).mul_by_cofactor()
+        );
+        assert!(!ed25519_verify(key.public_key(), message, &signature));
+    }
+
+    #[test]
+    fn test_generate_round_trip() {
+        let key = Ed25519SigningKey::generate();
+        let copy = Ed25519SigningKey::from_seed(key.seed());
+        assert_eq!(copy.public_key(), key.public_key());
+
+        let signature = key.sign(b"header");
+        assert_eq!(copy.sign(b"header"), signature);
+        assert!(ed25519_verify(key.public_key(), b"header", &signature));
+        assert!(!ed25519_verify(
+            Ed25519SigningKey::generate().public_key(),
+            b"header",
+            &signature
+        ));
+    }
+}
@@ -0,0 +1,255 @@
+use super::constant::*;
+use super::field::FieldElement;
+use super::scalar::Scalar;
+use std::ops::{Add, Neg, Sub};
+
+const D: FieldElement = FieldElement::from_limbs(EDWARDS_D);
+const D2: FieldElement = FieldElement::from_limbs(EDWARDS_D2);
+
+/// a point on the twisted Edwards curve -x^2 + y^2 = 1 + d x^2 y^2 that Ed25519 is defined on,
+/// in extended coordinates (X : Y : Z : T) with x = X/Z, y = Y/Z and xy = T/Z
+///
+/// the addition formulas are complete, so the same code handles doubling and the identity
+#[derive(Debug, Clone, Copy)]
+pub struct EdwardsPoint {
+    x: FieldElement,
+    y: FieldElement,
+    z: FieldElement,
+    t: FieldElement,
+}
+
+impl EdwardsPoint {
+    pub const IDENTITY: Self = EdwardsPoint {
+        x: FieldElement::ZERO,
+        y: FieldElement::ONE,
+        z: FieldElement::ONE,
+        t: FieldElement::ZERO,
+    };
+
+    /// the Ed25519 base point B
+    pub fn basepoint() -> Self {
+        Self::decompress(&ED25519_BASEPOINT).unwrap()
+    }
+
+    /// decode a point per RFC 8032 section 5.1.3 - `None` for a y that isn't below p, a y
+    /// with no matching x, or "negative" zero
+    ///
+    /// variable time, as points being decoded are public
+    pub fn decompress(bytes: &[u8; 32]) -> Option<Self> {
+        let sign = bytes[31] >> 7 == 1;
+        let mut y_bytes = *bytes;
+        y_bytes[31] &= 0x7f;
+        let y = FieldElement::from_bytes(&y_bytes);
+        if y.to_bytes() != y_bytes {
+            return None;
+        }
+
+        // x^2 = u / v, and the candidate root is u v^3 (u v^7)^((p - 5) / 8)
+        let yy = y.square();
+        let u = yy - FieldElement::ONE;
+        let v = D * yy + FieldElement::ONE;
+        let v3 = v.square() * v;
+        let mut x = u * v3 * (u * v3.square() * v).pow_p58();
+
+        let vxx = v * x.square();
+        if vxx != u {
+            if vxx != -u {
+                return None;
+            }
+            x = x * FieldElement::from_limbs(SQRT_M1);
+        }
+
+        if x.is_zero() && sign {
+            return None;
+        }
+        if x.is_negative() != sign {
+            x = -x;
+        }
+
+        Some(EdwardsPoint {
+            x,
+            y,
+            z: FieldElement::ONE,
+            t: x * y,
+        })
+    }
+
+    /// the 32-byte encoding - y with the low bit of x in the top bit
+    pub fn compress(&self) -> [u8; 32] {
+        let z_inv = self.z.invert();
+        let x = self.x * z_inv;
+        let mut bytes = (self.y * z_inv).to_bytes();
+        bytes[31] |= (x.is_negative() as u8) << 7;
+        bytes
+    }
+
+    pub fn double(&self) -> Self {
+        let a = self.x.square();
+        let b = self.y.square();
+        let c = self.z.square().mul_small(2);
+        let h = a + b;
+        let e = h - (self.x + self.y).square();
+        let g = a - b;
+        let f = c + g;
+
+        EdwardsPoint {
+            x: e * f,
+            y: g * h,
+            z: f * g,
+            t: e * h,
+        }
+    }
+
+    fn conditional_select(a: &Self, b: &Self, choice: bool) -> Self {
+        EdwardsPoint {
+            x: FieldElement::conditional_select(&a.x, &b.x, choice),
+            y: FieldElement::conditional_select(&a.y, &b.y, choice),
+            z: FieldElement::conditional_select(&a.z, &b.z, choice),
+            t: FieldElement::conditional_select(&a.t, &b.t, choice),
+        }
+    }
+
+    /// `[scalar]self` in constant time - a double and an add for every bit, keeping the sum
+    /// or not by a select
+    pub fn mul(&self, scalar: &Scalar) -> Self {
+        let mut acc = Self::IDENTITY;
+        for i in (0..253).rev() {
+            acc = acc.double();
+            acc = Self::conditional_select(&acc, &(acc + *self), scalar.bit(i));
+        }
+        acc
+    }
+
+    /// `[8]self`, which clears any small-order component
+    pub fn mul_by_cofactor(&self) -> Self {
+        self.double().double().double()
+    }
+
+    /// whether the point's order divides the cofactor 8 - such points are the torsion that
+    /// cofactored and cofactorless verification treat differently
+    pub fn is_small_order(&self) -> bool {
+        self.mul_by_cofactor() == Self::IDENTITY
+    }
+}
+
+impl Add for EdwardsPoint {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let a = (self.y - self.x) * (rhs.y - rhs.x);
+        let b = (self.y + self.x) * (rhs.y + rhs.x);
+        let c = self.t * D2 * rhs.t;
+        let d = (self.z * rhs.z).mul_small(2);
+        let e = b - a;
+        let f = d - c;
+        let g = d + c;
+        let h = b + a;
+
+        EdwardsPoint {
+            x: e * f,
+            y: g * h,
+            z: f * g,
+            t: e * h,
+        }
+    }
+}
+
+impl Neg for EdwardsPoint {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        EdwardsPoint {
+            x: -self.x,
+            t: -self.t,
+            ..self
+        }
+    }
+}
+
+impl Sub for EdwardsPoint {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        self + -rhs
+    }
+}
+
+impl PartialEq for EdwardsPoint {
+    /// compares the affine points, cross-multiplying rather than dividing out Z
+    fn eq(&self, other: &Self) -> bool {
+        self.x * other.z == other.x * self.z && self.y * other.z == other.y * self.z
+    }
+}
+
+impl Eq for EdwardsPoint {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    fn point(s: &str) -> Option<EdwardsPoint> {
+        EdwardsPoint::decompress(&hex(s).try_into().unwrap())
+    }
+
+    #[test]
+    fn test_basepoint() {
+        let b = EdwardsPoint::basepoint();
+        assert_eq!(b.compress(), ED25519_BASEPOINT);
+
+        // [L]B is the identity, and [2]B agrees whichever way it's computed
+        let mut l_minus_one = [0u8; 32];
+        for (i, limb) in ED25519_ORDER.iter().enumerate() {
+            l_minus_one[8 * i..8 * i + 8].copy_from_slice(&limb.to_le_bytes());
+        }
+        l_minus_one[0] -= 1;
+        let l_minus_one = Scalar::from_canonical_bytes(&l_minus_one).unwrap();
+        assert_eq!(b.mul(&l_minus_one) + b, EdwardsPoint::IDENTITY);
+        assert_eq!(b.mul(&l_minus_one), -b);
+
+        let mut two = [0u8; 32];
+        two[0] = 2;
+        let two = Scalar::from_canonical_bytes(&two).unwrap();
+        assert_eq!(b.mul(&two), b + b);
+        assert_eq!(b.double(), b + b);
+        assert_eq!(
+            (b + b).compress().to_vec(),
+            // checked against an independent implementation
+            hex("c9a3f86aae465f0e56513864510f3997561fa2c9e85ea21dc2292309f3cd6022")
+        );
+    }
+
+    #[test]
+    fn test_decompress_edge_cases() {
+        // y = 1 is the identity, and it has no "negative" twin
+        let one = "0100000000000000000000000000000000000000000000000000000000000000";
+        assert_eq!(point(one), Some(EdwardsPoint::IDENTITY));
+        assert_eq!(
+            point("0100000000000000000000000000000000000000000000000000000000000080"),
+            None
+        );
+
+        // y = p + 1 is a non-canonical encoding of the identity
+        assert_eq!(
+            point("eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f"),
+            None
+        );
+
+        // y = 2 has no x on the curve
+        assert_eq!(
+            point("0200000000000000000000000000000000000000000000000000000000000000"),
+            None
+        );
+
+        // a point of order 8, and a round trip of its negation
+        let torsion =
+            point("c7176a703d4dd84fba3c0b760d10670f2a2053fa2c39ccc64ec7fd7792ac037a").unwrap();
+        assert!(torsion.is_small_order());
+        assert_ne!(torsion.double().double(), EdwardsPoint::IDENTITY);
+        assert_eq!(
+            EdwardsPoint::decompress(&(-torsion).compress()),
+            Some(-torsion)
+        );
+        assert!(!EdwardsPoint::basepoint().is_small_order());
+    }
+}
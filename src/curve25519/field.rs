@@ -0,0 +1,347 @@
+use super::constant::*;
+use std::ops::{Add, Mul, Neg, Sub};
+
+/// an element of GF(2^255 - 19) in radix 2^51 - five limbs of 51 bits, with a few bits of
+/// headroom so that additions don't need to carry straight away
+///
+/// every operation runs in constant time
+#[derive(Debug, Clone, Copy)]
+pub struct FieldElement([u64; 5]);
+
+impl FieldElement {
+    pub const ZERO: Self = FieldElement([0; 5]);
+    pub const ONE: Self = FieldElement([1, 0, 0, 0, 0]);
+
+    /// an element from 51-bit limbs, least significant first - for constants
+    pub const fn from_limbs(limbs: [u64; 5]) -> Self {
+        FieldElement(limbs)
+    }
+
+    /// decode a little-endian encoding, ignoring the top bit - values from p up to 2^255 - 1
+    /// are accepted and reduced, as RFC 7748 requires
+    pub fn from_bytes(bytes: &[u8; FIELD_ELEMENT_SIZE]) -> Self {
+        let load = |i: usize| u64::from_le_bytes(bytes[i..i + 8].try_into().unwrap());
+
+        FieldElement([
+            load(0) & LIMB_MASK,
+            (load(6) >> 3) & LIMB_MASK,
+            (load(12) >> 6) & LIMB_MASK,
+            (load(19) >> 1) & LIMB_MASK,
+            (load(24) >> 12) & LIMB_MASK,
+        ])
+    }
+
+    /// the canonical little-endian encoding, fully reduced below p
+    pub fn to_bytes(&self) -> [u8; FIELD_ELEMENT_SIZE] {
+        let mut limbs = Self::carry(self.0).0;
+
+        // limbs is now below 2p, so subtracting p once is enough - the value is at least p
+        // exactly when adding 19 carries out of the top limb
+        let mut q = (limbs[0] + 19) >> LIMB_BITS;
+        for limb in &limbs[1..] {
+            q = (limb + q) >> LIMB_BITS;
+        }
+        limbs[0] += 19 * q;
+        for i in 0..4 {
+            limbs[i + 1] += limbs[i] >> LIMB_BITS;
+            limbs[i] &= LIMB_MASK;
+        }
+        limbs[4] &= LIMB_MASK;
+
+        let mut out = [0u8; FIELD_ELEMENT_SIZE];
+        let mut acc = 0u128;
+        let mut acc_bits = 0;
+        let mut bytes = out.iter_mut();
+        for limb in limbs {
+            acc |= (limb as u128) << acc_bits;
+            acc_bits += LIMB_BITS;
+            while acc_bits >= 8 {
+                *bytes.next().unwrap() = acc as u8;
+                acc >>= 8;
+                acc_bits -= 8;
+            }
+        }
+        // 255 bits leave 7 over for the last byte
+        *bytes.next().unwrap() = acc as u8;
+        out
+    }
+
+    // bring every limb back to 51 bits plus a small carry into the bottom one, folding the
+    // top carry round as 2^255 = 19 mod p
+    fn carry(mut limbs: [u64; 5]) -> Self {
+        let mut carry = 0;
+        for limb in limbs.iter_mut() {
+            *limb += carry;
+            carry = *limb >> LIMB_BITS;
+            *limb &= LIMB_MASK;
+        }
+        limbs[0] += 19 * carry;
+        FieldElement(limbs)
+    }
+
+    // the same for the 102-bit limbs of a product
+    fn carry_wide(wide: [u128; 5]) -> Self {
+        let mut limbs = [0u64; 5];
+        let mut carry = 0u128;
+        for (limb, w) in limbs.iter_mut().zip(wide) {
+            let w = w + carry;
+            *limb = w as u64 & LIMB_MASK;
+            carry = w >> LIMB_BITS;
+        }
+        // carry < 2^58, so neither the product with 19 nor the sum can overflow
+        limbs[0] += carry as u64 * 19;
+        limbs[1] += limbs[0] >> LIMB_BITS;
+        limbs[0] &= LIMB_MASK;
+        FieldElement(limbs)
+    }
+
+    pub fn square(&self) -> Self {
+        *self * *self
+    }
+
+    /// squares `n` times in a row
+    pub fn square_n(&self, n: u32) -> Self {
+        let mut x = *self;
+        for _ in 0..n {
+            x = x.square();
+        }
+        x
+    }
+
+    /// multiply by a small constant
+    pub fn mul_small(&self, k: u32) -> Self {
+        Self::carry_wide(self.0.map(|limb| limb as u128 * k as u128))
+    }
+
+    // z^(2^250 - 1) and z^11, from which both z^(p - 2) and z^((p - 5) / 8) are a few more
+    // steps
+    fn pow_2_250_1(&self) -> (Self, Self) {
+        let z2 = self.square();
+        let z9 = z2.square_n(2) * *self;
+        let z11 = z9 * z2;
+        let z_5_0 = z11.square() * z9;
+        let z_10_0 = z_5_0.square_n(5) * z_5_0;
+        let z_20_0 = z_10_0.square_n(10) * z_10_0;
+        let z_40_0 = z_20_0.square_n(20) * z_20_0;
+        let z_50_0 = z_40_0.square_n(10) * z_10_0;
+        let z_100_0 = z_50_0.square_n(50) * z_50_0;
+        let z_200_0 = z_100_0.square_n(100) * z_100_0;
+        let z_250_0 = z_200_0.square_n(50) * z_50_0;
+        (z_250_0, z11)
+    }
+
+    /// the inverse by Fermat's little theorem, z^(p - 2) - zero maps to zero
+    pub fn invert(&self) -> Self {
+        let (z_250_0, z11) = self.pow_2_250_1();
+        z_250_0.square_n(5) * z11
+    }
+
+    /// z^((p - 5) / 8) = z^(2^252 - 3), the exponentiation at the heart of the square root
+    pub fn pow_p58(&self) -> Self {
+        let (z_250_0, _) = self.pow_2_250_1();
+        z_250_0.square_n(2) * *self
+    }
+
+    /// whether the canonical encoding is odd - the "sign" of x in a compressed point
+    pub fn is_negative(&self) -> bool {
+        self.to_bytes()[0] & 1 == 1
+    }
+
+    /// `b` when `choice` is set and `a` otherwise, without branching on it
+    pub fn conditional_select(a: &Self, b: &Self, choice: bool) -> Self {
+        let (mut a, mut b) = (*a, *b);
+        Self::conditional_swap(&mut a, &mut b, choice);
+        a
+    }
+
+    /// swap `a` and `b` when `choice` is set, without branching on it
+    pub fn conditional_swap(a: &mut Self, b: &mut Self, choice: bool) {
+        let mask = 0u64.wrapping_sub(choice as u64);
+        for (x, y) in a.0.iter_mut().zip(b.0.iter_mut()) {
+            let t = mask & (*x ^ *y);
+            *x ^= t;
+            *y ^= t;
+        }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        let bytes = self.to_bytes();
+        std::hint::black_box(bytes.iter().fold(0, |acc, b| acc | b)) == 0
+    }
+}
+
+impl Add for FieldElement {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self {
+        let mut limbs = self.0;
+        for (a, b) in limbs.iter_mut().zip(rhs.0) {
+            *a += b;
+        }
+        Self::carry(limbs)
+    }
+}
+
+impl Sub for FieldElement {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self {
+        let mut limbs = self.0;
+        for ((a, b), two_p) in limbs.iter_mut().zip(rhs.0).zip(TWO_P) {
+            *a = *a + two_p - b;
+        }
+        Self::carry(limbs)
+    }
+}
+
+impl Neg for FieldElement {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self::ZERO - self
+    }
+}
+
+impl Mul for FieldElement {
+    type Output = Self;
+
+    fn mul(self, rhs: Self) -> Self {
+        let m = |a: u64, b: u64| a as u128 * b as u128;
+        let [a0, a1, a2, a3, a4] = self.0;
+        let [b0, b1, b2, b3, b4] = rhs.0;
+        // limbs that wrap past 2^255 come back in multiplied by 19
+        let (b1_19, b2_19, b3_19, b4_19) = (b1 * 19, b2 * 19, b3 * 19, b4 * 19);
+
+        Self::carry_wide([
+            m(a0, b0) + m(a1, b4_19) + m(a2, b3_19) + m(a3, b2_19) + m(a4, b1_19),
+            m(a0, b1) + m(a1, b0) + m(a2, b4_19) + m(a3, b3_19) + m(a4, b2_19),
+            m(a0, b2) + m(a1, b1) + m(a2, b0) + m(a3, b4_19) + m(a4, b3_19),
+            m(a0, b3) + m(a1, b2) + m(a2, b1) + m(a3, b0) + m(a4, b4_19),
+            m(a0, b4) + m(a1, b3) + m(a2, b2) + m(a3, b1) + m(a4, b0),
+        ])
+    }
+}
+
+impl PartialEq for FieldElement {
+    /// compares the canonical encodings in constant time
+    fn eq(&self, other: &Self) -> bool {
+        crate::ct::ct_eq(&self.to_bytes(), &other.to_bytes())
+    }
+}
+
+impl Eq for FieldElement {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigint::modular::{
+        add_mod, mod_inverse_vartime, mul_mod_vartime, sub_mod, Montgomery,
+    };
+    use crate::bigint::uint::U256;
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    const CASES: usize = 300;
+
+    fn p() -> U256 {
+        (U256::ONE << 255).wrapping_sub(&U256::from_u64(19))
+    }
+
+    fn to_uint(x: &FieldElement) -> U256 {
+        let mut bytes = x.to_bytes();
+        bytes.reverse();
+        U256::from_be_bytes(&bytes).unwrap()
+    }
+
+    fn random_element(rng: &mut impl Rng) -> (FieldElement, U256) {
+        let mut bytes = [0u8; 32];
+        rng.fill(&mut bytes);
+        let x = FieldElement::from_bytes(&bytes);
+
+        bytes[31] &= 0x7f;
+        bytes.reverse();
+        let value = U256::from_be_bytes(&bytes).unwrap().div_rem_vartime(&p()).1;
+        (x, value)
+    }
+
+    #[test]
+    fn test_against_bigint() {
+        let mut rng = StdRng::seed_from_u64(25519);
+        let p = p();
+
+        for _ in 0..CASES {
+            let (a, a_int) = random_element(&mut rng);
+            let (b, b_int) = random_element(&mut rng);
+
+            assert_eq!(to_uint(&(a + b)), add_mod(&a_int, &b_int, &p));
+            assert_eq!(to_uint(&(a - b)), sub_mod(&a_int, &b_int, &p));
+            assert_eq!(to_uint(&-a), sub_mod(&U256::ZERO, &a_int, &p));
+            assert_eq!(to_uint(&(a * b)), mul_mod_vartime(&a_int, &b_int, &p));
+            assert_eq!(
+                to_uint(&a.mul_small(A24)),
+                mul_mod_vartime(&a_int, &U256::from_u64(A24 as u64), &p)
+            );
+            assert_eq!(
+                to_uint(&a.invert()),
+                mod_inverse_vartime(&a_int, &p).unwrap()
+            );
+            let p58 = (p.wrapping_sub(&U256::from_u64(5))) >> 3;
+            assert_eq!(
+                to_uint(&a.pow_p58()),
+                Montgomery::new(&p).unwrap().pow_vartime(&a_int, &p58)
+            );
+        }
+    }
+
+    #[test]
+    fn test_long_chains_stay_reduced() {
+        // limbs left slightly over 51 bits by one operation are fine as inputs to the next
+        let mut rng = StdRng::seed_from_u64(1);
+        let (mut x, mut x_int) = random_element(&mut rng);
+        let (y, y_int) = random_element(&mut rng);
+        let p = p();
+
+        for _ in 0..1000 {
+            x = (x - y) * (x + y) - y;
+            x_int = sub_mod(
+                &mul_mod_vartime(
+                    &sub_mod(&x_int, &y_int, &p),
+                    &add_mod(&x_int, &y_int, &p),
+                    &p,
+                ),
+                &y_int,
+                &p,
+            );
+        }
+        assert_eq!(to_uint(&x), x_int);
+    }
+
+    #[test]
+    fn test_encoding() {
+        // p and 2^255 - 1 are non-canonical encodings of 0 and 18
+        let mut p_bytes = [0xffu8; 32];
+        p_bytes[0] = 0xed;
+        p_bytes[31] = 0x7f;
+        assert!(FieldElement::from_bytes(&p_bytes).is_zero());
+        assert_eq!(FieldElement::from_bytes(&p_bytes).to_bytes(), [0u8; 32]);
+
+        let mut expected = [0u8; 32];
+        expected[0] = 18;
+        assert_eq!(FieldElement::from_bytes(&[0xff; 32]).to_bytes(), expected);
+
+        // p - 1 is canonical and round trips
+        p_bytes[0] = 0xec;
+        assert_eq!(FieldElement::from_bytes(&p_bytes).to_bytes(), p_bytes);
+        assert_eq!(FieldElement::from_bytes(&p_bytes), -FieldElement::ONE);
+
+        assert!(FieldElement::ZERO.invert().is_zero());
+    }
+
+    #[test]
+    fn test_conditional_swap() {
+        let (mut a, mut b) = (FieldElement::ONE, FieldElement::ZERO);
+        FieldElement::conditional_swap(&mut a, &mut b, false);
+        assert_eq!((a, b), (FieldElement::ONE, FieldElement::ZERO));
+        FieldElement::conditional_swap(&mut a, &mut b, true);
+        assert_eq!((a, b), (FieldElement::ZERO, FieldElement::ONE));
+    }
+}
@@ -0,0 +1,28 @@
+pub mod constant;
+pub mod ed25519;
+pub mod edwards;
+pub mod field;
+pub mod scalar;
+pub mod x25519;
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CurveError {
+    /// the peer's public key is a point of small order, which forces the shared secret to zero
+    /// whatever the private key
+    LowOrderPoint,
+    /// an Ed25519ctx or Ed25519ph context is longer than 255 bytes
+    ContextTooLong,
+}
+
+impl fmt::Display for CurveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CurveError::LowOrderPoint => write!(f, "public key is a low-order point"),
+            CurveError::ContextTooLong => write!(f, "context longer than 255 bytes"),
+        }
+    }
+}
+
+impl std::error::Error for CurveError {}
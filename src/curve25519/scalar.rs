@@ -0,0 +1,119 @@
+use super::constant::*;
+use crate::bigint::modular::{add_mod, Montgomery};
+use crate::bigint::uint::U256;
+
+const ORDER: U256 = U256::from_limbs(ED25519_ORDER);
+const R_MOD_ORDER: U256 = U256::from_limbs(ED25519_R_MOD_ORDER);
+
+/// an integer modulo L, the order of the Ed25519 base point, on top of the crate's bigint layer
+/// - arithmetic on secret scalars runs in constant time
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Scalar(U256);
+
+fn order() -> Montgomery<4> {
+    Montgomery::new(&ORDER).unwrap()
+}
+
+fn from_le_bytes(bytes: &[u8; 32]) -> U256 {
+    let mut be = *bytes;
+    be.reverse();
+    U256::from_be_bytes(&be).unwrap()
+}
+
+impl Scalar {
+    pub const ZERO: Self = Scalar(U256::ZERO);
+
+    /// a little-endian 256-bit integer reduced modulo L
+    pub fn from_bytes_mod_order(bytes: &[u8; 32]) -> Self {
+        Scalar(order().mul_mod(&from_le_bytes(bytes), &U256::ONE))
+    }
+
+    /// a little-endian 512-bit integer, such as a SHA-512 output, reduced modulo L
+    pub fn from_bytes_mod_order_wide(bytes: &[u8; 64]) -> Self {
+        let order = order();
+        let lo = from_le_bytes(bytes[..32].try_into().unwrap());
+        let hi = from_le_bytes(bytes[32..].try_into().unwrap());
+
+        // lo + hi * 2^256, with each half reduced separately
+        Scalar(add_mod(
+            &order.mul_mod(&lo, &U256::ONE),
+            &order.mul_mod(&hi, &R_MOD_ORDER),
+            &ORDER,
+        ))
+    }
+
+    /// `None` unless the little-endian integer is already below L - the check that keeps
+    /// signatures from being malleable
+    pub fn from_canonical_bytes(bytes: &[u8; 32]) -> Option<Self> {
+        let x = from_le_bytes(bytes);
+        (x < ORDER).then_some(Scalar(x))
+    }
+
+    /// the little-endian encoding
+    pub fn to_bytes(&self) -> [u8; 32] {
+        let mut bytes: [u8; 32] = self.0.to_be_bytes().try_into().unwrap();
+        bytes.reverse();
+        bytes
+    }
+
+    /// `self * b + c mod L`
+    pub fn mul_add(&self, b: &Self, c: &Self) -> Self {
+        Scalar(add_mod(&order().mul_mod(&self.0, &b.0), &c.0, &ORDER))
+    }
+
+    /// the bit at position `i`, counting from the least significant
+    pub fn bit(&self, i: u32) -> bool {
+        self.0.bit(i)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigint::modular::mul_mod_vartime;
+    use crate::bigint::uint::{Uint, U512};
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    #[test]
+    fn test_reduction_against_bigint() {
+        let mut rng = StdRng::seed_from_u64(8032);
+        let order = Uint::<8>::from_be_bytes(&ORDER.to_be_bytes()).unwrap();
+
+        for _ in 0..200 {
+            let mut wide = [0u8; 64];
+            rng.fill(&mut wide[..]);
+            let mut be = wide;
+            be.reverse();
+            let expected = U512::from_be_bytes(&be).unwrap().div_rem_vartime(&order).1;
+            assert_eq!(
+                Scalar::from_bytes_mod_order_wide(&wide).0.to_be_bytes(),
+                expected.to_be_bytes()[32..]
+            );
+
+            let narrow: [u8; 32] = wide[..32].try_into().unwrap();
+            let a = Scalar::from_bytes_mod_order(&narrow);
+            assert_eq!(a.0, from_le_bytes(&narrow).div_rem_vartime(&ORDER).1);
+
+            let b = Scalar::from_bytes_mod_order_wide(&wide);
+            let c = Scalar::from_bytes_mod_order(&[0xff; 32]);
+            assert_eq!(
+                a.mul_add(&b, &c).0,
+                add_mod(&mul_mod_vartime(&a.0, &b.0, &ORDER), &c.0, &ORDER)
+            );
+        }
+    }
+
+    #[test]
+    fn test_canonical_encoding() {
+        let l = Scalar(ORDER).to_bytes();
+        assert_eq!(Scalar::from_canonical_bytes(&l), None);
+        assert_eq!(Scalar::from_bytes_mod_order(&l), Scalar::ZERO);
+
+        let l_minus_one = Scalar(ORDER.wrapping_sub(&U256::ONE));
+        assert_eq!(
+            Scalar::from_canonical_bytes(&l_minus_one.to_bytes()),
+            Some(l_minus_one)
+        );
+        assert_eq!(Scalar::from_canonical_bytes(&[0xff; 32]), None);
+    }
+}
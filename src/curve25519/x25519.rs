@@ -0,0 +1,207 @@
+use super::constant::*;
+use super::field::FieldElement;
+use super::CurveError;
+use rand::{thread_rng, Rng};
+
+pub type X25519SecretKey = [u8; KEY_SIZE];
+pub type X25519PublicKey = [u8; KEY_SIZE];
+pub type X25519SharedSecret = [u8; KEY_SIZE];
+
+/// clear the low three bits so the scalar is a multiple of the cofactor 8, and fix the top
+/// bit so that every scalar takes the same number of ladder steps
+pub fn clamp_scalar(scalar: &[u8; KEY_SIZE]) -> [u8; KEY_SIZE] {
+    let mut k = *scalar;
+    k[0] &= 0xf8;
+    k[31] &= 0x7f;
+    k[31] |= 0x40;
+    k
+}
+
+/// the X25519 function from RFC 7748 section 5 - clamps `scalar` and multiplies it into the
+/// point with u-coordinate `u` by the Montgomery ladder, in constant time
+///
+/// the top bit of `u` is ignored and non-canonical values are reduced, as the RFC requires
+pub fn x25519(scalar: &[u8; KEY_SIZE], u: &[u8; KEY_SIZE]) -> [u8; KEY_SIZE] {
+    let k = clamp_scalar(scalar);
+    let x1 = FieldElement::from_bytes(u);
+
+    let (mut x2, mut z2) = (FieldElement::ONE, FieldElement::ZERO);
+    let (mut x3, mut z3) = (x1, FieldElement::ONE);
+    let mut swap = false;
+
+    for t in (0..255).rev() {
+        let k_t = (k[t / 8] >> (t % 8)) & 1 == 1;
+        swap ^= k_t;
+        FieldElement::conditional_swap(&mut x2, &mut x3, swap);
+        FieldElement::conditional_swap(&mut z2, &mut z3, swap);
+        swap = k_t;
+
+        let a = x2 + z2;
+        let aa = a.square();
+        let b = x2 - z2;
+        let bb = b.square();
+        let e = aa - bb;
+        let c = x3 + z3;
+        let d = x3 - z3;
+        let da = d * a;
+        let cb = c * b;
+
+        x3 = (da + cb).square();
+        z3 = x1 * (da - cb).square();
+        x2 = aa * bb;
+        z2 = e * (aa + e.mul_small(A24));
+    }
+    FieldElement::conditional_swap(&mut x2, &mut x3, swap);
+    FieldElement::conditional_swap(&mut z2, &mut z3, swap);
+
+    (x2 * z2.invert()).to_bytes()
+}
+
+/// the public key for `secret` - its product with the base point
+pub fn x25519_public_key(secret: &X25519SecretKey) -> X25519PublicKey {
+    x25519(secret, &X25519_BASEPOINT)
+}
+
+/// a fresh random secret key and its public key, for an ephemeral exchange
+pub fn x25519_generate_keypair() -> (X25519SecretKey, X25519PublicKey) {
+    let mut secret = [0u8; KEY_SIZE];
+    thread_rng().fill(&mut secret);
+    (secret, x25519_public_key(&secret))
+}
+
+/// the shared secret between our `secret` and the peer's public key
+///
+/// a low-order peer key gives an all-zero secret whatever our key is, so that's rejected -
+/// checked in constant time, as RFC 7748 section 6.1 suggests. the secret is a curve point
+/// rather than a uniform key, so pass it through a KDF such as HKDF before use
+pub fn x25519_shared_secret(
+    secret: &X25519SecretKey,
+    peer_public: &X25519PublicKey,
+) -> Result<X25519SharedSecret, CurveError> {
+    let shared = x25519(secret, peer_public);
+    let zero = shared.iter().fold(0u8, |acc, b| acc | b);
+    if std::hint::black_box(zero) == 0 {
+        return Err(CurveError::LowOrderPoint);
+    }
+    Ok(shared)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aead::Aead;
+    use crate::chacha20::chacha20poly1305::ChaCha20Poly1305;
+    use crate::hkdf::Hkdf;
+    use crate::sha2::sha256::Sha256;
+    use crate::test_util::hex;
+
+    fn bytes(s: &str) -> [u8; 32] {
+        hex(s).try_into().unwrap()
+    }
+
+    #[test]
+    fn test_rfc7748_vectors() {
+        // RFC 7748 section 5.2 - the second u-coordinate has its top bit set
+        assert_eq!(
+            x25519(
+                &bytes("a546e36bf0527c9d3b16154b82465edd62144c0ac1fc5a18506a2244ba449ac4"),
+                &bytes("e6db6867583030db3594c1a424b15f7c726624ec26b3353b10a903a6d0ab1c4c"),
+            ),
+            bytes("c3da55379de9c6908e94ea4df28d084f32eccf03491c71f754b4075577a28552")
+        );
+        assert_eq!(
+            x25519(
+                &bytes("4b66e9d4d1b4673c5ad22691957d6af5c11b6421e0ea01d42ca4169e7918ba0d"),
+                &bytes("e5210f12786811d3f4b7959d0538ae2c31dbe7106fc03c3efc4cd549c715a493"),
+            ),
+            bytes("95cbde9476e8907d7aade45cb4b873f88b595a68799fa152e6f8f7647aac7957")
+        );
+    }
+
+    #[test]
+    fn test_rfc7748_iterated() {
+        // RFC 7748 section 5.2 - k and u both start as the base point, then each step takes
+        // the result as the new k and the old k as the new u
+        let (mut k, mut u) = (X25519_BASEPOINT, X25519_BASEPOINT);
+        for i in 1..=1000 {
+            (k, u) = (x25519(&k, &u), k);
+            if i == 1 {
+                assert_eq!(
+                    k,
+                    bytes("422c8e7a6227d7bca1350b3e2bb7279f7897b87bb6854b783c60e80311ae3079")
+                );
+            }
+        }
+        assert_eq!(
+            k,
+            bytes("684cf59ba83309552800ef566f2f4d3c1c3887c49360e3875f2eb94d99532c51")
+        );
+    }
+
+    #[test]
+    fn test_rfc7748_diffie_hellman() {
+        // RFC 7748 section 6.1
+        let alice = bytes("77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a");
+        let bob = bytes("5dab087e624a8a4b79e17f8b83800ee66f3bb1292618b6fd1c2f8b27ff88e0eb");
+        let alice_public = x25519_public_key(&alice);
+        let bob_public = x25519_public_key(&bob);
+
+        assert_eq!(
+            alice_public,
+            bytes("8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a")
+        );
+        assert_eq!(
+            bob_public,
+            bytes("de9edb7d7b7dc1b4d35b61c2ece435373f8343c85b78674dadfc7e146f882b4f")
+        );
+
+        let shared = bytes("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742");
+        assert_eq!(x25519_shared_secret(&alice, &bob_public), Ok(shared));
+        assert_eq!(x25519_shared_secret(&bob, &alice_public), Ok(shared));
+    }
+
+    #[test]
+    fn test_rejects_low_order_points() {
+        let (secret, _) = x25519_generate_keypair();
+
+        // 0, 1, a point of order 8, and p + 1 as a non-canonical 1
+        for u in [
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "0100000000000000000000000000000000000000000000000000000000000000",
+            "e0eb7a7c3b41b8ae1656e3faf19fc46ada098deb9c32b1fd866205165f49b800",
+            "eeffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff7f",
+        ] {
+            assert_eq!(
+                x25519_shared_secret(&secret, &bytes(u)),
+                Err(CurveError::LowOrderPoint)
+            );
+        }
+    }
+
+    #[test]
+    fn test_ephemeral_exchange_into_aead() {
+        // both sides derive the same ChaCha20-Poly1305 key from the exchange
+        let (alice, alice_public) = x25519_generate_keypair();
+        let (bob, bob_public) = x25519_generate_keypair();
+        assert_ne!(alice_public, bob_public);
+
+        let derive = |secret, peer_public| {
+            let shared = x25519_shared_secret(secret, peer_public).unwrap();
+            let mut key = [0u8; 32];
+            Hkdf::<Sha256>::extract(b"", &shared)
+                .expand(b"x25519 test", &mut key)
+                .unwrap();
+            ChaCha20Poly1305::new(&key)
+        };
+
+        let ciphertext = derive(&alice, &bob_public)
+            .encrypt(&[0u8; 12], b"", b"hello bob")
+            .unwrap();
+        assert_eq!(
+            derive(&bob, &alice_public)
+                .decrypt(&[0u8; 12], b"", &ciphertext)
+                .unwrap(),
+            b"hello bob"
+        );
+    }
+}
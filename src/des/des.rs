@@ -0,0 +1,276 @@
+use super::constant::*;
+use crate::block_cipher::BlockCipher;
+
+pub type DesKey = [u8; KEY_SIZE];
+
+/// apply one of the FIPS 46-3 bit selection tables to the low `input_bits` bits of `input`
+fn permute(input: u64, table: &[u8], input_bits: u32) -> u64 {
+    table.iter().fold(0, |out, &position| {
+        (out << 1) | ((input >> (input_bits - position as u32)) & 1)
+    })
+}
+
+/// the cipher function f - expand R to 48 bits, mix in the subkey, substitute through the
+/// S-boxes and permute the 32-bit result
+fn feistel(right: u32, subkey: u64) -> u32 {
+    let expanded = permute(right as u64, &E, 32) ^ subkey;
+
+    let mut substituted = 0u64;
+    for (i, sbox) in SBOXES.iter().enumerate() {
+        // the outer two bits of each 6-bit group pick the row, the inner four the column
+        let group = (expanded >> (42 - 6 * i)) & 0x3f;
+        let row = ((group >> 4) & 0b10) | (group & 1);
+        let column = (group >> 1) & 0xf;
+        substituted = (substituted << 4) | sbox[(row * 16 + column) as usize] as u64;
+    }
+
+    permute(substituted, &P, 32) as u32
+}
+
+/// the 16 48-bit round keys - the parity bits of `key` are ignored
+fn key_schedule(key: &DesKey) -> [u64; NUM_ROUNDS] {
+    let selected = permute(u64::from_be_bytes(*key), &PC1, 64);
+    let mut c = (selected >> 28) as u32;
+    let mut d = (selected & 0xfffffff) as u32;
+
+    let rotate = |half: u32, shift: u32| ((half << shift) | (half >> (28 - shift))) & 0xfffffff;
+
+    let mut subkeys = [0u64; NUM_ROUNDS];
+    for (subkey, &shift) in subkeys.iter_mut().zip(KEY_SHIFTS.iter()) {
+        c = rotate(c, shift);
+        d = rotate(d, shift);
+        *subkey = permute(((c as u64) << 28) | d as u64, &PC2, 56);
+    }
+    subkeys
+}
+
+/// the 16-round Feistel network between the initial and final permutations - decryption is
+/// the same network with the subkeys in reverse order
+fn crypt<'a>(block: u64, subkeys: impl Iterator<Item = &'a u64>) -> u64 {
+    let permuted = permute(block, &IP, 64);
+    let mut left = (permuted >> 32) as u32;
+    let mut right = permuted as u32;
+
+    for &subkey in subkeys {
+        (left, right) = (right, left ^ feistel(right, subkey));
+    }
+
+    // the halves are swapped one last time ahead of the final permutation
+    permute(((right as u64) << 32) | left as u64, &FP, 64)
+}
+
+/// the Data Encryption Standard (FIPS 46-3) - its 56-bit key is far too short to resist brute
+/// force, so this is only for talking to systems that still require it, usually by way of
+/// triple DES
+#[derive(Clone)]
+pub struct Des {
+    subkeys: [u64; NUM_ROUNDS],
+}
+
+impl Des {
+    pub fn encrypt_u64(&self, block: u64) -> u64 {
+        crypt(block, self.subkeys.iter())
+    }
+
+    pub fn decrypt_u64(&self, block: u64) -> u64 {
+        crypt(block, self.subkeys.iter().rev())
+    }
+}
+
+impl BlockCipher for Des {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+
+    type Key = DesKey;
+
+    fn new(key: &DesKey) -> Self {
+        Des {
+            subkeys: key_schedule(key),
+        }
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let output = self.encrypt_u64(u64::from_be_bytes((&*block).try_into().unwrap()));
+        block.copy_from_slice(&output.to_be_bytes());
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let output = self.decrypt_u64(u64::from_be_bytes((&*block).try_into().unwrap()));
+        block.copy_from_slice(&output.to_be_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ODD_PARITY_ZERO_KEY: DesKey = [0x01; 8];
+
+    // SP 800-20 table 1 - the variable plaintext known answer test, encrypting each single-bit
+    // plaintext from 0x8000000000000000 down to 0x01 under the all-zero (odd parity) key
+    const VARIABLE_PLAINTEXT: [u64; 64] = [
+        0x95f8a5e5dd31d900,
+        0xdd7f121ca5015619,
+        0x2e8653104f3834ea,
+        0x4bd388ff6cd81d4f,
+        0x20b9e767b2fb1456,
+        0x55579380d77138ef,
+        0x6cc5defaaf04512f,
+        0x0d9f279ba5d87260,
+        0xd9031b0271bd5a0a,
+        0x424250b37c3dd951,
+        0xb8061b7ecd9a21e5,
+        0xf15d0f286b65bd28,
+        0xadd0cc8d6e5deba1,
+        0xe6d5f82752ad63d1,
+        0xecbfe3bd3f591a5e,
+        0xf356834379d165cd,
+        0x2b9f982f20037fa9,
+        0x889de068a16f0be6,
+        0xe19e275d846a1298,
+        0x329a8ed523d71aec,
+        0xe7fce22557d23c97,
+        0x12a9f5817ff2d65d,
+        0xa484c3ad38dc9c19,
+        0xfbe00a8a1ef8ad72,
+        0x750d079407521363,
+        0x64feed9c724c2faf,
+        0xf02b263b328e2b60,
+        0x9d64555a9a10b852,
+        0xd106ff0bed5255d7,
+        0xe1652c6b138c64a5,
+        0xe428581186ec8f46,
+        0xaeb5f5ede22d1a36,
+        0xe943d7568aec0c5c,
+        0xdf98c8276f54b04b,
+        0xb160e4680f6c696f,
+        0xfa0752b07d9c4ab8,
+        0xca3a2b036dbc8502,
+        0x5e0905517bb59bcf,
+        0x814eeb3b91d90726,
+        0x4d49db1532919c9f,
+        0x25eb5fc3f8cf0621,
+        0xab6a20c0620d1c6f,
+        0x79e90dbc98f92cca,
+        0x866ecedd8072bb0e,
+        0x8b54536f2f3e64a8,
+        0xea51d3975595b86b,
+        0xcaffc6ac4542de31,
+        0x8dd45a2ddf90796c,
+        0x1029d55e880ec2d0,
+        0x5d86cb23639dbea9,
+        0x1d1ca853ae7c0c5f,
+        0xce332329248f3228,
+        0x8405d1abe24fb942,
+        0xe643d78090ca4207,
+        0x48221b9937748a23,
+        0xdd7c0bbd61fafd54,
+        0x2fbc291a570db5c4,
+        0xe07c30d7e4e26e12,
+        0x0953e2258e8e90a1,
+        0x5b711bc4ceebf2ee,
+        0xcc083f1e6d9e85f6,
+        0xd2fd8867d50d2dfe,
+        0x06e7ea22ce92708f,
+        0x166b40b44aba4bd6,
+    ];
+
+    // SP 800-20 table 3 - the variable key known answer test, encrypting zero under each key
+    // with a single non-parity bit set
+    const VARIABLE_KEY: [u64; 56] = [
+        0x95a8d72813daa94d,
+        0x0eec1487dd8c26d5,
+        0x7ad16ffb79c45926,
+        0xd3746294ca6a6cf3,
+        0x809f5f873c1fd761,
+        0xc02faffec989d1fc,
+        0x4615aa1d33e72f10,
+        0x2055123350c00858,
+        0xdf3b99d6577397c8,
+        0x31fe17369b5288c9,
+        0xdfdd3cc64dae1642,
+        0x178c83ce2b399d94,
+        0x50f636324a9b7f80,
+        0xa8468ee3bc18f06d,
+        0xa2dc9e92fd3cde92,
+        0xcac09f797d031287,
+        0x90ba680b22aeb525,
+        0xce7a24f350e280b6,
+        0x882bff0aa01a0b87,
+        0x25610288924511c2,
+        0xc71516c29c75d170,
+        0x5199c29a52c9f059,
+        0xc22f0a294a71f29f,
+        0xee371483714c02ea,
+        0xa81fbd448f9e522f,
+        0x4f644c92e192dfed,
+        0x1afa9a66a6df92ae,
+        0xb3c1cc715cb879d8,
+        0x19d032e64ab0bd8b,
+        0x3cfaa7a7dc8720dc,
+        0xb7265f7f447ac6f3,
+        0x9db73b3c0d163f54,
+        0x8181b65babf4a975,
+        0x93c9b64042eaa240,
+        0x5570530829705592,
+        0x8638809e878787a0,
+        0x41b9a79af79ac208,
+        0x7a9be42f2009a892,
+        0x29038d56ba6d2745,
+        0x5495c6abf1e5df51,
+        0xae13dbd561488933,
+        0x024d1ffa8904e389,
+        0xd1399712f99bf02e,
+        0x14c1d7c1cffec79e,
+        0x1de5279dae3bed6f,
+        0xe941a33f85501303,
+        0xda99dbbc9a03f379,
+        0xb7fc92f91d8e92e9,
+        0xae8e5caa3ca04e85,
+        0x9cc62df43b6eed74,
+        0xd863dbb5c59a91a0,
+        0xa1ab2190545b91d7,
+        0x0875041e64c570f7,
+        0x5a594528bebef1cc,
+        0xfcdb3291de21f0c0,
+        0x869efd7f9f265a09,
+    ];
+
+    #[test]
+    fn test_textbook_vector() {
+        let des = Des::new(&0x133457799bbcdff1u64.to_be_bytes());
+        assert_eq!(des.encrypt_u64(0x0123456789abcdef), 0x85e813540f0ab405);
+        assert_eq!(des.decrypt_u64(0x85e813540f0ab405), 0x0123456789abcdef);
+    }
+
+    #[test]
+    fn test_sp800_20_variable_plaintext() {
+        let des = Des::new(&ODD_PARITY_ZERO_KEY);
+        for (i, &expected) in VARIABLE_PLAINTEXT.iter().enumerate() {
+            let plaintext = 1u64 << (63 - i);
+            assert_eq!(des.encrypt_u64(plaintext), expected);
+
+            // table 2, the inverse permutation test, runs the same table backwards
+            assert_eq!(des.decrypt_u64(expected), plaintext);
+        }
+    }
+
+    #[test]
+    fn test_sp800_20_variable_key() {
+        let key_bits = (0..64).filter(|bit| bit % 8 != 7);
+        for (bit, &expected) in key_bits.zip(VARIABLE_KEY.iter()) {
+            let key = u64::from_be_bytes(ODD_PARITY_ZERO_KEY) | (1 << (63 - bit));
+            let des = Des::new(&key.to_be_bytes());
+            assert_eq!(des.encrypt_u64(0), expected);
+        }
+    }
+
+    #[test]
+    fn test_parity_bits_are_ignored() {
+        let mut block = *b"parity!!";
+        Des::new(&[0x01; 8]).encrypt_block(&mut block);
+
+        let mut other = *b"parity!!";
+        Des::new(&[0x00; 8]).encrypt_block(&mut other);
+        assert_eq!(block, other);
+    }
+}
@@ -0,0 +1,4 @@
+pub mod constant;
+#[allow(clippy::module_inception)]
+pub mod des;
+pub mod tdea;
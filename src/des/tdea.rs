@@ -0,0 +1,149 @@
+use super::{
+    constant::*,
+    des::{Des, DesKey},
+};
+use crate::block_cipher::BlockCipher;
+
+pub type TdeaKey = [u8; TDEA_KEY_SIZE];
+pub type TdeaTwoKey = [u8; TDEA_TWO_KEY_SIZE];
+
+/// the Triple Data Encryption Algorithm (SP 800-67) - DES in encrypt-decrypt-encrypt order
+/// under a bundle of three keys, or two with K3 = K1
+///
+/// SP 800-67 rev 2 only allows three-key TDEA, and only for decrypting or for encrypting at most
+/// 2^20 blocks per key bundle - two-key TDEA is kept for legacy interoperability
+#[derive(Clone)]
+pub struct Tdea {
+    k1: Des,
+    k2: Des,
+    k3: Des,
+}
+
+fn split(key: &[u8]) -> DesKey {
+    key.try_into().unwrap()
+}
+
+impl Tdea {
+    /// keying option 2 - K1 || K2, with K1 reused as K3
+    pub fn new_two_key(key: &TdeaTwoKey) -> Self {
+        let k1 = Des::new(&split(&key[..KEY_SIZE]));
+        Tdea {
+            k2: Des::new(&split(&key[KEY_SIZE..])),
+            k3: k1.clone(),
+            k1,
+        }
+    }
+}
+
+impl BlockCipher for Tdea {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+
+    /// keying option 1 - K1 || K2 || K3
+    type Key = TdeaKey;
+
+    fn new(key: &TdeaKey) -> Self {
+        Tdea {
+            k1: Des::new(&split(&key[..KEY_SIZE])),
+            k2: Des::new(&split(&key[KEY_SIZE..2 * KEY_SIZE])),
+            k3: Des::new(&split(&key[2 * KEY_SIZE..])),
+        }
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let input = u64::from_be_bytes((&*block).try_into().unwrap());
+        let output = self
+            .k3
+            .encrypt_u64(self.k2.decrypt_u64(self.k1.encrypt_u64(input)));
+        block.copy_from_slice(&output.to_be_bytes());
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let input = u64::from_be_bytes((&*block).try_into().unwrap());
+        let output = self
+            .k1
+            .decrypt_u64(self.k2.encrypt_u64(self.k3.decrypt_u64(input)));
+        block.copy_from_slice(&output.to_be_bytes());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::{cbc::*, ecb::*};
+    use crate::test_util::hex;
+
+    const SP800_67_PLAINTEXT: &[u8] = b"The qufck brown fox jump";
+
+    fn sp800_67_key() -> TdeaKey {
+        hex("0123456789abcdef 23456789abcdef01 456789abcdef0123")
+            .try_into()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sp800_67_example() {
+        // the ECB example from SP 800-67 rev 1 appendix B
+        let tdea = Tdea::new(&sp800_67_key());
+
+        let mut buffer = SP800_67_PLAINTEXT.to_vec();
+        ecb_encrypt_blocks(&tdea, &mut buffer);
+        assert_eq!(
+            buffer,
+            hex("a826fd8ce53b855f cce21c8112256fe6 68d5c05dd9b6b900")
+        );
+
+        ecb_decrypt_blocks(&tdea, &mut buffer);
+        assert_eq!(buffer, SP800_67_PLAINTEXT);
+    }
+
+    #[test]
+    fn test_two_key_option() {
+        // checked against an independent implementation
+        let tdea = Tdea::new_two_key(&sp800_67_key()[..16].try_into().unwrap());
+
+        let mut buffer = SP800_67_PLAINTEXT.to_vec();
+        ecb_encrypt_blocks(&tdea, &mut buffer);
+        assert_eq!(
+            buffer,
+            hex("c44862f70cf2fbdc 9077d0909fa91b88 4cabd61fc58e0cbb")
+        );
+
+        // the same as three-key TDEA with K3 = K1
+        let mut key = sp800_67_key();
+        key.copy_within(..8, 16);
+        let mut three_key = SP800_67_PLAINTEXT.to_vec();
+        ecb_encrypt_blocks(&Tdea::new(&key), &mut three_key);
+        assert_eq!(buffer, three_key);
+    }
+
+    #[test]
+    fn test_single_des_compatibility() {
+        // with all three keys equal, EDE collapses to single DES
+        let key = [0x13, 0x34, 0x57, 0x79, 0x9b, 0xbc, 0xdf, 0xf1];
+        let mut bundle = [0u8; TDEA_KEY_SIZE];
+        for chunk in bundle.chunks_exact_mut(KEY_SIZE) {
+            chunk.copy_from_slice(&key);
+        }
+
+        let mut block = hex("0123456789abcdef");
+        Tdea::new(&bundle).encrypt_block(&mut block);
+        assert_eq!(block, hex("85e813540f0ab405"));
+    }
+
+    #[test]
+    fn test_cbc_with_eight_byte_blocks() {
+        // checked against an independent implementation
+        let tdea = Tdea::new(&sp800_67_key());
+        let iv = hex("f69f2445df4f9b17");
+
+        let mut buffer = SP800_67_PLAINTEXT.to_vec();
+        cbc_encrypt_blocks(&tdea, &iv, &mut buffer);
+        assert_eq!(
+            buffer,
+            hex("a5c282bad0de3774 becd2e04386b589f b5057d8552fc4336")
+        );
+
+        cbc_decrypt_blocks(&tdea, &iv, &mut buffer);
+        assert_eq!(buffer, SP800_67_PLAINTEXT);
+    }
+}
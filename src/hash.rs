@@ -0,0 +1,27 @@
+/// the streaming interface shared by every fixed-output hash function in the crate, so that
+/// constructions such as HMAC can be written once and used with any of them
+pub trait HashFunction: Clone {
+    /// size in bytes of the blocks the compression function consumes
+    const BLOCK_SIZE: usize;
+    /// size in bytes of the digest
+    const OUTPUT_SIZE: usize;
+
+    type Output: AsRef<[u8]> + Copy;
+
+    fn new() -> Self;
+    fn update(&mut self, data: &[u8]);
+    fn finalize(self) -> Self::Output;
+
+    /// one-shot hash of `data`
+    fn digest(data: &[u8]) -> Self::Output {
+        let mut hasher = Self::new();
+        hasher.update(data);
+        hasher.finalize()
+    }
+}
+
+/// reads output from an extendable-output function - each call continues the output stream
+/// from where the previous one stopped
+pub trait XofReader {
+    fn read(&mut self, out: &mut [u8]);
+}
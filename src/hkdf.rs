@@ -0,0 +1,223 @@
+use crate::hash::HashFunction;
+use crate::hmac::Hmac;
+use std::fmt;
+use std::marker::PhantomData;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HkdfError {
+    /// more than 255 * HashLen bytes of output were requested
+    OutputTooLong,
+    /// a pseudorandom key shorter than the hash output was supplied to `from_prk`
+    InvalidPrkLength,
+}
+
+impl fmt::Display for HkdfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HkdfError::OutputTooLong => write!(f, "requested output exceeds 255 hash lengths"),
+            HkdfError::InvalidPrkLength => write!(f, "pseudorandom key is shorter than the hash"),
+        }
+    }
+}
+
+impl std::error::Error for HkdfError {}
+
+/// HKDF (RFC 5869) over HMAC with any of the crate's hash functions - holds the pseudorandom key
+/// produced by the extract step, from which any number of keys can be expanded
+#[derive(Clone)]
+pub struct Hkdf<H: HashFunction> {
+    prk: Vec<u8>,
+    _hash: PhantomData<H>,
+}
+
+impl<H: HashFunction> Hkdf<H> {
+    /// the extract step - an empty `salt` is the same as the RFC's default of HashLen zeros,
+    /// since HMAC zero-pads its key either way
+    pub fn extract(salt: &[u8], ikm: &[u8]) -> Self {
+        let mut mac = Hmac::<H>::new(salt);
+        mac.update(ikm);
+
+        Hkdf {
+            prk: mac.finalize().as_ref().to_vec(),
+            _hash: PhantomData,
+        }
+    }
+
+    /// skip the extract step when the input keying material is already a uniformly random key
+    pub fn from_prk(prk: &[u8]) -> Result<Self, HkdfError> {
+        if prk.len() < H::OUTPUT_SIZE {
+            return Err(HkdfError::InvalidPrkLength);
+        }
+
+        Ok(Hkdf {
+            prk: prk.to_vec(),
+            _hash: PhantomData,
+        })
+    }
+
+    pub fn prk(&self) -> &[u8] {
+        &self.prk
+    }
+
+    /// the expand step, filling `out` with keying material bound to `info`
+    pub fn expand(&self, info: &[u8], out: &mut [u8]) -> Result<(), HkdfError> {
+        if out.len() > 255 * H::OUTPUT_SIZE {
+            return Err(HkdfError::OutputTooLong);
+        }
+
+        // T(i) = HMAC(PRK, T(i - 1) | info | i), with T(0) empty
+        let mut previous: Option<H::Output> = None;
+        for (i, chunk) in out.chunks_mut(H::OUTPUT_SIZE).enumerate() {
+            let mut mac = Hmac::<H>::new(&self.prk);
+            if let Some(previous) = &previous {
+                mac.update(previous.as_ref());
+            }
+            mac.update(info);
+            mac.update(&[(i + 1) as u8]);
+
+            let block = mac.finalize();
+            chunk.copy_from_slice(&block.as_ref()[..chunk.len()]);
+            previous = Some(block);
+        }
+
+        Ok(())
+    }
+
+    /// expand straight into a fixed-size key, e.g. `let key: AesKey128 = hkdf.expand_key(info)?`
+    pub fn expand_key<const N: usize>(&self, info: &[u8]) -> Result<[u8; N], HkdfError> {
+        let mut key = [0u8; N];
+        self.expand(info, &mut key)?;
+        Ok(key)
+    }
+}
+
+/// one-shot HKDF extract-then-expand
+pub fn hkdf<H: HashFunction>(
+    salt: &[u8],
+    ikm: &[u8],
+    info: &[u8],
+    out: &mut [u8],
+) -> Result<(), HkdfError> {
+    Hkdf::<H>::extract(salt, ikm).expand(info, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::aes::AesKey128;
+    use crate::sha2::sha256::Sha256;
+    use crate::test_util::hex;
+
+    // (IKM, salt, info, PRK, OKM)
+    type HkdfCase = (Vec<u8>, Vec<u8>, Vec<u8>, &'static str, &'static str);
+
+    // RFC 5869 appendix A
+    fn rfc5869_sha256_cases() -> Vec<HkdfCase> {
+        vec![
+            // A.1 - basic test case
+            (
+                vec![0x0b; 22],
+                (0x00..=0x0c).collect(),
+                (0xf0..=0xf9).collect(),
+                "077709362c2e32df0ddc3f0dc47bba6390b6c73bb50f9c3122ec844ad7c2b3e5",
+                "3cb25f25faacd57a90434f64d0362f2a2d2d0a90cf1a5a4c5db02d56ecc4c5bf
+                 34007208d5b887185865",
+            ),
+            // A.2 - longer inputs and outputs
+            (
+                (0x00..=0x4f).collect(),
+                (0x60..=0xaf).collect(),
+                (0xb0..=0xff).collect(),
+                "06a6b88c5853361a06104c9ceb35b45cef760014904671014a193f40c15fc244",
+                "b11e398dc80327a1c8e7f78c596a49344f012eda2d4efad8a050cc4c19afa97c
+                 59045a99cac7827271cb41c65e590e09da3275600c2f09b8367793a9aca3db71
+                 cc30c58179ec3e87c14c01d5c1f3434f1d87",
+            ),
+            // A.3 - zero-length salt and info
+            (
+                vec![0x0b; 22],
+                Vec::new(),
+                Vec::new(),
+                "19ef24a32c717b167f33a91d6f648bdf96596776afdb6377ac434c1c293ccb04",
+                "8da4e775a563c18f715f802a063c5a31b8a11f5c5ee1879ec3454e5f3c738d2d
+                 9d201395faa4b61a96c8",
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_rfc5869_sha256_vectors() {
+        for (ikm, salt, info, prk, okm) in rfc5869_sha256_cases() {
+            let hkdf = Hkdf::<Sha256>::extract(&salt, &ikm);
+            assert_eq!(hkdf.prk().to_vec(), hex(prk));
+
+            let expected = hex(okm);
+            let mut out = vec![0u8; expected.len()];
+            hkdf.expand(&info, &mut out).unwrap();
+            assert_eq!(out, expected);
+
+            // expanding from the bare PRK gives the same output
+            let mut out = vec![0u8; expected.len()];
+            Hkdf::<Sha256>::from_prk(&hex(prk))
+                .unwrap()
+                .expand(&info, &mut out)
+                .unwrap();
+            assert_eq!(out, expected);
+        }
+    }
+
+    #[test]
+    fn test_expand_into_key_types() {
+        let hkdf = Hkdf::<Sha256>::extract(b"salt", b"master secret");
+
+        let key: AesKey128 = hkdf.expand_key(b"file 1").unwrap();
+        let mut long = [0u8; 40];
+        hkdf.expand(b"file 1", &mut long).unwrap();
+
+        // shorter outputs are prefixes of longer ones for the same info
+        assert_eq!(key[..], long[..16]);
+        assert_ne!(key, hkdf.expand_key::<16>(b"file 2").unwrap());
+    }
+
+    #[test]
+    fn test_output_length_limit() {
+        let hkdf = Hkdf::<Sha256>::extract(b"", b"ikm");
+
+        let mut max = vec![0u8; 255 * 32];
+        assert_eq!(hkdf.expand(b"", &mut max), Ok(()));
+
+        let mut too_long = vec![0u8; 255 * 32 + 1];
+        assert_eq!(
+            hkdf.expand(b"", &mut too_long),
+            Err(HkdfError::OutputTooLong)
+        );
+        assert_eq!(
+            Hkdf::<Sha256>::from_prk(&[0u8; 31]).err(),
+            Some(HkdfError::InvalidPrkLength)
+        );
+    }
+
+    #[cfg(feature = "legacy")]
+    #[test]
+    fn test_rfc5869_sha1_vector() {
+        use crate::legacy::sha1::Sha1;
+
+        // A.4 - basic test case with SHA-1
+        let hkdf = Hkdf::<Sha1>::extract(&(0x00..=0x0c).collect::<Vec<u8>>(), &[0x0b; 11]);
+        assert_eq!(
+            hkdf.prk().to_vec(),
+            hex("9b6c18c432a7bf8f0e71c8eb88f4b30baa2ba243")
+        );
+
+        let mut out = [0u8; 42];
+        hkdf.expand(&(0xf0..=0xf9).collect::<Vec<u8>>(), &mut out)
+            .unwrap();
+        assert_eq!(
+            out.to_vec(),
+            hex(
+                "085a01ea1b10f36933068b56efa5ad81a4f14b822f5b091568a9cdd4f155fda2
+                 c22e422478d305f3f896"
+            )
+        );
+    }
+}
@@ -0,0 +1,245 @@
+use crate::ct::ct_eq;
+use crate::hash::HashFunction;
+use crate::mac::Mac;
+
+const IPAD: u8 = 0x36;
+const OPAD: u8 = 0x5c;
+// RFC 2104 section 5 - a truncated tag keeps at least 80 bits
+const MIN_TRUNCATED_TAG_SIZE: usize = 10;
+
+/// the shortest tag `verify_truncated` accepts for a hash with `output_size`-byte digests
+pub fn min_truncated_len(output_size: usize) -> usize {
+    output_size.div_ceil(2).max(MIN_TRUNCATED_TAG_SIZE)
+}
+
+/// HMAC (RFC 2104) over any of the crate's hash functions
+#[derive(Clone)]
+pub struct Hmac<H: HashFunction> {
+    inner: H,
+    // the outer hash is keyed up front so `finalize` only has to feed it the inner digest
+    outer: H,
+}
+
+impl<H: HashFunction> Hmac<H> {
+    /// keys longer than the hash's block size are hashed first, shorter ones are zero-padded
+    pub fn new(key: &[u8]) -> Self {
+        let mut block_key = vec![0u8; H::BLOCK_SIZE];
+        if key.len() > H::BLOCK_SIZE {
+            let hashed = H::digest(key);
+            block_key[..H::OUTPUT_SIZE].copy_from_slice(hashed.as_ref());
+        } else {
+            block_key[..key.len()].copy_from_slice(key);
+        }
+
+        let mut inner = H::new();
+        let mut outer = H::new();
+        inner.update(&block_key.iter().map(|b| b ^ IPAD).collect::<Vec<u8>>());
+        outer.update(&block_key.iter().map(|b| b ^ OPAD).collect::<Vec<u8>>());
+
+        Hmac { inner, outer }
+    }
+
+    pub fn update(&mut self, data: &[u8]) {
+        self.inner.update(data);
+    }
+
+    pub fn finalize(self) -> H::Output {
+        let inner_digest = self.inner.finalize();
+
+        let mut outer = self.outer;
+        outer.update(inner_digest.as_ref());
+        outer.finalize()
+    }
+
+    /// compare the computed tag against `tag` in constant time - only the full tag is accepted
+    pub fn verify(self, tag: &[u8]) -> bool {
+        let computed = self.finalize();
+        ct_eq(computed.as_ref(), tag)
+    }
+
+    /// compare a truncated tag against the leading bytes of the computed tag in constant time -
+    /// RFC 2104 section 5 only allows truncation down to half the hash output, and never below
+    /// 80 bits, so anything shorter is rejected
+    pub fn verify_truncated(self, tag: &[u8]) -> bool {
+        if tag.len() < min_truncated_len(H::OUTPUT_SIZE) || tag.len() > H::OUTPUT_SIZE {
+            return false;
+        }
+
+        let computed = self.finalize();
+        ct_eq(&computed.as_ref()[..tag.len()], tag)
+    }
+}
+
+impl<H: HashFunction> Mac for Hmac<H> {
+    const OUTPUT_SIZE: usize = H::OUTPUT_SIZE;
+
+    type Key = [u8];
+    type Output = H::Output;
+
+    fn new(key: &Self::Key) -> Self {
+        Hmac::new(key)
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        Hmac::update(self, data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        Hmac::finalize(self)
+    }
+
+    fn verify(self, tag: &[u8]) -> bool {
+        Hmac::verify(self, tag)
+    }
+}
+
+/// one-shot HMAC
+pub fn hmac<H: HashFunction>(key: &[u8], data: &[u8]) -> H::Output {
+    let mut mac = Hmac::<H>::new(key);
+    mac.update(data);
+    mac.finalize()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha2::{sha256::Sha256, sha512::Sha512};
+    use crate::test_util::hex;
+
+    const LARGE_KEY_MSG: &[u8] = b"Test Using Larger Than Block-Size Key - Hash Key First";
+    const LARGE_KEY_AND_DATA_MSG: &[u8] = b"This is a test using a larger than block-size key \
+        and a larger than block-size data. The key needs to be hashed before being used by the \
+        HMAC algorithm.";
+
+    // RFC 4231 section 4 - (key, data, HMAC-SHA-256, HMAC-SHA-512)
+    fn rfc4231_cases() -> Vec<(Vec<u8>, Vec<u8>, &'static str, &'static str)> {
+        vec![
+            (
+                vec![0x0b; 20],
+                b"Hi There".to_vec(),
+                "b0344c61d8db38535ca8afceaf0bf12b881dc200c9833da726e9376c2e32cff7",
+                "87aa7cdea5ef619d4ff0b4241a1d6cb02379f4e2ce4ec2787ad0b30545e17cde
+                 daa833b7d6b8a702038b274eaea3f4e4be9d914eeb61f1702e696c203a126854",
+            ),
+            (
+                b"Jefe".to_vec(),
+                b"what do ya want for nothing?".to_vec(),
+                "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+                "164b7a7bfcf819e2e395fbe73b56e0a387bd64222e831fd610270cd7ea250554
+                 9758bf75c05a994a6d034f65f8f0e6fdcaeab1a34d4a6b4b636e070a38bce737",
+            ),
+            (
+                vec![0xaa; 20],
+                vec![0xdd; 50],
+                "773ea91e36800e46854db8ebd09181a72959098b3ef8c122d9635514ced565fe",
+                "fa73b0089d56a284efb0f0756c890be9b1b5dbdd8ee81a3655f83e33b2279d39
+                 bf3e848279a722c806b485a47e67c807b946a337bee8942674278859e13292fb",
+            ),
+            (
+                (0x01..=0x19).collect(),
+                vec![0xcd; 50],
+                "82558a389a443c0ea4cc819899f2083a85f0faa3e578f8077a2e3ff46729665b",
+                "b0ba465637458c6990e5a8c5f61d4af7e576d97ff94b872de76f8050361ee3db
+                 a91ca5c11aa25eb4d679275cc5788063a5f19741120c4f2de2adebeb10a298dd",
+            ),
+            (
+                vec![0xaa; 131],
+                LARGE_KEY_MSG.to_vec(),
+                "60e431591ee0b67f0d8a26aacbf5b77f8e0bc6213728c5140546040f0ee37f54",
+                "80b24263c7c1a3ebb71493c1dd7be8b49b46d1f41b4aeec1121b013783f8f352
+                 6b56d037e05f2598bd0fd2215d6a1e5295e64f73f63f0aec8b915a985d786598",
+            ),
+            (
+                vec![0xaa; 131],
+                LARGE_KEY_AND_DATA_MSG.to_vec(),
+                "9b09ffa71b942fcb27635fbcd5b0e944bfdc63644f0713938a7f51535c3a35e2",
+                "e37b6a775dc87dbaa4dfa9f96e5e3ffddebd71f8867289865df5a32d20cdc944
+                 b6022cac3c4982b10d5eeb55c3e4de15134676fb6de0446065c97440fa8c6a58",
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_rfc4231_vectors() {
+        for (key, data, sha256_tag, sha512_tag) in rfc4231_cases() {
+            assert_eq!(hmac::<Sha256>(&key, &data).to_vec(), hex(sha256_tag));
+            assert_eq!(hmac::<Sha512>(&key, &data).to_vec(), hex(sha512_tag));
+        }
+    }
+
+    #[test]
+    fn test_rfc4231_truncated_output() {
+        // test case 5 - only the leading 128 bits are specified
+        let key = [0x0c; 20];
+        let data = b"Test With Truncation";
+        let sha256_tag = hex("a3b6167473100ee06e0c796c2955552b");
+        let sha512_tag = hex("415fad6271580a531d4179bc891d87a6");
+
+        let mut mac = Hmac::<Sha256>::new(&key);
+        mac.update(data);
+        assert!(mac.clone().verify_truncated(&sha256_tag));
+        assert!(!mac.verify(&sha256_tag));
+
+        // 128 bits is less than half of a SHA-512 tag, which RFC 2104 doesn't allow
+        let mut mac = Hmac::<Sha512>::new(&key);
+        mac.update(data);
+        assert_eq!(mac.clone().finalize()[..16], sha512_tag);
+        assert!(!mac.verify_truncated(&sha512_tag));
+    }
+
+    #[test]
+    fn test_short_tags_are_rejected() {
+        let key = [0x0b; 20];
+        let tag = hmac::<Sha256>(&key, b"Hi There");
+        let mut mac = Hmac::<Sha256>::new(&key);
+        mac.update(b"Hi There");
+
+        for len in [0, 1, 10, 16, 31] {
+            assert!(!mac.clone().verify(&tag[..len]));
+        }
+        assert!(!Mac::verify(mac.clone(), &tag[..16]));
+
+        assert_eq!(min_truncated_len(32), 16);
+        assert_eq!(min_truncated_len(64), 32);
+        assert_eq!(min_truncated_len(16), 10);
+        for len in [0, 1, 10, 15] {
+            assert!(!mac.clone().verify_truncated(&tag[..len]));
+        }
+        for len in [16, 24, 32] {
+            assert!(mac.clone().verify_truncated(&tag[..len]));
+        }
+        assert!(!mac.verify_truncated(&[&tag[..], &[0]].concat()));
+    }
+
+    #[test]
+    fn test_streaming_and_verify() {
+        let key = [0xaa; 131];
+        let expected = hmac::<Sha256>(&key, LARGE_KEY_AND_DATA_MSG);
+
+        let mut mac = <Hmac<Sha256> as Mac>::new(&key);
+        for chunk in LARGE_KEY_AND_DATA_MSG.chunks(13) {
+            Mac::update(&mut mac, chunk);
+        }
+        assert!(mac.clone().verify(&expected));
+        assert!(!mac.clone().verify(&[]));
+
+        let mut bad = expected;
+        bad[31] ^= 0x01;
+        assert!(!mac.verify(&bad));
+    }
+
+    #[cfg(feature = "legacy")]
+    #[test]
+    fn test_hmac_sha1_rfc2202() {
+        use crate::legacy::sha1::Sha1;
+
+        assert_eq!(
+            hmac::<Sha1>(&[0x0b; 20], b"Hi There").to_vec(),
+            hex("b617318655057264e28bc0b6fb378c8ef146be00")
+        );
+        assert_eq!(
+            hmac::<Sha1>(&[0xaa; 80], LARGE_KEY_MSG).to_vec(),
+            hex("aa4ae5e15272d00e95705637ce8a3b55ed402112")
+        );
+    }
+}
@@ -0,0 +1,469 @@
+use super::{constant::*, KdfError};
+use crate::blake2::{blake2::Blake2b, constant::BLAKE2B_OUTPUT_SIZE};
+
+type Block = [u64; ARGON2_BLOCK_WORDS];
+
+/// the Argon2 variants (RFC 9106 section 3.1) - Argon2id is the recommended one, the others are
+/// here for completeness and testing
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Argon2Variant {
+    /// data-dependent memory access - fastest, but leaks timing information about the password
+    Argon2d = 0,
+    /// data-independent memory access
+    Argon2i = 1,
+    /// Argon2i for the first half of the first pass, Argon2d after that
+    Argon2id = 2,
+}
+
+/// Argon2 cost parameters - `memory_kib` is the memory size in 1 KiB blocks, `iterations` the
+/// number of passes over it and `lanes` the degree of parallelism
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Argon2Params {
+    memory_kib: u32,
+    iterations: u32,
+    lanes: u32,
+}
+
+impl Argon2Params {
+    pub fn new(memory_kib: u32, iterations: u32, lanes: u32) -> Result<Self, KdfError> {
+        if lanes == 0 || lanes >= 1 << 24 || iterations == 0 {
+            return Err(KdfError::InvalidCost);
+        }
+        // every lane needs at least two blocks per slice
+        if (memory_kib as u64) < 8 * lanes as u64 {
+            return Err(KdfError::InvalidCost);
+        }
+
+        Ok(Argon2Params {
+            memory_kib,
+            iterations,
+            lanes,
+        })
+    }
+
+    pub fn memory_kib(&self) -> u32 {
+        self.memory_kib
+    }
+
+    pub fn iterations(&self) -> u32 {
+        self.iterations
+    }
+
+    pub fn lanes(&self) -> u32 {
+        self.lanes
+    }
+}
+
+impl Default for Argon2Params {
+    /// the second recommended option of RFC 9106 section 4 - 64 MiB, 3 passes, 4 lanes
+    fn default() -> Self {
+        Argon2Params {
+            memory_kib: 64 * 1024,
+            iterations: 3,
+            lanes: 4,
+        }
+    }
+}
+
+fn blake2b(parts: &[&[u8]], out: &mut [u8]) {
+    let mut hasher = Blake2b::new(out.len()).unwrap();
+    for part in parts {
+        hasher.update(part);
+    }
+    hasher.finalize_into(out);
+}
+
+// the variable-length hash H' (RFC 9106 section 3.3)
+fn hash_prime(input: &[&[u8]], out: &mut [u8]) {
+    let len_prefix = (out.len() as u32).to_le_bytes();
+    let mut parts = vec![&len_prefix[..]];
+    parts.extend_from_slice(input);
+
+    if out.len() <= BLAKE2B_OUTPUT_SIZE {
+        blake2b(&parts, out);
+        return;
+    }
+
+    // chain 64-byte hashes, keeping the first half of each, until the tail fits in one more
+    let mut v = [0u8; BLAKE2B_OUTPUT_SIZE];
+    blake2b(&parts, &mut v);
+    out[..32].copy_from_slice(&v[..32]);
+
+    let mut offset = 32;
+    while out.len() - offset > BLAKE2B_OUTPUT_SIZE {
+        let previous = v;
+        blake2b(&[&previous], &mut v);
+        out[offset..offset + 32].copy_from_slice(&v[..32]);
+        offset += 32;
+    }
+
+    blake2b(&[&v], &mut out[offset..]);
+}
+
+fn block_from_bytes(bytes: &[u8]) -> Block {
+    let mut block = [0u64; ARGON2_BLOCK_WORDS];
+    for (word, chunk) in block.iter_mut().zip(bytes.chunks_exact(8)) {
+        *word = u64::from_le_bytes(chunk.try_into().unwrap());
+    }
+    block
+}
+
+// BLAKE2b's G with the additions replaced by a + b + 2 * lo(a) * lo(b)
+fn gb(v: &mut Block, a: usize, b: usize, c: usize, d: usize) {
+    fn f_blamka(x: u64, y: u64) -> u64 {
+        let product = (x & 0xffff_ffff).wrapping_mul(y & 0xffff_ffff);
+        x.wrapping_add(y).wrapping_add(product.wrapping_mul(2))
+    }
+
+    v[a] = f_blamka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(32);
+    v[c] = f_blamka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(24);
+    v[a] = f_blamka(v[a], v[b]);
+    v[d] = (v[d] ^ v[a]).rotate_right(16);
+    v[c] = f_blamka(v[c], v[d]);
+    v[b] = (v[b] ^ v[c]).rotate_right(63);
+}
+
+// the permutation P over the sixteen words at `idx`
+fn permute(v: &mut Block, idx: [usize; 16]) {
+    gb(v, idx[0], idx[4], idx[8], idx[12]);
+    gb(v, idx[1], idx[5], idx[9], idx[13]);
+    gb(v, idx[2], idx[6], idx[10], idx[14]);
+    gb(v, idx[3], idx[7], idx[11], idx[15]);
+    gb(v, idx[0], idx[5], idx[10], idx[15]);
+    gb(v, idx[1], idx[6], idx[11], idx[12]);
+    gb(v, idx[2], idx[7], idx[8], idx[13]);
+    gb(v, idx[3], idx[4], idx[9], idx[14]);
+}
+
+// the compression function G (RFC 9106 section 3.5) - the block is viewed as an 8x8 matrix of
+// 16-byte registers and P is applied to each row and then each column
+fn compress(x: &Block, y: &Block) -> Block {
+    let mut r = [0u64; ARGON2_BLOCK_WORDS];
+    for ((r, x), y) in r.iter_mut().zip(x.iter()).zip(y.iter()) {
+        *r = x ^ y;
+    }
+
+    let mut z = r;
+    for row in 0..8 {
+        permute(&mut z, std::array::from_fn(|i| 16 * row + i));
+    }
+    for column in 0..8 {
+        permute(
+            &mut z,
+            std::array::from_fn(|i| 16 * (i / 2) + 2 * column + (i % 2)),
+        );
+    }
+
+    for (z, r) in z.iter_mut().zip(r.iter()) {
+        *z ^= r;
+    }
+    z
+}
+
+// generates the pseudo-random reference indices for data-independent addressing, 128 at a time
+struct AddressGenerator {
+    input: Block,
+    addresses: Block,
+}
+
+impl AddressGenerator {
+    fn new(pass: u64, lane: u64, slice: u64, blocks: u64, passes: u64, variant: u64) -> Self {
+        let mut input = [0u64; ARGON2_BLOCK_WORDS];
+        input[..6].copy_from_slice(&[pass, lane, slice, blocks, passes, variant]);
+
+        AddressGenerator {
+            input,
+            addresses: [0u64; ARGON2_BLOCK_WORDS],
+        }
+    }
+
+    fn next_block(&mut self) {
+        let zero = [0u64; ARGON2_BLOCK_WORDS];
+        self.input[6] += 1;
+        self.addresses = compress(&zero, &compress(&zero, &self.input));
+    }
+}
+
+/// Argon2 (RFC 9106) with an optional `secret` (a pepper) and `associated_data`, filling `out`
+/// with the tag - pass an `AesKey128` to derive a key directly
+pub fn argon2(
+    variant: Argon2Variant,
+    password: &[u8],
+    salt: &[u8],
+    secret: &[u8],
+    associated_data: &[u8],
+    params: &Argon2Params,
+    out: &mut [u8],
+) -> Result<(), KdfError> {
+    if salt.len() < ARGON2_MIN_SALT_LEN {
+        return Err(KdfError::SaltTooShort);
+    }
+    if out.len() < ARGON2_MIN_OUTPUT_LEN || out.len() as u64 > u32::MAX as u64 {
+        return Err(KdfError::InvalidOutputLength);
+    }
+
+    let lanes = params.lanes as usize;
+    let passes = params.iterations;
+
+    // H_0 over the parameters and all inputs, each prefixed by its length
+    let mut h0 = [0u8; BLAKE2B_OUTPUT_SIZE];
+    let header: Vec<u8> = [
+        params.lanes,
+        out.len() as u32,
+        params.memory_kib,
+        passes,
+        ARGON2_VERSION,
+        variant as u32,
+    ]
+    .iter()
+    .flat_map(|v| v.to_le_bytes())
+    .collect();
+    let lengths: Vec<[u8; 4]> = [password, salt, secret, associated_data]
+        .iter()
+        .map(|s| (s.len() as u32).to_le_bytes())
+        .collect();
+    blake2b(
+        &[
+            &header,
+            &lengths[0],
+            password,
+            &lengths[1],
+            salt,
+            &lengths[2],
+            secret,
+            &lengths[3],
+            associated_data,
+        ],
+        &mut h0,
+    );
+
+    // round the memory down to a multiple of 4 * lanes blocks
+    let segment_len = params.memory_kib as usize / (ARGON2_SYNC_POINTS * lanes);
+    let lane_len = segment_len * ARGON2_SYNC_POINTS;
+    let block_count = lane_len * lanes;
+    let mut memory = vec![[0u64; ARGON2_BLOCK_WORDS]; block_count];
+
+    for lane in 0..lanes {
+        for column in 0..2 {
+            let mut bytes = [0u8; ARGON2_BLOCK_SIZE];
+            hash_prime(
+                &[
+                    &h0,
+                    &(column as u32).to_le_bytes(),
+                    &(lane as u32).to_le_bytes(),
+                ],
+                &mut bytes,
+            );
+            memory[lane * lane_len + column] = block_from_bytes(&bytes);
+        }
+    }
+
+    for pass in 0..passes as usize {
+        for slice in 0..ARGON2_SYNC_POINTS {
+            // the lanes of a slice are independent of each other, but they're filled in turn
+            for lane in 0..lanes {
+                let data_independent = match variant {
+                    Argon2Variant::Argon2d => false,
+                    Argon2Variant::Argon2i => true,
+                    Argon2Variant::Argon2id => pass == 0 && slice < ARGON2_SYNC_POINTS / 2,
+                };
+                let mut addresses = AddressGenerator::new(
+                    pass as u64,
+                    lane as u64,
+                    slice as u64,
+                    block_count as u64,
+                    passes as u64,
+                    variant as u64,
+                );
+
+                // the first two blocks of each lane were filled from H_0
+                let start = if pass == 0 && slice == 0 { 2 } else { 0 };
+                if data_independent && start != 0 {
+                    addresses.next_block();
+                }
+
+                for index in start..segment_len {
+                    let column = slice * segment_len + index;
+                    let current = lane * lane_len + column;
+                    let previous = if column == 0 {
+                        current + lane_len - 1
+                    } else {
+                        current - 1
+                    };
+
+                    let pseudo_random = if data_independent {
+                        if index % ARGON2_BLOCK_WORDS == 0 {
+                            addresses.next_block();
+                        }
+                        addresses.addresses[index % ARGON2_BLOCK_WORDS]
+                    } else {
+                        memory[previous][0]
+                    };
+                    let j1 = pseudo_random & 0xffff_ffff;
+                    let j2 = pseudo_random >> 32;
+
+                    // the first slice of the first pass can only reference its own lane
+                    let ref_lane = if pass == 0 && slice == 0 {
+                        lane
+                    } else {
+                        (j2 % lanes as u64) as usize
+                    };
+                    let same_lane = ref_lane == lane;
+
+                    // the blocks that may be referenced - everything finished so far, less the
+                    // current slice of other lanes and the block just computed
+                    let finished = if pass == 0 {
+                        slice * segment_len
+                    } else {
+                        lane_len - segment_len
+                    };
+                    let area = if same_lane {
+                        finished + index - 1
+                    } else if index == 0 {
+                        finished - 1
+                    } else {
+                        finished
+                    };
+
+                    // map j1 non-uniformly onto the area, favouring recent blocks
+                    let x = (j1 * j1) >> 32;
+                    let y = (area as u64 * x) >> 32;
+                    let relative = area - 1 - y as usize;
+                    let area_start = if pass == 0 || slice == ARGON2_SYNC_POINTS - 1 {
+                        0
+                    } else {
+                        (slice + 1) * segment_len
+                    };
+                    let ref_column = (area_start + relative) % lane_len;
+
+                    let block =
+                        compress(&memory[previous], &memory[ref_lane * lane_len + ref_column]);
+                    if pass == 0 {
+                        memory[current] = block;
+                    } else {
+                        // version 1.3 xors over the previous pass instead of overwriting
+                        for (m, b) in memory[current].iter_mut().zip(block.iter()) {
+                            *m ^= b;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // xor the last column of every lane together and hash it down to the tag
+    let mut last = [0u64; ARGON2_BLOCK_WORDS];
+    for lane in 0..lanes {
+        for (l, m) in last
+            .iter_mut()
+            .zip(memory[lane * lane_len + lane_len - 1].iter())
+        {
+            *l ^= m;
+        }
+    }
+    let last_bytes: Vec<u8> = last.iter().flat_map(|w| w.to_le_bytes()).collect();
+    hash_prime(&[&last_bytes], out);
+
+    Ok(())
+}
+
+/// Argon2id without a secret or associated data
+pub fn argon2id(
+    password: &[u8],
+    salt: &[u8],
+    params: &Argon2Params,
+    out: &mut [u8],
+) -> Result<(), KdfError> {
+    argon2(
+        Argon2Variant::Argon2id,
+        password,
+        salt,
+        &[],
+        &[],
+        params,
+        out,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::aes::AesKey128;
+    use crate::test_util::hex;
+
+    #[test]
+    fn test_rfc9106_vectors() {
+        // RFC 9106 section 5 - 32 KiB, 3 passes, 4 lanes and the same inputs for all variants
+        let params = Argon2Params::new(32, 3, 4).unwrap();
+        let password = [0x01; 32];
+        let salt = [0x02; 16];
+        let secret = [0x03; 8];
+        let associated_data = [0x04; 12];
+
+        let cases = [
+            (
+                Argon2Variant::Argon2d,
+                "512b391b6f1162975371d30919734294f868e3be3984f3c1a13a4db9fabe4acb",
+            ),
+            (
+                Argon2Variant::Argon2i,
+                "c814d9d1dc7f37aa13f0d77f2494bda1c8de6b016dd388d29952a4c4672b6ce8",
+            ),
+            (
+                Argon2Variant::Argon2id,
+                "0d640df58d78766c08c037a34a8b53c9d01ef0452d75b65eb52520e96b01e659",
+            ),
+        ];
+
+        for (variant, expected) in cases {
+            let mut tag = [0u8; 32];
+            argon2(
+                variant,
+                &password,
+                &salt,
+                &secret,
+                &associated_data,
+                &params,
+                &mut tag,
+            )
+            .unwrap();
+            assert_eq!(tag.to_vec(), hex(expected), "{:?}", variant);
+        }
+    }
+
+    #[test]
+    fn test_derive_aes_key() {
+        let params = Argon2Params::new(64, 2, 1).unwrap();
+        let mut key: AesKey128 = [0u8; 16];
+        argon2id(b"correct horse", b"somesaltsomesalt", &params, &mut key).unwrap();
+
+        // tags of different lengths are unrelated, since the length is hashed into H_0
+        let mut long = [0u8; 32];
+        argon2id(b"correct horse", b"somesaltsomesalt", &params, &mut long).unwrap();
+        assert_ne!(key[..], long[..16]);
+
+        let mut again: AesKey128 = [0u8; 16];
+        argon2id(b"correct horse", b"somesaltsomesalt", &params, &mut again).unwrap();
+        assert_eq!(key, again);
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        assert_eq!(Argon2Params::new(32, 0, 4), Err(KdfError::InvalidCost));
+        assert_eq!(Argon2Params::new(32, 3, 0), Err(KdfError::InvalidCost));
+        assert_eq!(Argon2Params::new(31, 3, 4), Err(KdfError::InvalidCost));
+
+        let params = Argon2Params::new(32, 1, 1).unwrap();
+        let mut out = [0u8; 32];
+        assert_eq!(
+            argon2id(b"password", b"short", &params, &mut out),
+            Err(KdfError::SaltTooShort)
+        );
+        assert_eq!(
+            argon2id(b"password", b"saltsalt", &params, &mut out[..3]),
+            Err(KdfError::InvalidOutputLength)
+        );
+    }
+}
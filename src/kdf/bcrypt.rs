@@ -0,0 +1,268 @@
+use super::{constant::*, KdfError};
+use crate::blowfish::blowfish::Blowfish;
+use crate::ct::ct_eq;
+use rand::{thread_rng, Rng};
+
+pub type BcryptSalt = [u8; BCRYPT_SALT_SIZE];
+pub type BcryptHash = [u8; BCRYPT_HASH_SIZE];
+
+// EksBlowfishSetup - the salted key schedule followed by 2^cost rounds of alternately
+// re-keying with the key and with the salt
+fn eks_blowfish_setup(cost: u32, salt: &BcryptSalt, key: &[u8]) -> Blowfish {
+    // bcrypt NUL terminates the password and cuts it to 72 bytes, so the key always fits
+    let mut state =
+        Blowfish::try_new_salted(key, salt).expect("bcrypt keys are 1 to 72 bytes long");
+
+    for _ in 0..1u64 << cost {
+        state.expand_key(key, &[]);
+        state.expand_key(salt, &[]);
+    }
+    state
+}
+
+/// bcrypt - the raw 23-byte hash of `password` under `salt` at a work factor of 2^`cost`
+///
+/// the password is NUL terminated and everything past 72 bytes is ignored, as in OpenBSD
+pub fn bcrypt(password: &[u8], salt: &BcryptSalt, cost: u32) -> Result<BcryptHash, KdfError> {
+    if !(BCRYPT_MIN_COST..=BCRYPT_MAX_COST).contains(&cost) {
+        return Err(KdfError::InvalidCost);
+    }
+
+    let mut key = password.to_vec();
+    key.push(0);
+    key.truncate(BCRYPT_MAX_KEY_LEN);
+
+    let state = eks_blowfish_setup(cost, salt, &key);
+
+    let mut ctext = [0u32; 6];
+    for (word, chunk) in ctext.iter_mut().zip(BCRYPT_MAGIC.chunks_exact(4)) {
+        *word = u32::from_be_bytes(chunk.try_into().unwrap());
+    }
+    for pair in ctext.chunks_exact_mut(2) {
+        let mut block = [pair[0], pair[1]];
+        for _ in 0..64 {
+            block = state.encrypt_words(block);
+        }
+        pair.copy_from_slice(&block);
+    }
+
+    let mut out = [0u8; 24];
+    for (chunk, word) in out.chunks_exact_mut(4).zip(ctext) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    Ok(out[..BCRYPT_HASH_SIZE].try_into().unwrap())
+}
+
+// bcrypt's radix-64 - base64 bit order over `BCRYPT_ALPHABET`, without padding
+fn encode_radix64(data: &[u8], out: &mut String) {
+    for chunk in data.chunks(3) {
+        let mut bits = [0u8; 3];
+        bits[..chunk.len()].copy_from_slice(chunk);
+        let group = u32::from_be_bytes([0, bits[0], bits[1], bits[2]]);
+
+        for i in 0..=chunk.len() {
+            let index = (group >> (18 - 6 * i)) & 0x3f;
+            out.push(BCRYPT_ALPHABET[index as usize] as char);
+        }
+    }
+}
+
+// decode radix-64 into exactly `out.len()` bytes - any bits left over in the last character are
+// ignored, as OpenBSD does
+fn decode_radix64(encoded: &[u8], out: &mut [u8]) -> Result<(), KdfError> {
+    if encoded.len() != (out.len() * 4).div_ceil(3) {
+        return Err(KdfError::MalformedHash);
+    }
+
+    for (chunk, bytes) in encoded.chunks(4).zip(out.chunks_mut(3)) {
+        let mut group = 0u32;
+        for (i, c) in chunk.iter().enumerate() {
+            let value = BCRYPT_ALPHABET
+                .iter()
+                .position(|a| a == c)
+                .ok_or(KdfError::MalformedHash)?;
+            group |= (value as u32) << (18 - 6 * i);
+        }
+        let len = bytes.len();
+        bytes.copy_from_slice(&group.to_be_bytes()[1..1 + len]);
+    }
+    Ok(())
+}
+
+/// hash `password` under a fresh random salt into a `$2b$` hash string
+pub fn bcrypt_hash(password: &[u8], cost: u32) -> Result<String, KdfError> {
+    let mut salt = [0u8; BCRYPT_SALT_SIZE];
+    thread_rng().fill(&mut salt[..]);
+    bcrypt_hash_with_salt(password, &salt, cost)
+}
+
+/// hash `password` under `salt` into a `$2b$` hash string
+pub fn bcrypt_hash_with_salt(
+    password: &[u8],
+    salt: &BcryptSalt,
+    cost: u32,
+) -> Result<String, KdfError> {
+    let hash = bcrypt(password, salt, cost)?;
+
+    let mut out = String::with_capacity(BCRYPT_HASH_STRING_LEN);
+    out.push_str(&format!("$2b${cost:02}$"));
+    encode_radix64(salt, &mut out);
+    encode_radix64(&hash, &mut out);
+    Ok(out)
+}
+
+// split a hash string into its cost, salt and hash - `$2a$`, `$2b$` and `$2y$` only differ in
+// how the implementations that produced them handled bugs long since fixed, and hash the same way
+fn parse_hash_string(hash: &str) -> Result<(u32, BcryptSalt, BcryptHash), KdfError> {
+    let hash = hash.as_bytes();
+    if hash.len() != BCRYPT_HASH_STRING_LEN {
+        return Err(KdfError::MalformedHash);
+    }
+
+    let (prefix, rest) = hash.split_at(4);
+    if !matches!(prefix, b"$2a$" | b"$2b$" | b"$2y$") {
+        return Err(KdfError::MalformedHash);
+    }
+
+    let (cost, rest) = rest.split_at(3);
+    if !cost[..2].iter().all(u8::is_ascii_digit) || cost[2] != b'$' {
+        return Err(KdfError::MalformedHash);
+    }
+    let cost = (cost[0] - b'0') as u32 * 10 + (cost[1] - b'0') as u32;
+
+    let (encoded_salt, encoded_hash) = rest.split_at(22);
+    let mut salt = [0u8; BCRYPT_SALT_SIZE];
+    let mut hash = [0u8; BCRYPT_HASH_SIZE];
+    decode_radix64(encoded_salt, &mut salt)?;
+    decode_radix64(encoded_hash, &mut hash)?;
+
+    Ok((cost, salt, hash))
+}
+
+/// check `password` against a `$2a$`, `$2b$` or `$2y$` hash string, comparing the hashes in
+/// constant time
+pub fn bcrypt_verify(password: &[u8], hash: &str) -> Result<bool, KdfError> {
+    let (cost, salt, expected) = parse_hash_string(hash)?;
+    let actual = bcrypt(password, &salt, cost)?;
+    Ok(ct_eq(&actual, &expected))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // from OpenBSD's bcrypt regression tests, by way of jBCrypt
+    const OPENBSD_VECTORS: [(&str, &str); 7] = [
+        (
+            "",
+            "$2a$06$DCq7YPn5Rq63x1Lad4cll.TV4S6ytwfsfvkgY8jIucDrjc8deX1s.",
+        ),
+        (
+            "",
+            "$2a$08$HqWuK6/Ng6sg9gQzbLrgb.Tl.ZHfXLhvt/SgVyWhQqgqcZ7ZuUtye",
+        ),
+        (
+            "a",
+            "$2a$06$m0CrhHm10qJ3lXRY.5zDGO3rS2KdeeWLuGmsfGlMfOxih58VYVfxe",
+        ),
+        (
+            "abc",
+            "$2a$06$If6bvum7DFjUnE9p2uDeDu0YHzrHM6tf.iqN8.yx.jNN1ILEf7h0i",
+        ),
+        (
+            "abcdefghijklmnopqrstuvwxyz",
+            "$2a$06$.rCVZVOThsIa97pEDOxvGuRRgzG64bvtJ0938xuqzv18d3ZpQhstC",
+        ),
+        (
+            "abcdefghijklmnopqrstuvwxyz",
+            "$2a$08$aTsUwsyowQuzRrDqFflhgekJ8d9/7Z3GV3UcgvzQW3J5zMyrTvlz.",
+        ),
+        (
+            "~!@#$%^&*()      ~!@#$%^&*()PNBFRD",
+            "$2a$06$fPIsBO8qRqkjj273rfaOI.HtSV9jLDpTbZn782DC6/t7qT67P6FfO",
+        ),
+    ];
+
+    #[test]
+    fn test_openbsd_vectors() {
+        for (password, hash) in OPENBSD_VECTORS {
+            assert!(bcrypt_verify(password.as_bytes(), hash).unwrap(), "{hash}");
+
+            // re-hashing under the parsed salt reproduces the string, up to the version
+            let (cost, salt, _) = parse_hash_string(hash).unwrap();
+            let rehashed = bcrypt_hash_with_salt(password.as_bytes(), &salt, cost).unwrap();
+            assert_eq!(rehashed[4..], hash[4..]);
+        }
+    }
+
+    #[test]
+    fn test_wrong_password() {
+        let (_, hash) = OPENBSD_VECTORS[3];
+        assert!(!bcrypt_verify(b"abd", hash).unwrap());
+        assert!(!bcrypt_verify(b"abc\0", hash).unwrap());
+    }
+
+    #[test]
+    fn test_versions_hash_alike() {
+        // a $2b$ hash produced by the Python bcrypt package
+        let hash = "$2b$04$EGdrhbKUv8Oc9vGiXX0HQOxSg445d458Muh7DAHskb6QbtCvdxcie";
+        for version in ["$2a$", "$2y$"] {
+            let other = format!("{version}{}", &hash[4..]);
+            assert!(bcrypt_verify(b"correctbatteryhorsestapler", &other).unwrap());
+        }
+
+        let unsupported = format!("$2x${}", &hash[4..]);
+        assert_eq!(
+            bcrypt_verify(b"correctbatteryhorsestapler", &unsupported),
+            Err(KdfError::MalformedHash)
+        );
+    }
+
+    #[test]
+    fn test_long_passwords_truncate() {
+        let salt = [0x5a; BCRYPT_SALT_SIZE];
+        let long = [b'x'; 100];
+        assert_eq!(
+            bcrypt(&long, &salt, 4).unwrap(),
+            bcrypt(&long[..72], &salt, 4).unwrap()
+        );
+        assert_ne!(
+            bcrypt(&long[..71], &salt, 4).unwrap(),
+            bcrypt(&long[..72], &salt, 4).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_random_salt_round_trip() {
+        let hash = bcrypt_hash(b"hunter2", 4).unwrap();
+        assert!(hash.starts_with("$2b$04$"));
+        assert_eq!(hash.len(), BCRYPT_HASH_STRING_LEN);
+        assert!(bcrypt_verify(b"hunter2", &hash).unwrap());
+        assert!(!bcrypt_verify(b"hunter3", &hash).unwrap());
+    }
+
+    #[test]
+    fn test_invalid_cost_and_format() {
+        let salt = [0u8; BCRYPT_SALT_SIZE];
+        assert_eq!(bcrypt(b"", &salt, 3), Err(KdfError::InvalidCost));
+        assert_eq!(bcrypt(b"", &salt, 32), Err(KdfError::InvalidCost));
+
+        let hash = "$2b$04$EGdrhbKUv8Oc9vGiXX0HQOxSg445d458Muh7DAHskb6QbtCvdxcie";
+        for bad in [
+            &hash[..59],
+            "$2b$4$EGdrhbKUv8Oc9vGiXX0HQOxSg445d458Muh7DAHskb6QbtCvdxcie1",
+            "$2b$04EGdrhbKUv8Oc9vGiXX0HQOxSg445d458Muh7DAHskb6QbtCvdxcie1",
+            "$2b$04$EGdrhbKUv8Oc9vGiXX0HQOxSg445d458Muh7DAHskb6QbtCvdxci!",
+            "2b$04$$EGdrhbKUv8Oc9vGiXX0HQOxSg445d458Muh7DAHskb6QbtCvdxcie",
+        ] {
+            assert_eq!(
+                bcrypt_verify(b"", bad),
+                Err(KdfError::MalformedHash),
+                "{bad}"
+            );
+        }
+
+        let too_cheap = format!("$2b$03${}", &hash[7..]);
+        assert_eq!(bcrypt_verify(b"", &too_cheap), Err(KdfError::InvalidCost));
+    }
+}
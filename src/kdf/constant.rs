@@ -0,0 +1,24 @@
+pub const ARGON2_VERSION: u32 = 0x13;
+pub const ARGON2_BLOCK_SIZE: usize = 1024;
+// a block as 64-bit words
+pub const ARGON2_BLOCK_WORDS: usize = ARGON2_BLOCK_SIZE / 8;
+// number of slices each pass over a lane is split into
+pub const ARGON2_SYNC_POINTS: usize = 4;
+pub const ARGON2_MIN_SALT_LEN: usize = 8;
+pub const ARGON2_MIN_OUTPUT_LEN: usize = 4;
+
+pub const SCRYPT_BLOCK_SIZE: usize = 64;
+
+pub const BCRYPT_SALT_SIZE: usize = 16;
+// bcrypt computes 24 bytes but the hash string only keeps the first 23
+pub const BCRYPT_HASH_SIZE: usize = 23;
+// passwords are truncated to this many bytes, counting the NUL terminator
+pub const BCRYPT_MAX_KEY_LEN: usize = 72;
+pub const BCRYPT_MIN_COST: u32 = 4;
+pub const BCRYPT_MAX_COST: u32 = 31;
+pub const BCRYPT_MAGIC: &[u8; 24] = b"OrpheanBeholderScryDoubt";
+// "$2b$" + two cost digits + "$" + 22 salt characters + 31 hash characters
+pub const BCRYPT_HASH_STRING_LEN: usize = 60;
+// bcrypt's radix-64 alphabet - the same encoding as base64 with its own character order
+pub const BCRYPT_ALPHABET: &[u8; 64] =
+    b"./ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789";
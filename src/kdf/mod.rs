@@ -0,0 +1,31 @@
+pub mod argon2;
+pub mod bcrypt;
+pub mod constant;
+pub mod pbkdf2;
+pub mod scrypt;
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KdfError {
+    /// a cost parameter is zero, out of range or inconsistent with the others
+    InvalidCost,
+    /// the requested output is empty or longer than the function can produce
+    InvalidOutputLength,
+    SaltTooShort,
+    /// a password hash string isn't in the expected format
+    MalformedHash,
+}
+
+impl fmt::Display for KdfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KdfError::InvalidCost => write!(f, "invalid key derivation cost parameters"),
+            KdfError::InvalidOutputLength => write!(f, "invalid key derivation output length"),
+            KdfError::SaltTooShort => write!(f, "salt too short"),
+            KdfError::MalformedHash => write!(f, "malformed password hash"),
+        }
+    }
+}
+
+impl std::error::Error for KdfError {}
@@ -0,0 +1,106 @@
+use super::KdfError;
+use crate::hash::HashFunction;
+use crate::hmac::Hmac;
+use crate::sha2::sha256::Sha256;
+
+/// PBKDF2 (RFC 8018 section 5.2) with HMAC over any of the crate's hash functions, filling `out`
+/// with derived key material - pass an `AesKey128` to derive a key directly
+pub fn pbkdf2<H: HashFunction>(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    out: &mut [u8],
+) -> Result<(), KdfError> {
+    if iterations == 0 {
+        return Err(KdfError::InvalidCost);
+    }
+    if out.is_empty() || out.len() as u64 > u32::MAX as u64 * H::OUTPUT_SIZE as u64 {
+        return Err(KdfError::InvalidOutputLength);
+    }
+
+    // the password-keyed HMAC state is reused for every iteration
+    let keyed = Hmac::<H>::new(password);
+
+    for (i, chunk) in out.chunks_mut(H::OUTPUT_SIZE).enumerate() {
+        // U_1 = PRF(P, S || INT(i)), U_j = PRF(P, U_{j-1}), T_i = U_1 ^ ... ^ U_c
+        let mut mac = keyed.clone();
+        mac.update(salt);
+        mac.update(&(i as u32 + 1).to_be_bytes());
+        let mut u = mac.finalize();
+        let mut t = u.as_ref().to_vec();
+
+        for _ in 1..iterations {
+            let mut mac = keyed.clone();
+            mac.update(u.as_ref());
+            u = mac.finalize();
+            for (t, u) in t.iter_mut().zip(u.as_ref().iter()) {
+                *t ^= u;
+            }
+        }
+
+        chunk.copy_from_slice(&t[..chunk.len()]);
+    }
+
+    Ok(())
+}
+
+/// PBKDF2-HMAC-SHA256
+pub fn pbkdf2_hmac_sha256(
+    password: &[u8],
+    salt: &[u8],
+    iterations: u32,
+    out: &mut [u8],
+) -> Result<(), KdfError> {
+    pbkdf2::<Sha256>(password, salt, iterations, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::aes::AesKey128;
+    use crate::test_util::hex;
+
+    #[test]
+    fn test_rfc7914_vectors() {
+        // RFC 7914 section 11
+        let mut out = [0u8; 64];
+        pbkdf2_hmac_sha256(b"passwd", b"salt", 1, &mut out).unwrap();
+        assert_eq!(
+            out.to_vec(),
+            hex(
+                "55ac046e56e3089fec1691c22544b605f94185216dde0465e68b9d57c20dacbc
+                 49ca9cccf179b645991664b39d77ef317c71b845b1e30bd509112041d3a19783"
+            )
+        );
+
+        let mut out = [0u8; 64];
+        pbkdf2_hmac_sha256(b"Password", b"NaCl", 80000, &mut out).unwrap();
+        assert_eq!(
+            out.to_vec(),
+            hex(
+                "4ddcd8f60b98be21830cee5ef22701f9641a4418d04c0414aeff08876b34ab56
+                 a1d425a1225833549adb841b51c9b3176a272bdebba1d078478f62b397f33c8d"
+            )
+        );
+    }
+
+    #[test]
+    fn test_derive_aes_key() {
+        let mut key: AesKey128 = [0u8; 16];
+        pbkdf2_hmac_sha256(b"password", b"salt", 4096, &mut key).unwrap();
+        assert_eq!(key.to_vec(), hex("c5e478d59288c841aa530db6845c4c8d"));
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        let mut out = [0u8; 16];
+        assert_eq!(
+            pbkdf2_hmac_sha256(b"password", b"salt", 0, &mut out),
+            Err(KdfError::InvalidCost)
+        );
+        assert_eq!(
+            pbkdf2_hmac_sha256(b"password", b"salt", 1, &mut []),
+            Err(KdfError::InvalidOutputLength)
+        );
+    }
+}
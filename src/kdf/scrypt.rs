@@ -0,0 +1,183 @@
+use super::{constant::SCRYPT_BLOCK_SIZE, pbkdf2::pbkdf2_hmac_sha256, KdfError};
+use crate::salsa20::salsa20::salsa20_8_core;
+
+/// scrypt cost parameters - `log_n` is the base-2 logarithm of the CPU/memory cost N, `r` the
+/// block size and `p` the parallelization; memory use is 128 * r * N bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScryptParams {
+    log_n: u8,
+    r: u32,
+    p: u32,
+}
+
+impl ScryptParams {
+    pub fn new(log_n: u8, r: u32, p: u32) -> Result<Self, KdfError> {
+        // N must be a power of two greater than 1 and below 2^(128 * r / 8), and p * r < 2^30
+        if log_n == 0 || log_n as u64 >= 16 * r as u64 || log_n >= usize::BITS as u8 {
+            return Err(KdfError::InvalidCost);
+        }
+        if r == 0 || p == 0 || r as u64 * p as u64 >= 1 << 30 {
+            return Err(KdfError::InvalidCost);
+        }
+
+        // the working memory has to be addressable
+        (1usize << log_n)
+            .checked_mul(128)
+            .and_then(|n| n.checked_mul(r as usize))
+            .ok_or(KdfError::InvalidCost)?;
+
+        Ok(ScryptParams { log_n, r, p })
+    }
+
+    pub fn log_n(&self) -> u8 {
+        self.log_n
+    }
+
+    pub fn r(&self) -> u32 {
+        self.r
+    }
+
+    pub fn p(&self) -> u32 {
+        self.p
+    }
+}
+
+fn xor_into(dst: &mut [u8], src: &[u8]) {
+    for (d, s) in dst.iter_mut().zip(src.iter()) {
+        *d ^= s;
+    }
+}
+
+// scryptBlockMix (RFC 7914 section 4) - `input` is 2r 64-byte blocks, and the even-indexed
+// outputs are written to the first half of `out`, the odd-indexed ones to the second half
+fn block_mix(input: &[u8], out: &mut [u8]) {
+    let blocks = input.len() / SCRYPT_BLOCK_SIZE;
+    let mut x: [u8; SCRYPT_BLOCK_SIZE] =
+        input[input.len() - SCRYPT_BLOCK_SIZE..].try_into().unwrap();
+
+    for (i, block) in input.chunks_exact(SCRYPT_BLOCK_SIZE).enumerate() {
+        xor_into(&mut x, block);
+        salsa20_8_core(&mut x);
+
+        let position = (i / 2) + (i % 2) * (blocks / 2);
+        out[position * SCRYPT_BLOCK_SIZE..(position + 1) * SCRYPT_BLOCK_SIZE].copy_from_slice(&x);
+    }
+}
+
+// the first 8 bytes of the last 64-byte block as a little-endian integer
+fn integerify(block: &[u8]) -> u64 {
+    let last = &block[block.len() - SCRYPT_BLOCK_SIZE..];
+    u64::from_le_bytes(last[..8].try_into().unwrap())
+}
+
+// scryptROMix (RFC 7914 section 5), in place on one 128 * r byte block
+fn ro_mix(block: &mut [u8], n: usize) {
+    let len = block.len();
+    let mut v = vec![0u8; len * n];
+    let mut scratch = vec![0u8; len];
+
+    for i in 0..n {
+        v[i * len..(i + 1) * len].copy_from_slice(block);
+        block_mix(block, &mut scratch);
+        block.copy_from_slice(&scratch);
+    }
+
+    for _ in 0..n {
+        // n is a power of two, so masking is the same as reducing mod n
+        let j = (integerify(block) as usize) & (n - 1);
+        xor_into(block, &v[j * len..(j + 1) * len]);
+        block_mix(block, &mut scratch);
+        block.copy_from_slice(&scratch);
+    }
+}
+
+/// scrypt (RFC 7914), filling `out` with derived key material - pass an `AesKey128` to derive a
+/// key directly
+pub fn scrypt(
+    password: &[u8],
+    salt: &[u8],
+    params: &ScryptParams,
+    out: &mut [u8],
+) -> Result<(), KdfError> {
+    if out.is_empty() {
+        return Err(KdfError::InvalidOutputLength);
+    }
+
+    let block_len = 128 * params.r as usize;
+    let mut b = vec![0u8; block_len * params.p as usize];
+    pbkdf2_hmac_sha256(password, salt, 1, &mut b)?;
+
+    for block in b.chunks_exact_mut(block_len) {
+        ro_mix(block, 1 << params.log_n);
+    }
+
+    pbkdf2_hmac_sha256(password, &b, 1, out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::aes::aes::AesKey128;
+    use crate::test_util::hex;
+
+    #[test]
+    fn test_rfc7914_vectors() {
+        // RFC 7914 section 12 - the N = 2^20 case is left out for its 1 GiB of memory
+        let cases: [(&str, &str, u8, u32, u32, &str); 3] = [
+            (
+                "",
+                "",
+                4,
+                1,
+                1,
+                "77d6576238657b203b19ca42c18a0497f16b4844e3074ae8dfdffa3fede21442
+                 fcd0069ded0948f8326a753a0fc81f17e8d3e0fb2e0d3628cf35e20c38d18906",
+            ),
+            (
+                "password",
+                "NaCl",
+                10,
+                8,
+                16,
+                "fdbabe1c9d3472007856e7190d01e9fe7c6ad7cbc8237830e77376634b373162
+                 2eaf30d92e22a3886ff109279d9830dac727afb94a83ee6d8360cbdfa2cc0640",
+            ),
+            (
+                "pleaseletmein",
+                "SodiumChloride",
+                14,
+                8,
+                1,
+                "7023bdcb3afd7348461c06cd81fd38ebfda8fbba904f8e3ea9b543f6545da1f2
+                 d5432955613f0fcf62d49705242a9af9e61e85dc0d651e40dfcf017b45575887",
+            ),
+        ];
+
+        for (password, salt, log_n, r, p, expected) in cases {
+            let params = ScryptParams::new(log_n, r, p).unwrap();
+            let mut out = [0u8; 64];
+            scrypt(password.as_bytes(), salt.as_bytes(), &params, &mut out).unwrap();
+            assert_eq!(out.to_vec(), hex(expected));
+        }
+    }
+
+    #[test]
+    fn test_derive_aes_key() {
+        let params = ScryptParams::new(4, 1, 1).unwrap();
+        let mut key: AesKey128 = [0u8; 16];
+        scrypt(b"", b"", &params, &mut key).unwrap();
+        assert_eq!(key.to_vec(), hex("77d6576238657b203b19ca42c18a0497"));
+    }
+
+    #[test]
+    fn test_invalid_parameters() {
+        assert_eq!(ScryptParams::new(0, 8, 1), Err(KdfError::InvalidCost));
+        assert_eq!(ScryptParams::new(16, 1, 1), Err(KdfError::InvalidCost));
+        assert_eq!(ScryptParams::new(10, 0, 1), Err(KdfError::InvalidCost));
+        assert_eq!(ScryptParams::new(10, 8, 0), Err(KdfError::InvalidCost));
+        assert_eq!(
+            ScryptParams::new(10, 1 << 15, 1 << 15),
+            Err(KdfError::InvalidCost)
+        );
+    }
+}
@@ -0,0 +1,67 @@
+pub const MD5_BLOCK_SIZE: usize = 64;
+pub const SHA1_BLOCK_SIZE: usize = 64;
+
+pub const MD5_INIT: [u32; 4] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476];
+
+// integer part of 2^32 * |sin(i + 1)|
+pub const MD5_K: [u32; 64] = [
+    0xd76aa478, 0xe8c7b756, 0x242070db, 0xc1bdceee, 0xf57c0faf, 0x4787c62a, 0xa8304613, 0xfd469501,
+    0x698098d8, 0x8b44f7af, 0xffff5bb1, 0x895cd7be, 0x6b901122, 0xfd987193, 0xa679438e, 0x49b40821,
+    0xf61e2562, 0xc040b340, 0x265e5a51, 0xe9b6c7aa, 0xd62f105d, 0x02441453, 0xd8a1e681, 0xe7d3fbc8,
+    0x21e1cde6, 0xc33707d6, 0xf4d50d87, 0x455a14ed, 0xa9e3e905, 0xfcefa3f8, 0x676f02d9, 0x8d2a4c8a,
+    0xfffa3942, 0x8771f681, 0x6d9d6122, 0xfde5380c, 0xa4beea44, 0x4bdecfa9, 0xf6bb4b60, 0xbebfbc70,
+    0x289b7ec6, 0xeaa127fa, 0xd4ef3085, 0x04881d05, 0xd9d4d039, 0xe6db99e5, 0x1fa27cf8, 0xc4ac5665,
+    0xf4292244, 0x432aff97, 0xab9423a7, 0xfc93a039, 0x655b59c3, 0x8f0ccc92, 0xffeff47d, 0x85845dd1,
+    0x6fa87e4f, 0xfe2ce6e0, 0xa3014314, 0x4e0811a1, 0xf7537e82, 0xbd3af235, 0x2ad7d2bb, 0xeb86d391,
+];
+
+// per-round left rotation amounts
+pub const MD5_SHIFTS: [u32; 64] = [
+    7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, 7, 12, 17, 22, //
+    5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, 5, 9, 14, 20, //
+    4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, 4, 11, 16, 23, //
+    6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21, 6, 10, 15, 21,
+];
+
+pub const SHA1_INIT: [u32; 5] = [0x67452301, 0xefcdab89, 0x98badcfe, 0x10325476, 0xc3d2e1f0];
+
+// one round constant per group of 20 steps
+pub const SHA1_K: [u32; 4] = [0x5a827999, 0x6ed9eba1, 0x8f1bbcdc, 0xca62c1d6];
+
+/// the SHA-1 disturbance vectors checked by collision detection, as (type, K, b, test step) -
+/// the same 32 vectors as the reference sha1collisiondetection library, which cover every
+/// published and known-feasible near-collision attack including SHAttered's II(52,0)
+pub const SHA1_DISTURBANCE_VECTORS: [(u8, usize, u32, usize); 32] = [
+    (1, 43, 0, 58),
+    (1, 44, 0, 58),
+    (1, 45, 0, 58),
+    (1, 46, 0, 58),
+    (1, 46, 2, 58),
+    (1, 47, 0, 58),
+    (1, 47, 2, 58),
+    (1, 48, 0, 58),
+    (1, 48, 2, 58),
+    (1, 49, 0, 58),
+    (1, 49, 2, 58),
+    (1, 50, 0, 65),
+    (1, 50, 2, 65),
+    (1, 51, 0, 65),
+    (1, 51, 2, 65),
+    (1, 52, 0, 65),
+    (2, 45, 0, 58),
+    (2, 46, 0, 58),
+    (2, 46, 2, 58),
+    (2, 47, 0, 58),
+    (2, 48, 0, 58),
+    (2, 49, 0, 58),
+    (2, 49, 2, 58),
+    (2, 50, 0, 65),
+    (2, 50, 2, 65),
+    (2, 51, 0, 65),
+    (2, 51, 2, 65),
+    (2, 52, 0, 65),
+    (2, 53, 0, 65),
+    (2, 54, 0, 65),
+    (2, 55, 0, 65),
+    (2, 56, 0, 65),
+];
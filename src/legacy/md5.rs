@@ -0,0 +1,185 @@
+use super::constant::*;
+use crate::hash::HashFunction;
+
+pub type Md5Digest = [u8; 16];
+
+fn compress(state: &mut [u32; 4], block: &[u8; MD5_BLOCK_SIZE]) {
+    let mut m = [0u32; 16];
+    for (word, chunk) in m.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+
+    let [mut a, mut b, mut c, mut d] = *state;
+
+    for i in 0..64 {
+        let (f, g) = match i / 16 {
+            0 => ((b & c) | (!b & d), i),
+            1 => ((d & b) | (!d & c), (5 * i + 1) % 16),
+            2 => (b ^ c ^ d, (3 * i + 5) % 16),
+            _ => (c ^ (b | !d), (7 * i) % 16),
+        };
+
+        let rotated = a
+            .wrapping_add(f)
+            .wrapping_add(MD5_K[i])
+            .wrapping_add(m[g])
+            .rotate_left(MD5_SHIFTS[i]);
+
+        a = d;
+        d = c;
+        c = b;
+        b = b.wrapping_add(rotated);
+    }
+
+    for (s, v) in state.iter_mut().zip([a, b, c, d]) {
+        *s = s.wrapping_add(v);
+    }
+}
+
+/// MD5 (RFC 1321)
+///
+/// **BROKEN - do not use for anything security relevant.** Collisions can be found in seconds on
+/// a laptop and chosen-prefix collisions are practical. This only exists to check checksums
+/// produced by legacy systems.
+#[derive(Clone)]
+pub struct Md5 {
+    state: [u32; 4],
+    buffer: [u8; MD5_BLOCK_SIZE],
+    buffer_len: usize,
+    // total message length in bytes
+    length: u64,
+}
+
+impl Md5 {
+    pub fn new() -> Self {
+        Md5 {
+            state: MD5_INIT,
+            buffer: [0u8; MD5_BLOCK_SIZE],
+            buffer_len: 0,
+            length: 0,
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.length = self.length.wrapping_add(data.len() as u64);
+
+        // top up a partially filled buffer first
+        if self.buffer_len > 0 {
+            let take = (MD5_BLOCK_SIZE - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+
+            if self.buffer_len < MD5_BLOCK_SIZE {
+                return;
+            }
+
+            compress(&mut self.state, &self.buffer);
+            self.buffer_len = 0;
+        }
+
+        let mut blocks = data.chunks_exact(MD5_BLOCK_SIZE);
+        for block in &mut blocks {
+            compress(&mut self.state, block.try_into().unwrap());
+        }
+
+        let remainder = blocks.remainder();
+        self.buffer[..remainder.len()].copy_from_slice(remainder);
+        self.buffer_len = remainder.len();
+    }
+
+    pub fn finalize(mut self) -> Md5Digest {
+        let bit_length = self.length.wrapping_mul(8);
+
+        // same padding as SHA-1/SHA-2, except the length is little-endian
+        self.buffer[self.buffer_len] = 0x80;
+        self.buffer[self.buffer_len + 1..].fill(0);
+        if self.buffer_len >= MD5_BLOCK_SIZE - 8 {
+            compress(&mut self.state, &self.buffer);
+            self.buffer.fill(0);
+        }
+
+        self.buffer[MD5_BLOCK_SIZE - 8..].copy_from_slice(&bit_length.to_le_bytes());
+        compress(&mut self.state, &self.buffer);
+
+        let mut digest = [0u8; 16];
+        for (chunk, word) in digest.chunks_exact_mut(4).zip(self.state.iter()) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+        digest
+    }
+}
+
+impl Default for Md5 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Md5 {
+    const BLOCK_SIZE: usize = MD5_BLOCK_SIZE;
+    const OUTPUT_SIZE: usize = 16;
+
+    type Output = Md5Digest;
+
+    fn new() -> Self {
+        Md5::new()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        Md5::update(self, data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        Md5::finalize(self)
+    }
+}
+
+/// one-shot MD5 - **broken**, see [`Md5`]
+pub fn md5(data: &[u8]) -> Md5Digest {
+    Md5::digest(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    #[test]
+    fn test_rfc1321_test_suite() {
+        let cases: [(&[u8], &str); 7] = [
+            (b"", "d41d8cd98f00b204e9800998ecf8427e"),
+            (b"a", "0cc175b9c0f1b6a831c399e269772661"),
+            (b"abc", "900150983cd24fb0d6963f7d28e17f72"),
+            (b"message digest", "f96b697d7cb7938d525a2f31aaf161d0"),
+            (
+                b"abcdefghijklmnopqrstuvwxyz",
+                "c3fcd3d76192e4007dfb496cca67e13b",
+            ),
+            (
+                b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789",
+                "d174ab98d277d9f5a5611c2c9f419d9f",
+            ),
+            (
+                b"12345678901234567890123456789012345678901234567890123456789012345678901234567890",
+                "57edf4a22be3c955ac49da2e2107b67a",
+            ),
+        ];
+
+        for (message, expected) in cases {
+            assert_eq!(md5(message).to_vec(), hex(expected));
+        }
+    }
+
+    #[test]
+    fn test_streaming_matches_one_shot() {
+        let data: Vec<u8> = (0..300).map(|i| i as u8).collect();
+
+        for split in [0, 1, 55, 56, 63, 64, 65, 128, 299] {
+            let mut hasher = Md5::new();
+            hasher.update(&data[..split]);
+            hasher.update(&data[split..]);
+            assert_eq!(hasher.finalize(), md5(&data));
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub mod constant;
+pub mod md5;
+pub mod sha1;
@@ -0,0 +1,501 @@
+use super::constant::*;
+use crate::hash::HashFunction;
+use std::fmt;
+
+pub type Sha1Digest = [u8; 20];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sha1Error {
+    /// the message contains a block from a near-collision attack, so its digest can't be trusted
+    CollisionDetected,
+}
+
+impl fmt::Display for Sha1Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Sha1Error::CollisionDetected => write!(f, "sha-1 collision attack detected"),
+        }
+    }
+}
+
+impl std::error::Error for Sha1Error {}
+
+// the message differences of a local-collision disturbance vector of type I or II - each
+// disturbance at step i is cancelled by corrections at steps i+1 to i+5
+const fn message_differences(kind: u8, k: usize, b: u32) -> [u32; 80] {
+    // dv[i + 5] is the disturbance at step i, for steps -5 to 79
+    let mut dv = [0u32; 85];
+    let bit = 1u32 << b;
+    if kind == 1 {
+        dv[k + 15 + 5] = bit;
+    } else {
+        dv[k + 1 + 5] = bit.rotate_left(31);
+        dv[k + 3 + 5] = bit.rotate_left(31);
+        dv[k + 15 + 5] = bit;
+    }
+
+    // the vector follows the message expansion, which can be run in both directions
+    let mut i = k + 16 + 5;
+    while i < 85 {
+        dv[i] = (dv[i - 3] ^ dv[i - 8] ^ dv[i - 14] ^ dv[i - 16]).rotate_left(1);
+        i += 1;
+    }
+    let mut i = k + 5;
+    while i > 0 {
+        i -= 1;
+        dv[i] = dv[i + 16].rotate_right(1) ^ dv[i + 13] ^ dv[i + 8] ^ dv[i + 2];
+    }
+
+    let mut dm = [0u32; 80];
+    let mut i = 0;
+    while i < 80 {
+        let j = i + 5;
+        dm[i] = dv[j]
+            ^ dv[j - 1].rotate_left(5)
+            ^ dv[j - 2]
+            ^ (dv[j - 3] ^ dv[j - 4] ^ dv[j - 5]).rotate_left(30);
+        i += 1;
+    }
+    dm
+}
+
+const fn build_dv_differences() -> [[u32; 80]; 32] {
+    let mut table = [[0u32; 80]; 32];
+    let mut i = 0;
+    while i < 32 {
+        let (kind, k, b, _) = SHA1_DISTURBANCE_VECTORS[i];
+        table[i] = message_differences(kind, k, b);
+        i += 1;
+    }
+    table
+}
+
+static DV_DIFFERENCES: [[u32; 80]; 32] = build_dv_differences();
+
+fn expand(block: &[u8; SHA1_BLOCK_SIZE]) -> [u32; 80] {
+    let mut w = [0u32; 80];
+    for (word, chunk) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes(chunk.try_into().unwrap());
+    }
+    for t in 16..80 {
+        w[t] = (w[t - 3] ^ w[t - 8] ^ w[t - 14] ^ w[t - 16]).rotate_left(1);
+    }
+    w
+}
+
+fn f(t: usize, b: u32, c: u32, d: u32) -> u32 {
+    match t / 20 {
+        0 => (b & c) | (!b & d),
+        2 => (b & c) | (b & d) | (c & d),
+        _ => b ^ c ^ d,
+    }
+}
+
+fn step(state: &mut [u32; 5], t: usize, w: u32) {
+    let [a, b, c, d, e] = *state;
+    let temp = a
+        .rotate_left(5)
+        .wrapping_add(f(t, b, c, d))
+        .wrapping_add(e)
+        .wrapping_add(SHA1_K[t / 20])
+        .wrapping_add(w);
+    *state = [temp, a, b.rotate_left(30), c, d];
+}
+
+// undo `step` - every input word but `e` is still present in the output state, so `e` can be
+// solved for
+fn step_back(state: &mut [u32; 5], t: usize, w: u32) {
+    let [temp, a, b, c, d] = *state;
+    let b = b.rotate_right(30);
+    let e = temp
+        .wrapping_sub(a.rotate_left(5))
+        .wrapping_sub(f(t, b, c, d))
+        .wrapping_sub(SHA1_K[t / 20])
+        .wrapping_sub(w);
+    *state = [a, b, c, d, e];
+}
+
+fn add_state(ihv: &[u32; 5], state: &[u32; 5]) -> [u32; 5] {
+    let mut out = *ihv;
+    for (o, s) in out.iter_mut().zip(state.iter()) {
+        *o = o.wrapping_add(*s);
+    }
+    out
+}
+
+// rebuild the compression around the state before step `t` - walking backwards gives the input
+// chaining value, walking forwards gives the output
+fn recompress(t: usize, state: &[u32; 5], w: &[u32; 80]) -> ([u32; 5], [u32; 5]) {
+    let mut ihv_in = *state;
+    for i in (0..t).rev() {
+        step_back(&mut ihv_in, i, w[i]);
+    }
+
+    let mut working = *state;
+    for (i, word) in w.iter().enumerate().skip(t) {
+        step(&mut working, i, *word);
+    }
+
+    (ihv_in, add_state(&ihv_in, &working))
+}
+
+// compress one block, returning whether it looks like the second block of a collision attack -
+// for each disturbance vector, apply its message differences and recompress from the step where
+// the attack needs the internal states to agree; landing on the same output chaining value from
+// a different input means a near-collision block has been found
+fn compress(ihv: &mut [u32; 5], block: &[u8; SHA1_BLOCK_SIZE], detect: bool) -> bool {
+    let w = expand(block);
+
+    let mut state = *ihv;
+    let mut saved_states = [[0u32; 5]; 2];
+    for (t, word) in w.iter().enumerate() {
+        if t == 58 {
+            saved_states[0] = state;
+        } else if t == 65 {
+            saved_states[1] = state;
+        }
+        step(&mut state, t, *word);
+    }
+
+    let ihv_out = add_state(ihv, &state);
+
+    let mut detected = false;
+    if detect {
+        for (&(_, _, _, test_step), dm) in
+            SHA1_DISTURBANCE_VECTORS.iter().zip(DV_DIFFERENCES.iter())
+        {
+            let mut w2 = w;
+            for (word, diff) in w2.iter_mut().zip(dm.iter()) {
+                *word ^= diff;
+            }
+
+            let saved = &saved_states[if test_step == 58 { 0 } else { 1 }];
+            let (_, ihv_out2) = recompress(test_step, saved, &w2);
+            if ihv_out2 == ihv_out {
+                detected = true;
+            }
+        }
+    }
+
+    *ihv = ihv_out;
+    detected
+}
+
+/// SHA-1 (FIPS 180-4)
+///
+/// **BROKEN - do not use for anything security relevant.** Practical collisions have been
+/// demonstrated (SHAttered, 2017) and chosen-prefix collisions are affordable (Shambles, 2020).
+/// This only exists to verify checksums and HMAC-SHA1 signatures from legacy systems.
+///
+/// [`Sha1::with_collision_detection`] enables the counter-cryptanalysis check from
+/// sha1collisiondetection, which recognises messages crafted with the known near-collision
+/// attacks; it recompresses each block once per disturbance vector, so it is around 30 times
+/// slower than plain hashing.
+#[derive(Clone)]
+pub struct Sha1 {
+    state: [u32; 5],
+    buffer: [u8; SHA1_BLOCK_SIZE],
+    buffer_len: usize,
+    // total message length in bytes
+    length: u64,
+    detect_collisions: bool,
+    collision_detected: bool,
+}
+
+impl Sha1 {
+    pub fn new() -> Self {
+        Sha1 {
+            state: SHA1_INIT,
+            buffer: [0u8; SHA1_BLOCK_SIZE],
+            buffer_len: 0,
+            length: 0,
+            detect_collisions: false,
+            collision_detected: false,
+        }
+    }
+
+    /// SHA-1 that checks every block for signs of a collision attack - see `finalize_checked`
+    pub fn with_collision_detection() -> Self {
+        Sha1 {
+            detect_collisions: true,
+            ..Self::new()
+        }
+    }
+
+    fn compress_block(&mut self, block: &[u8; SHA1_BLOCK_SIZE]) {
+        if compress(&mut self.state, block, self.detect_collisions) {
+            self.collision_detected = true;
+        }
+    }
+
+    pub fn update(&mut self, mut data: &[u8]) {
+        self.length = self.length.wrapping_add(data.len() as u64);
+
+        // top up a partially filled buffer first
+        if self.buffer_len > 0 {
+            let take = (SHA1_BLOCK_SIZE - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+
+            if self.buffer_len < SHA1_BLOCK_SIZE {
+                return;
+            }
+
+            let block = self.buffer;
+            self.compress_block(&block);
+            self.buffer_len = 0;
+        }
+
+        let mut blocks = data.chunks_exact(SHA1_BLOCK_SIZE);
+        for block in &mut blocks {
+            self.compress_block(block.try_into().unwrap());
+        }
+
+        let remainder = blocks.remainder();
+        self.buffer[..remainder.len()].copy_from_slice(remainder);
+        self.buffer_len = remainder.len();
+    }
+
+    fn finalize_state(&mut self) -> Sha1Digest {
+        let bit_length = self.length.wrapping_mul(8);
+
+        // append a single 1 bit, then zeros up to the last 8 bytes of a block, which hold the
+        // message length in bits
+        let mut block = self.buffer;
+        block[self.buffer_len] = 0x80;
+        block[self.buffer_len + 1..].fill(0);
+        if self.buffer_len >= SHA1_BLOCK_SIZE - 8 {
+            self.compress_block(&block);
+            block.fill(0);
+        }
+
+        block[SHA1_BLOCK_SIZE - 8..].copy_from_slice(&bit_length.to_be_bytes());
+        self.compress_block(&block);
+
+        let mut digest = [0u8; 20];
+        for (chunk, word) in digest.chunks_exact_mut(4).zip(self.state.iter()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+        digest
+    }
+
+    pub fn finalize(mut self) -> Sha1Digest {
+        self.finalize_state()
+    }
+
+    /// like `finalize`, but fails if collision detection is enabled and found an attack block
+    pub fn finalize_checked(mut self) -> Result<Sha1Digest, Sha1Error> {
+        let digest = self.finalize_state();
+        if self.collision_detected {
+            return Err(Sha1Error::CollisionDetected);
+        }
+        Ok(digest)
+    }
+}
+
+impl Default for Sha1 {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl HashFunction for Sha1 {
+    const BLOCK_SIZE: usize = SHA1_BLOCK_SIZE;
+    const OUTPUT_SIZE: usize = 20;
+
+    type Output = Sha1Digest;
+
+    fn new() -> Self {
+        Sha1::new()
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        Sha1::update(self, data);
+    }
+
+    fn finalize(self) -> Self::Output {
+        Sha1::finalize(self)
+    }
+}
+
+/// one-shot SHA-1 - **broken**, see [`Sha1`]
+pub fn sha1(data: &[u8]) -> Sha1Digest {
+    Sha1::digest(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    const SHORT_MSG: &[u8] = b"abc";
+    const LONG_MSG: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+
+    #[test]
+    fn test_sha1_fips180_vectors() {
+        assert_eq!(
+            sha1(SHORT_MSG).to_vec(),
+            hex("a9993e364706816aba3e25717850c26c9cd0d89d")
+        );
+        assert_eq!(
+            sha1(LONG_MSG).to_vec(),
+            hex("84983e441c3bd26ebaae4aa1f95129e5e54670f1")
+        );
+        assert_eq!(
+            sha1(b"").to_vec(),
+            hex("da39a3ee5e6b4b0d3255bfef95601890afd80709")
+        );
+    }
+
+    #[test]
+    fn test_one_million_a() {
+        let mut hasher = Sha1::new();
+        let chunk = [b'a'; 1000];
+        for _ in 0..1000 {
+            hasher.update(&chunk);
+        }
+
+        assert_eq!(
+            hasher.finalize().to_vec(),
+            hex("34aa973cd4c4daa4f61eeb2bdbad27316534016f")
+        );
+    }
+
+    #[test]
+    fn test_streaming_matches_one_shot() {
+        let data: Vec<u8> = (0..300).map(|i| i as u8).collect();
+
+        for split in [0, 1, 55, 56, 63, 64, 65, 128, 299] {
+            let mut hasher = Sha1::new();
+            hasher.update(&data[..split]);
+            hasher.update(&data[split..]);
+            assert_eq!(hasher.finalize(), sha1(&data));
+        }
+    }
+
+    #[test]
+    fn test_disturbance_vector_differences() {
+        // the leading and trailing message differences of DV I(43,0), as listed by
+        // sha1collisiondetection
+        let dm = &DV_DIFFERENCES[0];
+        assert_eq!(
+            dm[..8],
+            [
+                0x08000000, 0x9800000c, 0xd8000010, 0x08000010, 0xb8000010, 0x98000000, 0x60000000,
+                0x00000008
+            ]
+        );
+        assert_eq!(dm[78..], [0x80000161, 0x80000599]);
+
+        // and of DV II(52,0), the one SHAttered was built on
+        let dm = &DV_DIFFERENCES[27];
+        assert_eq!(SHA1_DISTURBANCE_VECTORS[27], (2, 52, 0, 65));
+        assert_eq!(
+            dm[..8],
+            [
+                0x0c000002, 0xc0000010, 0xb400001c, 0x3c000004, 0xbc00001a, 0x20000010, 0x2400001c,
+                0xec000014
+            ]
+        );
+        assert_eq!(dm[78..], [0x00000089, 0x00000014]);
+
+        // the differences must themselves be a valid expanded message
+        for dm in DV_DIFFERENCES.iter() {
+            for t in 16..80 {
+                assert_eq!(
+                    dm[t],
+                    (dm[t - 3] ^ dm[t - 8] ^ dm[t - 14] ^ dm[t - 16]).rotate_left(1)
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_recompression_round_trips() {
+        let block: [u8; 64] = core::array::from_fn(|i| (i * 7) as u8);
+        let w = expand(&block);
+
+        let mut state = SHA1_INIT;
+        let mut at_58 = [0u32; 5];
+        for (t, word) in w.iter().enumerate() {
+            if t == 58 {
+                at_58 = state;
+            }
+            step(&mut state, t, *word);
+        }
+
+        let (ihv_in, ihv_out) = recompress(58, &at_58, &w);
+        assert_eq!(ihv_in, SHA1_INIT);
+        assert_eq!(ihv_out, add_state(&SHA1_INIT, &state));
+    }
+
+    #[test]
+    fn test_collision_detection_passes_ordinary_input() {
+        let data: Vec<u8> = (0..1000).map(|i| (i * 31) as u8).collect();
+
+        let mut hasher = Sha1::with_collision_detection();
+        hasher.update(&data);
+        assert_eq!(hasher.finalize_checked(), Ok(sha1(&data)));
+
+        let mut hasher = Sha1::with_collision_detection();
+        hasher.update(LONG_MSG);
+        assert_eq!(
+            hasher.finalize_checked().unwrap().to_vec(),
+            hex("84983e441c3bd26ebaae4aa1f95129e5e54670f1")
+        );
+    }
+    // the first 320 bytes of shattered-1.pdf and shattered-2.pdf - a shared prefix ending in the
+    // JPEG header, then the two-block near-collision pairs that make the full files collide
+    const SHATTERED_PREFIX: &str =
+        "255044462d312e330a25e2e3cfd30a0a0a312030206f626a0a3c3c2f57696474
+         682032203020522f4865696768742033203020522f547970652034203020522f
+         537562747970652035203020522f46696c7465722036203020522f436f6c6f72
+         53706163652037203020522f4c656e6774682038203020522f42697473506572
+         436f6d706f6e656e7420383e3e0a73747265616d0affd8fffe00245348412d31
+         20697320646561642121212121852fec092339759c39b1a1c63c4c97e1fffe01";
+    const SHATTERED_1_BLOCKS: &str =
+        "7346dc9166b67e118f029ab621b2560ff9ca67cca8c7f85ba84c79030c2b3de2
+         18f86db3a90901d5df45c14f26fedfb3dc38e96ac22fe7bd728f0e45bce046d2
+         3c570feb141398bb552ef5a0a82be331fea48037b8b5d71f0e332edf93ac3500
+         eb4ddc0decc1a864790c782c76215660dd309791d06bd0af3f98cda4bc4629b1";
+    const SHATTERED_2_BLOCKS: &str =
+        "7f46dc93a6b67e013b029aaa1db2560b45ca67d688c7f84b8c4c791fe02b3df6
+         14f86db1690901c56b45c1530afedfb76038e972722fe7ad728f0e4904e046c2
+         30570fe9d41398abe12ef5bc942be33542a4802d98b5d70f2a332ec37fac3514
+         e74ddc0f2cc1a874cd0c78305a21566461309789606bd0bf3f98cda8044629a1";
+
+    #[test]
+    fn test_collision_detection_catches_shattered() {
+        let first = [hex(SHATTERED_PREFIX), hex(SHATTERED_1_BLOCKS)].concat();
+        let second = [hex(SHATTERED_PREFIX), hex(SHATTERED_2_BLOCKS)].concat();
+        assert_ne!(first, second);
+        assert_eq!(sha1(&first), sha1(&second));
+        assert_eq!(
+            sha1(&first).to_vec(),
+            hex("f92d74e3874587aaf443d1db961d4e26dde13e9c")
+        );
+
+        for message in [&first, &second] {
+            let mut hasher = Sha1::with_collision_detection();
+            hasher.update(message);
+            assert_eq!(hasher.finalize_checked(), Err(Sha1Error::CollisionDetected));
+
+            // the attack blocks are caught however the input is split up
+            let mut hasher = Sha1::with_collision_detection();
+            for chunk in message.chunks(37) {
+                hasher.update(chunk);
+            }
+            assert_eq!(hasher.finalize_checked(), Err(Sha1Error::CollisionDetected));
+
+            // the prefix on its own is fine, and detection is off unless asked for
+            let mut hasher = Sha1::with_collision_detection();
+            hasher.update(&message[..192]);
+            assert!(hasher.finalize_checked().is_ok());
+
+            let mut hasher = Sha1::new();
+            hasher.update(message);
+            assert_eq!(hasher.finalize_checked(), Ok(sha1(message)));
+        }
+    }
+}
@@ -0,0 +1,30 @@
+pub mod aead;
+pub mod aes;
+pub mod aria;
+pub mod bigint;
+pub mod blake2;
+pub mod blake3;
+pub mod block_cipher;
+pub mod blowfish;
+pub mod camellia;
+pub mod chacha20;
+pub mod ct;
+pub mod curve25519;
+pub mod des;
+pub mod hash;
+pub mod hkdf;
+pub mod hmac;
+pub mod kdf;
+#[cfg(feature = "legacy")]
+pub mod legacy;
+pub mod mac;
+pub mod rsa;
+pub mod salsa20;
+pub mod serpent;
+pub mod sha2;
+pub mod sha3;
+pub mod sm4;
+pub mod twofish;
+
+#[cfg(test)]
+mod test_util;
@@ -0,0 +1,24 @@
+/// the streaming interface shared by the crate's message authentication codes, so that callers
+/// can be written once for HMAC, AES-CMAC and friends
+pub trait Mac: Clone {
+    /// size in bytes of the full tag
+    const OUTPUT_SIZE: usize;
+
+    type Key: ?Sized;
+    type Output: AsRef<[u8]> + Copy;
+
+    fn new(key: &Self::Key) -> Self;
+    fn update(&mut self, data: &[u8]);
+    fn finalize(self) -> Self::Output;
+
+    /// compare the computed tag against `tag` in constant time - only a full `OUTPUT_SIZE` tag
+    /// is accepted, so a short tag can't be used to make forgery easier
+    fn verify(self, tag: &[u8]) -> bool;
+
+    /// one-shot tag of `data`
+    fn mac(key: &Self::Key, data: &[u8]) -> Self::Output {
+        let mut mac = Self::new(key);
+        mac.update(data);
+        mac.finalize()
+    }
+}
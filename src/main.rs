@@ -1,14 +1,39 @@
-pub mod aes;
-
 use std::io::prelude::*;
-use std::{fs::File, io};
+use std::{env, fs::File, io};
 
-use aes::{
+use crypt::aes::{
     aes::*,
     cbc::{cbc_decrypt, cbc_encrypt},
 };
+use crypt::blake3::blake3::Blake3;
+use crypt::block_cipher::BlockCipher;
+
+/// print the BLAKE3 digest of each file, in the same format as `b3sum`
+fn hash_files(paths: &[String]) -> io::Result<()> {
+    for path in paths {
+        let mut data = Vec::new();
+        File::open(path)?.read_to_end(&mut data)?;
+
+        let mut hasher = Blake3::new();
+        hasher.update_parallel(&data);
+
+        let digest: String = hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect();
+        println!("{}  {}", digest, path);
+    }
+
+    Ok(())
+}
 
 fn main() -> io::Result<()> {
+    let args: Vec<String> = env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("hash") {
+        return hash_files(&args[1..]);
+    }
+
     let mut input_file = File::open("src/aes/test-input")?;
     let mut key_file = File::open("src/aes/test-key")?;
 
@@ -19,12 +44,20 @@ fn main() -> io::Result<()> {
     key_file.read_to_end(&mut key_buf)?;
 
     let key: AesKey128 = key_buf.try_into().unwrap();
+    let cipher = Aes128::new(&key);
+
+    let enc_out = cbc_encrypt(&input_buf[..], &cipher);
 
-    let enc_out = cbc_encrypt(&input_buf[..], &key);
+    // the iv is the first block of the output
+    println!("\niv:");
+    for byte in &enc_out[..Aes128::BLOCK_SIZE] {
+        print!("{:02x?}", byte);
+    }
+    println!();
     //println!("{:02x?}", enc_out);
 
     let check_decr = enc_out.clone();
-    let dec_out = cbc_decrypt(key, &check_decr);
+    let _dec_out = cbc_decrypt(&cipher, &check_decr);
 
-    return Ok(());
+    Ok(())
 }
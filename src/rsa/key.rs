@@ -0,0 +1,366 @@
+use super::constant::*;
+use super::prime::generate_prime;
+use super::RsaError;
+use crate::bigint::modular::{gcd_vartime, mod_inverse_vartime, sub_mod, Montgomery};
+use crate::bigint::uint::Uint;
+use rand::{thread_rng, Rng};
+
+/// an RSA public key with a modulus of up to `64 * LIMBS` bits - `U2048`-sized keys are
+/// `RsaPublicKey<32>`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RsaPublicKey<const LIMBS: usize> {
+    n: Uint<LIMBS>,
+    e: Uint<LIMBS>,
+    n_mont: Montgomery<LIMBS>,
+    // the modulus length in bits
+    bits: u32,
+}
+
+impl<const LIMBS: usize> RsaPublicKey<LIMBS> {
+    /// a public key from the big-endian modulus and exponent
+    ///
+    /// the modulus has to be odd and at least `RSA_MIN_MODULUS_BITS` long, and the exponent
+    /// odd and between 3 and the modulus
+    pub fn new(n: &[u8], e: &[u8]) -> Result<Self, RsaError> {
+        let n = Uint::from_be_bytes(n).ok_or(RsaError::InvalidKeySize)?;
+        let e = Uint::from_be_bytes(e).ok_or(RsaError::InvalidKey)?;
+        Self::from_uints(&n, &e)
+    }
+
+    fn from_uints(n: &Uint<LIMBS>, e: &Uint<LIMBS>) -> Result<Self, RsaError> {
+        let bits = n.bits_vartime();
+        if bits < RSA_MIN_MODULUS_BITS {
+            return Err(RsaError::InvalidKeySize);
+        }
+        if !e.is_odd() || *e < Uint::from_u64(3) || e >= n {
+            return Err(RsaError::InvalidKey);
+        }
+
+        Ok(RsaPublicKey {
+            n: *n,
+            e: *e,
+            n_mont: Montgomery::new(n).ok_or(RsaError::InvalidKey)?,
+            bits,
+        })
+    }
+
+    pub fn modulus(&self) -> &Uint<LIMBS> {
+        &self.n
+    }
+
+    pub fn exponent(&self) -> &Uint<LIMBS> {
+        &self.e
+    }
+
+    /// the modulus length in bits
+    pub fn bits(&self) -> u32 {
+        self.bits
+    }
+
+    /// the modulus length in bytes - the size of every ciphertext and signature
+    pub fn size(&self) -> usize {
+        self.bits.div_ceil(8) as usize
+    }
+
+    /// OS2IP - a `size()`-byte string as an integer, `None` if it isn't below the modulus
+    pub(crate) fn os2ip(&self, bytes: &[u8]) -> Option<Uint<LIMBS>> {
+        if bytes.len() != self.size() {
+            return None;
+        }
+        Uint::from_be_bytes(bytes).filter(|x| *x < self.n)
+    }
+
+    /// I2OSP - an integer below the modulus as a `size()`-byte string
+    pub(crate) fn i2osp(&self, x: &Uint<LIMBS>) -> Vec<u8> {
+        x.to_be_bytes_padded(self.size()).unwrap()
+    }
+
+    /// RSAEP / RSAVP1 - `m^e mod n` for `m` below the modulus
+    pub(crate) fn rsaep(&self, m: &Uint<LIMBS>) -> Uint<LIMBS> {
+        self.n_mont.pow_vartime(m, &self.e)
+    }
+}
+
+/// an RSA private key in CRT form - private operations are blinded and checked against the
+/// public key before anything is returned
+#[derive(Clone)]
+pub struct RsaPrivateKey<const LIMBS: usize> {
+    public: RsaPublicKey<LIMBS>,
+    d: Uint<LIMBS>,
+    p: Uint<LIMBS>,
+    q: Uint<LIMBS>,
+    // d mod (p - 1) and d mod (q - 1)
+    dp: Uint<LIMBS>,
+    dq: Uint<LIMBS>,
+    // q^-1 mod p
+    qinv: Uint<LIMBS>,
+    p_mont: Montgomery<LIMBS>,
+    q_mont: Montgomery<LIMBS>,
+}
+
+impl<const LIMBS: usize> RsaPrivateKey<LIMBS> {
+    /// generate a key with a `bits`-bit modulus and the public exponent 65537
+    ///
+    /// `bits` has to be at least `RSA_MIN_MODULUS_BITS` and fit in `LIMBS` - key generation
+    /// takes a while, most of it spent testing candidate primes
+    pub fn generate(bits: u32) -> Result<Self, RsaError> {
+        if bits < RSA_MIN_MODULUS_BITS || bits > Uint::<LIMBS>::BITS {
+            return Err(RsaError::InvalidKeySize);
+        }
+
+        let e = Uint::from_u64(RSA_DEFAULT_EXPONENT);
+        let (p_bits, q_bits) = (bits.div_ceil(2), bits / 2);
+        // a prime with p = 1 mod e would leave e without an inverse mod p - 1
+        let prime = |bits| loop {
+            let p = generate_prime::<LIMBS>(bits);
+            if p.rem_u64(RSA_DEFAULT_EXPONENT) != 1 {
+                break p;
+            }
+        };
+
+        loop {
+            let (p, q) = (prime(p_bits), prime(q_bits));
+
+            // FIPS 186-5 wants p and q to differ somewhere in their top 100 bits, so that n
+            // can't be factored from its square root
+            let diff = if p > q {
+                p.wrapping_sub(&q)
+            } else {
+                q.wrapping_sub(&p)
+            };
+            if diff.bits_vartime() <= q_bits - 100 {
+                continue;
+            }
+
+            let n = p.wrapping_mul(&q);
+            let (p1, q1) = (p.wrapping_sub(&Uint::ONE), q.wrapping_sub(&Uint::ONE));
+            // d is the inverse of e modulo lcm(p - 1, q - 1), the smallest exponent that works
+            let lambda = p1
+                .div_rem_vartime(&gcd_vartime(&p1, &q1))
+                .0
+                .wrapping_mul(&q1);
+            let Some(d) = mod_inverse_vartime(&e, &lambda) else {
+                continue;
+            };
+            // and FIPS 186-5 also rules out a d short enough to be found from n and e
+            if d.bits_vartime() <= bits / 2 {
+                continue;
+            }
+
+            return Self::from_uints(&n, &e, &d, &p, &q);
+        }
+    }
+
+    /// a private key from its big-endian components, checking that they're consistent - the
+    /// CRT values are computed rather than taken from the caller
+    pub fn from_components(
+        n: &[u8],
+        e: &[u8],
+        d: &[u8],
+        p: &[u8],
+        q: &[u8],
+    ) -> Result<Self, RsaError> {
+        let parse = |bytes| Uint::from_be_bytes(bytes).ok_or(RsaError::InvalidKeySize);
+        Self::from_uints(&parse(n)?, &parse(e)?, &parse(d)?, &parse(p)?, &parse(q)?)
+    }
+
+    fn from_uints(
+        n: &Uint<LIMBS>,
+        e: &Uint<LIMBS>,
+        d: &Uint<LIMBS>,
+        p: &Uint<LIMBS>,
+        q: &Uint<LIMBS>,
+    ) -> Result<Self, RsaError> {
+        let public = RsaPublicKey::from_uints(n, e)?;
+        if p.checked_mul(q) != Some(*n) || p == q {
+            return Err(RsaError::InvalidKey);
+        }
+        let p_mont = Montgomery::new(p).ok_or(RsaError::InvalidKey)?;
+        let q_mont = Montgomery::new(q).ok_or(RsaError::InvalidKey)?;
+
+        let (p1, q1) = (p.wrapping_sub(&Uint::ONE), q.wrapping_sub(&Uint::ONE));
+        let (dp, dq) = (d.div_rem_vartime(&p1).1, d.div_rem_vartime(&q1).1);
+        // e * d = 1 modulo both p - 1 and q - 1, which is the same as modulo their lcm
+        for (d, m) in [(&dp, &p1), (&dq, &q1)] {
+            let (lo, hi) = d.widening_mul(e);
+            if Uint::rem_wide_vartime(&lo, &hi, m) != Uint::ONE {
+                return Err(RsaError::InvalidKey);
+            }
+        }
+        let qinv = mod_inverse_vartime(q, p).ok_or(RsaError::InvalidKey)?;
+
+        Ok(RsaPrivateKey {
+            public,
+            d: *d,
+            p: *p,
+            q: *q,
+            dp,
+            dq,
+            qinv,
+            p_mont,
+            q_mont,
+        })
+    }
+
+    pub fn public_key(&self) -> &RsaPublicKey<LIMBS> {
+        &self.public
+    }
+
+    pub fn private_exponent(&self) -> &Uint<LIMBS> {
+        &self.d
+    }
+
+    /// the prime factors of the modulus
+    pub fn primes(&self) -> (&Uint<LIMBS>, &Uint<LIMBS>) {
+        (&self.p, &self.q)
+    }
+
+    // a random value in [1, n)
+    fn random_unit(&self) -> Uint<LIMBS> {
+        let n = &self.public.n;
+        loop {
+            let mut limbs = [0u64; LIMBS];
+            thread_rng().fill(&mut limbs[..]);
+            let r = Uint::from_limbs(limbs) >> (Uint::<LIMBS>::BITS - self.public.bits);
+            if !r.is_zero() && r < *n {
+                break r;
+            }
+        }
+    }
+
+    /// RSADP / RSASP1 - `c^d mod n` for `c` below the modulus
+    ///
+    /// `c` is blinded with a fresh random `r^e` so the exponentiations work on a value the
+    /// caller doesn't know, the two halves are combined with Garner's formula, and the result
+    /// is checked with the public exponent - a faulty CRT result would otherwise give away a
+    /// factor of n
+    pub(crate) fn rsadp(&self, c: &Uint<LIMBS>) -> Result<Uint<LIMBS>, RsaError> {
+        let n_mont = &self.public.n_mont;
+        let n = &self.public.n;
+
+        // r^-1 comes from a variable-time inversion of r * s for a second random s, so the
+        // timing only depends on a product that's discarded
+        let (r, s) = (self.random_unit(), self.random_unit());
+        let rs_inv = mod_inverse_vartime(&n_mont.mul_mod(&r, &s), n).ok_or(RsaError::InvalidKey)?;
+        let r_inv = n_mont.mul_mod(&rs_inv, &s);
+        let blinded = n_mont.mul_mod(c, &self.public.rsaep(&r));
+
+        let (p_bits, q_bits) = (self.p.bits_vartime(), self.q.bits_vartime());
+        let m1 = self.p_mont.pow_bounded(&blinded, &self.dp, p_bits);
+        let m2 = self.q_mont.pow_bounded(&blinded, &self.dq, q_bits);
+        // h = qinv * (m1 - m2) mod p, then m = m2 + h * q
+        let m2_mod_p = self.p_mont.mul_mod(&m2, &Uint::ONE);
+        let h = self
+            .p_mont
+            .mul_mod(&sub_mod(&m1, &m2_mod_p, &self.p), &self.qinv);
+        let m = m2.wrapping_add(&h.wrapping_mul(&self.q));
+
+        let m = n_mont.mul_mod(&m, &r_inv);
+        if !self.public.rsaep(&m).ct_eq(c) {
+            return Err(RsaError::FaultDetected);
+        }
+        Ok(m)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_key::*;
+    use super::*;
+    use crate::bigint::uint::U1024;
+    use crate::test_util::hex;
+
+    #[test]
+    fn test_round_trip() {
+        let key = test_key();
+        let public = key.public_key();
+        assert_eq!(public.bits(), 2048);
+        assert_eq!(public.size(), 256);
+
+        let mut rng_bytes = [0u8; 256];
+        for i in 0..4 {
+            rng_bytes.iter_mut().for_each(|b| *b = thread_rng().gen());
+            rng_bytes[0] = i;
+            let m = public.os2ip(&rng_bytes).unwrap();
+            let c = public.rsaep(&m);
+            assert_eq!(key.rsadp(&c).unwrap(), m);
+        }
+
+        // the private operation agrees with plain exponentiation by d
+        let c = Uint::from_u64(2);
+        assert_eq!(
+            key.rsadp(&c).unwrap(),
+            public.n_mont.pow(&c, key.private_exponent())
+        );
+    }
+
+    #[test]
+    fn test_os2ip_range() {
+        let key = test_key();
+        let public = key.public_key();
+
+        assert!(public.os2ip(&[0u8; 255]).is_none());
+        assert!(public.os2ip(&[0xffu8; 256]).is_none());
+        assert!(public.os2ip(&public.i2osp(&public.n)).is_none());
+        let below = public.n.wrapping_sub(&Uint::ONE);
+        assert_eq!(public.os2ip(&public.i2osp(&below)), Some(below));
+    }
+
+    #[test]
+    fn test_from_components_rejects_inconsistent_keys() {
+        let (n, e, d, p, q) = (hex(N), hex(E), hex(D), hex(P), hex(Q));
+        assert!(RsaPrivateKey::<32>::from_components(&n, &e, &d, &p, &q).is_ok());
+
+        // p and q swapped is still a valid key
+        assert!(RsaPrivateKey::<32>::from_components(&n, &e, &d, &q, &p).is_ok());
+
+        let mut bad_d = d.clone();
+        *bad_d.last_mut().unwrap() ^= 2;
+        assert_eq!(
+            RsaPrivateKey::<32>::from_components(&n, &e, &bad_d, &p, &q).err(),
+            Some(RsaError::InvalidKey)
+        );
+        assert_eq!(
+            RsaPrivateKey::<32>::from_components(&n, &e, &d, &p, &p).err(),
+            Some(RsaError::InvalidKey)
+        );
+        assert_eq!(
+            RsaPrivateKey::<32>::from_components(&n, &[2], &d, &p, &q).err(),
+            Some(RsaError::InvalidKey)
+        );
+        // a 2048-bit modulus doesn't fit 1024-bit limbs
+        assert_eq!(
+            RsaPrivateKey::<16>::from_components(&n, &e, &d, &p, &q).err(),
+            Some(RsaError::InvalidKeySize)
+        );
+        assert_eq!(
+            RsaPublicKey::<32>::new(&n[..100], &e).err(),
+            Some(RsaError::InvalidKeySize)
+        );
+    }
+
+    #[test]
+    fn test_generate() {
+        assert_eq!(
+            RsaPrivateKey::<16>::generate(512).err(),
+            Some(RsaError::InvalidKeySize)
+        );
+        assert_eq!(
+            RsaPrivateKey::<16>::generate(1088).err(),
+            Some(RsaError::InvalidKeySize)
+        );
+
+        let key = RsaPrivateKey::<16>::generate(1024).unwrap();
+        let public = key.public_key();
+        assert_eq!(public.bits(), 1024);
+        assert_eq!(*public.exponent(), U1024::from_u64(65537));
+
+        let (p, q) = key.primes();
+        assert_eq!(p.wrapping_mul(q), *public.modulus());
+        assert_eq!(p.bits_vartime(), 512);
+        assert_eq!(q.bits_vartime(), 512);
+
+        let m = U1024::from_u64(0x1234_5678);
+        assert_eq!(key.rsadp(&public.rsaep(&m)).unwrap(), m);
+    }
+}
@@ -0,0 +1,41 @@
+use crate::hash::HashFunction;
+
+/// MGF1 (RFC 8017 appendix B.2.1) - xor the mask generated from `seed` into `out`, which is how
+/// both OAEP and PSS use it
+pub fn mgf1_xor<H: HashFunction>(seed: &[u8], out: &mut [u8]) {
+    for (counter, chunk) in out.chunks_mut(H::OUTPUT_SIZE).enumerate() {
+        let mut hasher = H::new();
+        hasher.update(seed);
+        hasher.update(&(counter as u32).to_be_bytes());
+        let mask = hasher.finalize();
+
+        for (byte, m) in chunk.iter_mut().zip(mask.as_ref()) {
+            *byte ^= m;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha2::sha256::Sha256;
+    use crate::test_util::hex;
+
+    #[test]
+    fn test_mgf1_sha256() {
+        // checked against an independent implementation
+        let mut mask = [0u8; 50];
+        mgf1_xor::<Sha256>(b"seed", &mut mask);
+        assert_eq!(
+            mask.to_vec(),
+            hex(
+                "336f28a022193939585a1b4edc989f870917f3a5f6ddd16e4fb357084a6bdfc2
+                 73a649427664d03bbb062e456425488416c5"
+            )
+        );
+
+        // xoring the mask in twice gives back the input
+        mgf1_xor::<Sha256>(b"seed", &mut mask);
+        assert_eq!(mask, [0u8; 50]);
+    }
+}
@@ -0,0 +1,42 @@
+pub mod constant;
+pub mod key;
+pub mod mgf1;
+pub mod oaep;
+pub mod pkcs1v15;
+pub mod prime;
+pub mod pss;
+
+#[cfg(test)]
+mod test_key;
+
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RsaError {
+    /// the modulus is below the minimum size, doesn't fit the key type, or is too small for
+    /// the padding scheme and hash
+    InvalidKeySize,
+    /// the key components are malformed or inconsistent with each other
+    InvalidKey,
+    /// the message is too long to be padded into the modulus
+    MessageTooLong,
+    /// the ciphertext couldn't be decrypted - deliberately says nothing about why
+    DecryptionFailed,
+    /// a private key operation gave a result that didn't check out against the public key,
+    /// and was withheld rather than risk leaking a factor of the modulus
+    FaultDetected,
+}
+
+impl fmt::Display for RsaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RsaError::InvalidKeySize => write!(f, "invalid RSA key size"),
+            RsaError::InvalidKey => write!(f, "invalid RSA key"),
+            RsaError::MessageTooLong => write!(f, "message too long for the RSA modulus"),
+            RsaError::DecryptionFailed => write!(f, "RSA decryption failed"),
+            RsaError::FaultDetected => write!(f, "fault detected in RSA private key operation"),
+        }
+    }
+}
+
+impl std::error::Error for RsaError {}
@@ -0,0 +1,242 @@
+use super::key::{RsaPrivateKey, RsaPublicKey};
+use super::mgf1::mgf1_xor;
+use super::RsaError;
+use crate::ct::ct_eq;
+use crate::hash::HashFunction;
+use rand::{thread_rng, Rng};
+
+/// the longest message `oaep_encrypt` takes under `key` with hash `H`
+pub fn oaep_max_message_len<H: HashFunction, const LIMBS: usize>(
+    key: &RsaPublicKey<LIMBS>,
+) -> usize {
+    key.size().saturating_sub(2 * H::OUTPUT_SIZE + 2)
+}
+
+/// RSAES-OAEP encryption (RFC 8017 section 7.1.1), with `H` as both the label hash and the MGF1
+/// hash - the usual way to wrap a short symmetric key for the holder of the private key
+pub fn oaep_encrypt<H: HashFunction, const LIMBS: usize>(
+    key: &RsaPublicKey<LIMBS>,
+    message: &[u8],
+    label: &[u8],
+) -> Result<Vec<u8>, RsaError> {
+    let mut seed = vec![0u8; H::OUTPUT_SIZE];
+    thread_rng().fill(&mut seed[..]);
+    encrypt_with_seed::<H, LIMBS>(key, message, label, &seed)
+}
+
+// the deterministic part of encryption, split out so the tests can fix the seed
+fn encrypt_with_seed<H: HashFunction, const LIMBS: usize>(
+    key: &RsaPublicKey<LIMBS>,
+    message: &[u8],
+    label: &[u8],
+    seed: &[u8],
+) -> Result<Vec<u8>, RsaError> {
+    let (k, h_len) = (key.size(), H::OUTPUT_SIZE);
+    if k < 2 * h_len + 2 {
+        return Err(RsaError::InvalidKeySize);
+    }
+    if message.len() > oaep_max_message_len::<H, LIMBS>(key) {
+        return Err(RsaError::MessageTooLong);
+    }
+
+    // EM = 0x00 || maskedSeed || maskedDB, with DB = lHash || PS || 0x01 || M
+    let mut em = vec![0u8; k];
+    let (masked_seed, db) = em[1..].split_at_mut(h_len);
+    let db_len = db.len();
+    db[..h_len].copy_from_slice(H::digest(label).as_ref());
+    db[db_len - message.len() - 1] = 0x01;
+    db[db_len - message.len()..].copy_from_slice(message);
+
+    masked_seed.copy_from_slice(seed);
+    mgf1_xor::<H>(seed, db);
+    mgf1_xor::<H>(db, masked_seed);
+
+    // the leading zero byte keeps EM below the modulus
+    let m = key.os2ip(&em).unwrap();
+    Ok(key.i2osp(&key.rsaep(&m)))
+}
+
+/// RSAES-OAEP decryption (RFC 8017 section 7.1.2)
+///
+/// every way the padding can be wrong gives the same `DecryptionFailed`, and the checks run in
+/// constant time - telling them apart is Manger's attack
+pub fn oaep_decrypt<H: HashFunction, const LIMBS: usize>(
+    key: &RsaPrivateKey<LIMBS>,
+    ciphertext: &[u8],
+    label: &[u8],
+) -> Result<Vec<u8>, RsaError> {
+    let public = key.public_key();
+    let h_len = H::OUTPUT_SIZE;
+    if public.size() < 2 * h_len + 2 {
+        return Err(RsaError::InvalidKeySize);
+    }
+
+    let c = public.os2ip(ciphertext).ok_or(RsaError::DecryptionFailed)?;
+    let mut em = public.i2osp(&key.rsadp(&c)?);
+
+    let (y, rest) = em.split_first_mut().unwrap();
+    let (masked_seed, db) = rest.split_at_mut(h_len);
+    mgf1_xor::<H>(db, masked_seed);
+    mgf1_xor::<H>(masked_seed, db);
+
+    let (l_hash, padded) = db.split_at(h_len);
+    let mut good = ct_eq(l_hash, H::digest(label).as_ref()) & (*y == 0);
+
+    // the message starts after the first 0x01, and only zeros may come before it - every byte
+    // is looked at however early that is
+    let mut looking = true;
+    let mut index = 0;
+    for (i, &byte) in padded.iter().enumerate() {
+        let (is_zero, is_one) = (byte == 0, byte == 1);
+        index |= (looking & is_one) as usize * i;
+        good &= !looking | is_zero | is_one;
+        looking &= !is_one;
+    }
+    good &= !looking;
+
+    if !std::hint::black_box(good) {
+        return Err(RsaError::DecryptionFailed);
+    }
+    Ok(padded[index + 1..].to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_key::test_key;
+    use super::*;
+    use crate::aes::aes::{Aes128, AesKey128};
+    use crate::aes::cbc::{cbc_decrypt, cbc_encrypt};
+    use crate::block_cipher::BlockCipher;
+    use crate::sha2::sha256::Sha256;
+    use crate::sha2::sha512::Sha512;
+    use crate::test_util::hex;
+
+    const MESSAGE: &[u8] = b"the quick brown fox jumps over the lazy dog";
+
+    const OAEP_EMPTY: &str = "47c86e1829338fef4a6de4d6fd328e83d4bef121b1ffa37ff61178744444714b
+                              2783c156804ccea1cb9d5134443fa6d3e8a6fd7ced97e56c6042646c31ad567c
+                              06f11356e7ad50bd76f16a27fffa231e77fb93e35a45fb2fee3beccbd7c978d5
+                              d658f7c9bc0bd3893e0ebdbada308ab0ca159667c91c7839ffb1bf8b3948c27f
+                              dcd7b39996696753964d6d0a3ba802dd9ed6b8884f39d84597a5fda01c136d3c
+                              765582556cd34fb9005844fb5dac79b4e4ee59e9fe4e9d7aef5e0a2f9f0ef953
+                              e9c7a999fde0cf45b4fe6e33b18283a4d1dbcd22816314e395b86d53acbf787d
+                              8eacbc046a3d3526a1cc27651c337d05714d328bf3824417f7a973c168f2173e";
+    const OAEP_LABEL: &str = "74582cfed3082a18bdb9435412608f9470d450651e3353f05c366c610ed8d7de
+                              be7db4c454fe682b150ea90c0f581ab9f6cd4849c169107af411c0a75ef8a2f5
+                              9ea07d62116e56e6dd3068d5315b0ed78e1de24cf39532c06dfc79766f1bea0f
+                              1fad74a5e08fbfccf8f99de8be540c2918cb5b9f9290703d68eb610ced3241cc
+                              a1d7ea85c197612bcd30e883ad53573c9399047f42bf77215f5a338ffb8c64f0
+                              4bc22cff5a400f7cb42202d1bbfb3c46e3dd67533c4558d72a835b147ec0195b
+                              5b927997977fd036cad20ffd7b17f1188833bda2c6a245499f296f9adaf69eb4
+                              19a4565336591bc571616369c736d307b1afff3e10eed14503a287b3d6ae8398";
+    const OAEP_SHA512_EXT: &str = "203b24203dab8b4dc1bad9106759553f0a98ccc394cd9cb0b3ce16f0b8168f5c
+                                   6a1f832e99dbb9768b8dbe6b5c8e8b1b8f0425726f40a3cb16e9fffccb3d7fcc
+                                   77bf49de00dc7d86c97aa2ea8dd0a8a13061622de91cf5787704ce9623c2bf78
+                                   4541108494ff8ee54a3ce736876ab57c63f630fa4a7fbc6d663da5813d77c1e2
+                                   e452359b2f1661d0930850bc69c711444a88abedbc108fe7457fcf3fef67a21e
+                                   4c946ef59b8e50365212368b751b0914269cf317df908b6500f408004f48531a
+                                   57f222c9e6db50cf665b097751c6688b076446c906f54468bc5c2dc3d96210d4
+                                   f5a92d5691a05d9ada14847c03b9319c567f4bc277fd5d348263fd277e22f58a";
+
+    #[test]
+    fn test_oaep_sha256_fixed_seed() {
+        // checked against an independent implementation, and decrypted by another library
+        let key = test_key();
+        let seed: Vec<u8> = (0..32).collect();
+
+        for (label, expected) in [(&b""[..], OAEP_EMPTY), (b"label", OAEP_LABEL)] {
+            let ciphertext =
+                encrypt_with_seed::<Sha256, 32>(key.public_key(), MESSAGE, label, &seed).unwrap();
+            assert_eq!(ciphertext, hex(expected));
+            assert_eq!(
+                oaep_decrypt::<Sha256, 32>(&key, &ciphertext, label).unwrap(),
+                MESSAGE
+            );
+        }
+    }
+
+    #[test]
+    fn test_oaep_sha512_from_another_library() {
+        let key = test_key();
+        assert_eq!(
+            oaep_decrypt::<Sha512, 32>(&key, &hex(OAEP_SHA512_EXT), b"").unwrap(),
+            b"wrapped by another library"
+        );
+    }
+
+    #[test]
+    fn test_oaep_round_trip_lengths() {
+        let key = test_key();
+        let public = key.public_key();
+        let max = oaep_max_message_len::<Sha256, 32>(public);
+        assert_eq!(max, 190);
+
+        for len in [0, 1, 32, max] {
+            let message: Vec<u8> = (0..len).map(|i| i as u8).collect();
+            let ciphertext = oaep_encrypt::<Sha256, 32>(public, &message, b"").unwrap();
+            assert_eq!(ciphertext.len(), 256);
+            assert_eq!(
+                oaep_decrypt::<Sha256, 32>(&key, &ciphertext, b"").unwrap(),
+                message
+            );
+        }
+
+        assert_eq!(
+            oaep_encrypt::<Sha256, 32>(public, &[0u8; 191], b""),
+            Err(RsaError::MessageTooLong)
+        );
+        // encryption is randomised
+        assert_ne!(
+            oaep_encrypt::<Sha256, 32>(public, MESSAGE, b"").unwrap(),
+            oaep_encrypt::<Sha256, 32>(public, MESSAGE, b"").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_oaep_rejects_bad_ciphertexts() {
+        let key = test_key();
+        let ciphertext = hex(OAEP_LABEL);
+
+        // the wrong label, a corrupted ciphertext, the wrong hash, and inputs that aren't
+        // ciphertexts at all all fail the same way
+        let mut corrupted = ciphertext.clone();
+        corrupted[100] ^= 0x01;
+        let modulus = key.public_key().i2osp(key.public_key().modulus());
+        for (ciphertext, label) in [
+            (&ciphertext[..], &b""[..]),
+            (&corrupted, b"label"),
+            (&ciphertext[1..], b"label"),
+            (&modulus, b"label"),
+            (&[0u8; 256], b"label"),
+        ] {
+            assert_eq!(
+                oaep_decrypt::<Sha256, 32>(&key, ciphertext, label),
+                Err(RsaError::DecryptionFailed)
+            );
+        }
+        assert_eq!(
+            oaep_decrypt::<Sha512, 32>(&key, &ciphertext, b"label"),
+            Err(RsaError::DecryptionFailed)
+        );
+    }
+
+    #[test]
+    fn test_wrap_cbc_key() {
+        // the random AES key behind a CBC ciphertext travels wrapped under the recipient's key
+        let key = test_key();
+        let mut aes_key: AesKey128 = [0u8; 16];
+        thread_rng().fill(&mut aes_key);
+
+        let wrapped = oaep_encrypt::<Sha256, 32>(key.public_key(), &aes_key, b"").unwrap();
+        let ciphertext = cbc_encrypt(MESSAGE, &Aes128::new(&aes_key));
+
+        let unwrapped: AesKey128 = oaep_decrypt::<Sha256, 32>(&key, &wrapped, b"")
+            .unwrap()
+            .try_into()
+            .unwrap();
+        assert_eq!(
+            cbc_decrypt(&Aes128::new(&unwrapped), &ciphertext).unwrap(),
+            MESSAGE
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use super::constant::*;
+use super::key::RsaPublicKey;
+use crate::hash::HashFunction;
+#[cfg(feature = "legacy")]
+use crate::legacy::{md5::Md5, sha1::Sha1};
+use crate::sha2::sha256::{Sha224, Sha256};
+use crate::sha2::sha512::{Sha384, Sha512, Sha512_224, Sha512_256};
+
+// the 0x00 0x01 header, at least eight 0xff bytes and the 0x00 separator
+const MIN_PADDING_LEN: usize = 11;
+
+/// a hash with an ASN.1 DigestInfo encoding, which PKCS#1 v1.5 signatures wrap the digest in
+pub trait Pkcs1v15Digest: HashFunction {
+    /// the DER encoding of the DigestInfo up to the digest itself
+    const DIGEST_INFO_PREFIX: &'static [u8];
+}
+
+macro_rules! digest_info {
+    ($($hash:ty => $prefix:expr),* $(,)?) => {
+        $(
+            impl Pkcs1v15Digest for $hash {
+                const DIGEST_INFO_PREFIX: &'static [u8] = $prefix;
+            }
+        )*
+    };
+}
+
+digest_info!(
+    Sha224 => SHA224_DIGEST_INFO,
+    Sha256 => SHA256_DIGEST_INFO,
+    Sha384 => SHA384_DIGEST_INFO,
+    Sha512 => SHA512_DIGEST_INFO,
+    Sha512_224 => SHA512_224_DIGEST_INFO,
+    Sha512_256 => SHA512_256_DIGEST_INFO,
+);
+
+#[cfg(feature = "legacy")]
+digest_info!(Sha1 => SHA1_DIGEST_INFO, Md5 => MD5_DIGEST_INFO);
+
+/// RSASSA-PKCS1-v1_5 verification (RFC 8017 section 8.2.2), for checking signatures from
+/// systems that predate PSS - there's deliberately no way to make new ones
+///
+/// the expected encoding is built from the message and compared whole, rather than parsing the
+/// signature's DigestInfo, which is where lenient verifiers have let forgeries through
+pub fn pkcs1v15_verify<H: Pkcs1v15Digest, const LIMBS: usize>(
+    key: &RsaPublicKey<LIMBS>,
+    message: &[u8],
+    signature: &[u8],
+) -> bool {
+    let k = key.size();
+    let t_len = H::DIGEST_INFO_PREFIX.len() + H::OUTPUT_SIZE;
+    if k < t_len + MIN_PADDING_LEN {
+        return false;
+    }
+    let Some(s) = key.os2ip(signature) else {
+        return false;
+    };
+
+    // EM = 0x00 || 0x01 || PS || 0x00 || T, with PS all 0xff
+    let mut expected = vec![0xffu8; k];
+    expected[0] = 0x00;
+    expected[1] = 0x01;
+    expected[k - t_len - 1] = 0x00;
+    expected[k - t_len..k - H::OUTPUT_SIZE].copy_from_slice(H::DIGEST_INFO_PREFIX);
+    expected[k - H::OUTPUT_SIZE..].copy_from_slice(H::digest(message).as_ref());
+
+    key.i2osp(&key.rsaep(&s)) == expected
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_key::test_key;
+    use super::*;
+    use crate::test_util::hex;
+
+    const MESSAGE: &[u8] = b"the quick brown fox jumps over the lazy dog";
+
+    const PKCS_SHA256: &str = "98308abe9692623e8062c4554821839eb78756a4043175eeb847b979fff4292e
+                               a4001b30c818efcabd55a1e00ed9b6d5d3d6aaec1ef17458acb42456e7bf6369
+                               407d2ad60bb95cd9062461c3634b68d99f67a97fcd36554c57a758a5688a53c1
+                               9348b6215911e1072a91c0ca8497cfcd6933d8020f571faf8c079b136b6716f6
+                               4d36ba35c6cc8734c673cca9a85576880ea68c03864b88c847259e79f0c2e236
+                               e31db588a1623ee64bdafabef4413dd738a5c692535dc3945c66e6d80206789d
+                               7732c22c93950c6aa79d87d37f90d62ed5d121c2b9b0c009c20edad98da88cca
+                               1701c9d891a8476b9d31f96f62df29345e21b8a458695604d96a1ad569c2fd04";
+    const PKCS_SHA384: &str = "69bc7d414ac21acfe4b329ea60d413a8323852a6a2eb5b821f1aa5105e49613e
+                               8d21c3ce0f2284377d7b5c4edd66a484036669c09f9b40f8e46e21e6aeb33657
+                               cc31597f3f97b893414c64f860715691b9162c2fb3092f745ada5d753f29cb46
+                               bf8c42907c08214af7d1613e6850cd9177b3596548e31898cfdb1818884206c9
+                               f62ad42d10657cbb5aad0115ccc749f99edd9a8513799304062ab1724f8f9e83
+                               da6339dbf35b89ae0d0f7e77eadd79fae1d66cb231e57efea3e3efadb074eae5
+                               4db68b72b820693eba1db5b944f17e9325bdc7307ed38b0e85081b31ed1c70b3
+                               1ea89b133e7d8d3fcf70f2aaa0dd60d8b97ca162f1940ddcbb94b572ff6260c7";
+    #[cfg(feature = "legacy")]
+    const PKCS_SHA1: &str = "11e08cb86c949f4df06fd2673c7c133dc5bd501787b877ee5e61dba112d4af3b
+                             4313ed96d49982555ab475c822c18710b629690ae5f6fa86880fe239935b9935
+                             aedaa3d2b8e74f3dbcdb4afd9d0de027543294b9651ea2326d07a901bb151260
+                             ee6c80fee6766f4c093dc7ab160c510ec61ddee3f50eedd1739a11f20c1155cc
+                             9839341108dee55fdd90d378094f97bac51438bd85b35ec8e438f0ec887ce387
+                             28e54c662a20832b36b28701b5f8d1d8ff5bcdcf61ac442cb6df0a49fa0bbd87
+                             cea1357eec9ac061516b412b4b4231e46c7acb0bd10bdc3db2255f1e60e316da
+                             fe95700cc8578b4ee8bce487920e2c3d25c95b394ca5a44cf44cc09395e11c3e";
+
+    #[test]
+    fn test_pkcs1v15_from_another_library() {
+        let key = test_key();
+        let public = key.public_key();
+
+        assert!(pkcs1v15_verify::<Sha256, 32>(
+            public,
+            MESSAGE,
+            &hex(PKCS_SHA256)
+        ));
+        assert!(pkcs1v15_verify::<Sha384, 32>(
+            public,
+            MESSAGE,
+            &hex(PKCS_SHA384)
+        ));
+
+        // a signature only verifies under the hash it was made with
+        assert!(!pkcs1v15_verify::<Sha384, 32>(
+            public,
+            MESSAGE,
+            &hex(PKCS_SHA256)
+        ));
+        assert!(!pkcs1v15_verify::<Sha512_256, 32>(
+            public,
+            MESSAGE,
+            &hex(PKCS_SHA256)
+        ));
+    }
+
+    #[test]
+    fn test_pkcs1v15_rejects_bad_signatures() {
+        let key = test_key();
+        let public = key.public_key();
+        let signature = hex(PKCS_SHA256);
+
+        assert!(!pkcs1v15_verify::<Sha256, 32>(
+            public,
+            b"the quick brown fox",
+            &signature
+        ));
+        assert!(!pkcs1v15_verify::<Sha256, 32>(
+            public,
+            MESSAGE,
+            &signature[1..]
+        ));
+        for i in [0, 128, 255] {
+            let mut bad = signature.clone();
+            bad[i] ^= 0x80;
+            assert!(!pkcs1v15_verify::<Sha256, 32>(public, MESSAGE, &bad));
+        }
+        let modulus = public.i2osp(public.modulus());
+        assert!(!pkcs1v15_verify::<Sha256, 32>(public, MESSAGE, &modulus));
+    }
+
+    #[cfg(feature = "legacy")]
+    #[test]
+    fn test_pkcs1v15_sha1() {
+        let key = test_key();
+        let public = key.public_key();
+        assert!(pkcs1v15_verify::<Sha1, 32>(
+            public,
+            MESSAGE,
+            &hex(PKCS_SHA1)
+        ));
+        assert!(!pkcs1v15_verify::<Md5, 32>(
+            public,
+            MESSAGE,
+            &hex(PKCS_SHA1)
+        ));
+    }
+}
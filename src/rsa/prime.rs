@@ -0,0 +1,147 @@
+use super::constant::*;
+use crate::bigint::modular::Montgomery;
+use crate::bigint::uint::Uint;
+use rand::{thread_rng, Rng};
+
+// a uniformly random value below 2^bits
+fn random_bits<const LIMBS: usize>(bits: u32) -> Uint<LIMBS> {
+    let mut limbs = [0u64; LIMBS];
+    thread_rng().fill(&mut limbs[..]);
+
+    let value = Uint::from_limbs(limbs);
+    if bits >= Uint::<LIMBS>::BITS {
+        value
+    } else {
+        value.wrapping_sub(&((value >> bits) << bits))
+    }
+}
+
+/// Miller-Rabin with `MILLER_RABIN_ROUNDS` random bases, after trial division by the small
+/// primes - a composite passes with probability at most 4^-rounds
+///
+/// the exponentiations run in constant time, but the trial division and the number of rounds
+/// taken by a composite don't - neither says anything about a candidate that's kept
+pub fn is_probable_prime<const LIMBS: usize>(n: &Uint<LIMBS>) -> bool {
+    for &p in SMALL_PRIMES.iter() {
+        if n.rem_u64(p) == 0 {
+            return *n == Uint::from_u64(p);
+        }
+    }
+    if *n < Uint::from_u64(2) {
+        return false;
+    }
+    if !n.is_odd() {
+        return *n == Uint::from_u64(2);
+    }
+    // anything left below the largest small prime squared has no factor to find
+    let last = SMALL_PRIMES[SMALL_PRIMES.len() - 1];
+    if *n < Uint::from_u64(last * last) {
+        return true;
+    }
+
+    let mont = Montgomery::new(n).unwrap();
+    let bits = n.bits_vartime();
+    let n_minus_one = n.wrapping_sub(&Uint::ONE);
+
+    // n - 1 = d * 2^s with d odd
+    let mut s = 0;
+    while !n_minus_one.bit(s) {
+        s += 1;
+    }
+    let d = n_minus_one >> s;
+
+    'rounds: for _ in 0..MILLER_RABIN_ROUNDS {
+        // a base in [2, 2^(bits-1)), which is below n - 1
+        let a = loop {
+            let a = random_bits::<LIMBS>(bits - 1);
+            if a > Uint::ONE {
+                break a;
+            }
+        };
+
+        let mut x = mont.pow_bounded(&a, &d, bits);
+        if x == Uint::ONE || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..s {
+            x = mont.mul_mod(&x, &x);
+            if x == n_minus_one {
+                continue 'rounds;
+            }
+        }
+        return false;
+    }
+
+    true
+}
+
+/// a random probable prime of exactly `bits` bits, with the top two bits set so that the
+/// product of two such primes has exactly twice as many bits
+///
+/// panics unless `bits` is at least 16 and fits in the integer type
+pub fn generate_prime<const LIMBS: usize>(bits: u32) -> Uint<LIMBS> {
+    assert!(
+        (16..=Uint::<LIMBS>::BITS).contains(&bits),
+        "prime size out of range"
+    );
+
+    loop {
+        let mut candidate = random_bits::<LIMBS>(bits);
+        candidate.set_bit(bits - 1);
+        candidate.set_bit(bits - 2);
+        candidate.set_bit(0);
+
+        if is_probable_prime(&candidate) {
+            return candidate;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::bigint::uint::{U1024, U256};
+
+    #[test]
+    fn test_small_numbers() {
+        let primes: Vec<u64> = (0..3000)
+            .filter(|&n| is_probable_prime(&U256::from_u64(n)))
+            .collect();
+        let expected: Vec<u64> = (0..3000)
+            .filter(|&n| n > 1 && (2..n).take_while(|d| d * d <= n).all(|d| n % d != 0))
+            .collect();
+        assert_eq!(primes, expected);
+    }
+
+    #[test]
+    fn test_known_primes_and_composites() {
+        // 2^255 - 19, 2^521 - 1 and 2^607 - 1 are prime, 2^523 - 1 isn't
+        let mersenne = |p: u32| (U1024::ONE << p).wrapping_sub(&U1024::ONE);
+        assert!(is_probable_prime(
+            &(U1024::ONE << 255).wrapping_sub(&U1024::from_u64(19))
+        ));
+        assert!(is_probable_prime(&mersenne(521)));
+        assert!(is_probable_prime(&mersenne(607)));
+        assert!(!is_probable_prime(&mersenne(523)));
+
+        // Carmichael numbers, all of whose factors are beyond trial division, fool the Fermat
+        // test but not Miller-Rabin
+        for n in [11_346_205_609u64, 13_079_177_569, 21_515_221_081] {
+            assert!(!is_probable_prime(&U256::from_u64(n)));
+        }
+
+        // the product of two large primes has no small factor to give it away
+        let p = (U1024::ONE << 255).wrapping_sub(&U1024::from_u64(19));
+        assert!(!is_probable_prime(&p.wrapping_mul(&mersenne(521))));
+    }
+
+    #[test]
+    fn test_generate_prime() {
+        for bits in [16, 100, 256] {
+            let p = generate_prime::<4>(bits);
+            assert_eq!(p.bits_vartime(), bits);
+            assert!(p.bit(bits - 2));
+            assert!(is_probable_prime(&p));
+        }
+    }
+}
@@ -0,0 +1,220 @@
+use super::key::{RsaPrivateKey, RsaPublicKey};
+use super::mgf1::mgf1_xor;
+use super::RsaError;
+use crate::bigint::uint::Uint;
+use crate::hash::HashFunction;
+use rand::{thread_rng, Rng};
+
+// H(0x00 * 8 || mHash || salt), the hash the encoding commits to
+fn message_hash<H: HashFunction>(message: &[u8], salt: &[u8]) -> H::Output {
+    let mut hasher = H::new();
+    hasher.update(&[0u8; 8]);
+    hasher.update(H::digest(message).as_ref());
+    hasher.update(salt);
+    hasher.finalize()
+}
+
+// the encoded message is one bit shorter than the modulus, so it's always below it
+fn encoded_len<const LIMBS: usize>(key: &RsaPublicKey<LIMBS>) -> (u32, usize) {
+    let em_bits = key.bits() - 1;
+    (em_bits, em_bits.div_ceil(8) as usize)
+}
+
+/// RSASSA-PSS signing (RFC 8017 section 8.1.1) with `H` for both the message hash and MGF1,
+/// and a random salt as long as the hash - the choice RFC 8017 recommends and most verifiers
+/// expect
+pub fn pss_sign<H: HashFunction, const LIMBS: usize>(
+    key: &RsaPrivateKey<LIMBS>,
+    message: &[u8],
+) -> Result<Vec<u8>, RsaError> {
+    let mut salt = vec![0u8; H::OUTPUT_SIZE];
+    thread_rng().fill(&mut salt[..]);
+    sign_with_salt::<H, LIMBS>(key, message, &salt)
+}
+
+// EMSA-PSS encoding then RSASP1 - the salt is a parameter so the tests can fix it
+fn sign_with_salt<H: HashFunction, const LIMBS: usize>(
+    key: &RsaPrivateKey<LIMBS>,
+    message: &[u8],
+    salt: &[u8],
+) -> Result<Vec<u8>, RsaError> {
+    let public = key.public_key();
+    let (em_bits, em_len) = encoded_len(public);
+    let h_len = H::OUTPUT_SIZE;
+    if em_len < h_len + salt.len() + 2 {
+        return Err(RsaError::InvalidKeySize);
+    }
+
+    // EM = maskedDB || H || 0xbc, with DB = PS || 0x01 || salt
+    let h = message_hash::<H>(message, salt);
+    let mut em = vec![0u8; em_len];
+    let (db, rest) = em.split_at_mut(em_len - h_len - 1);
+    let db_len = db.len();
+    db[db_len - salt.len() - 1] = 0x01;
+    db[db_len - salt.len()..].copy_from_slice(salt);
+    mgf1_xor::<H>(h.as_ref(), db);
+    db[0] &= 0xff >> (8 * em_len as u32 - em_bits);
+    rest[..h_len].copy_from_slice(h.as_ref());
+    rest[h_len] = 0xbc;
+
+    let m = Uint::from_be_bytes(&em).unwrap();
+    Ok(public.i2osp(&key.rsadp(&m)?))
+}
+
+/// RSASSA-PSS verification (RFC 8017 section 8.1.2) for signatures made with a `salt_len`-byte
+/// salt - the salt length isn't recoverable from the signature, so the caller has to know it
+pub fn pss_verify<H: HashFunction, const LIMBS: usize>(
+    key: &RsaPublicKey<LIMBS>,
+    message: &[u8],
+    signature: &[u8],
+    salt_len: usize,
+) -> bool {
+    let (em_bits, em_len) = encoded_len(key);
+    let h_len = H::OUTPUT_SIZE;
+    if em_len < h_len + salt_len + 2 {
+        return false;
+    }
+
+    let Some(s) = key.os2ip(signature) else {
+        return false;
+    };
+    let Some(mut em) = key.rsaep(&s).to_be_bytes_padded(em_len) else {
+        return false;
+    };
+    if em[em_len - 1] != 0xbc {
+        return false;
+    }
+
+    let (db, rest) = em.split_at_mut(em_len - h_len - 1);
+    let top_mask = 0xff >> (8 * em_len as u32 - em_bits);
+    if db[0] & !top_mask != 0 {
+        return false;
+    }
+    let h = &rest[..h_len];
+    mgf1_xor::<H>(h, db);
+    db[0] &= top_mask;
+
+    let (padding, salt) = db.split_at(db.len() - salt_len);
+    let (ps, separator) = padding.split_at(padding.len() - 1);
+    if ps.iter().any(|&b| b != 0) || separator[0] != 0x01 {
+        return false;
+    }
+
+    message_hash::<H>(message, salt).as_ref() == h
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::test_key::test_key;
+    use super::*;
+    use crate::sha2::sha256::Sha256;
+    use crate::sha2::sha512::{Sha384, Sha512};
+    use crate::test_util::hex;
+
+    const MESSAGE: &[u8] = b"the quick brown fox jumps over the lazy dog";
+
+    const PSS_KAT: &str = "235505a7a25ab2394501b9d161709182b88f5bf7348226cb65ea59d9ab01bbee
+                           048e0fa957ee1c10b32e7a8a29121b2d8b8587a6e648e2ef331e651f2cd24bf8
+                           b7713759f3046d8cb43a0f73ae48f209b4b8c23095ede5b78f454c1378994bf6
+                           10fab155bf8b7edf520352acd81e813fd0720f85106117d277b9820dea793668
+                           4b0bacf8c0f838201268c326febe625f659214a5d897d557366961b876e1465c
+                           b1031be28993b1a9fff29c024d5fcfebb699d4176145002b73b2486844c82549
+                           32652bb32a142017d1f34ccf8aff93b639f519145b5736271e3f9fbace633d77
+                           e8f45c695b67170d3b6688d684203f3b413d88296a87e991a6d0406f3d39a141";
+    const PSS_EXT_MAX: &str = "1591ac3eabf78a4cb3d92e2604f3ce256a6967cb69bade79d1a426e81665afbb
+                               03b71218c668c87420a9bede9117029969658e13d8f2362ef2e77e32401c2bf9
+                               a07dfcda1ff67c33da28cc30e315429779a911265403f42a56906f6a41c5b23b
+                               fa100fa0189d2c53d2db4cdd5ec800f56213270abbda201eff8f8051f506e44c
+                               6d1f441206158b187f98f4bb30311ebcbc98a0140ea47bcbf535f2abdf609d4f
+                               32bae5a1bf6e44aafa8886ca68cb4219ddb2024be6b8666afda799d36210164b
+                               345ea88580a3cb3e216fb0b2ffdda31a763ec3246d02b1d8d4f78a68e4ff0087
+                               839cfa8131a2e01c20a6e68d08d19b498fda2227ff0147db5a907fd42d080898";
+
+    #[test]
+    fn test_pss_sha256_fixed_salt() {
+        // checked against an independent implementation, and verified by another library
+        let key = test_key();
+        let salt: Vec<u8> = (100..132).collect();
+
+        let signature = sign_with_salt::<Sha256, 32>(&key, MESSAGE, &salt).unwrap();
+        assert_eq!(signature, hex(PSS_KAT));
+        assert!(pss_verify::<Sha256, 32>(
+            key.public_key(),
+            MESSAGE,
+            &signature,
+            32
+        ));
+    }
+
+    #[test]
+    fn test_pss_maximum_salt_from_another_library() {
+        // signed with the longest salt the key allows, 256 - 32 - 2 bytes
+        let key = test_key();
+        let signature = hex(PSS_EXT_MAX);
+        assert!(pss_verify::<Sha256, 32>(
+            key.public_key(),
+            MESSAGE,
+            &signature,
+            222
+        ));
+        assert!(!pss_verify::<Sha256, 32>(
+            key.public_key(),
+            MESSAGE,
+            &signature,
+            32
+        ));
+    }
+
+    #[test]
+    fn test_pss_round_trip() {
+        let key = test_key();
+        let public = key.public_key();
+
+        for message in [&b""[..], MESSAGE, &[0x5a; 1000]] {
+            let signature = pss_sign::<Sha384, 32>(&key, message).unwrap();
+            assert!(pss_verify::<Sha384, 32>(public, message, &signature, 48));
+
+            let signature = pss_sign::<Sha512, 32>(&key, message).unwrap();
+            assert!(pss_verify::<Sha512, 32>(public, message, &signature, 64));
+        }
+
+        // an empty salt makes the signature deterministic
+        let a = sign_with_salt::<Sha256, 32>(&key, MESSAGE, &[]).unwrap();
+        assert_eq!(a, sign_with_salt::<Sha256, 32>(&key, MESSAGE, &[]).unwrap());
+        assert!(pss_verify::<Sha256, 32>(public, MESSAGE, &a, 0));
+
+        assert_eq!(
+            sign_with_salt::<Sha512, 32>(&key, MESSAGE, &[0u8; 200]),
+            Err(RsaError::InvalidKeySize)
+        );
+    }
+
+    #[test]
+    fn test_pss_rejects_bad_signatures() {
+        let key = test_key();
+        let public = key.public_key();
+        let signature = hex(PSS_KAT);
+
+        assert!(!pss_verify::<Sha256, 32>(
+            public,
+            b"the quick brown fox",
+            &signature,
+            32
+        ));
+        assert!(!pss_verify::<Sha512, 32>(public, MESSAGE, &signature, 32));
+        assert!(!pss_verify::<Sha256, 32>(public, MESSAGE, &signature, 31));
+        assert!(!pss_verify::<Sha256, 32>(
+            public,
+            MESSAGE,
+            &signature[1..],
+            32
+        ));
+        for i in [0, 128, 255] {
+            let mut bad = signature.clone();
+            bad[i] ^= 0x01;
+            assert!(!pss_verify::<Sha256, 32>(public, MESSAGE, &bad, 32));
+        }
+        let modulus = public.i2osp(public.modulus());
+        assert!(!pss_verify::<Sha256, 32>(public, MESSAGE, &modulus, 32));
+    }
+}
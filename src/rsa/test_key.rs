@@ -0,0 +1,33 @@
+// a fixed 2048-bit key shared by the RSA tests, generated with an independent implementation
+use super::key::RsaPrivateKey;
+use crate::test_util::hex;
+
+pub const N: &str = "a061fd9f9dac2f9f24b6a348f42bad01237952a7c7fef7d6340f4824b4a7e7d0
+                     c8df68311b87006479aed9d340df9f5cff3109e738a758b7e7fa8f9cad91d2ac
+                     e7b3f6d86043124f20aeeff357704db7f547a7a165cd10670c6228f54f801cb0
+                     18fd71e96284c8df64753466cf858358a91f2a1255ee011ff86eb10a1c051cc4
+                     0c342bfab58ae401d3819862bbcd283b87d078d45297809c496e3cb27e6e4ac6
+                     e410f4f490243b1fe52e8e51ddbcee150262485204bf8f92783dc569f477c01b
+                     07a2ae511b7d3049f1e69c62d5994585c68c4eb96a24f067d5d15e04743cc96f
+                     8608a976a1903e5c9df8fbcdd21bd2afbc7e0b8702bcdf6da3ff436e426d39c5";
+pub const D: &str = "178a527ae15e72cdf1ca51dca5241cfc500be06f2a7a78bf8abc1c7b70bdbd57
+                     86884e0779321f43601de3306ff2f2b5e869f6f966761f9e3cb128ef9705528e
+                     18e3300dd1d13e6879545bf95461f2325850d860a6ebcaa6c96945f83d9f40f6
+                     b2f04e4e326ce97bea878e75b1ab77dc2554a2ecf68a5cc567c63d7b8da1cd77
+                     187c9889a16bef6ab9655560d59a08e5a5e4ac66249555ca1bcb1375bc90c55f
+                     d4cb76397fe49386d76afb078d592f229501e6cfc9d071521df6a69b9dc081d1
+                     ea9f52f048a0aeae08cc6345baf89d2495f09941b8562cd33b72f8a6d5ef5c36
+                     60e751d3cc8a58138039f87d7d32c0d51b373f45759fb6390e55d22ffb696831";
+pub const P: &str = "d66833e123d84d17921028c491e72975c946c8c63f4dfa257866d6be920121eb
+                     eccacd0e0c482836505d6ff71a9cc87ead78eed93909202e6cee9f41305c45df
+                     e83c13763219075481aec18ca4b3892c9bc88a6ca2220ef550f4705fcc6b14f3
+                     4fc358f32e0e970c36d20828c52a29e8eac82a4f7ec9e0d1ff405e6a7fd032d5";
+pub const Q: &str = "bf7edb406eaa85f8b112316952d02dd1e466676ceea946d1e5f9add3b8845764
+                     83239c7cd9b9b7ded9d990238af8ccabf89df575ee74f15e5acbc545e7997b9d
+                     eb5f3ea4d181b81cd1e93954aa0b98c639e5684d751b616b7c5c7d64b6fbc88b
+                     9765bae23984d814969083a833004f2076fd57ed3874bd0d6322449f1e8f0331";
+pub const E: &str = "010001";
+
+pub fn test_key() -> RsaPrivateKey<32> {
+    RsaPrivateKey::from_components(&hex(N), &hex(E), &hex(D), &hex(P), &hex(Q)).unwrap()
+}
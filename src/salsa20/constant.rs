@@ -0,0 +1,18 @@
+pub const KEY_SIZE: usize = 32;
+pub const NONCE_SIZE: usize = 8;
+pub const HNONCE_SIZE: usize = 16;
+pub const XNONCE_SIZE: usize = 24;
+pub const BLOCK_SIZE: usize = 64;
+
+// the 64-bit block counter would allow 2^70 bytes, more than a u64 byte position can address -
+// the keystream stops at the last whole block the position can reach the end of
+pub const MAX_KEYSTREAM_LEN: u64 = u64::MAX - u64::MAX % BLOCK_SIZE as u64;
+
+// "expand 32-byte k" as little-endian words, placed on the diagonal of the state
+pub const SIGMA: [u32; 4] = [0x61707865, 0x3320646e, 0x79622d32, 0x6b206574];
+
+// NaCl's crypto_secretbox works on buffers with this many leading zero bytes in the plaintext,
+// and leaves half as many in the ciphertext ahead of the tag
+pub const SECRETBOX_ZERO_BYTES: usize = 32;
+pub const SECRETBOX_BOX_ZERO_BYTES: usize = 16;
+pub const SECRETBOX_TAG_SIZE: usize = 16;
@@ -0,0 +1,403 @@
+use super::constant::*;
+use std::fmt;
+
+pub type SalsaKey = [u8; KEY_SIZE];
+pub type SalsaNonce = [u8; NONCE_SIZE];
+pub type XSalsaNonce = [u8; XNONCE_SIZE];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SalsaError {
+    /// the byte position would run past the end of the keystream
+    KeystreamExhausted,
+}
+
+impl fmt::Display for SalsaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SalsaError::KeystreamExhausted => write!(f, "salsa20 keystream exhausted"),
+        }
+    }
+}
+
+impl std::error::Error for SalsaError {}
+
+/// apply `rounds` rounds (alternating column and row rounds) to the state in place
+fn rounds(x: &mut [u32; 16], rounds: usize) {
+    for _ in 0..rounds / 2 {
+        for (a, b, c, d) in [
+            // column round
+            (0, 4, 8, 12),
+            (5, 9, 13, 1),
+            (10, 14, 2, 6),
+            (15, 3, 7, 11),
+            // row round
+            (0, 1, 2, 3),
+            (5, 6, 7, 4),
+            (10, 11, 8, 9),
+            (15, 12, 13, 14),
+        ] {
+            x[b] ^= x[a].wrapping_add(x[d]).rotate_left(7);
+            x[c] ^= x[b].wrapping_add(x[a]).rotate_left(9);
+            x[d] ^= x[c].wrapping_add(x[b]).rotate_left(13);
+            x[a] ^= x[d].wrapping_add(x[c]).rotate_left(18);
+        }
+    }
+}
+
+/// the Salsa20 core with `ROUNDS` rounds - the rounds followed by adding the input back in,
+/// applied to a 64-byte block in place
+pub fn salsa20_core<const ROUNDS: usize>(block: &mut [u8; BLOCK_SIZE]) {
+    let mut input = [0u32; 16];
+    for (word, chunk) in input.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+
+    let mut x = input;
+    rounds(&mut x, ROUNDS);
+
+    for ((chunk, x), input) in block.chunks_exact_mut(4).zip(x.iter()).zip(input.iter()) {
+        chunk.copy_from_slice(&x.wrapping_add(*input).to_le_bytes());
+    }
+}
+
+/// the Salsa20/8 core (RFC 7914 section 3) used by scrypt's BlockMix
+pub fn salsa20_8_core(block: &mut [u8; BLOCK_SIZE]) {
+    salsa20_core::<8>(block);
+}
+
+fn initial_state(key: &SalsaKey, input: &[u8; HNONCE_SIZE]) -> [u32; 16] {
+    let word =
+        |bytes: &[u8], i: usize| u32::from_le_bytes(bytes[4 * i..4 * i + 4].try_into().unwrap());
+
+    [
+        SIGMA[0],
+        word(key, 0),
+        word(key, 1),
+        word(key, 2),
+        word(key, 3),
+        SIGMA[1],
+        word(input, 0),
+        word(input, 1),
+        word(input, 2),
+        word(input, 3),
+        SIGMA[2],
+        word(key, 4),
+        word(key, 5),
+        word(key, 6),
+        word(key, 7),
+        SIGMA[3],
+    ]
+}
+
+/// HSalsa20 - derives a subkey from a key and 16-byte input, taking the diagonal and the input
+/// positions of the state after 20 rounds, without the final addition
+pub fn hsalsa20(key: &SalsaKey, input: &[u8; HNONCE_SIZE]) -> SalsaKey {
+    let mut x = initial_state(key, input);
+    rounds(&mut x, 20);
+
+    let mut subkey = [0u8; KEY_SIZE];
+    for (chunk, i) in subkey.chunks_exact_mut(4).zip([0, 5, 10, 15, 6, 7, 8, 9]) {
+        chunk.copy_from_slice(&x[i].to_le_bytes());
+    }
+    subkey
+}
+
+fn xor_into(data: &mut [u8], keystream: &[u8]) {
+    for (d, k) in data.iter_mut().zip(keystream) {
+        *d ^= k;
+    }
+}
+
+/// the Salsa20 stream cipher with `ROUNDS` rounds, a 64-bit nonce and a 64-bit block counter -
+/// encryption and decryption are the same operation
+#[derive(Clone)]
+pub struct Salsa<const ROUNDS: usize> {
+    state: [u32; 16],
+    // byte offset into the keystream, counted from block 0
+    position: u64,
+    // the most recently generated block, kept for calls that end part way through it
+    buffer: [u8; BLOCK_SIZE],
+    buffer_block: Option<u64>,
+}
+
+/// the full 20-round Salsa20
+pub type Salsa20 = Salsa<20>;
+/// the reduced-round Salsa20/12 from the eSTREAM portfolio
+pub type Salsa20_12 = Salsa<12>;
+/// the reduced-round Salsa20/8
+pub type Salsa20_8 = Salsa<8>;
+
+impl<const ROUNDS: usize> Salsa<ROUNDS> {
+    pub fn new(key: &SalsaKey, nonce: &SalsaNonce) -> Self {
+        let mut input = [0u8; HNONCE_SIZE];
+        input[..NONCE_SIZE].copy_from_slice(nonce);
+
+        Salsa {
+            state: initial_state(key, &input),
+            position: 0,
+            buffer: [0u8; BLOCK_SIZE],
+            buffer_block: None,
+        }
+    }
+
+    /// the current keystream position in bytes
+    pub fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// move to an arbitrary keystream byte position
+    pub fn seek(&mut self, position: u64) -> Result<(), SalsaError> {
+        if position > MAX_KEYSTREAM_LEN {
+            return Err(SalsaError::KeystreamExhausted);
+        }
+        self.position = position;
+        Ok(())
+    }
+
+    fn keystream_block(&self, block: u64) -> [u8; BLOCK_SIZE] {
+        let mut state = self.state;
+        state[8] = block as u32;
+        state[9] = (block >> 32) as u32;
+
+        let mut x = state;
+        rounds(&mut x, ROUNDS);
+
+        let mut out = [0u8; BLOCK_SIZE];
+        for ((chunk, x), input) in out.chunks_exact_mut(4).zip(x.iter()).zip(state.iter()) {
+            chunk.copy_from_slice(&x.wrapping_add(*input).to_le_bytes());
+        }
+        out
+    }
+
+    /// xor the keystream into `data`, advancing the position - fails without touching `data` if
+    /// it would run past the end of the keystream
+    pub fn apply_keystream(&mut self, mut data: &mut [u8]) -> Result<(), SalsaError> {
+        match self.position.checked_add(data.len() as u64) {
+            Some(end) if end <= MAX_KEYSTREAM_LEN => {}
+            _ => return Err(SalsaError::KeystreamExhausted),
+        }
+
+        while !data.is_empty() {
+            let block = self.position / BLOCK_SIZE as u64;
+            let offset = (self.position % BLOCK_SIZE as u64) as usize;
+
+            if self.buffer_block != Some(block) {
+                self.buffer = self.keystream_block(block);
+                self.buffer_block = Some(block);
+            }
+
+            let take = (BLOCK_SIZE - offset).min(data.len());
+            xor_into(&mut data[..take], &self.buffer[offset..offset + take]);
+            self.position += take as u64;
+            data = &mut data[take..];
+        }
+
+        Ok(())
+    }
+}
+
+/// XSalsa20 - Salsa20 with a 192-bit nonce, long enough to be picked at random, using a subkey
+/// derived by HSalsa20 from the key and the first 16 bytes of the nonce
+#[derive(Clone)]
+pub struct XSalsa20(Salsa20);
+
+impl XSalsa20 {
+    pub fn new(key: &SalsaKey, nonce: &XSalsaNonce) -> Self {
+        let subkey = hsalsa20(key, nonce[..HNONCE_SIZE].try_into().unwrap());
+        XSalsa20(Salsa20::new(
+            &subkey,
+            nonce[HNONCE_SIZE..].try_into().unwrap(),
+        ))
+    }
+
+    pub fn position(&self) -> u64 {
+        self.0.position()
+    }
+
+    pub fn seek(&mut self, position: u64) -> Result<(), SalsaError> {
+        self.0.seek(position)
+    }
+
+    pub fn apply_keystream(&mut self, data: &mut [u8]) -> Result<(), SalsaError> {
+        self.0.apply_keystream(data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    fn ecrypt_key() -> SalsaKey {
+        let mut key = [0u8; KEY_SIZE];
+        key[0] = 0x80;
+        key
+    }
+
+    #[test]
+    fn test_salsa20_8_core_rfc7914() {
+        // RFC 7914 section 8
+        let mut block: [u8; 64] = hex(
+            "7e879a214f3ec9867ca940e641718f26baee555b8c61c1b50df846116dcd3b1d
+             ee24f319df9b3d8514121e4b5ac5aa3276021d2909c74829edebc68db8b8c25e",
+        )
+        .try_into()
+        .unwrap();
+        salsa20_8_core(&mut block);
+
+        assert_eq!(
+            block.to_vec(),
+            hex(
+                "a41f859c6608cc993b81cacb020cef05044b2181a2fd337dfd7b1c6396682f29
+                 b4393168e3c9e6bcfe6bc5b7a06d96bae424cc102c91745c24ad673dc7618f81"
+            )
+        );
+    }
+
+    #[test]
+    fn test_salsa20_ecrypt_vector() {
+        // eSTREAM test vectors, 256-bit key set 1 vector 0
+        let mut keystream = [0u8; 64];
+        Salsa20::new(&ecrypt_key(), &[0u8; 8])
+            .apply_keystream(&mut keystream)
+            .unwrap();
+        assert_eq!(
+            keystream.to_vec(),
+            hex(
+                "e3be8fdd8beca2e3ea8ef9475b29a6e7003951e1097a5c38d23b7a5fad9f6844
+                 b22c97559e2723c7cbbd3fe4fc8d9a0744652a83e72a9c461876af4d7ef1a117"
+            )
+        );
+    }
+
+    #[test]
+    fn test_reduced_round_variants() {
+        // the same key under 8 and 12 rounds, checked against an independent implementation
+        let mut keystream = [0u8; 64];
+        Salsa20_8::new(&ecrypt_key(), &[0u8; 8])
+            .apply_keystream(&mut keystream)
+            .unwrap();
+        assert_eq!(
+            keystream.to_vec(),
+            hex(
+                "b1f599e9b0d96df436ae31f5ef589565b92d245db5a1d4c7a78e5e8d0146f8a4
+                 9d326c1a3bf50c052c9c8f114dc74972c4469591e31c9ed11927aa9871f38583"
+            )
+        );
+
+        let mut keystream = [0u8; 64];
+        Salsa20_12::new(&ecrypt_key(), &[0u8; 8])
+            .apply_keystream(&mut keystream)
+            .unwrap();
+        assert_eq!(
+            keystream.to_vec(),
+            hex(
+                "afe411ed1c4e07e4d0cde3b33e31ec190fa4cc796a58bafb848ead8d07d02cd2
+                 d4b6f9f30cb0b57007e3733895cc8d1060107975acaeeb689b6cf614ab64a3d6"
+            )
+        );
+    }
+
+    #[test]
+    fn test_seek() {
+        let key: SalsaKey = core::array::from_fn(|i| i as u8);
+        let nonce: SalsaNonce = core::array::from_fn(|i| i as u8);
+
+        let mut full = [0u8; 300];
+        Salsa20::new(&key, &nonce)
+            .apply_keystream(&mut full)
+            .unwrap();
+
+        let mut cipher = Salsa20::new(&key, &nonce);
+        cipher.seek(190).unwrap();
+        let mut part = [0u8; 70];
+        cipher.apply_keystream(&mut part).unwrap();
+        assert_eq!(part[..], full[190..260]);
+        assert_eq!(cipher.position(), 260);
+        assert_eq!(
+            part.to_vec(),
+            hex(
+                "eab380496d4dd3d328cf316a11d235b5867bfc6edf12f72d1ff959f9f2fcf13f
+                 f847fd3eea839b15e1b08f2b4c5d8a1b936527c0ee35acf850fe2b6465015a63
+                 1f7f2740bf07"
+            )
+        );
+    }
+
+    #[test]
+    fn test_keystream_limit() {
+        let mut cipher = Salsa20::new(&[0u8; 32], &[0u8; 8]);
+        assert_eq!(
+            cipher.seek(MAX_KEYSTREAM_LEN + 1),
+            Err(SalsaError::KeystreamExhausted)
+        );
+        assert_eq!(cipher.seek(u64::MAX), Err(SalsaError::KeystreamExhausted));
+
+        // the last block can be used, but not a byte past it - the position never wraps back
+        // to block 0
+        cipher.seek(MAX_KEYSTREAM_LEN - 64).unwrap();
+        let mut data = [0u8; 65];
+        assert_eq!(
+            cipher.apply_keystream(&mut data),
+            Err(SalsaError::KeystreamExhausted)
+        );
+        assert_eq!(data, [0u8; 65]);
+        assert_eq!(cipher.apply_keystream(&mut data[..64]), Ok(()));
+        assert_eq!(cipher.position(), MAX_KEYSTREAM_LEN);
+        assert_eq!(
+            cipher.apply_keystream(&mut data[..1]),
+            Err(SalsaError::KeystreamExhausted)
+        );
+
+        // the last block is the one with counter 2^58 - 1, not block 0 again
+        let mut first = [0u8; 64];
+        Salsa20::new(&[0u8; 32], &[0u8; 8])
+            .apply_keystream(&mut first)
+            .unwrap();
+        assert_ne!(data[..64], first);
+
+        let mut cipher = XSalsa20::new(&[0u8; 32], &[0u8; 24]);
+        assert_eq!(cipher.seek(u64::MAX), Err(SalsaError::KeystreamExhausted));
+        cipher.seek(MAX_KEYSTREAM_LEN).unwrap();
+        assert_eq!(
+            cipher.apply_keystream(&mut [0u8; 1]),
+            Err(SalsaError::KeystreamExhausted)
+        );
+    }
+
+    #[test]
+    fn test_hsalsa20() {
+        // NaCl's core1 test - HSalsa20 of the X25519 shared secret from its box examples
+        let shared: SalsaKey =
+            hex("4a5d9d5ba4ce2de1728e3bf480350f25e07e21c947d19e3376f09b3c1e161742")
+                .try_into()
+                .unwrap();
+        assert_eq!(
+            hsalsa20(&shared, &[0u8; 16]).to_vec(),
+            hex("1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389")
+        );
+    }
+
+    #[test]
+    fn test_xsalsa20() {
+        // checked against an independent implementation - the NaCl secretbox vectors cover
+        // XSalsa20 end to end
+        let key: SalsaKey = core::array::from_fn(|i| i as u8);
+        let nonce: XSalsaNonce = core::array::from_fn(|i| i as u8);
+
+        let mut keystream = [0u8; 100];
+        let mut cipher = XSalsa20::new(&key, &nonce);
+        for chunk in keystream.chunks_mut(33) {
+            cipher.apply_keystream(chunk).unwrap();
+        }
+        assert_eq!(
+            keystream.to_vec(),
+            hex(
+                "7cb660afdd9ec6468f57dd6d2433f93428fd82cd7386c5471a24d8ad2a525b6e
+                 5eff384fc7caa210bb3c8f3e688f4a9752a546df8c253fef17a2679455c7a1e1
+                 83dbf5d545b0f502b98de0997a66ab432341689ff397dc4fbc1f27bd1a6197f5
+                 dc80ff19"
+            )
+        );
+    }
+}
@@ -0,0 +1,191 @@
+use super::constant::*;
+use super::salsa20::{SalsaKey, XSalsa20, XSalsaNonce};
+use crate::aead::AeadError;
+use crate::chacha20::poly1305::{poly1305, Poly1305Key};
+use crate::ct::ct_eq;
+
+// the first 32 bytes of keystream become the Poly1305 key and the message is encrypted with
+// the rest - NaCl gets this by encrypting 32 zero bytes ahead of the message
+fn keyed_stream(key: &SalsaKey, nonce: &XSalsaNonce) -> (XSalsa20, Poly1305Key) {
+    let mut cipher = XSalsa20::new(key, nonce);
+    let mut otk = [0u8; SECRETBOX_ZERO_BYTES];
+    cipher
+        .apply_keystream(&mut otk)
+        .expect("the start of the keystream is always available");
+    (cipher, otk)
+}
+
+/// encrypt `buffer` in place with XSalsa20-Poly1305, returning the tag
+pub fn secretbox_seal_detached(
+    key: &SalsaKey,
+    nonce: &XSalsaNonce,
+    buffer: &mut [u8],
+) -> [u8; SECRETBOX_TAG_SIZE] {
+    let (mut cipher, otk) = keyed_stream(key, nonce);
+    cipher
+        .apply_keystream(buffer)
+        .expect("a buffer can't outrun a fresh keystream");
+    poly1305(&otk, buffer)
+}
+
+/// check `tag` in constant time and only then decrypt `buffer` in place
+pub fn secretbox_open_detached(
+    key: &SalsaKey,
+    nonce: &XSalsaNonce,
+    buffer: &mut [u8],
+    tag: &[u8],
+) -> Result<(), AeadError> {
+    let (mut cipher, otk) = keyed_stream(key, nonce);
+    if !ct_eq(&poly1305(&otk, buffer), tag) {
+        return Err(AeadError::AuthenticationFailed);
+    }
+
+    cipher
+        .apply_keystream(buffer)
+        .expect("a buffer can't outrun a fresh keystream");
+    Ok(())
+}
+
+/// seal to tag || ciphertext - the layout of libsodium's `crypto_secretbox_easy`
+pub fn secretbox_seal(key: &SalsaKey, nonce: &XSalsaNonce, plaintext: &[u8]) -> Vec<u8> {
+    let mut out = vec![0u8; SECRETBOX_TAG_SIZE + plaintext.len()];
+    let (tag, ciphertext) = out.split_at_mut(SECRETBOX_TAG_SIZE);
+    ciphertext.copy_from_slice(plaintext);
+    tag.copy_from_slice(&secretbox_seal_detached(key, nonce, ciphertext));
+    out
+}
+
+/// open tag || ciphertext as produced by `secretbox_seal` or `crypto_secretbox_easy`
+pub fn secretbox_open(
+    key: &SalsaKey,
+    nonce: &XSalsaNonce,
+    boxed: &[u8],
+) -> Result<Vec<u8>, AeadError> {
+    if boxed.len() < SECRETBOX_TAG_SIZE {
+        return Err(AeadError::CiphertextTooShort);
+    }
+
+    let (tag, ciphertext) = boxed.split_at(SECRETBOX_TAG_SIZE);
+    let mut out = ciphertext.to_vec();
+    secretbox_open_detached(key, nonce, &mut out, tag)?;
+    Ok(out)
+}
+
+/// the original NaCl `crypto_secretbox` calling convention - `buffer` holds 32 zero bytes
+/// followed by the message, and comes back as 16 zero bytes, the tag and the ciphertext
+///
+/// panics if `buffer` is shorter than the 32-byte prefix
+pub fn secretbox_seal_padded(key: &SalsaKey, nonce: &XSalsaNonce, buffer: &mut [u8]) {
+    assert!(
+        buffer.len() >= SECRETBOX_ZERO_BYTES,
+        "secretbox buffer is missing its zero prefix"
+    );
+
+    let tag = secretbox_seal_detached(key, nonce, &mut buffer[SECRETBOX_ZERO_BYTES..]);
+    buffer[..SECRETBOX_BOX_ZERO_BYTES].fill(0);
+    buffer[SECRETBOX_BOX_ZERO_BYTES..SECRETBOX_ZERO_BYTES].copy_from_slice(&tag);
+}
+
+/// the inverse of `secretbox_seal_padded` - `buffer` holds 16 zero bytes, the tag and the
+/// ciphertext, and comes back as 32 zero bytes followed by the message
+pub fn secretbox_open_padded(
+    key: &SalsaKey,
+    nonce: &XSalsaNonce,
+    buffer: &mut [u8],
+) -> Result<(), AeadError> {
+    if buffer.len() < SECRETBOX_ZERO_BYTES {
+        return Err(AeadError::CiphertextTooShort);
+    }
+
+    let (prefix, ciphertext) = buffer.split_at_mut(SECRETBOX_ZERO_BYTES);
+    secretbox_open_detached(key, nonce, ciphertext, &prefix[SECRETBOX_BOX_ZERO_BYTES..])?;
+    prefix.fill(0);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    // NaCl's secretbox test - firstkey is the HSalsa20 of the box example's shared secret
+    fn nacl_inputs() -> (SalsaKey, XSalsaNonce, Vec<u8>) {
+        let key = hex("1b27556473e985d462cd51197a9a46c76009549eac6474f206c4ee0844f68389");
+        let nonce = hex("69696ee955b62b73cd62bda875fc73d68219e0036b7a0b37");
+        let message = hex(
+            "be075fc53c81f2d5cf141316ebeb0c7b5228c52a4c62cbd44b66849b64244ffc
+             e5ecbaaf33bd751a1ac728d45e6c61296cdc3c01233561f41db66cce314adb31
+             0e3be8250c46f06dceea3a7fa1348057e2f6556ad6b1318a024a838f21af1fde
+             048977eb48f59ffd4924ca1c60902e52f0a089bc76897040e082f93776384864
+             5e0705",
+        );
+        (key.try_into().unwrap(), nonce.try_into().unwrap(), message)
+    }
+
+    const NACL_BOX: &str = "f3ffc7703f9400e52a7dfb4b3d3305d9
+                            8e993b9f48681273c29650ba32fc76ce48332ea7164d96a4476fb8c531a1186a
+                            c0dfc17c98dce87b4da7f011ec48c97271d2c20f9b928fe2270d6fb863d51738
+                            b48eeee314a7cc8ab932164548e526ae90224368517acfeabd6bb3732bc0e9da
+                            99832b61ca01b6de56244a9e88d5f9b37973f622a43d14a6599b1f654cb45a74
+                            e355a5";
+
+    #[test]
+    fn test_nacl_secretbox_vector() {
+        let (key, nonce, message) = nacl_inputs();
+
+        let boxed = secretbox_seal(&key, &nonce, &message);
+        assert_eq!(boxed, hex(NACL_BOX));
+        assert_eq!(secretbox_open(&key, &nonce, &boxed).unwrap(), message);
+    }
+
+    #[test]
+    fn test_nacl_padded_convention() {
+        let (key, nonce, message) = nacl_inputs();
+
+        let mut buffer = vec![0u8; SECRETBOX_ZERO_BYTES];
+        buffer.extend_from_slice(&message);
+        secretbox_seal_padded(&key, &nonce, &mut buffer);
+
+        assert_eq!(buffer[..SECRETBOX_BOX_ZERO_BYTES], [0u8; 16]);
+        assert_eq!(buffer[SECRETBOX_BOX_ZERO_BYTES..], hex(NACL_BOX)[..]);
+
+        secretbox_open_padded(&key, &nonce, &mut buffer).unwrap();
+        assert_eq!(buffer[..SECRETBOX_ZERO_BYTES], [0u8; 32]);
+        assert_eq!(buffer[SECRETBOX_ZERO_BYTES..], message[..]);
+    }
+
+    #[test]
+    fn test_empty_message() {
+        // checked against an independent implementation
+        let boxed = secretbox_seal(&[0u8; 32], &[0u8; 24], &[]);
+        assert_eq!(boxed, hex("5c8636d9998d194d605ac3ba3cff1512"));
+        assert_eq!(secretbox_open(&[0u8; 32], &[0u8; 24], &boxed).unwrap(), b"");
+    }
+
+    #[test]
+    fn test_rejects_tampering() {
+        let (key, nonce, message) = nacl_inputs();
+        let boxed = secretbox_seal(&key, &nonce, &message);
+
+        for i in [0, 15, 16, boxed.len() - 1] {
+            let mut bad = boxed.clone();
+            bad[i] ^= 0x01;
+            assert_eq!(
+                secretbox_open(&key, &nonce, &bad),
+                Err(AeadError::AuthenticationFailed)
+            );
+        }
+
+        let mut other_nonce = nonce;
+        other_nonce[23] ^= 0x01;
+        assert!(secretbox_open(&key, &other_nonce, &boxed).is_err());
+        assert_eq!(
+            secretbox_open(&key, &nonce, &boxed[..15]),
+            Err(AeadError::CiphertextTooShort)
+        );
+        assert_eq!(
+            secretbox_open_padded(&key, &nonce, &mut [0u8; 31]),
+            Err(AeadError::CiphertextTooShort)
+        );
+    }
+}
@@ -0,0 +1,207 @@
+use super::constant::*;
+use crate::block_cipher::{BlockCipher, KeySizeError};
+
+type State = [u32; 4];
+
+// apply a 4-bit S-box in bitslice mode - bit `i` of the four words forms the nibble at
+// position `i`, with word 0 as its least significant bit
+fn sbox(table: &[u8; 16], x: State) -> State {
+    let mut out = [0u32; 4];
+    for bit in 0..32 {
+        let nibble = (0..4).fold(0, |acc, j| acc | (((x[j] >> bit) & 1) << j));
+        let y = table[nibble as usize] as u32;
+        for (j, word) in out.iter_mut().enumerate() {
+            *word |= ((y >> j) & 1) << bit;
+        }
+    }
+    out
+}
+
+fn linear_transform(mut x: State) -> State {
+    x[0] = x[0].rotate_left(13);
+    x[2] = x[2].rotate_left(3);
+    x[1] ^= x[0] ^ x[2];
+    x[3] ^= x[2] ^ (x[0] << 3);
+    x[1] = x[1].rotate_left(1);
+    x[3] = x[3].rotate_left(7);
+    x[0] ^= x[1] ^ x[3];
+    x[2] ^= x[3] ^ (x[1] << 7);
+    x[0] = x[0].rotate_left(5);
+    x[2] = x[2].rotate_left(22);
+    x
+}
+
+fn inv_linear_transform(mut x: State) -> State {
+    x[2] = x[2].rotate_right(22);
+    x[0] = x[0].rotate_right(5);
+    x[2] ^= x[3] ^ (x[1] << 7);
+    x[0] ^= x[1] ^ x[3];
+    x[3] = x[3].rotate_right(7);
+    x[1] = x[1].rotate_right(1);
+    x[3] ^= x[2] ^ (x[0] << 3);
+    x[1] ^= x[0] ^ x[2];
+    x[2] = x[2].rotate_right(3);
+    x[0] = x[0].rotate_right(13);
+    x
+}
+
+fn xor(x: State, k: &State) -> State {
+    core::array::from_fn(|i| x[i] ^ k[i])
+}
+
+/// the Serpent block cipher - 32 rounds of a bitsliced SPN, for 128, 192 and 256-bit keys
+///
+/// blocks and keys use the little-endian byte order of the NESSIE vectors, libgcrypt and the
+/// Linux kernel - the tables in the original AES submission print each value reversed
+#[derive(Clone)]
+pub struct Serpent {
+    round_keys: [State; NUM_ROUNDS + 1],
+}
+
+impl Serpent {
+    /// the cipher under `key`, or `InvalidLength` unless it is 16, 24 or 32 bytes long - use
+    /// this rather than `new` for keys from outside the program
+    pub fn try_new(key: &[u8]) -> Result<Self, KeySizeError> {
+        if !matches!(key.len(), 16 | 24 | 32) {
+            return Err(KeySizeError::InvalidLength);
+        }
+
+        // short keys are padded to 256 bits with a single one bit followed by zeros
+        let mut padded = [0u8; 32];
+        padded[..key.len()].copy_from_slice(key);
+        if key.len() < 32 {
+            padded[key.len()] = 0x01;
+        }
+
+        let mut w = [0u32; 8 + 4 * (NUM_ROUNDS + 1)];
+        for (word, chunk) in w.iter_mut().zip(padded.chunks_exact(4)) {
+            *word = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+        for i in 0..4 * (NUM_ROUNDS + 1) {
+            w[i + 8] = (w[i] ^ w[i + 3] ^ w[i + 5] ^ w[i + 7] ^ PHI ^ i as u32).rotate_left(11);
+        }
+
+        // round key i is the prekeys 4i to 4i + 3 through S-box (3 - i) mod 8
+        let mut round_keys = [[0u32; 4]; NUM_ROUNDS + 1];
+        for (i, (rk, prekeys)) in round_keys
+            .iter_mut()
+            .zip(w[8..].chunks_exact(4))
+            .enumerate()
+        {
+            *rk = sbox(&SBOXES[(8 + 3 - i % 8) % 8], prekeys.try_into().unwrap());
+        }
+
+        Ok(Serpent { round_keys })
+    }
+}
+
+impl BlockCipher for Serpent {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+
+    /// a 128, 192 or 256-bit key
+    type Key = [u8];
+
+    /// panics unless `key` is 16, 24 or 32 bytes long - see `try_new`
+    fn new(key: &[u8]) -> Self {
+        Self::try_new(key).expect("serpent keys are 16, 24 or 32 bytes")
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let mut x = [0u32; 4];
+        for (word, chunk) in x.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        for (i, rk) in self.round_keys[..NUM_ROUNDS].iter().enumerate() {
+            x = sbox(&SBOXES[i % 8], xor(x, rk));
+            x = if i < NUM_ROUNDS - 1 {
+                linear_transform(x)
+            } else {
+                // the last round replaces the linear transformation with a key addition
+                xor(x, &self.round_keys[NUM_ROUNDS])
+            };
+        }
+
+        for (chunk, word) in block.chunks_exact_mut(4).zip(x) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let mut x = [0u32; 4];
+        for (word, chunk) in x.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_le_bytes(chunk.try_into().unwrap());
+        }
+
+        for (i, rk) in self.round_keys[..NUM_ROUNDS].iter().enumerate().rev() {
+            x = if i < NUM_ROUNDS - 1 {
+                inv_linear_transform(x)
+            } else {
+                xor(x, &self.round_keys[NUM_ROUNDS])
+            };
+            x = xor(sbox(&SBOXES_INV[i % 8], x), rk);
+        }
+
+        for (chunk, word) in block.chunks_exact_mut(4).zip(x) {
+            chunk.copy_from_slice(&word.to_le_bytes());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    fn check(key: &str, ciphertext: &str) {
+        let cipher = Serpent::new(&hex(key));
+        let mut block = [0u8; 16];
+        cipher.encrypt_block(&mut block);
+        assert_eq!(block.to_vec(), hex(ciphertext));
+        cipher.decrypt_block(&mut block);
+        assert_eq!(block, [0u8; 16]);
+    }
+
+    #[test]
+    fn test_nessie_vectors() {
+        // NESSIE Serpent test vectors, zero plaintext
+        check(
+            "00000000000000000000000000000000",
+            "3620b17ae6a993d09618b8768266bae9",
+        );
+        check(
+            "80000000000000000000000000000000",
+            "264e5481eff42a4606abda06c0bfda3d",
+        );
+        check(
+            "0000000000000000000000000000000000000000000000000000000000000000",
+            "49672ba898d98df95019180445491089",
+        );
+    }
+
+    #[test]
+    fn test_192_bit_key() {
+        // checked against an independent implementation
+        let key: Vec<u8> = (0..24).collect();
+        let cipher = Serpent::new(&key);
+
+        let mut block = hex("202122232425262728292a2b2c2d2e2f");
+        cipher.encrypt_block(&mut block);
+        assert_eq!(block, hex("4f490be049156faed16a0d178ad188bb"));
+        cipher.decrypt_block(&mut block);
+        assert_eq!(block, hex("202122232425262728292a2b2c2d2e2f"));
+    }
+
+    #[test]
+    fn test_rejects_other_key_sizes() {
+        for len in [0, 8, 15, 17, 23, 25, 31, 33, 64] {
+            assert_eq!(
+                Serpent::try_new(&vec![0u8; len]).err(),
+                Some(KeySizeError::InvalidLength)
+            );
+        }
+        for len in [16, 24, 32] {
+            assert!(Serpent::try_new(&vec![0u8; len]).is_ok());
+        }
+    }
+}
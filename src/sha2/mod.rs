@@ -0,0 +1,54 @@
+pub mod constant;
+pub mod sha256;
+pub mod sha512;
+
+/// define a public SHA-2 hash type wrapping a shared core with the given initial hash values -
+/// the digest length is taken from the output type, truncating the final state as needed
+macro_rules! sha2_variant {
+    ($(#[$doc:meta])* $name:ident, $core:ident, $init:expr, $block_size:expr, $output:ty) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name($core);
+
+        impl $name {
+            pub fn new() -> Self {
+                $name($core::new($init))
+            }
+
+            pub fn update(&mut self, data: &[u8]) {
+                self.0.update(data);
+            }
+
+            pub fn finalize(self) -> $output {
+                self.0.finalize()
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl HashFunction for $name {
+            const BLOCK_SIZE: usize = $block_size;
+            const OUTPUT_SIZE: usize = std::mem::size_of::<$output>();
+
+            type Output = $output;
+
+            fn new() -> Self {
+                $name::new()
+            }
+
+            fn update(&mut self, data: &[u8]) {
+                $name::update(self, data);
+            }
+
+            fn finalize(self) -> Self::Output {
+                $name::finalize(self)
+            }
+        }
+    };
+}
+
+use sha2_variant;
@@ -0,0 +1,236 @@
+use super::{constant::*, sha2_variant};
+use crate::hash::HashFunction;
+
+pub type Sha256Digest = [u8; 32];
+pub type Sha224Digest = [u8; 28];
+
+fn compress(state: &mut [u32; 8], block: &[u8; SHA256_BLOCK_SIZE]) {
+    // message schedule
+    let mut w = [0u32; 64];
+    for (word, chunk) in w.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_be_bytes(chunk.try_into().unwrap());
+    }
+
+    for t in 16..64 {
+        let s0 = w[t - 15].rotate_right(7) ^ w[t - 15].rotate_right(18) ^ (w[t - 15] >> 3);
+        let s1 = w[t - 2].rotate_right(17) ^ w[t - 2].rotate_right(19) ^ (w[t - 2] >> 10);
+        w[t] = w[t - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[t - 7])
+            .wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+
+    for (k, w) in K256.iter().zip(w.iter()) {
+        let s1 = e.rotate_right(6) ^ e.rotate_right(11) ^ e.rotate_right(25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(*k)
+            .wrapping_add(*w);
+
+        let s0 = a.rotate_right(2) ^ a.rotate_right(13) ^ a.rotate_right(22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *s = s.wrapping_add(v);
+    }
+}
+
+/// the shared SHA-224/SHA-256 state - the two only differ in their initial hash values and in
+/// how much of the final state is output
+#[derive(Clone)]
+struct Sha256Core {
+    state: [u32; 8],
+    buffer: [u8; SHA256_BLOCK_SIZE],
+    buffer_len: usize,
+    // total message length in bytes
+    length: u64,
+}
+
+impl Sha256Core {
+    fn new(init: [u32; 8]) -> Self {
+        Sha256Core {
+            state: init,
+            buffer: [0u8; SHA256_BLOCK_SIZE],
+            buffer_len: 0,
+            length: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.length = self.length.wrapping_add(data.len() as u64);
+
+        // top up a partially filled buffer first
+        if self.buffer_len > 0 {
+            let take = (SHA256_BLOCK_SIZE - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+
+            if self.buffer_len < SHA256_BLOCK_SIZE {
+                return;
+            }
+
+            compress(&mut self.state, &self.buffer);
+            self.buffer_len = 0;
+        }
+
+        let mut blocks = data.chunks_exact(SHA256_BLOCK_SIZE);
+        for block in &mut blocks {
+            compress(&mut self.state, block.try_into().unwrap());
+        }
+
+        let remainder = blocks.remainder();
+        self.buffer[..remainder.len()].copy_from_slice(remainder);
+        self.buffer_len = remainder.len();
+    }
+
+    fn finalize<const N: usize>(mut self) -> [u8; N] {
+        let bit_length = self.length.wrapping_mul(8);
+
+        // append a single 1 bit, then zeros up to the last 8 bytes of a block, which hold the
+        // message length in bits
+        self.buffer[self.buffer_len] = 0x80;
+        self.buffer[self.buffer_len + 1..].fill(0);
+        if self.buffer_len >= SHA256_BLOCK_SIZE - 8 {
+            compress(&mut self.state, &self.buffer);
+            self.buffer.fill(0);
+        }
+
+        self.buffer[SHA256_BLOCK_SIZE - 8..].copy_from_slice(&bit_length.to_be_bytes());
+        compress(&mut self.state, &self.buffer);
+
+        let mut digest = [0u8; 32];
+        for (chunk, word) in digest.chunks_exact_mut(4).zip(self.state.iter()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+
+        digest[..N].try_into().unwrap()
+    }
+}
+
+sha2_variant!(
+    /// SHA-256 (FIPS 180-4) with a streaming `update`/`finalize` interface
+    Sha256,
+    Sha256Core,
+    H256_INIT,
+    SHA256_BLOCK_SIZE,
+    Sha256Digest
+);
+
+sha2_variant!(
+    /// SHA-224 (FIPS 180-4) - SHA-256 with different initial hash values, truncated to 224 bits
+    Sha224,
+    Sha256Core,
+    H224_INIT,
+    SHA256_BLOCK_SIZE,
+    Sha224Digest
+);
+
+/// one-shot SHA-256
+pub fn sha256(data: &[u8]) -> Sha256Digest {
+    Sha256::digest(data)
+}
+
+/// one-shot SHA-224
+pub fn sha224(data: &[u8]) -> Sha224Digest {
+    Sha224::digest(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    const SHORT_MSG: &[u8] = b"abc";
+    const LONG_MSG: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+
+    #[test]
+    fn test_sha256_nist_vectors() {
+        assert_eq!(
+            sha256(SHORT_MSG).to_vec(),
+            hex("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert_eq!(
+            sha256(LONG_MSG).to_vec(),
+            hex("248d6a61d20638b8e5c026930c3e6039a33ce45964ff2167f6ecedd419db06c1")
+        );
+        assert_eq!(
+            sha256(b"").to_vec(),
+            hex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+        );
+    }
+
+    #[test]
+    fn test_sha224_nist_vectors() {
+        assert_eq!(
+            sha224(SHORT_MSG).to_vec(),
+            hex("23097d223405d8228642a477bda255b32aadbce4bda0b3f7e36c9da7")
+        );
+        assert_eq!(
+            sha224(LONG_MSG).to_vec(),
+            hex("75388b16512776cc5dba5da1fd890150b0c6455cb4f58b1952522525")
+        );
+        assert_eq!(
+            sha224(b"").to_vec(),
+            hex("d14a028c2a3a2bc9476102bb288234c415a2b01f828ea62ac5b3e42f")
+        );
+    }
+
+    #[test]
+    fn test_one_million_a() {
+        let mut sha256_hasher = Sha256::new();
+        let mut sha224_hasher = Sha224::new();
+
+        // feed the input in pieces that straddle block boundaries
+        let chunk = [b'a'; 1000];
+        for _ in 0..1000 {
+            sha256_hasher.update(&chunk);
+            sha224_hasher.update(&chunk);
+        }
+
+        assert_eq!(
+            sha256_hasher.finalize().to_vec(),
+            hex("cdc76e5c9914fb9281a1c7e284d73e67f1809a48a497200e046d39ccc7112cd0")
+        );
+        assert_eq!(
+            sha224_hasher.finalize().to_vec(),
+            hex("20794655980c91d8bbb4c1ea97618a4bf03f42581948b2ee4ee7ad67")
+        );
+    }
+
+    #[test]
+    fn test_hash_function_trait() {
+        let mut hasher = <Sha256 as HashFunction>::new();
+        HashFunction::update(&mut hasher, SHORT_MSG);
+        assert_eq!(HashFunction::finalize(hasher), sha256(SHORT_MSG));
+        assert_eq!(Sha256::OUTPUT_SIZE, 32);
+        assert_eq!(Sha224::BLOCK_SIZE, 64);
+    }
+
+    #[test]
+    fn test_streaming_matches_one_shot() {
+        let data: Vec<u8> = (0..300).map(|i| i as u8).collect();
+
+        for split in [0, 1, 55, 56, 63, 64, 65, 128, 299] {
+            let mut hasher = Sha256::new();
+            hasher.update(&data[..split]);
+            hasher.update(&data[split..]);
+            assert_eq!(hasher.finalize(), sha256(&data));
+        }
+    }
+}
@@ -0,0 +1,297 @@
+use super::{constant::*, sha2_variant};
+use crate::hash::HashFunction;
+
+pub type Sha512Digest = [u8; 64];
+pub type Sha384Digest = [u8; 48];
+pub type Sha512_224Digest = [u8; 28];
+pub type Sha512_256Digest = [u8; 32];
+
+fn compress(state: &mut [u64; 8], block: &[u8; SHA512_BLOCK_SIZE]) {
+    // message schedule
+    let mut w = [0u64; 80];
+    for (word, chunk) in w.iter_mut().zip(block.chunks_exact(8)) {
+        *word = u64::from_be_bytes(chunk.try_into().unwrap());
+    }
+
+    for t in 16..80 {
+        let s0 = w[t - 15].rotate_right(1) ^ w[t - 15].rotate_right(8) ^ (w[t - 15] >> 7);
+        let s1 = w[t - 2].rotate_right(19) ^ w[t - 2].rotate_right(61) ^ (w[t - 2] >> 6);
+        w[t] = w[t - 16]
+            .wrapping_add(s0)
+            .wrapping_add(w[t - 7])
+            .wrapping_add(s1);
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = *state;
+
+    for (k, w) in K512.iter().zip(w.iter()) {
+        let s1 = e.rotate_right(14) ^ e.rotate_right(18) ^ e.rotate_right(41);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h
+            .wrapping_add(s1)
+            .wrapping_add(ch)
+            .wrapping_add(*k)
+            .wrapping_add(*w);
+
+        let s0 = a.rotate_right(28) ^ a.rotate_right(34) ^ a.rotate_right(39);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0.wrapping_add(maj);
+
+        h = g;
+        g = f;
+        f = e;
+        e = d.wrapping_add(t1);
+        d = c;
+        c = b;
+        b = a;
+        a = t1.wrapping_add(t2);
+    }
+
+    for (s, v) in state.iter_mut().zip([a, b, c, d, e, f, g, h]) {
+        *s = s.wrapping_add(v);
+    }
+}
+
+/// the shared state of the 64-bit SHA-2 family - SHA-384 and SHA-512/t are SHA-512 with different
+/// initial hash values and a truncated output
+#[derive(Clone)]
+struct Sha512Core {
+    state: [u64; 8],
+    buffer: [u8; SHA512_BLOCK_SIZE],
+    buffer_len: usize,
+    // total message length in bytes - the padding encodes the length in bits as 128 bits
+    length: u128,
+}
+
+impl Sha512Core {
+    fn new(init: [u64; 8]) -> Self {
+        Sha512Core {
+            state: init,
+            buffer: [0u8; SHA512_BLOCK_SIZE],
+            buffer_len: 0,
+            length: 0,
+        }
+    }
+
+    fn update(&mut self, mut data: &[u8]) {
+        self.length = self.length.wrapping_add(data.len() as u128);
+
+        // top up a partially filled buffer first
+        if self.buffer_len > 0 {
+            let take = (SHA512_BLOCK_SIZE - self.buffer_len).min(data.len());
+            self.buffer[self.buffer_len..self.buffer_len + take].copy_from_slice(&data[..take]);
+            self.buffer_len += take;
+            data = &data[take..];
+
+            if self.buffer_len < SHA512_BLOCK_SIZE {
+                return;
+            }
+
+            compress(&mut self.state, &self.buffer);
+            self.buffer_len = 0;
+        }
+
+        let mut blocks = data.chunks_exact(SHA512_BLOCK_SIZE);
+        for block in &mut blocks {
+            compress(&mut self.state, block.try_into().unwrap());
+        }
+
+        let remainder = blocks.remainder();
+        self.buffer[..remainder.len()].copy_from_slice(remainder);
+        self.buffer_len = remainder.len();
+    }
+
+    fn finalize<const N: usize>(mut self) -> [u8; N] {
+        let bit_length = self.length.wrapping_mul(8);
+
+        // append a single 1 bit, then zeros up to the last 16 bytes of a block, which hold the
+        // message length in bits
+        self.buffer[self.buffer_len] = 0x80;
+        self.buffer[self.buffer_len + 1..].fill(0);
+        if self.buffer_len >= SHA512_BLOCK_SIZE - 16 {
+            compress(&mut self.state, &self.buffer);
+            self.buffer.fill(0);
+        }
+
+        self.buffer[SHA512_BLOCK_SIZE - 16..].copy_from_slice(&bit_length.to_be_bytes());
+        compress(&mut self.state, &self.buffer);
+
+        let mut digest = [0u8; 64];
+        for (chunk, word) in digest.chunks_exact_mut(8).zip(self.state.iter()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+
+        digest[..N].try_into().unwrap()
+    }
+}
+
+sha2_variant!(
+    /// SHA-512 (FIPS 180-4) with a streaming `update`/`finalize` interface
+    Sha512,
+    Sha512Core,
+    H512_INIT,
+    SHA512_BLOCK_SIZE,
+    Sha512Digest
+);
+
+sha2_variant!(
+    /// SHA-384 (FIPS 180-4) - SHA-512 with different initial hash values, truncated to 384 bits
+    Sha384,
+    Sha512Core,
+    H384_INIT,
+    SHA512_BLOCK_SIZE,
+    Sha384Digest
+);
+
+sha2_variant!(
+    /// SHA-512/224 (FIPS 180-4) - SHA-512 with its own initial hash values, truncated to 224 bits
+    Sha512_224,
+    Sha512Core,
+    H512_224_INIT,
+    SHA512_BLOCK_SIZE,
+    Sha512_224Digest
+);
+
+sha2_variant!(
+    /// SHA-512/256 (FIPS 180-4) - SHA-512 with its own initial hash values, truncated to 256 bits
+    Sha512_256,
+    Sha512Core,
+    H512_256_INIT,
+    SHA512_BLOCK_SIZE,
+    Sha512_256Digest
+);
+
+/// one-shot SHA-512
+pub fn sha512(data: &[u8]) -> Sha512Digest {
+    Sha512::digest(data)
+}
+
+/// one-shot SHA-384
+pub fn sha384(data: &[u8]) -> Sha384Digest {
+    Sha384::digest(data)
+}
+
+/// one-shot SHA-512/224
+pub fn sha512_224(data: &[u8]) -> Sha512_224Digest {
+    Sha512_224::digest(data)
+}
+
+/// one-shot SHA-512/256
+pub fn sha512_256(data: &[u8]) -> Sha512_256Digest {
+    Sha512_256::digest(data)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    const SHORT_MSG: &[u8] = b"abc";
+    const LONG_MSG: &[u8] = b"abcdefghbcdefghicdefghijdefghijkefghijklfghijklmghijklmnhijklmnoijklmnopjklmnopqklmnopqrlmnopqrsmnopqrstnopqrstu";
+
+    #[test]
+    fn test_sha512_nist_vectors() {
+        assert_eq!(
+            sha512(SHORT_MSG).to_vec(),
+            hex(
+                "ddaf35a193617abacc417349ae20413112e6fa4e89a97ea20a9eeee64b55d39a
+                 2192992a274fc1a836ba3c23a3feebbd454d4423643ce80e2a9ac94fa54ca49f"
+            )
+        );
+        assert_eq!(
+            sha512(LONG_MSG).to_vec(),
+            hex(
+                "8e959b75dae313da8cf4f72814fc143f8f7779c6eb9f7fa17299aeadb6889018
+                 501d289e4900f7e4331b99dec4b5433ac7d329eeb6dd26545e96e55b874be909"
+            )
+        );
+        assert_eq!(
+            sha512(b"").to_vec(),
+            hex(
+                "cf83e1357eefb8bdf1542850d66d8007d620e4050b5715dc83f4a921d36ce9ce
+                 47d0d13c5d85f2b0ff8318d2877eec2f63b931bd47417a81a538327af927da3e"
+            )
+        );
+    }
+
+    #[test]
+    fn test_sha384_nist_vectors() {
+        assert_eq!(
+            sha384(SHORT_MSG).to_vec(),
+            hex(
+                "cb00753f45a35e8bb5a03d699ac65007272c32ab0eded1631a8b605a43ff5bed
+                 8086072ba1e7cc2358baeca134c825a7"
+            )
+        );
+        assert_eq!(
+            sha384(LONG_MSG).to_vec(),
+            hex(
+                "09330c33f71147e83d192fc782cd1b4753111b173b3b05d22fa08086e3b0f712
+                 fcc7c71a557e2db966c3e9fa91746039"
+            )
+        );
+    }
+
+    #[test]
+    fn test_sha512_224_nist_vectors() {
+        assert_eq!(
+            sha512_224(SHORT_MSG).to_vec(),
+            hex("4634270f707b6a54daae7530460842e20e37ed265ceee9a43e8924aa")
+        );
+        assert_eq!(
+            sha512_224(LONG_MSG).to_vec(),
+            hex("23fec5bb94d60b23308192640b0c453335d664734fe40e7268674af9")
+        );
+    }
+
+    #[test]
+    fn test_sha512_256_nist_vectors() {
+        assert_eq!(
+            sha512_256(SHORT_MSG).to_vec(),
+            hex("53048e2681941ef99b2e29b76b4c7dabe4c2d0c634fc6d46e0e2f13107e7af23")
+        );
+        assert_eq!(
+            sha512_256(LONG_MSG).to_vec(),
+            hex("3928e184fb8690f840da3988121d31be65cb9d3ef83ee6146feac861e19b563a")
+        );
+    }
+
+    #[test]
+    fn test_one_million_a() {
+        let mut sha512_hasher = Sha512::new();
+        let mut sha384_hasher = Sha384::new();
+
+        let chunk = [b'a'; 1000];
+        for _ in 0..1000 {
+            sha512_hasher.update(&chunk);
+            sha384_hasher.update(&chunk);
+        }
+
+        assert_eq!(
+            sha512_hasher.finalize().to_vec(),
+            hex(
+                "e718483d0ce769644e2e42c7bc15b4638e1f98b13b2044285632a803afa973eb
+                 de0ff244877ea60a4cb0432ce577c31beb009c5c2c49aa2e4eadb217ad8cc09b"
+            )
+        );
+        assert_eq!(
+            sha384_hasher.finalize().to_vec(),
+            hex(
+                "9d0e1809716474cb086e834e310a4a1ced149e9c00f248527972cec5704c2a5b
+                 07b8b3dc38ecc4ebae97ddd87f3d8985"
+            )
+        );
+    }
+
+    #[test]
+    fn test_streaming_matches_one_shot() {
+        let data: Vec<u8> = (0..600).map(|i| i as u8).collect();
+
+        for split in [0, 1, 111, 112, 127, 128, 129, 256, 599] {
+            let mut hasher = Sha512::new();
+            hasher.update(&data[..split]);
+            hasher.update(&data[split..]);
+            assert_eq!(hasher.finalize(), sha512(&data));
+        }
+    }
+}
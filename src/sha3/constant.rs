@@ -0,0 +1,56 @@
+pub const KECCAK_ROUNDS: usize = 24;
+// width of the Keccak-f[1600] state in bytes
+pub const KECCAK_STATE_BYTES: usize = 200;
+
+// domain separation suffixes, including the first bit of the pad10*1 padding
+pub const SHA3_DOMAIN: u8 = 0x06;
+pub const SHAKE_DOMAIN: u8 = 0x1f;
+pub const CSHAKE_DOMAIN: u8 = 0x04;
+
+// rate in bytes for each capacity - r = 200 - 2 * (security level in bytes)
+pub const RATE_128: usize = 168;
+pub const RATE_224: usize = 144;
+pub const RATE_256: usize = 136;
+pub const RATE_384: usize = 104;
+pub const RATE_512: usize = 72;
+
+// tag lengths `verify` expects - twice the security level, as for the SHA-3 digests
+pub const KMAC128_TAG_SIZE: usize = 32;
+pub const KMAC256_TAG_SIZE: usize = 64;
+
+// iota step round constants
+pub const ROUND_CONSTANTS: [u64; KECCAK_ROUNDS] = [
+    0x0000000000000001,
+    0x0000000000008082,
+    0x800000000000808a,
+    0x8000000080008000,
+    0x000000000000808b,
+    0x0000000080000001,
+    0x8000000080008081,
+    0x8000000000008009,
+    0x000000000000008a,
+    0x0000000000000088,
+    0x0000000080008009,
+    0x000000008000000a,
+    0x000000008000808b,
+    0x800000000000008b,
+    0x8000000000008089,
+    0x8000000000008003,
+    0x8000000000008002,
+    0x8000000000000080,
+    0x000000000000800a,
+    0x800000008000000a,
+    0x8000000080008081,
+    0x8000000000008080,
+    0x0000000080000001,
+    0x8000000080008008,
+];
+
+// rho step rotation offsets, indexed by lane x + 5y
+pub const RHO_OFFSETS: [u32; 25] = [
+    0, 1, 62, 28, 27, //
+    36, 44, 6, 55, 20, //
+    3, 10, 43, 25, 39, //
+    41, 45, 15, 21, 8, //
+    18, 2, 61, 56, 14, //
+];
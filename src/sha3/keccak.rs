@@ -0,0 +1,141 @@
+use super::constant::*;
+
+/// the Keccak-f[1600] permutation over 25 64-bit lanes, indexed x + 5y
+pub fn keccak_f1600(state: &mut [u64; 25]) {
+    for rc in ROUND_CONSTANTS {
+        // theta
+        let mut c = [0u64; 5];
+        for x in 0..5 {
+            c[x] = state[x] ^ state[x + 5] ^ state[x + 10] ^ state[x + 15] ^ state[x + 20];
+        }
+        for x in 0..5 {
+            let d = c[(x + 4) % 5] ^ c[(x + 1) % 5].rotate_left(1);
+            for y in 0..5 {
+                state[x + 5 * y] ^= d;
+            }
+        }
+
+        // rho and pi
+        let mut b = [0u64; 25];
+        for x in 0..5 {
+            for y in 0..5 {
+                b[y + 5 * ((2 * x + 3 * y) % 5)] =
+                    state[x + 5 * y].rotate_left(RHO_OFFSETS[x + 5 * y]);
+            }
+        }
+
+        // chi
+        for y in 0..5 {
+            for x in 0..5 {
+                state[x + 5 * y] =
+                    b[x + 5 * y] ^ (!b[(x + 1) % 5 + 5 * y] & b[(x + 2) % 5 + 5 * y]);
+            }
+        }
+
+        // iota
+        state[0] ^= rc;
+    }
+}
+
+/// a Keccak sponge with a byte-oriented absorb/squeeze interface
+#[derive(Clone)]
+pub struct KeccakSponge {
+    state: [u64; 25],
+    rate: usize,
+    domain: u8,
+    // byte offset into the current rate-sized block, for both absorbing and squeezing
+    offset: usize,
+    squeezing: bool,
+}
+
+impl KeccakSponge {
+    /// `rate` is in bytes and `domain` holds the domain separation bits along with the first
+    /// bit of the padding
+    pub fn new(rate: usize, domain: u8) -> Self {
+        assert!(rate > 0 && rate < KECCAK_STATE_BYTES && rate.is_multiple_of(8));
+
+        KeccakSponge {
+            state: [0u64; 25],
+            rate,
+            domain,
+            offset: 0,
+            squeezing: false,
+        }
+    }
+
+    pub fn rate(&self) -> usize {
+        self.rate
+    }
+
+    fn xor_byte(&mut self, index: usize, byte: u8) {
+        self.state[index / 8] ^= (byte as u64) << (8 * (index % 8));
+    }
+
+    fn get_byte(&self, index: usize) -> u8 {
+        (self.state[index / 8] >> (8 * (index % 8))) as u8
+    }
+
+    pub fn absorb(&mut self, data: &[u8]) {
+        assert!(!self.squeezing, "cannot absorb after squeezing has started");
+
+        for &byte in data {
+            self.xor_byte(self.offset, byte);
+            self.offset += 1;
+
+            if self.offset == self.rate {
+                keccak_f1600(&mut self.state);
+                self.offset = 0;
+            }
+        }
+    }
+
+    fn pad(&mut self) {
+        // pad10*1 - the domain byte carries the leading 1 bit, the final 1 bit is the top bit
+        // of the last byte of the block
+        self.xor_byte(self.offset, self.domain);
+        self.xor_byte(self.rate - 1, 0x80);
+        keccak_f1600(&mut self.state);
+
+        self.offset = 0;
+        self.squeezing = true;
+    }
+
+    /// fill `out` with output - can be called repeatedly to read a longer output in pieces
+    pub fn squeeze(&mut self, out: &mut [u8]) {
+        if !self.squeezing {
+            self.pad();
+        }
+
+        for byte in out.iter_mut() {
+            if self.offset == self.rate {
+                keccak_f1600(&mut self.state);
+                self.offset = 0;
+            }
+
+            *byte = self.get_byte(self.offset);
+            self.offset += 1;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keccak_f1600_zero_state() {
+        // first lanes of Keccak-f[1600] applied once and twice to the all-zero state, from the
+        // Keccak team's KeccakF-1600-IntermediateValues.txt
+        let mut state = [0u64; 25];
+
+        keccak_f1600(&mut state);
+        assert_eq!(state[0], 0xf1258f7940e1dde7);
+        assert_eq!(state[1], 0x84d5ccf933c0478a);
+        assert_eq!(state[24], 0xeaf1ff7b5ceca249);
+
+        keccak_f1600(&mut state);
+        assert_eq!(state[0], 0x2d5c954df96ecb3c);
+        assert_eq!(state[1], 0x6a332cd07057b56d);
+        assert_eq!(state[24], 0x20d06cd26a8fbf5c);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod constant;
+pub mod keccak;
+#[allow(clippy::module_inception)]
+pub mod sha3;
+pub mod sp800_185;
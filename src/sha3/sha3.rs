@@ -0,0 +1,289 @@
+use super::{constant::*, keccak::KeccakSponge};
+use crate::hash::{HashFunction, XofReader};
+
+/// define a fixed-output SHA-3 hash over the Keccak sponge with the given rate
+macro_rules! sha3_variant {
+    ($(#[$doc:meta])* $name:ident, $rate:expr, $output_size:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name(KeccakSponge);
+
+        impl $name {
+            pub fn new() -> Self {
+                $name(KeccakSponge::new($rate, SHA3_DOMAIN))
+            }
+
+            pub fn update(&mut self, data: &[u8]) {
+                self.0.absorb(data);
+            }
+
+            pub fn finalize(mut self) -> [u8; $output_size] {
+                let mut digest = [0u8; $output_size];
+                self.0.squeeze(&mut digest);
+                digest
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl HashFunction for $name {
+            const BLOCK_SIZE: usize = $rate;
+            const OUTPUT_SIZE: usize = $output_size;
+
+            type Output = [u8; $output_size];
+
+            fn new() -> Self {
+                $name::new()
+            }
+
+            fn update(&mut self, data: &[u8]) {
+                $name::update(self, data);
+            }
+
+            fn finalize(self) -> Self::Output {
+                $name::finalize(self)
+            }
+        }
+    };
+}
+
+sha3_variant!(
+    /// SHA3-224 (FIPS 202)
+    Sha3_224,
+    RATE_224,
+    28
+);
+sha3_variant!(
+    /// SHA3-256 (FIPS 202)
+    Sha3_256,
+    RATE_256,
+    32
+);
+sha3_variant!(
+    /// SHA3-384 (FIPS 202)
+    Sha3_384,
+    RATE_384,
+    48
+);
+sha3_variant!(
+    /// SHA3-512 (FIPS 202)
+    Sha3_512,
+    RATE_512,
+    64
+);
+
+/// squeezes output from a finalized sponge - each `read` continues where the last one stopped
+#[derive(Clone)]
+pub struct KeccakReader(KeccakSponge);
+
+impl KeccakReader {
+    pub(crate) fn new(sponge: KeccakSponge) -> Self {
+        KeccakReader(sponge)
+    }
+}
+
+impl XofReader for KeccakReader {
+    fn read(&mut self, out: &mut [u8]) {
+        self.0.squeeze(out);
+    }
+}
+
+/// define a SHAKE extendable-output function over the Keccak sponge with the given rate
+macro_rules! shake_variant {
+    ($(#[$doc:meta])* $name:ident, $rate:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name(KeccakSponge);
+
+        impl $name {
+            pub fn new() -> Self {
+                $name(KeccakSponge::new($rate, SHAKE_DOMAIN))
+            }
+
+            pub fn update(&mut self, data: &[u8]) {
+                self.0.absorb(data);
+            }
+
+            /// finish absorbing and return a reader over the unbounded output
+            pub fn finalize_xof(self) -> KeccakReader {
+                KeccakReader::new(self.0)
+            }
+
+            /// finish absorbing and fill `out` with the first `out.len()` bytes of output
+            pub fn finalize_into(self, out: &mut [u8]) {
+                self.finalize_xof().read(out);
+            }
+        }
+
+        impl Default for $name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+    };
+}
+
+shake_variant!(
+    /// SHAKE128 (FIPS 202)
+    Shake128,
+    RATE_128
+);
+shake_variant!(
+    /// SHAKE256 (FIPS 202)
+    Shake256,
+    RATE_256
+);
+
+/// one-shot SHA3-224
+pub fn sha3_224(data: &[u8]) -> [u8; 28] {
+    Sha3_224::digest(data)
+}
+
+/// one-shot SHA3-256
+pub fn sha3_256(data: &[u8]) -> [u8; 32] {
+    Sha3_256::digest(data)
+}
+
+/// one-shot SHA3-384
+pub fn sha3_384(data: &[u8]) -> [u8; 48] {
+    Sha3_384::digest(data)
+}
+
+/// one-shot SHA3-512
+pub fn sha3_512(data: &[u8]) -> [u8; 64] {
+    Sha3_512::digest(data)
+}
+
+/// one-shot SHAKE128 with `output_len` bytes of output
+pub fn shake128(data: &[u8], output_len: usize) -> Vec<u8> {
+    let mut shake = Shake128::new();
+    shake.update(data);
+
+    let mut output = vec![0u8; output_len];
+    shake.finalize_into(&mut output);
+    output
+}
+
+/// one-shot SHAKE256 with `output_len` bytes of output
+pub fn shake256(data: &[u8], output_len: usize) -> Vec<u8> {
+    let mut shake = Shake256::new();
+    shake.update(data);
+
+    let mut output = vec![0u8; output_len];
+    shake.finalize_into(&mut output);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    const SHORT_MSG: &[u8] = b"abc";
+    const LONG_MSG: &[u8] = b"abcdbcdecdefdefgefghfghighijhijkijkljklmklmnlmnomnopnopq";
+
+    #[test]
+    fn test_sha3_fips202_vectors() {
+        assert_eq!(
+            sha3_224(b"").to_vec(),
+            hex("6b4e03423667dbb73b6e15454f0eb1abd4597f9a1b078e3f5b5a6bc7")
+        );
+        assert_eq!(
+            sha3_224(SHORT_MSG).to_vec(),
+            hex("e642824c3f8cf24ad09234ee7d3c766fc9a3a5168d0c94ad73b46fdf")
+        );
+        assert_eq!(
+            sha3_256(SHORT_MSG).to_vec(),
+            hex("3a985da74fe225b2045c172d6bd390bd855f086e3e9d525b46bfe24511431532")
+        );
+        assert_eq!(
+            sha3_256(LONG_MSG).to_vec(),
+            hex("41c0dba2a9d6240849100376a8235e2c82e1b9998a999e21db32dd97496d3376")
+        );
+        assert_eq!(
+            sha3_384(SHORT_MSG).to_vec(),
+            hex(
+                "ec01498288516fc926459f58e2c6ad8df9b473cb0fc08c2596da7cf0e49be4b2
+                 98d88cea927ac7f539f1edf228376d25"
+            )
+        );
+        assert_eq!(
+            sha3_512(SHORT_MSG).to_vec(),
+            hex(
+                "b751850b1a57168a5693cd924b6b096e08f621827444f70d884f5d0240d2712e
+                 10e116e9192af3c91a7ec57647e3934057340b4cf408d5a56592f8274eec53f0"
+            )
+        );
+        assert_eq!(
+            sha3_512(LONG_MSG).to_vec(),
+            hex(
+                "04a371e84ecfb5b8b77cb48610fca8182dd457ce6f326a0fd3d7ec2f1e91636d
+                 ee691fbe0c985302ba1b0d8dc78c086346b533b49c030d99a27daf1139d6e75e"
+            )
+        );
+    }
+
+    #[test]
+    fn test_sha3_one_million_a() {
+        let mut hasher = Sha3_256::new();
+        let chunk = [b'a'; 1000];
+        for _ in 0..1000 {
+            hasher.update(&chunk);
+        }
+
+        assert_eq!(
+            hasher.finalize().to_vec(),
+            hex("5c8875ae474a3634ba4fd55ec85bffd661f32aca75c6d699d0cdcb6c115891c1")
+        );
+    }
+
+    #[test]
+    fn test_shake_vectors() {
+        assert_eq!(
+            shake128(b"", 32),
+            hex("7f9c2ba4e88f827d616045507605853ed73b8093f6efbc88eb1a6eacfa66ef26")
+        );
+        assert_eq!(
+            shake256(b"", 64),
+            hex(
+                "46b9dd2b0ba88d13233b3feb743eeb243fcd52ea62b81b82b50c27646ed5762f
+                 d75dc4ddd8c0f200cb05019d67b592f6fc821c49479ab48640292eacb3b7c4be"
+            )
+        );
+
+        // long outputs span several squeeze blocks
+        assert_eq!(
+            shake128(SHORT_MSG, 200)[168..],
+            hex("6aa01b3f5af057805f973ff8ecb8b226ac32ada6f01c1fcd4818cb006aa5b4cd")
+        );
+
+        let input: Vec<u8> = (0..768).map(|i| i as u8).collect();
+        assert_eq!(
+            shake256(&input, 500)[468..],
+            hex("4a8ce135b27316b4ee2b2d59660279f02de7f8e3bfc80f386f92df574f0d6d46")
+        );
+    }
+
+    #[test]
+    fn test_shake_repeated_reads() {
+        let expected = shake128(SHORT_MSG, 400);
+
+        let mut shake = Shake128::new();
+        shake.update(SHORT_MSG);
+        let mut reader = shake.finalize_xof();
+
+        // uneven reads that cross block boundaries must join up into the same stream
+        let mut output = Vec::new();
+        for len in [1, 30, 137, 168, 64] {
+            let mut chunk = vec![0u8; len];
+            reader.read(&mut chunk);
+            output.extend_from_slice(&chunk);
+        }
+
+        assert_eq!(output, expected);
+    }
+}
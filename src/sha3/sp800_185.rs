@@ -0,0 +1,300 @@
+use super::{constant::*, keccak::KeccakSponge, sha3::KeccakReader};
+use crate::{ct::ct_eq, hash::XofReader};
+
+/// `left_encode` from SP 800-185 section 2.3.1 - the byte count followed by `x` big-endian
+pub fn left_encode(x: u64) -> Vec<u8> {
+    let bytes = x.to_be_bytes();
+    let skip = bytes.iter().take_while(|&&b| b == 0).count().min(7);
+
+    let mut encoded = vec![(8 - skip) as u8];
+    encoded.extend_from_slice(&bytes[skip..]);
+    encoded
+}
+
+/// `right_encode` from SP 800-185 section 2.3.1 - `x` big-endian followed by the byte count
+pub fn right_encode(x: u64) -> Vec<u8> {
+    let bytes = x.to_be_bytes();
+    let skip = bytes.iter().take_while(|&&b| b == 0).count().min(7);
+
+    let mut encoded = bytes[skip..].to_vec();
+    encoded.push((8 - skip) as u8);
+    encoded
+}
+
+/// `encode_string` from SP 800-185 section 2.3.2 - the bit length of `s` followed by `s`
+pub fn encode_string(s: &[u8]) -> Vec<u8> {
+    let mut encoded = left_encode(8 * s.len() as u64);
+    encoded.extend_from_slice(s);
+    encoded
+}
+
+/// absorb `bytepad(encode_string(a) || encode_string(b) || ..., w)` without building it in memory
+fn absorb_bytepad(sponge: &mut KeccakSponge, strings: &[&[u8]]) {
+    let w = sponge.rate();
+    let prefix = left_encode(w as u64);
+    sponge.absorb(&prefix);
+
+    let mut written = prefix.len();
+    for s in strings {
+        let encoded = encode_string(s);
+        sponge.absorb(&encoded);
+        written += encoded.len();
+    }
+
+    let padding = (w - written % w) % w;
+    sponge.absorb(&vec![0u8; padding]);
+}
+
+/// cSHAKE with a function name `n` and customization string `s` (SP 800-185 section 3) - with
+/// both empty it is exactly SHAKE
+fn cshake_sponge(rate: usize, n: &[u8], s: &[u8]) -> KeccakSponge {
+    if n.is_empty() && s.is_empty() {
+        return KeccakSponge::new(rate, SHAKE_DOMAIN);
+    }
+
+    let mut sponge = KeccakSponge::new(rate, CSHAKE_DOMAIN);
+    absorb_bytepad(&mut sponge, &[n, s]);
+    sponge
+}
+
+/// define a cSHAKE variant with the given rate
+macro_rules! cshake_variant {
+    ($(#[$doc:meta])* $name:ident, $rate:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name(KeccakSponge);
+
+        impl $name {
+            /// `function_name` is reserved for NIST-defined functions - applications should only
+            /// set the `customization` string
+            pub fn new(function_name: &[u8], customization: &[u8]) -> Self {
+                $name(cshake_sponge($rate, function_name, customization))
+            }
+
+            pub fn update(&mut self, data: &[u8]) {
+                self.0.absorb(data);
+            }
+
+            pub fn finalize_xof(self) -> KeccakReader {
+                KeccakReader::new(self.0)
+            }
+
+            pub fn finalize_into(self, out: &mut [u8]) {
+                self.finalize_xof().read(out);
+            }
+        }
+    };
+}
+
+cshake_variant!(
+    /// cSHAKE128 (SP 800-185)
+    CShake128,
+    RATE_128
+);
+cshake_variant!(
+    /// cSHAKE256 (SP 800-185)
+    CShake256,
+    RATE_256
+);
+
+/// define a KMAC variant with the given rate
+macro_rules! kmac_variant {
+    ($(#[$doc:meta])* $name:ident, $rate:expr, $tag_size:expr) => {
+        $(#[$doc])*
+        #[derive(Clone)]
+        pub struct $name(KeccakSponge);
+
+        impl $name {
+            /// length in bytes of the tags accepted by `verify`
+            pub const TAG_SIZE: usize = $tag_size;
+
+            pub fn new(key: &[u8], customization: &[u8]) -> Self {
+                let mut sponge = cshake_sponge($rate, b"KMAC", customization);
+                absorb_bytepad(&mut sponge, &[key]);
+                $name(sponge)
+            }
+
+            pub fn update(&mut self, data: &[u8]) {
+                self.0.absorb(data);
+            }
+
+            /// fill `out` with a tag of exactly `out.len()` bytes - the requested length is bound
+            /// into the tag, so a shorter tag is not a prefix of a longer one
+            pub fn finalize_into(mut self, out: &mut [u8]) {
+                self.0.absorb(&right_encode(8 * out.len() as u64));
+                self.0.squeeze(out);
+            }
+
+            /// KMACXOF - finish with an arbitrary-length output reader
+            pub fn finalize_xof(mut self) -> KeccakReader {
+                self.0.absorb(&right_encode(0));
+                KeccakReader::new(self.0)
+            }
+
+            /// compare the `TAG_SIZE`-byte tag against `tag` in constant time - a tag of any
+            /// other length is rejected rather than recomputed at that length, which would let
+            /// whoever supplied it choose how little there is to guess
+            pub fn verify(self, tag: &[u8]) -> bool {
+                if tag.len() != Self::TAG_SIZE {
+                    return false;
+                }
+
+                let mut computed = [0u8; $tag_size];
+                self.finalize_into(&mut computed);
+                ct_eq(&computed, tag)
+            }
+        }
+    };
+}
+
+kmac_variant!(
+    /// KMAC128 (SP 800-185)
+    Kmac128,
+    RATE_128,
+    KMAC128_TAG_SIZE
+);
+kmac_variant!(
+    /// KMAC256 (SP 800-185)
+    Kmac256,
+    RATE_256,
+    KMAC256_TAG_SIZE
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sha3::sha3::shake128;
+    use crate::test_util::hex;
+
+    #[test]
+    fn test_encodings() {
+        assert_eq!(left_encode(0), [0x01, 0x00]);
+        assert_eq!(left_encode(168), [0x01, 0xa8]);
+        assert_eq!(left_encode(0x1234), [0x02, 0x12, 0x34]);
+        assert_eq!(right_encode(0), [0x00, 0x01]);
+        assert_eq!(right_encode(256), [0x01, 0x00, 0x02]);
+        assert_eq!(encode_string(b""), [0x01, 0x00]);
+    }
+
+    // SP 800-185 cSHAKE samples
+    #[test]
+    fn test_cshake_samples() {
+        let mut out = [0u8; 32];
+        let mut cshake = CShake128::new(b"", b"Email Signature");
+        cshake.update(&[0x00, 0x01, 0x02, 0x03]);
+        cshake.finalize_into(&mut out);
+        assert_eq!(
+            out.to_vec(),
+            hex("c1c36925b6409a04f1b504fcbca9d82b4017277cb5ed2b2065fc1d3814d5aaf5")
+        );
+
+        let data: Vec<u8> = (0..200).map(|i| i as u8).collect();
+        let mut cshake = CShake128::new(b"", b"Email Signature");
+        cshake.update(&data);
+        cshake.finalize_into(&mut out);
+        assert_eq!(
+            out.to_vec(),
+            hex("c5221d50e4f822d96a2e8881a961420f294b7b24fe3d2094baed2c6524cc166b")
+        );
+
+        let mut out = [0u8; 64];
+        let mut cshake = CShake256::new(b"", b"Email Signature");
+        cshake.update(&[0x00, 0x01, 0x02, 0x03]);
+        cshake.finalize_into(&mut out);
+        assert_eq!(
+            out.to_vec(),
+            hex(
+                "d008828e2b80ac9d2218ffee1d070c48b8e4c87bff32c9699d5b6896eee0edd1
+                 64020e2be0560858d9c00c037e34a96937c561a74c412bb4c746469527281c8c"
+            )
+        );
+    }
+
+    #[test]
+    fn test_cshake_without_customization_is_shake() {
+        let mut out = [0u8; 32];
+        let mut cshake = CShake128::new(b"", b"");
+        cshake.update(b"abc");
+        cshake.finalize_into(&mut out);
+
+        assert_eq!(out.to_vec(), shake128(b"abc", 32));
+    }
+
+    // SP 800-185 KMAC samples
+    #[test]
+    fn test_kmac_samples() {
+        let key: Vec<u8> = (0x40..0x60).collect();
+        let data = [0x00, 0x01, 0x02, 0x03];
+
+        let mut out = [0u8; 32];
+        let mut kmac = Kmac128::new(&key, b"");
+        kmac.update(&data);
+        kmac.finalize_into(&mut out);
+        assert_eq!(
+            out.to_vec(),
+            hex("e5780b0d3ea6f7d3a429c5706aa43a00fadbd7d49628839e3187243f456ee14e")
+        );
+
+        let mut kmac = Kmac128::new(&key, b"My Tagged Application");
+        kmac.update(&data);
+        kmac.finalize_into(&mut out);
+        assert_eq!(
+            out.to_vec(),
+            hex("3b1fba963cd8b0b59e8c1a6d71888b7143651af8ba0a7070c0979e2811324aa5")
+        );
+
+        let mut out = [0u8; 64];
+        let mut kmac = Kmac256::new(&key, b"My Tagged Application");
+        kmac.update(&data);
+        kmac.finalize_into(&mut out);
+        assert_eq!(
+            out.to_vec(),
+            hex(
+                "20c570c31346f703c9ac36c61c03cb64c3970d0cfc787e9b79599d273a68d2f7
+                 f69d4cc3de9d104a351689f27cf6f5951f0103f33f4f24871024d9c27773a8dd"
+            )
+        );
+    }
+
+    #[test]
+    fn test_kmac_verify() {
+        let key = [0x61; 32];
+        let mut kmac = Kmac256::new(&key, b"");
+        kmac.update(b"message");
+
+        let mut tag = [0u8; 64];
+        kmac.clone().finalize_into(&mut tag);
+        assert!(kmac.clone().verify(&tag));
+
+        // the length is bound into the tag, so a truncated tag must not verify
+        assert!(!kmac.clone().verify(&tag[..32]));
+
+        tag[0] ^= 0x01;
+        assert!(!kmac.verify(&tag));
+    }
+
+    #[test]
+    fn test_kmac_verify_rejects_short_tags() {
+        let key = [0x61; 32];
+
+        let mut kmac = Kmac128::new(&key, b"");
+        kmac.update(b"message");
+        let mut tag = [0u8; 32];
+        kmac.clone().finalize_into(&mut tag);
+        assert!(kmac.clone().verify(&tag));
+
+        // a correct tag recomputed at the attacker's length still has to be refused
+        for len in [0, 1, 16] {
+            let mut short = vec![0u8; len];
+            kmac.clone().finalize_into(&mut short);
+            assert!(!kmac.clone().verify(&short));
+        }
+
+        let mut kmac = Kmac256::new(&key, b"");
+        kmac.update(b"message");
+        let mut short = [0u8; 1];
+        kmac.clone().finalize_into(&mut short);
+        assert!(!kmac.clone().verify(&[]));
+        assert!(!kmac.verify(&short));
+    }
+}
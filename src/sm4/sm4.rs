@@ -0,0 +1,121 @@
+use super::constant::*;
+use crate::block_cipher::BlockCipher;
+
+pub type Sm4Key = [u8; KEY_SIZE];
+
+// the fixed key-schedule constants CK - byte j of CK_i is (4i + j) * 7 mod 256
+const fn ck(i: usize) -> u32 {
+    let mut word = 0u32;
+    let mut j = 0;
+    while j < 4 {
+        word = (word << 8) | (((4 * i + j) * 7) % 256) as u32;
+        j += 1;
+    }
+    word
+}
+
+// the non-linear transformation tau - the S-box applied to each byte of a word
+fn tau(a: u32) -> u32 {
+    u32::from_be_bytes(a.to_be_bytes().map(|b| SBOX[b as usize]))
+}
+
+// the round transformation T, tau followed by the linear transformation L
+fn t(a: u32) -> u32 {
+    let b = tau(a);
+    b ^ b.rotate_left(2) ^ b.rotate_left(10) ^ b.rotate_left(18) ^ b.rotate_left(24)
+}
+
+// the key-schedule transformation T', which swaps L for the lighter L'
+fn t_key(a: u32) -> u32 {
+    let b = tau(a);
+    b ^ b.rotate_left(13) ^ b.rotate_left(23)
+}
+
+/// the SM4 block cipher (GB/T 32907-2016) - 32 rounds of an unbalanced Feistel network over
+/// 32-bit words, with a 128-bit key
+#[derive(Clone)]
+pub struct Sm4 {
+    round_keys: [u32; NUM_ROUNDS],
+}
+
+impl Sm4 {
+    fn crypt(block: &mut [u8], round_keys: impl Iterator<Item = u32>) {
+        let mut x = [0u32; 4];
+        for (word, chunk) in x.iter_mut().zip(block.chunks_exact(4)) {
+            *word = u32::from_be_bytes(chunk.try_into().unwrap());
+        }
+
+        for rk in round_keys {
+            let next = x[0] ^ t(x[1] ^ x[2] ^ x[3] ^ rk);
+            x = [x[1], x[2], x[3], next];
+        }
+
+        // the output is the last four words in reverse order
+        for (chunk, word) in block.chunks_exact_mut(4).zip(x.iter().rev()) {
+            chunk.copy_from_slice(&word.to_be_bytes());
+        }
+    }
+}
+
+impl BlockCipher for Sm4 {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+
+    type Key = Sm4Key;
+
+    fn new(key: &Sm4Key) -> Self {
+        let mut k = [0u32; 4];
+        for ((word, chunk), fk) in k.iter_mut().zip(key.chunks_exact(4)).zip(FK) {
+            *word = u32::from_be_bytes(chunk.try_into().unwrap()) ^ fk;
+        }
+
+        let mut round_keys = [0u32; NUM_ROUNDS];
+        for (i, rk) in round_keys.iter_mut().enumerate() {
+            *rk = k[0] ^ t_key(k[1] ^ k[2] ^ k[3] ^ ck(i));
+            k = [k[1], k[2], k[3], *rk];
+        }
+
+        Sm4 { round_keys }
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        Self::crypt(block, self.round_keys.iter().copied());
+    }
+
+    // decryption is the same network with the round keys in reverse
+    fn decrypt_block(&self, block: &mut [u8]) {
+        Self::crypt(block, self.round_keys.iter().rev().copied());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    #[test]
+    fn test_standard_example() {
+        // GB/T 32907-2016 appendix A, example 1
+        let key: Sm4Key = hex("0123456789abcdeffedcba9876543210").try_into().unwrap();
+        let cipher = Sm4::new(&key);
+
+        let mut block = key;
+        cipher.encrypt_block(&mut block);
+        assert_eq!(block.to_vec(), hex("681edf34d206965e86b3e94f536e4246"));
+
+        cipher.decrypt_block(&mut block);
+        assert_eq!(block, key);
+    }
+
+    #[test]
+    fn test_standard_example_iterated() {
+        // GB/T 32907-2016 appendix A, example 2 - the same block encrypted a million times
+        let key: Sm4Key = hex("0123456789abcdeffedcba9876543210").try_into().unwrap();
+        let cipher = Sm4::new(&key);
+
+        let mut block = key;
+        for _ in 0..1_000_000 {
+            cipher.encrypt_block(&mut block);
+        }
+        assert_eq!(block.to_vec(), hex("595298c7c6fd271f0402f804c33d3f66"));
+    }
+}
@@ -0,0 +1,122 @@
+use crate::aead::{Aead, AeadError};
+use crate::aes::aes::Aes128;
+use crate::aria::aria::Aria;
+use crate::block_cipher::BlockCipher;
+use crate::camellia::camellia::Camellia;
+use crate::serpent::serpent::Serpent;
+use crate::sm4::sm4::Sm4;
+use crate::twofish::twofish::Twofish;
+use std::collections::HashMap;
+
+/// decode a hex string into bytes, ignoring any whitespace so that long test vectors can be
+/// wrapped across lines
+pub fn hex(s: &str) -> Vec<u8> {
+    let digits: Vec<u8> = s
+        .bytes()
+        .filter(|b| !b.is_ascii_whitespace())
+        .map(|b| match b {
+            b'0'..=b'9' => b - b'0',
+            b'a'..=b'f' => b - b'a' + 10,
+            b'A'..=b'F' => b - b'A' + 10,
+            _ => panic!("invalid hex digit {:?}", b as char),
+        })
+        .collect();
+
+    assert!(digits.len().is_multiple_of(2), "odd number of hex digits");
+    digits.chunks_exact(2).map(|d| (d[0] << 4) | d[1]).collect()
+}
+
+/// parse test vectors laid out as blocks of `name = value` lines, one block per case, with
+/// blocks separated by blank lines and `#` starting a comment line
+pub fn vector_blocks(s: &str) -> Vec<HashMap<&str, &str>> {
+    let mut blocks = Vec::new();
+    let mut block = HashMap::new();
+
+    for line in s.lines().map(str::trim) {
+        if line.starts_with('#') {
+            continue;
+        }
+        if line.is_empty() {
+            if !block.is_empty() {
+                blocks.push(std::mem::take(&mut block));
+            }
+            continue;
+        }
+
+        let (name, value) = line
+            .split_once('=')
+            .expect("expected a `name = value` line");
+        block.insert(name.trim(), value.trim());
+    }
+    if !block.is_empty() {
+        blocks.push(block);
+    }
+
+    blocks
+}
+
+/// run a Wycheproof AEAD test set - valid cases have to seal to exactly the listed ciphertext
+/// and tag and open again, invalid ones have to be refused
+///
+/// returns the number of cases so callers can check none were lost
+pub fn wycheproof_aead<A: Aead>(vectors: &str) -> usize
+where
+    A::Key: TryFrom<Vec<u8>>,
+    A::Nonce: TryFrom<Vec<u8>>,
+{
+    let cases = vector_blocks(vectors);
+    for (i, case) in cases.iter().enumerate() {
+        let Ok(key) = A::Key::try_from(hex(case["key"])) else {
+            panic!("case {}: bad key size", i);
+        };
+        let Ok(nonce) = A::Nonce::try_from(hex(case["iv"])) else {
+            panic!("case {}: bad nonce size", i);
+        };
+        let (aad, msg) = (hex(case["aad"]), hex(case["msg"]));
+        let sealed = [hex(case["ct"]), hex(case["tag"])].concat();
+
+        let aead = A::new(&key);
+        match case["result"] {
+            "valid" => {
+                assert_eq!(
+                    aead.encrypt(&nonce, &aad, &msg).unwrap(),
+                    sealed,
+                    "case {}",
+                    i
+                );
+                assert_eq!(
+                    aead.decrypt(&nonce, &aad, &sealed).unwrap(),
+                    msg,
+                    "case {}",
+                    i
+                );
+            }
+            "invalid" => assert_eq!(
+                aead.decrypt(&nonce, &aad, &sealed),
+                Err(AeadError::AuthenticationFailed),
+                "case {}",
+                i
+            ),
+            result => panic!("case {}: unknown result {:?}", i, result),
+        }
+    }
+
+    cases.len()
+}
+
+/// a check written once against `BlockCipher`, so a mode can be run under every cipher
+pub trait CipherCheck {
+    fn check<C: BlockCipher>(&self, cipher: &C);
+}
+
+/// run `test` under each of the crate's 128-bit block ciphers, between them covering all three
+/// key sizes
+pub fn for_each_128_bit_cipher(test: impl CipherCheck) {
+    test.check(&Aes128::new(&[0x61; 16]));
+    test.check(&Camellia::new(&[0x61; 16]));
+    test.check(&Camellia::new(&[0x61; 32]));
+    test.check(&Serpent::new(&[0x61; 24]));
+    test.check(&Twofish::new(&[0x61; 32]));
+    test.check(&Aria::new(&[0x61; 16]));
+    test.check(&Sm4::new(&[0x61; 16]));
+}
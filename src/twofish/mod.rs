@@ -0,0 +1,3 @@
+pub mod constant;
+#[allow(clippy::module_inception)]
+pub mod twofish;
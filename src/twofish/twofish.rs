@@ -0,0 +1,264 @@
+use super::constant::*;
+use crate::block_cipher::{BlockCipher, KeySizeError};
+
+fn gf_mult(a: u8, b: u8, poly: u16) -> u8 {
+    let mut result = 0u16;
+    let mut a = a as u16;
+    let mut b = b;
+
+    while b != 0 {
+        if b & 1 == 1 {
+            result ^= a;
+        }
+        a <<= 1;
+        if a & 0x100 != 0 {
+            a ^= poly;
+        }
+        b >>= 1;
+    }
+
+    result as u8
+}
+
+fn mds(y: [u8; 4]) -> u32 {
+    let mut z = [0u8; 4];
+    for (z, row) in z.iter_mut().zip(MDS.iter()) {
+        *z = row
+            .iter()
+            .zip(y)
+            .fold(0, |acc, (&m, y)| acc ^ gf_mult(m, y, MDS_POLY));
+    }
+    u32::from_le_bytes(z)
+}
+
+// the function h without its MDS step - each byte runs through a chain of q permutations,
+// xored with a byte of each word of `l` in between
+fn h_bytes(x: [u8; 4], l: &[[u8; 4]]) -> [u8; 4] {
+    let q = |table: &[u8; 256], x: u8| table[x as usize];
+    let mut y = x;
+
+    if l.len() == 4 {
+        y = [
+            q(&Q1, y[0]) ^ l[3][0],
+            q(&Q0, y[1]) ^ l[3][1],
+            q(&Q0, y[2]) ^ l[3][2],
+            q(&Q1, y[3]) ^ l[3][3],
+        ];
+    }
+    if l.len() >= 3 {
+        y = [
+            q(&Q1, y[0]) ^ l[2][0],
+            q(&Q1, y[1]) ^ l[2][1],
+            q(&Q0, y[2]) ^ l[2][2],
+            q(&Q0, y[3]) ^ l[2][3],
+        ];
+    }
+
+    [
+        q(&Q1, q(&Q0, q(&Q0, y[0]) ^ l[1][0]) ^ l[0][0]),
+        q(&Q0, q(&Q0, q(&Q1, y[1]) ^ l[1][1]) ^ l[0][1]),
+        q(&Q1, q(&Q1, q(&Q0, y[2]) ^ l[1][2]) ^ l[0][2]),
+        q(&Q0, q(&Q1, q(&Q1, y[3]) ^ l[1][3]) ^ l[0][3]),
+    ]
+}
+
+fn h(x: u32, l: &[[u8; 4]]) -> u32 {
+    mds(h_bytes(x.to_le_bytes(), l))
+}
+
+/// the Twofish block cipher - a 16-round Feistel network with key-dependent S-boxes, for 128,
+/// 192 and 256-bit keys
+#[derive(Clone)]
+pub struct Twofish {
+    // the expanded key words K0 to K39 - whitening keys first, then two per round
+    k: [u32; 40],
+    // the key-dependent S-boxes with the MDS matrix folded in, one per input byte of g
+    s: [[u32; 256]; 4],
+}
+
+impl Twofish {
+    /// the cipher under `key`, or `InvalidLength` unless it is 16, 24 or 32 bytes long - use
+    /// this rather than `new` for keys from outside the program
+    pub fn try_new(key: &[u8]) -> Result<Self, KeySizeError> {
+        if !matches!(key.len(), 16 | 24 | 32) {
+            return Err(KeySizeError::InvalidLength);
+        }
+
+        let m: Vec<[u8; 4]> = key
+            .chunks_exact(4)
+            .map(|chunk| chunk.try_into().unwrap())
+            .collect();
+        let even: Vec<[u8; 4]> = m.iter().copied().step_by(2).collect();
+        let odd: Vec<[u8; 4]> = m.iter().copied().skip(1).step_by(2).collect();
+
+        // each 64 bits of key give one S-box key word through the RS code, used in reverse
+        let mut s_key: Vec<[u8; 4]> = key
+            .chunks_exact(8)
+            .map(|chunk| {
+                let mut word = [0u8; 4];
+                for (w, row) in word.iter_mut().zip(RS.iter()) {
+                    *w = row
+                        .iter()
+                        .zip(chunk)
+                        .fold(0, |acc, (&r, &m)| acc ^ gf_mult(r, m, RS_POLY));
+                }
+                word
+            })
+            .collect();
+        s_key.reverse();
+
+        const RHO: u32 = 0x01010101;
+        let mut k = [0u32; 40];
+        for (i, pair) in k.chunks_exact_mut(2).enumerate() {
+            let a = h(2 * i as u32 * RHO, &even);
+            let b = h((2 * i as u32 + 1) * RHO, &odd).rotate_left(8);
+            pair[0] = a.wrapping_add(b);
+            pair[1] = a.wrapping_add(b.wrapping_mul(2)).rotate_left(9);
+        }
+
+        let mut s = [[0u32; 256]; 4];
+        for x in 0..=255u8 {
+            let y = h_bytes([x; 4], &s_key);
+            for (j, table) in s.iter_mut().enumerate() {
+                let mut column = [0u8; 4];
+                column[j] = y[j];
+                table[x as usize] = mds(column);
+            }
+        }
+
+        Ok(Twofish { k, s })
+    }
+
+    fn g(&self, x: u32) -> u32 {
+        let b = x.to_le_bytes();
+        self.s[0][b[0] as usize]
+            ^ self.s[1][b[1] as usize]
+            ^ self.s[2][b[2] as usize]
+            ^ self.s[3][b[3] as usize]
+    }
+
+    // the F function's two outputs for round `r`
+    fn f(&self, r0: u32, r1: u32, round: usize) -> (u32, u32) {
+        let t0 = self.g(r0);
+        let t1 = self.g(r1.rotate_left(8));
+        (
+            t0.wrapping_add(t1).wrapping_add(self.k[2 * round + 8]),
+            t0.wrapping_add(t1.wrapping_mul(2))
+                .wrapping_add(self.k[2 * round + 9]),
+        )
+    }
+}
+
+fn words(block: &[u8]) -> [u32; 4] {
+    let mut words = [0u32; 4];
+    for (word, chunk) in words.iter_mut().zip(block.chunks_exact(4)) {
+        *word = u32::from_le_bytes(chunk.try_into().unwrap());
+    }
+    words
+}
+
+fn write_words(block: &mut [u8], words: [u32; 4]) {
+    for (chunk, word) in block.chunks_exact_mut(4).zip(words) {
+        chunk.copy_from_slice(&word.to_le_bytes());
+    }
+}
+
+impl BlockCipher for Twofish {
+    const BLOCK_SIZE: usize = BLOCK_SIZE;
+
+    /// a 128, 192 or 256-bit key
+    type Key = [u8];
+
+    /// panics unless `key` is 16, 24 or 32 bytes long - see `try_new`
+    fn new(key: &[u8]) -> Self {
+        Self::try_new(key).expect("twofish keys are 16, 24 or 32 bytes")
+    }
+
+    fn encrypt_block(&self, block: &mut [u8]) {
+        let mut r = words(block);
+        for (word, k) in r.iter_mut().zip(&self.k[..4]) {
+            *word ^= k;
+        }
+
+        for round in 0..NUM_ROUNDS {
+            let (f0, f1) = self.f(r[0], r[1], round);
+            r = [
+                (r[2] ^ f0).rotate_right(1),
+                r[3].rotate_left(1) ^ f1,
+                r[0],
+                r[1],
+            ];
+        }
+
+        // undo the last swap and whiten
+        let out = [r[2], r[3], r[0], r[1]];
+        write_words(block, core::array::from_fn(|i| out[i] ^ self.k[i + 4]));
+    }
+
+    fn decrypt_block(&self, block: &mut [u8]) {
+        let c = words(block);
+        let mut r: [u32; 4] = core::array::from_fn(|i| c[i] ^ self.k[i + 4]);
+        r = [r[2], r[3], r[0], r[1]];
+
+        for round in (0..NUM_ROUNDS).rev() {
+            let (f0, f1) = self.f(r[2], r[3], round);
+            r = [
+                r[0].rotate_left(1) ^ f0,
+                (r[1] ^ f1).rotate_right(1),
+                r[2],
+                r[3],
+            ];
+            r = [r[2], r[3], r[0], r[1]];
+        }
+
+        write_words(block, core::array::from_fn(|i| r[i] ^ self.k[i]));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::hex;
+
+    fn check(key: &str, plaintext: &str, ciphertext: &str) {
+        let cipher = Twofish::new(&hex(key));
+        let mut block = hex(plaintext);
+        cipher.encrypt_block(&mut block);
+        assert_eq!(block, hex(ciphertext));
+        cipher.decrypt_block(&mut block);
+        assert_eq!(block, hex(plaintext));
+    }
+
+    #[test]
+    fn test_specification_vectors() {
+        // the known answers from the Twofish paper's appendix
+        check(
+            "00000000000000000000000000000000",
+            "00000000000000000000000000000000",
+            "9f589f5cf6122c32b6bfec2f2ae8c35a",
+        );
+        check(
+            "0123456789abcdeffedcba98765432100011223344556677",
+            "00000000000000000000000000000000",
+            "cfd1d2e5a9be9cdf501f13b892bd2248",
+        );
+        check(
+            "0123456789abcdeffedcba987654321000112233445566778899aabbccddeeff",
+            "00000000000000000000000000000000",
+            "37527be0052334b89f0cfccae87cfa20",
+        );
+    }
+
+    #[test]
+    fn test_rejects_other_key_sizes() {
+        for len in [0, 8, 15, 17, 23, 25, 31, 33, 64] {
+            assert_eq!(
+                Twofish::try_new(&vec![0u8; len]).err(),
+                Some(KeySizeError::InvalidLength)
+            );
+        }
+        for len in [16, 24, 32] {
+            assert!(Twofish::try_new(&vec![0u8; len]).is_ok());
+        }
+    }
+}